@@ -0,0 +1,88 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use smartcoaster_host_core::{SessionHandlerError, SmartcoasterHostCustomScreenLayout};
+use serialport::SerialPort;
+use std::io::{Error as IoError, ErrorKind, Read, Result as IoResult, Write};
+use std::time::Duration;
+
+const BUFFER_SIZE: usize = 1024;
+
+/// Reads the custom screen layout from a device running the application, first setting it if a new
+/// layout is given, and prints the layout the device is using.
+pub(crate) fn custom_screen_layout(serial: &mut dyn SerialPort, new_layout: Option<u32>) -> IoResult<()> {
+    match new_layout {
+        Some(packed) => println!("Setting custom screen layout to {}", format_layout(packed)),
+        None => println!("Reading custom screen layout"),
+    }
+
+    let zero_buffer = [0u8; 0];
+    let mut session = SmartcoasterHostCustomScreenLayout::<BUFFER_SIZE>::new(new_layout);
+    session = SmartcoasterHostCustomScreenLayout::session_handler(session, &zero_buffer)
+        .map_err(session_error)?;
+
+    let mut rx_buffer = [0u8; BUFFER_SIZE];
+    loop {
+        if let Some(bytes_to_send) = SmartcoasterHostCustomScreenLayout::get_bytes_to_send(&mut session) {
+            log::trace!("Sending {} bytes", bytes_to_send.len());
+            serial.write_all(bytes_to_send)
+                .map_err(|e| IoError::new(ErrorKind::Other, format!("Failed to send: {}", e)))?;
+        }
+
+        if SmartcoasterHostCustomScreenLayout::is_session_ended(&session) {
+            break;
+        }
+
+        match serial.read(&mut rx_buffer) {
+            Ok(n) if n > 0 => {
+                log::trace!("Received {} bytes", n);
+                session = SmartcoasterHostCustomScreenLayout::session_handler(session, &rx_buffer[..n])
+                    .map_err(session_error)?;
+            }
+            Ok(_) => std::thread::sleep(Duration::from_millis(10)),
+            Err(ref e) if e.kind() == ErrorKind::TimedOut => {
+                log::error!("Timed out waiting for the device");
+                return Err(IoError::new(ErrorKind::TimedOut, "Timed out waiting for the device"));
+            }
+            Err(e) => {
+                log::error!("Serial read error: {}", e);
+                return Err(e);
+            }
+        }
+    }
+
+    if let Some(packed) = SmartcoasterHostCustomScreenLayout::get_layout(&session) {
+        println!("Custom screen layout: {}", format_layout(packed));
+        if new_layout.is_some_and(|new_layout| new_layout != packed) {
+            println!("The device did not accept the new layout");
+        }
+    }
+    Ok(())
+}
+
+/// Parses a layout given as hex, with or without a `0x` prefix.
+pub(crate) fn parse_layout(value: &str) -> IoResult<u32> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    u32::from_str_radix(digits, 16)
+        .map_err(|e| IoError::new(ErrorKind::InvalidInput, format!("Invalid layout '{}': {}", value, e)))
+}
+
+fn format_layout(packed: u32) -> String {
+    format!("{:#08x}", packed)
+}
+
+fn session_error(e: SessionHandlerError) -> IoError {
+    log::error!("Session handler error: {:?}", e);
+    IoError::new(ErrorKind::Other, format!("Session error: {:?}", e))
+}
@@ -0,0 +1,82 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+
+use smartcoaster_host_core::{ErrorLogEntry, SessionHandlerError, SmartcoasterHostErrorLogDownloader};
+use serialport::SerialPort;
+use std::io::{Error as IoError, ErrorKind, Read, Result as IoResult, Write};
+use std::time::Duration;
+
+const BUFFER_SIZE: usize = 1024;
+
+/// Downloads the error log from a device running the application and prints it, oldest first.
+pub(crate) fn download_error_log(serial: &mut dyn SerialPort) -> IoResult<()> {
+    println!("Downloading error log");
+
+    let zero_buffer = [0u8; 0];
+    let mut session = SmartcoasterHostErrorLogDownloader::<BUFFER_SIZE>::new();
+    session = SmartcoasterHostErrorLogDownloader::session_handler(session, &zero_buffer)
+        .map_err(session_error)?;
+
+    let mut rx_buffer = [0u8; BUFFER_SIZE];
+    loop {
+        if let Some(bytes_to_send) = SmartcoasterHostErrorLogDownloader::get_bytes_to_send(&mut session) {
+            log::trace!("Sending {} bytes", bytes_to_send.len());
+            serial.write_all(bytes_to_send)
+                .map_err(|e| IoError::new(ErrorKind::Other, format!("Failed to send: {}", e)))?;
+        }
+
+        if SmartcoasterHostErrorLogDownloader::is_session_ended(&session) {
+            break;
+        }
+
+        match serial.read(&mut rx_buffer) {
+            Ok(n) if n > 0 => {
+                log::trace!("Received {} bytes", n);
+                session = SmartcoasterHostErrorLogDownloader::session_handler(session, &rx_buffer[..n])
+                    .map_err(session_error)?;
+            }
+            Ok(_) => std::thread::sleep(Duration::from_millis(10)),
+            Err(ref e) if e.kind() == ErrorKind::TimedOut => {
+                log::error!("Timed out waiting for the device");
+                return Err(IoError::new(ErrorKind::TimedOut, "Timed out waiting for the device"));
+            }
+            Err(e) => {
+                log::error!("Serial read error: {}", e);
+                return Err(e);
+            }
+        }
+    }
+
+    let entries = SmartcoasterHostErrorLogDownloader::get_entries(&session);
+    println!("{} error log entries", entries.len());
+    for entry in entries {
+        println!("{}", format_entry(entry));
+    }
+
+    Ok(())
+}
+
+fn format_entry(entry: &ErrorLogEntry) -> String {
+    let timestamp = chrono::DateTime::from_timestamp(entry.timestamp, 0)
+        .map(|t| t.naive_utc().to_string())
+        .unwrap_or_else(|| format!("{}s", entry.timestamp));
+    let values: Vec<String> = entry.values.iter().flatten().map(|v| v.to_string()).collect();
+    format!("{}  module {:>3}  code {:>3}  {}", timestamp, entry.module, entry.code, values.join(", "))
+}
+
+fn session_error(e: SessionHandlerError) -> IoError {
+    log::error!("Session handler error: {:?}", e);
+    IoError::new(ErrorKind::Other, format!("Session error: {:?}", e))
+}
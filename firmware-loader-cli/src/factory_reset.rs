@@ -0,0 +1,72 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use smartcoaster_host_core::{SessionHandlerError, SmartcoasterHostFactoryReset};
+use serialport::SerialPort;
+use std::io::{Error as IoError, ErrorKind, Read, Result as IoResult, Write};
+use std::time::Duration;
+
+const BUFFER_SIZE: usize = 1024;
+
+/// Asks a device running the application to erase its settings and logs. It reboots once done.
+pub(crate) fn factory_reset(serial: &mut dyn SerialPort, keep_calibration: bool) -> IoResult<()> {
+    if keep_calibration {
+        println!("Requesting factory reset, keeping calibration");
+    } else {
+        println!("Requesting factory reset");
+    }
+
+    let zero_buffer = [0u8; 0];
+    let mut session = SmartcoasterHostFactoryReset::<BUFFER_SIZE>::new(keep_calibration);
+    session = SmartcoasterHostFactoryReset::session_handler(session, &zero_buffer)
+        .map_err(session_error)?;
+
+    let mut rx_buffer = [0u8; BUFFER_SIZE];
+    loop {
+        if let Some(bytes_to_send) = SmartcoasterHostFactoryReset::get_bytes_to_send(&mut session) {
+            log::trace!("Sending {} bytes", bytes_to_send.len());
+            serial.write_all(bytes_to_send)
+                .map_err(|e| IoError::new(ErrorKind::Other, format!("Failed to send: {}", e)))?;
+        }
+
+        if SmartcoasterHostFactoryReset::is_session_ended(&session) {
+            break;
+        }
+
+        match serial.read(&mut rx_buffer) {
+            Ok(n) if n > 0 => {
+                log::trace!("Received {} bytes", n);
+                session = SmartcoasterHostFactoryReset::session_handler(session, &rx_buffer[..n])
+                    .map_err(session_error)?;
+            }
+            Ok(_) => std::thread::sleep(Duration::from_millis(10)),
+            Err(ref e) if e.kind() == ErrorKind::TimedOut => {
+                log::error!("Timed out waiting for the device");
+                return Err(IoError::new(ErrorKind::TimedOut, "Timed out waiting for the device"));
+            }
+            Err(e) => {
+                log::error!("Serial read error: {}", e);
+                return Err(e);
+            }
+        }
+    }
+
+    println!("Factory reset started, the device will restart when it is complete");
+    Ok(())
+}
+
+fn session_error(e: SessionHandlerError) -> IoError {
+    log::error!("Session handler error: {:?}", e);
+    IoError::new(ErrorKind::Other, format!("Session error: {:?}", e))
+}
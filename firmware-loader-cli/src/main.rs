@@ -12,6 +12,10 @@
 // You should have received a copy of the GNU General Public License along with
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
+mod custom_screen;
+mod error_log;
+mod factory_reset;
+mod nvm_status;
 mod util;
 
 use smartcoaster_host_core::{SmartcoasterHostFirmwareLoader};
@@ -32,8 +36,20 @@ fn main() -> IoResult<()> {
 
     let args: Vec<String> = std::env::args().collect();
 
-    // Extract firmware file path (first positional arg after --log-level if present)
-    let firmware_file_path = util::extract_firmware_file_path(&args)?;
+    let error_log_mode = args.iter().any(|arg| arg == "--error-log");
+    let nvm_status_mode = args.iter().any(|arg| arg == "--nvm-status");
+    let factory_reset_mode = args.iter().any(|arg| arg == "--factory-reset");
+    let keep_calibration = args.iter().any(|arg| arg == "--keep-calibration");
+    let custom_screen_mode = args.iter().any(|arg| arg == "--custom-screen");
+    let new_custom_screen = args
+        .iter()
+        .position(|arg| arg == "--set-custom-screen")
+        .map(|i| {
+            args.get(i + 1)
+                .ok_or_else(|| IoError::new(ErrorKind::InvalidInput, "No layout given to --set-custom-screen"))
+                .and_then(|value| custom_screen::parse_layout(value))
+        })
+        .transpose()?;
 
     println!("Available serial ports:");
     let ports = serialport::available_ports()
@@ -71,6 +87,25 @@ fn main() -> IoResult<()> {
     // Give the device time to initialize
     std::thread::sleep(Duration::from_millis(100));
 
+    if error_log_mode {
+        return error_log::download_error_log(serial.as_mut());
+    }
+
+    if nvm_status_mode {
+        return nvm_status::read_nvm_status(serial.as_mut());
+    }
+
+    if factory_reset_mode {
+        return factory_reset::factory_reset(serial.as_mut(), keep_calibration);
+    }
+
+    if custom_screen_mode || new_custom_screen.is_some() {
+        return custom_screen::custom_screen_layout(serial.as_mut(), new_custom_screen);
+    }
+
+    // Extract firmware file path (first positional arg after --log-level if present)
+    let firmware_file_path = util::extract_firmware_file_path(&args)?;
+
     println!("Loading firmware data from file");
 
     // Read and process firmware file
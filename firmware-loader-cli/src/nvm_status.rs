@@ -0,0 +1,105 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use smartcoaster_host_core::{NvmPartitionEntry, NvmPartitionId, SessionHandlerError, SmartcoasterHostNvmStatusReader};
+use serialport::SerialPort;
+use std::io::{Error as IoError, ErrorKind, Read, Result as IoResult, Write};
+use std::time::Duration;
+
+const BUFFER_SIZE: usize = 1024;
+
+/// Reads the usage of each NVM partition from a device running the application and prints it.
+pub(crate) fn read_nvm_status(serial: &mut dyn SerialPort) -> IoResult<()> {
+    println!("Reading NVM status");
+
+    let zero_buffer = [0u8; 0];
+    let mut session = SmartcoasterHostNvmStatusReader::<BUFFER_SIZE>::new();
+    session = SmartcoasterHostNvmStatusReader::session_handler(session, &zero_buffer)
+        .map_err(session_error)?;
+
+    let mut rx_buffer = [0u8; BUFFER_SIZE];
+    loop {
+        if let Some(bytes_to_send) = SmartcoasterHostNvmStatusReader::get_bytes_to_send(&mut session) {
+            log::trace!("Sending {} bytes", bytes_to_send.len());
+            serial.write_all(bytes_to_send)
+                .map_err(|e| IoError::new(ErrorKind::Other, format!("Failed to send: {}", e)))?;
+        }
+
+        if SmartcoasterHostNvmStatusReader::is_session_ended(&session) {
+            break;
+        }
+
+        match serial.read(&mut rx_buffer) {
+            Ok(n) if n > 0 => {
+                log::trace!("Received {} bytes", n);
+                session = SmartcoasterHostNvmStatusReader::session_handler(session, &rx_buffer[..n])
+                    .map_err(session_error)?;
+            }
+            Ok(_) => std::thread::sleep(Duration::from_millis(10)),
+            Err(ref e) if e.kind() == ErrorKind::TimedOut => {
+                log::error!("Timed out waiting for the device");
+                return Err(IoError::new(ErrorKind::TimedOut, "Timed out waiting for the device"));
+            }
+            Err(e) => {
+                log::error!("Serial read error: {}", e);
+                return Err(e);
+            }
+        }
+    }
+
+    for partition in SmartcoasterHostNvmStatusReader::get_partitions(&session) {
+        println!("{}", format_partition(partition));
+    }
+
+    Ok(())
+}
+
+fn format_partition(entry: &NvmPartitionEntry) -> String {
+    let name = match entry.partition {
+        NvmPartitionId::ErrorLog => "Error log",
+        NvmPartitionId::DailyRollupLog => "Daily rollup log",
+        NvmPartitionId::ActivityLog => "Activity log",
+        NvmPartitionId::Settings => "Settings",
+    };
+    let mut lines = vec![
+        format!("{}:", name),
+        format!("  used {} of {} bytes", entry.used, entry.capacity),
+    ];
+    match entry.live_settings {
+        Some(live_settings) => lines.push(format!("  {} settings stored in {} items", live_settings, entry.record_count)),
+        None => lines.push(format!("  {} records", entry.record_count)),
+    }
+    if let (Some(oldest), Some(newest)) = (entry.oldest, entry.newest) {
+        lines.push(format!("  oldest {}", format_timestamp(oldest)));
+        lines.push(format!("  newest {}", format_timestamp(newest)));
+    }
+    if let Some(days) = entry.days_until_wrap {
+        lines.push(format!("  about {} days until the oldest records are overwritten", days));
+    }
+    if let (Some(max_page_erases), Some(total_erases)) = (entry.max_page_erases, entry.total_erases) {
+        lines.push(format!("  {} erases, the most erased page {} times", total_erases, max_page_erases));
+    }
+    lines.join("\n")
+}
+
+fn format_timestamp(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|t| t.naive_utc().to_string())
+        .unwrap_or_else(|| format!("{}s", timestamp))
+}
+
+fn session_error(e: SessionHandlerError) -> IoError {
+    log::error!("Session handler error: {:?}", e);
+    IoError::new(ErrorKind::Other, format!("Session error: {:?}", e))
+}
@@ -0,0 +1,327 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Daily summaries of consumption, so that statistics over several days don't require the raw
+//! consumption log to be scanned.
+
+use crate::monitoring_day;
+use crate::target_direction::TargetDirection;
+use chrono::{NaiveDateTime, NaiveTime, TimeDelta};
+
+/// Summary of a single monitoring day.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DailySummary {
+    pub day_start: NaiveDateTime,
+    pub total_consumption: f32,
+    pub drink_count: u32,
+    pub first_drink: Option<NaiveDateTime>,
+    pub last_drink: Option<NaiveDateTime>,
+    pub target_achieved: bool,
+}
+
+impl DailySummary {
+    pub fn new(day_start: NaiveDateTime) -> Self {
+        Self {
+            day_start,
+            total_consumption: 0.0,
+            drink_count: 0,
+            first_drink: None,
+            last_drink: None,
+            target_achieved: false,
+        }
+    }
+
+    /// Adds consumption to the day. Only consumption greater than zero counts as a drink.
+    pub fn add_drink(&mut self, time: NaiveDateTime, consumption: f32) {
+        if consumption <= 0.0 {
+            return;
+        }
+        self.total_consumption += consumption;
+        self.drink_count += 1;
+        self.first_drink.get_or_insert(time);
+        self.last_drink = Some(time);
+    }
+
+    /// Sets whether the target was achieved based on the day's total. `daily_target` is `None` when
+    /// no daily target was being monitored, in which case the target is never achieved.
+    pub fn complete(&mut self, daily_target: Option<f32>, direction: TargetDirection) {
+        self.target_achieved = match daily_target {
+            Some(target) if target > 0.0 => direction.is_achieved(self.total_consumption, target),
+            _ => false,
+        };
+    }
+}
+
+/// Builds daily summaries from consumption records, which must be added in time order.
+pub struct DailySummaryBuilder {
+    day_start_time: NaiveTime,
+    direction: TargetDirection,
+    /// The day being accumulated with the latest daily target seen for it.
+    current_day: Option<(DailySummary, Option<f32>)>,
+}
+
+impl DailySummaryBuilder {
+    pub fn new(day_start_time: NaiveTime, direction: TargetDirection) -> Self {
+        Self {
+            day_start_time,
+            direction,
+            current_day: None,
+        }
+    }
+
+    /// Adds a consumption record. Returns the summary of the previous day once a record for a
+    /// later day is seen.
+    pub fn add_record(
+        &mut self,
+        timestamp: NaiveDateTime,
+        consumption: f32,
+        daily_target: Option<f32>,
+    ) -> Option<DailySummary> {
+        let day = monitoring_day::day_start_for(timestamp, self.day_start_time);
+        let mut completed = None;
+        match self.current_day.as_mut() {
+            Some((summary, target)) if summary.day_start == day => {
+                summary.add_drink(timestamp, consumption);
+                *target = daily_target;
+                return None;
+            }
+            Some((summary, target)) => {
+                summary.complete(*target, self.direction);
+                completed = Some(*summary);
+            }
+            None => {}
+        }
+        let mut summary = DailySummary::new(day);
+        summary.add_drink(timestamp, consumption);
+        self.current_day = Some((summary, daily_target));
+        completed
+    }
+
+    /// Returns the summary of the last day seen if that day has finished by `now`.
+    pub fn finish(self, now: NaiveDateTime) -> Option<DailySummary> {
+        let today = monitoring_day::day_start_for(now, self.day_start_time);
+        match self.current_day {
+            Some((mut summary, target)) if summary.day_start < today => {
+                summary.complete(target, self.direction);
+                Some(summary)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The most recent `N` daily summaries.
+pub struct SummaryHistory<const N: usize> {
+    summaries: [Option<DailySummary>; N],
+}
+
+impl<const N: usize> SummaryHistory<N> {
+    pub const fn new() -> Self {
+        Self {
+            summaries: [None; N],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.summaries = [None; N];
+    }
+
+    /// Adds a summary, replacing any existing summary for the same day. Once full, the oldest
+    /// summary is dropped to make room, unless the new summary is older still.
+    pub fn add(&mut self, summary: DailySummary) {
+        let slot = self
+            .summaries
+            .iter()
+            .position(|s| s.is_some_and(|s| s.day_start == summary.day_start))
+            .or_else(|| self.summaries.iter().position(Option::is_none));
+        if let Some(slot) = slot {
+            self.summaries[slot] = Some(summary);
+            return;
+        }
+        if let Some(oldest) = self
+            .summaries
+            .iter_mut()
+            .flatten()
+            .min_by_key(|s| s.day_start)
+            && oldest.day_start < summary.day_start
+        {
+            *oldest = summary;
+        }
+    }
+
+    /// Start of the most recent day with a summary.
+    pub fn latest_day(&self) -> Option<NaiveDateTime> {
+        self.summaries.iter().flatten().map(|s| s.day_start).max()
+    }
+
+    /// Summary for the day `days_ago` days before the day starting at `today_start`. Matching is
+    /// by the day start falling within that day, so summaries made with a different day start time
+    /// are still found.
+    pub fn day(&self, today_start: NaiveDateTime, days_ago: u32) -> Option<&DailySummary> {
+        let start = today_start - TimeDelta::days(days_ago as i64);
+        let end = start + TimeDelta::days(1);
+        self.summaries
+            .iter()
+            .flatten()
+            .find(|s| s.day_start >= start && s.day_start < end)
+    }
+
+    /// Average total consumption over the days with a summary in the `days` before the day
+    /// starting at `today_start`. `None` if there are no summaries in that period.
+    pub fn average_total(&self, today_start: NaiveDateTime, days: u32) -> Option<f32> {
+        let start = today_start - TimeDelta::days(days as i64);
+        let (count, total) = self
+            .summaries
+            .iter()
+            .flatten()
+            .filter(|s| s.day_start >= start && s.day_start < today_start)
+            .fold((0, 0.0), |(count, total), s| {
+                (count + 1, total + s.total_consumption)
+            });
+        if count == 0 {
+            None
+        } else {
+            Some(total / count as f32)
+        }
+    }
+}
+
+impl<const N: usize> Default for SummaryHistory<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn dt(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 3, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn summary(day: u32, total: f32) -> DailySummary {
+        let mut s = DailySummary::new(dt(day, 4, 0));
+        s.add_drink(dt(day, 12, 0), total);
+        s
+    }
+
+    #[test]
+    fn drinks_are_counted() {
+        let mut s = DailySummary::new(dt(2, 4, 0));
+        s.add_drink(dt(2, 8, 0), 200.0);
+        s.add_drink(dt(2, 9, 0), 0.0);
+        s.add_drink(dt(2, 13, 30), 300.0);
+        assert_eq!(s.total_consumption, 500.0);
+        assert_eq!(s.drink_count, 2);
+        assert_eq!(s.first_drink, Some(dt(2, 8, 0)));
+        assert_eq!(s.last_drink, Some(dt(2, 13, 30)));
+    }
+
+    #[test]
+    fn target_achieved_needs_a_daily_target() {
+        let mut s = summary(2, 1500.0);
+        s.complete(Some(1200.0), TargetDirection::Minimum);
+        assert!(s.target_achieved);
+        s.complete(Some(1200.0), TargetDirection::Maximum);
+        assert!(!s.target_achieved);
+        s.complete(None, TargetDirection::Minimum);
+        assert!(!s.target_achieved);
+        s.complete(Some(0.0), TargetDirection::Minimum);
+        assert!(!s.target_achieved);
+    }
+
+    #[test]
+    fn builder_splits_records_into_days() {
+        let mut builder = DailySummaryBuilder::new(
+            NaiveTime::from_hms_opt(4, 0, 0).unwrap(),
+            TargetDirection::Minimum,
+        );
+        assert_eq!(builder.add_record(dt(2, 9, 0), 600.0, Some(1000.0)), None);
+        // after midnight still belongs to the 2nd
+        assert_eq!(builder.add_record(dt(3, 1, 0), 500.0, Some(1000.0)), None);
+        let day_2 = builder
+            .add_record(dt(3, 9, 0), 300.0, Some(1000.0))
+            .unwrap();
+        assert_eq!(day_2.day_start, dt(2, 4, 0));
+        assert_eq!(day_2.total_consumption, 1100.0);
+        assert_eq!(day_2.drink_count, 2);
+        assert_eq!(day_2.last_drink, Some(dt(3, 1, 0)));
+        assert!(day_2.target_achieved);
+
+        // the 3rd is still in progress
+        assert_eq!(builder.finish(dt(3, 12, 0)), None);
+    }
+
+    #[test]
+    fn builder_finishes_previous_day() {
+        let mut builder = DailySummaryBuilder::new(NaiveTime::MIN, TargetDirection::Minimum);
+        builder.add_record(dt(2, 9, 0), 600.0, Some(1000.0));
+        let day_2 = builder.finish(dt(3, 12, 0)).unwrap();
+        assert_eq!(day_2.day_start, dt(2, 0, 0));
+        assert!(!day_2.target_achieved);
+    }
+
+    #[test]
+    fn history_replaces_same_day_and_drops_oldest() {
+        let mut history = SummaryHistory::<3>::new();
+        history.add(summary(1, 100.0));
+        history.add(summary(2, 200.0));
+        history.add(summary(2, 250.0));
+        history.add(summary(3, 300.0));
+        assert_eq!(history.latest_day(), Some(dt(3, 4, 0)));
+        history.add(summary(4, 400.0));
+        assert_eq!(history.day(dt(5, 4, 0), 4), None);
+        assert_eq!(
+            history.day(dt(5, 4, 0), 3).map(|s| s.total_consumption),
+            Some(250.0)
+        );
+        // older than everything held so ignored
+        history.add(summary(1, 100.0));
+        assert_eq!(history.day(dt(5, 4, 0), 4), None);
+    }
+
+    #[test]
+    fn averages_over_days_with_summaries() {
+        let mut history = SummaryHistory::<30>::new();
+        history.add(summary(1, 900.0));
+        history.add(summary(8, 1000.0));
+        history.add(summary(9, 2000.0));
+        // today is not included
+        history.add(summary(10, 5000.0));
+        let today = dt(10, 4, 0);
+        assert_eq!(history.average_total(today, 7), Some(1500.0));
+        assert_eq!(history.average_total(today, 30), Some(1300.0));
+        assert_eq!(history.average_total(dt(20, 4, 0), 7), None);
+    }
+
+    #[test]
+    fn day_lookup_tolerates_day_start_changes() {
+        let mut history = SummaryHistory::<7>::new();
+        history.add(summary(8, 1000.0));
+        // day start has since moved to 06:00
+        assert_eq!(
+            history.day(dt(10, 6, 0), 2).map(|s| s.total_consumption),
+            None
+        );
+        assert_eq!(
+            history.day(dt(10, 6, 0), 3).map(|s| s.total_consumption),
+            Some(1000.0)
+        );
+    }
+}
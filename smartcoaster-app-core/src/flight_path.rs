@@ -0,0 +1,237 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! The daily target "flight path" - the ideal cumulative consumption through the drinking window
+//! for the monitoring day.
+
+use crate::monitoring_day;
+use chrono::{NaiveDateTime, NaiveTime, TimeDelta};
+
+/// Shape of the ideal cumulative consumption curve across the drinking window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PacingProfile {
+    /// Even consumption rate across the window.
+    Linear,
+    /// Twice the average rate at the start of the window, tapering off to nothing at the end.
+    FrontLoaded,
+}
+
+impl PacingProfile {
+    /// Fraction of the target that should have been consumed once `progress` (0.0 - 1.0) of the
+    /// window has elapsed.
+    fn planned_fraction(&self, progress: f32) -> f32 {
+        let progress = progress.clamp(0.0, 1.0);
+        match self {
+            PacingProfile::Linear => progress,
+            PacingProfile::FrontLoaded => progress * (2.0 - progress),
+        }
+    }
+}
+
+impl TryFrom<u8> for PacingProfile {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Linear),
+            1 => Ok(Self::FrontLoaded),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<PacingProfile> for u8 {
+    fn from(value: PacingProfile) -> Self {
+        match value {
+            PacingProfile::Linear => 0,
+            PacingProfile::FrontLoaded => 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FlightPath {
+    window_start: NaiveDateTime,
+    window_end: NaiveDateTime,
+    profile: PacingProfile,
+    target: f32,
+}
+
+impl FlightPath {
+    /// Creates the flight path for the monitoring day that `now` falls within. The window start and
+    /// end are times of day and are placed within the monitoring day, so a window may run past
+    /// midnight.
+    pub fn new(
+        now: NaiveDateTime,
+        day_start: NaiveTime,
+        window_start: NaiveTime,
+        window_end: NaiveTime,
+        profile: PacingProfile,
+        target: f32,
+    ) -> Self {
+        let window_start = monitoring_day::time_in_day(now, day_start, window_start);
+        let window_end = monitoring_day::target_in_day(now, day_start, window_end);
+        Self {
+            // an end before the start leaves no window, so everything is due at the end time
+            window_start: window_start.min(window_end),
+            window_end,
+            profile,
+            target,
+        }
+    }
+
+    pub fn window_start(&self) -> NaiveDateTime {
+        self.window_start
+    }
+
+    pub fn window_end(&self) -> NaiveDateTime {
+        self.window_end
+    }
+
+    /// Consumption that should have been achieved by `time` to be on the flight path.
+    pub fn planned_consumption_at(&self, time: NaiveDateTime) -> f32 {
+        if time >= self.window_end {
+            return self.target;
+        }
+        if time <= self.window_start {
+            return 0.0;
+        }
+        let window_length = (self.window_end - self.window_start).num_seconds() as f32;
+        let elapsed = (time - self.window_start).num_seconds() as f32;
+        self.target * self.profile.planned_fraction(elapsed / window_length)
+    }
+
+    /// Hours from `now` until the end of the window. Negative once the window has ended.
+    pub fn hours_until_end(&self, now: NaiveDateTime) -> f32 {
+        (self.window_end - now).num_minutes() as f32 / 60.0
+    }
+
+    /// How far `consumed` is ahead of the flight path at `now`. Negative when behind.
+    pub fn difference_from_plan(&self, now: NaiveDateTime, consumed: f32) -> f32 {
+        consumed - self.planned_consumption_at(now)
+    }
+
+    /// Consumption to aim for over the next hour. This is what the flight path expects over the
+    /// hour plus an even share of any deficit (or surplus) across the hours left in the window.
+    pub fn hourly_target(&self, now: NaiveDateTime, consumed: f32) -> f32 {
+        let hours_left = f32::max(self.hours_until_end(now), 1.0);
+        let expected_over_next_hour = self.planned_consumption_at(now + TimeDelta::hours(1))
+            - self.planned_consumption_at(now);
+        let deficit = -self.difference_from_plan(now, consumed);
+        f32::max(expected_over_next_hour + deficit / hours_left, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn dt(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 3, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn path(now: NaiveDateTime, profile: PacingProfile) -> FlightPath {
+        // 08:00 to 20:00 window, day starting at 04:00
+        FlightPath::new(now, time(4, 0), time(8, 0), time(20, 0), profile, 1200.0)
+    }
+
+    #[test]
+    fn nothing_planned_before_window() {
+        let p = path(dt(2, 6, 0), PacingProfile::Linear);
+        assert_eq!(p.planned_consumption_at(dt(2, 6, 0)), 0.0);
+        assert_eq!(p.planned_consumption_at(dt(2, 8, 0)), 0.0);
+        assert_eq!(p.hourly_target(dt(2, 6, 0), 0.0), 0.0);
+    }
+
+    #[test]
+    fn linear_path() {
+        let p = path(dt(2, 12, 0), PacingProfile::Linear);
+        assert_eq!(p.planned_consumption_at(dt(2, 14, 0)), 600.0);
+        assert_eq!(p.planned_consumption_at(dt(2, 20, 0)), 1200.0);
+        assert_eq!(p.planned_consumption_at(dt(2, 23, 0)), 1200.0);
+        assert_eq!(p.difference_from_plan(dt(2, 14, 0), 500.0), -100.0);
+    }
+
+    #[test]
+    fn front_loaded_path_is_ahead_of_linear() {
+        let p = path(dt(2, 12, 0), PacingProfile::FrontLoaded);
+        assert_eq!(p.planned_consumption_at(dt(2, 14, 0)), 900.0);
+        assert_eq!(p.planned_consumption_at(dt(2, 20, 0)), 1200.0);
+    }
+
+    #[test]
+    fn on_plan_hourly_target_follows_curve() {
+        let p = path(dt(2, 12, 0), PacingProfile::Linear);
+        assert_eq!(p.hourly_target(dt(2, 12, 0), 400.0), 100.0);
+    }
+
+    #[test]
+    fn deficit_is_spread_over_remaining_window() {
+        let p = path(dt(2, 12, 0), PacingProfile::Linear);
+        // 8 hours left and 400 ml behind => 50 ml/hour extra
+        assert_eq!(p.hourly_target(dt(2, 12, 0), 0.0), 150.0);
+    }
+
+    #[test]
+    fn last_hour_requires_remaining_volume() {
+        let p = path(dt(2, 19, 30), PacingProfile::Linear);
+        assert_eq!(p.hourly_target(dt(2, 19, 30), 1000.0), 200.0);
+        assert_eq!(p.hourly_target(dt(2, 21, 0), 1000.0), 200.0);
+    }
+
+    #[test]
+    fn target_never_negative_when_ahead() {
+        let p = path(dt(2, 12, 0), PacingProfile::Linear);
+        assert_eq!(p.hourly_target(dt(2, 12, 0), 1500.0), 0.0);
+    }
+
+    #[test]
+    fn window_past_midnight() {
+        let now = dt(3, 1, 0);
+        let p = FlightPath::new(
+            now,
+            time(6, 0),
+            time(18, 0),
+            time(2, 0),
+            PacingProfile::Linear,
+            800.0,
+        );
+        assert_eq!(p.window_start(), dt(2, 18, 0));
+        assert_eq!(p.window_end(), dt(3, 2, 0));
+        assert_eq!(p.planned_consumption_at(now), 700.0);
+        assert_eq!(p.hours_until_end(now), 1.0);
+    }
+
+    #[test]
+    fn inverted_window_is_all_due_at_end() {
+        let p = FlightPath::new(
+            dt(2, 12, 0),
+            time(4, 0),
+            time(20, 0),
+            time(10, 0),
+            PacingProfile::Linear,
+            1000.0,
+        );
+        assert_eq!(p.planned_consumption_at(dt(2, 9, 0)), 0.0);
+        assert_eq!(p.planned_consumption_at(dt(2, 10, 0)), 1000.0);
+    }
+}
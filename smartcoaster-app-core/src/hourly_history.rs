@@ -0,0 +1,183 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Consumption per clock hour over the most recent hours, used for the hourly bar graph.
+
+use chrono::{NaiveDateTime, TimeDelta, Timelike};
+
+/// Start of the clock hour that `time` falls within.
+pub fn hour_start(time: NaiveDateTime) -> NaiveDateTime {
+    time.with_minute(0)
+        .and_then(|t| t.with_second(0))
+        .and_then(|t| t.with_nanosecond(0))
+        .unwrap_or(time)
+}
+
+/// Bucket for `timestamp`, counted in hours back from the hour starting at `current_hour`.
+/// `None` if the timestamp is in the future or more than `bucket_count - 1` hours ago.
+pub fn bucket_index(
+    current_hour: NaiveDateTime,
+    timestamp: NaiveDateTime,
+    bucket_count: usize,
+) -> Option<usize> {
+    if timestamp >= current_hour + TimeDelta::hours(1) {
+        return None;
+    }
+    let hours_ago = (current_hour - hour_start(timestamp)).num_hours();
+    usize::try_from(hours_ago)
+        .ok()
+        .filter(|index| *index < bucket_count)
+}
+
+/// Consumption totals for the current hour and the `N - 1` hours before it.
+pub struct HourlyHistory<const N: usize> {
+    current_hour: NaiveDateTime,
+    /// Indexed by hours ago, so the current hour is first.
+    totals: [f32; N],
+}
+
+impl<const N: usize> HourlyHistory<N> {
+    pub fn new(now: NaiveDateTime) -> Self {
+        Self {
+            current_hour: hour_start(now),
+            totals: [0.0; N],
+        }
+    }
+
+    /// Start of the hour in the first bucket.
+    pub fn oldest_hour(&self) -> NaiveDateTime {
+        self.current_hour - TimeDelta::hours(N as i64 - 1)
+    }
+
+    pub fn clear(&mut self) {
+        self.totals = [0.0; N];
+    }
+
+    /// Moves the buckets on so the current hour is the one `now` falls within. Returns true if the
+    /// hour changed.
+    pub fn advance(&mut self, now: NaiveDateTime) -> bool {
+        let new_hour = hour_start(now);
+        if new_hour <= self.current_hour {
+            return false;
+        }
+        let shift = (new_hour - self.current_hour).num_hours() as usize;
+        if shift >= N {
+            self.totals = [0.0; N];
+        } else {
+            self.totals.copy_within(0..N - shift, shift);
+            self.totals[..shift].fill(0.0);
+        }
+        self.current_hour = new_hour;
+        true
+    }
+
+    /// Adds consumption to the bucket for `timestamp`. Consumption outside the buckets is ignored.
+    pub fn add(&mut self, timestamp: NaiveDateTime, consumption: f32) {
+        if let Some(index) = bucket_index(self.current_hour, timestamp, N) {
+            self.totals[index] += consumption;
+        }
+    }
+
+    pub fn hours_ago(&self, hours_ago: usize) -> f32 {
+        self.totals.get(hours_ago).copied().unwrap_or(0.0)
+    }
+
+    /// Total of the buckets for the hours starting from `start` up to, but not including, `end`.
+    /// Only whole buckets are counted, so times are effectively rounded down to the hour.
+    pub fn consumed_between(&self, start: NaiveDateTime, end: NaiveDateTime) -> f32 {
+        let start = hour_start(start);
+        self.totals
+            .iter()
+            .enumerate()
+            .filter(|(hours_ago, _)| {
+                let bucket_hour = self.current_hour - TimeDelta::hours(*hours_ago as i64);
+                bucket_hour >= start && bucket_hour < end
+            })
+            .map(|(_, total)| total)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn dt(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 3, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn hour_start_truncates() {
+        assert_eq!(hour_start(dt(2, 13, 59)), dt(2, 13, 0));
+        assert_eq!(hour_start(dt(2, 13, 0)), dt(2, 13, 0));
+    }
+
+    #[test]
+    fn bucket_counts_back_from_current_hour() {
+        let current = dt(2, 13, 0);
+        assert_eq!(bucket_index(current, dt(2, 13, 45), 12), Some(0));
+        assert_eq!(bucket_index(current, dt(2, 12, 59), 12), Some(1));
+        assert_eq!(bucket_index(current, dt(2, 2, 0), 12), Some(11));
+        assert_eq!(bucket_index(current, dt(2, 1, 59), 12), None);
+        assert_eq!(bucket_index(current, dt(2, 14, 0), 12), None);
+        // across midnight
+        assert_eq!(bucket_index(dt(3, 1, 0), dt(2, 23, 30), 12), Some(2));
+    }
+
+    #[test]
+    fn consumption_is_bucketed() {
+        let mut history = HourlyHistory::<12>::new(dt(2, 13, 10));
+        history.add(dt(2, 13, 5), 100.0);
+        history.add(dt(2, 13, 8), 50.0);
+        history.add(dt(2, 11, 30), 200.0);
+        history.add(dt(1, 11, 30), 300.0);
+        assert_eq!(history.hours_ago(0), 150.0);
+        assert_eq!(history.hours_ago(1), 0.0);
+        assert_eq!(history.hours_ago(2), 200.0);
+        assert_eq!(history.hours_ago(12), 0.0);
+        assert_eq!(history.oldest_hour(), dt(2, 2, 0));
+    }
+
+    #[test]
+    fn advancing_shifts_buckets() {
+        let mut history = HourlyHistory::<12>::new(dt(2, 13, 10));
+        history.add(dt(2, 13, 5), 100.0);
+        history.add(dt(2, 3, 0), 20.0);
+        assert!(!history.advance(dt(2, 13, 59)));
+        assert!(history.advance(dt(2, 15, 0)));
+        assert_eq!(history.hours_ago(0), 0.0);
+        assert_eq!(history.hours_ago(2), 100.0);
+        // the oldest bucket has dropped off
+        assert_eq!(history.hours_ago(11), 0.0);
+
+        assert!(history.advance(dt(3, 15, 0)));
+        assert_eq!(history.hours_ago(2), 0.0);
+    }
+
+    #[test]
+    fn consumed_between_counts_whole_hours() {
+        let mut history = HourlyHistory::<24>::new(dt(2, 13, 10));
+        history.add(dt(2, 3, 30), 50.0);
+        history.add(dt(2, 8, 15), 100.0);
+        history.add(dt(2, 12, 0), 200.0);
+        history.add(dt(2, 13, 5), 300.0);
+        assert_eq!(history.consumed_between(dt(2, 4, 0), dt(2, 12, 0)), 100.0);
+        assert_eq!(history.consumed_between(dt(2, 4, 0), dt(2, 12, 1)), 300.0);
+        assert_eq!(history.consumed_between(dt(2, 3, 45), dt(2, 14, 0)), 650.0);
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Helpers for working with a monitoring "day" that starts at a configurable time rather than at
+//! midnight. For example, with a day start of 04:00, consumption at 01:30 on the 2nd belongs to the
+//! day that started at 04:00 on the 1st.
+
+use chrono::{NaiveDateTime, NaiveTime, TimeDelta};
+
+/// Returns the start of the monitoring day that `now` falls within.
+pub fn day_start_for(now: NaiveDateTime, day_start: NaiveTime) -> NaiveDateTime {
+    let start_today = now.date().and_time(day_start);
+    if now >= start_today {
+        start_today
+    } else {
+        start_today - TimeDelta::days(1)
+    }
+}
+
+/// Returns the end of the monitoring day that `now` falls within. This is also the start of the
+/// following monitoring day.
+pub fn day_end_for(now: NaiveDateTime, day_start: NaiveTime) -> NaiveDateTime {
+    day_start_for(now, day_start) + TimeDelta::days(1)
+}
+
+/// True if both timestamps fall within the same monitoring day.
+pub fn is_same_day(a: NaiveDateTime, b: NaiveDateTime, day_start: NaiveTime) -> bool {
+    day_start_for(a, day_start) == day_start_for(b, day_start)
+}
+
+/// Hours elapsed since the start of the monitoring day that `now` falls within.
+pub fn hours_since_day_start(now: NaiveDateTime, day_start: NaiveTime) -> f32 {
+    (now - day_start_for(now, day_start)).num_seconds() as f32 / 3600.0
+}
+
+/// Returns when `time` occurs within the monitoring day that `now` falls within. A time earlier in
+/// the clock day than the day start is taken to be after midnight.
+pub fn time_in_day(now: NaiveDateTime, day_start: NaiveTime, time: NaiveTime) -> NaiveDateTime {
+    let start = day_start_for(now, day_start);
+    let occurrence = start.date().and_time(time);
+    if occurrence < start {
+        occurrence + TimeDelta::days(1)
+    } else {
+        occurrence
+    }
+}
+
+/// Returns when `target_time` occurs within the monitoring day that `now` falls within. A target
+/// time at or before the day start in the clock day is taken to be after midnight, at the end of
+/// the monitoring day.
+pub fn target_in_day(
+    now: NaiveDateTime,
+    day_start: NaiveTime,
+    target_time: NaiveTime,
+) -> NaiveDateTime {
+    let start = day_start_for(now, day_start);
+    let target = start.date().and_time(target_time);
+    if target <= start {
+        target + TimeDelta::days(1)
+    } else {
+        target
+    }
+}
+
+/// Hours from `now` until `target_time` within the current monitoring day. Negative once the target
+/// time has passed.
+pub fn hours_until_target(now: NaiveDateTime, day_start: NaiveTime, target_time: NaiveTime) -> f32 {
+    (target_in_day(now, day_start, target_time) - now).num_minutes() as f32 / 60.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn dt(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 3, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn midnight_day_start_matches_calendar_day() {
+        assert_eq!(day_start_for(dt(2, 0, 0), time(0, 0)), dt(2, 0, 0));
+        assert_eq!(day_start_for(dt(2, 23, 59), time(0, 0)), dt(2, 0, 0));
+        assert_eq!(day_end_for(dt(2, 12, 0), time(0, 0)), dt(3, 0, 0));
+    }
+
+    #[test]
+    fn day_start_is_inclusive() {
+        assert_eq!(day_start_for(dt(2, 4, 0), time(4, 0)), dt(2, 4, 0));
+        assert_eq!(day_start_for(dt(2, 3, 59), time(4, 0)), dt(1, 4, 0));
+    }
+
+    #[test]
+    fn after_midnight_belongs_to_previous_day() {
+        assert!(is_same_day(dt(1, 22, 0), dt(2, 1, 30), time(4, 0)));
+        assert!(!is_same_day(dt(2, 1, 30), dt(2, 4, 0), time(4, 0)));
+        assert!(!is_same_day(dt(1, 22, 0), dt(2, 1, 30), time(0, 0)));
+    }
+
+    #[test]
+    fn day_start_rolls_over_month_end() {
+        let end_of_feb = NaiveDate::from_ymd_opt(2025, 2, 28)
+            .unwrap()
+            .and_time(time(4, 0));
+        assert_eq!(day_start_for(dt(1, 2, 0), time(4, 0)), end_of_feb);
+    }
+
+    #[test]
+    fn hours_since_start() {
+        assert_eq!(hours_since_day_start(dt(2, 6, 30), time(4, 0)), 2.5);
+        assert_eq!(hours_since_day_start(dt(3, 2, 0), time(4, 0)), 22.0);
+        assert_eq!(hours_since_day_start(dt(2, 4, 0), time(4, 0)), 0.0);
+    }
+
+    #[test]
+    fn target_before_midnight() {
+        assert_eq!(
+            hours_until_target(dt(2, 12, 0), time(4, 0), time(18, 0)),
+            6.0
+        );
+        assert_eq!(
+            hours_until_target(dt(2, 19, 0), time(4, 0), time(18, 0)),
+            -1.0
+        );
+        // after midnight the target has passed for this monitoring day
+        assert_eq!(
+            hours_until_target(dt(3, 1, 0), time(4, 0), time(18, 0)),
+            -7.0
+        );
+    }
+
+    #[test]
+    fn target_after_midnight() {
+        assert_eq!(
+            target_in_day(dt(2, 22, 0), time(6, 0), time(2, 0)),
+            dt(3, 2, 0)
+        );
+        assert_eq!(
+            hours_until_target(dt(2, 22, 0), time(6, 0), time(2, 0)),
+            4.0
+        );
+        assert_eq!(
+            hours_until_target(dt(3, 1, 30), time(6, 0), time(2, 0)),
+            0.5
+        );
+        assert_eq!(
+            hours_until_target(dt(3, 3, 0), time(6, 0), time(2, 0)),
+            -1.0
+        );
+    }
+
+    #[test]
+    fn time_equal_to_day_start_is_start_of_day() {
+        assert_eq!(
+            time_in_day(dt(2, 12, 0), time(4, 0), time(4, 0)),
+            dt(2, 4, 0)
+        );
+        assert_eq!(
+            time_in_day(dt(2, 12, 0), time(4, 0), time(3, 0)),
+            dt(3, 3, 0)
+        );
+    }
+
+    #[test]
+    fn target_equal_to_day_start_is_end_of_day() {
+        assert_eq!(
+            target_in_day(dt(2, 12, 0), time(4, 0), time(4, 0)),
+            dt(3, 4, 0)
+        );
+        assert_eq!(
+            target_in_day(dt(2, 12, 0), time(0, 0), time(0, 0)),
+            dt(3, 0, 0)
+        );
+    }
+}
@@ -0,0 +1,197 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Time-since-last-drink reminders, with quiet hours during which no reminder is given.
+
+use crate::target_direction::TargetDirection;
+use chrono::{NaiveDateTime, NaiveTime, TimeDelta};
+
+/// How strongly the user should be reminded to drink.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReminderLevel {
+    NotDue,
+    /// The reminder interval has elapsed since the last drink.
+    Gentle,
+    /// Twice the reminder interval has elapsed since the last drink.
+    Insistent,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReminderSchedule {
+    interval: TimeDelta,
+    quiet_start: NaiveTime,
+    quiet_end: NaiveTime,
+}
+
+impl ReminderSchedule {
+    /// An `interval_minutes` of zero disables reminders. Quiet hours run from `quiet_start` up to
+    /// `quiet_end` and may span midnight. Equal start and end times mean there are no quiet hours.
+    pub fn new(interval_minutes: u8, quiet_start: NaiveTime, quiet_end: NaiveTime) -> Self {
+        Self {
+            interval: TimeDelta::minutes(interval_minutes as i64),
+            quiet_start,
+            quiet_end,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.interval > TimeDelta::zero()
+    }
+
+    /// True if `time` falls within the quiet hours.
+    pub fn is_quiet(&self, time: NaiveTime) -> bool {
+        if self.quiet_start == self.quiet_end {
+            false
+        } else if self.quiet_start < self.quiet_end {
+            time >= self.quiet_start && time < self.quiet_end
+        } else {
+            time >= self.quiet_start || time < self.quiet_end
+        }
+    }
+
+    /// The most recent end of quiet hours at or before `now`, if there are quiet hours.
+    fn last_quiet_end(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        if self.quiet_start == self.quiet_end {
+            return None;
+        }
+        let end_today = now.date().and_time(self.quiet_end);
+        if end_today <= now {
+            Some(end_today)
+        } else {
+            Some(end_today - TimeDelta::days(1))
+        }
+    }
+
+    /// Reminder level at `now` given the time of the last drink. Time spent in quiet hours does not
+    /// count towards a reminder, so the interval restarts once quiet hours end.
+    pub fn level(&self, now: NaiveDateTime, last_drink: NaiveDateTime) -> ReminderLevel {
+        if !self.is_enabled() || self.is_quiet(now.time()) {
+            return ReminderLevel::NotDue;
+        }
+        let counting_from = match self.last_quiet_end(now) {
+            Some(quiet_end) => last_drink.max(quiet_end),
+            None => last_drink,
+        };
+        let elapsed = now - counting_from;
+        if elapsed >= self.interval * 2 {
+            ReminderLevel::Insistent
+        } else if elapsed >= self.interval {
+            ReminderLevel::Gentle
+        } else {
+            ReminderLevel::NotDue
+        }
+    }
+
+    /// Reminder level for a target in `direction`. Reminders are to drink more, so there are none
+    /// while the target is a maximum to stay under.
+    pub fn level_for_target(
+        &self,
+        direction: TargetDirection,
+        now: NaiveDateTime,
+        last_drink: NaiveDateTime,
+    ) -> ReminderLevel {
+        match direction {
+            TargetDirection::Minimum => self.level(now, last_drink),
+            TargetDirection::Maximum => ReminderLevel::NotDue,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn dt(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 3, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn schedule() -> ReminderSchedule {
+        // hourly reminders, quiet from 22:00 to 07:00
+        ReminderSchedule::new(60, time(22, 0), time(7, 0))
+    }
+
+    #[test]
+    fn escalates_with_time_since_drink() {
+        let s = schedule();
+        assert_eq!(s.level(dt(2, 10, 59), dt(2, 10, 0)), ReminderLevel::NotDue);
+        assert_eq!(s.level(dt(2, 11, 0), dt(2, 10, 0)), ReminderLevel::Gentle);
+        assert_eq!(s.level(dt(2, 11, 59), dt(2, 10, 0)), ReminderLevel::Gentle);
+        assert_eq!(
+            s.level(dt(2, 12, 0), dt(2, 10, 0)),
+            ReminderLevel::Insistent
+        );
+    }
+
+    #[test]
+    fn disabled_when_interval_is_zero() {
+        let s = ReminderSchedule::new(0, time(22, 0), time(7, 0));
+        assert!(!s.is_enabled());
+        assert_eq!(s.level(dt(2, 18, 0), dt(2, 8, 0)), ReminderLevel::NotDue);
+    }
+
+    #[test]
+    fn quiet_hours_span_midnight() {
+        let s = schedule();
+        assert!(s.is_quiet(time(22, 0)));
+        assert!(s.is_quiet(time(3, 0)));
+        assert!(!s.is_quiet(time(7, 0)));
+        assert!(!s.is_quiet(time(21, 59)));
+        assert_eq!(s.level(dt(2, 23, 0), dt(2, 19, 0)), ReminderLevel::NotDue);
+    }
+
+    #[test]
+    fn quiet_hours_within_day() {
+        let s = ReminderSchedule::new(30, time(13, 0), time(14, 0));
+        assert!(s.is_quiet(time(13, 30)));
+        assert!(!s.is_quiet(time(14, 0)));
+        assert!(!s.is_quiet(time(12, 59)));
+    }
+
+    #[test]
+    fn no_quiet_hours_when_start_equals_end() {
+        let s = ReminderSchedule::new(60, time(0, 0), time(0, 0));
+        assert!(!s.is_quiet(time(3, 0)));
+        assert_eq!(s.level(dt(3, 3, 0), dt(2, 23, 0)), ReminderLevel::Insistent);
+    }
+
+    #[test]
+    fn interval_restarts_after_quiet_hours() {
+        let s = schedule();
+        // last drink the previous evening
+        assert_eq!(s.level(dt(3, 7, 30), dt(2, 21, 0)), ReminderLevel::NotDue);
+        assert_eq!(s.level(dt(3, 8, 0), dt(2, 21, 0)), ReminderLevel::Gentle);
+        assert_eq!(s.level(dt(3, 9, 0), dt(2, 21, 0)), ReminderLevel::Insistent);
+    }
+
+    #[test]
+    fn no_reminders_for_a_maximum_target() {
+        let s = schedule();
+        assert_eq!(
+            s.level_for_target(TargetDirection::Minimum, dt(2, 12, 0), dt(2, 10, 0)),
+            ReminderLevel::Insistent
+        );
+        assert_eq!(
+            s.level_for_target(TargetDirection::Maximum, dt(2, 12, 0), dt(2, 10, 0)),
+            ReminderLevel::NotDue
+        );
+    }
+}
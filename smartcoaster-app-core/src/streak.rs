@@ -0,0 +1,175 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Counting consecutive monitoring days on which the daily target was achieved.
+
+use crate::daily_summary::DailySummary;
+use chrono::{NaiveDateTime, TimeDelta};
+
+/// Keeps the streak of consecutive days on target from daily summaries, which must be added in
+/// time order.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StreakCounter {
+    /// Start of the most recent completed day on target.
+    last_achieved_day: Option<NaiveDateTime>,
+    streak: u32,
+}
+
+impl StreakCounter {
+    pub const fn new() -> Self {
+        Self {
+            last_achieved_day: None,
+            streak: 0,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Adds a completed day. A day that missed its target ends the streak, and a day on target
+    /// that doesn't follow the last one starts a new streak.
+    pub fn add_day(&mut self, summary: &DailySummary) {
+        if !summary.target_achieved {
+            self.clear();
+            return;
+        }
+        self.streak = if self.follows_last_achieved(summary.day_start) {
+            self.streak + 1
+        } else {
+            1
+        };
+        self.last_achieved_day = Some(summary.day_start);
+    }
+
+    /// Streak of completed days on target, ending with the day before the one starting at
+    /// `today_start`. Today is not included as it is still in progress.
+    pub fn completed_streak(&self, today_start: NaiveDateTime) -> u32 {
+        if self.follows_last_achieved(today_start) {
+            self.streak
+        } else {
+            0
+        }
+    }
+
+    /// Days are compared by date, so a change to the day start time doesn't break the streak.
+    fn follows_last_achieved(&self, day_start: NaiveDateTime) -> bool {
+        self.last_achieved_day
+            .is_some_and(|last| day_start.date() - last.date() == TimeDelta::days(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::target_direction::TargetDirection;
+    use chrono::NaiveDate;
+
+    fn day_start(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 3, day)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    /// Adds the summary for `day` with the day starting at 04:00.
+    fn add_day(c: &mut StreakCounter, day: u32, total: f32, target: Option<f32>) {
+        add_day_starting(c, day_start(day, 4), total, target);
+    }
+
+    fn add_day_starting(
+        c: &mut StreakCounter,
+        start: NaiveDateTime,
+        total: f32,
+        target: Option<f32>,
+    ) {
+        let mut summary = DailySummary::new(start);
+        summary.add_drink(start + TimeDelta::hours(8), total);
+        summary.complete(target, TargetDirection::Minimum);
+        c.add_day(&summary);
+    }
+
+    #[test]
+    fn consecutive_days_on_target() {
+        let mut c = StreakCounter::new();
+        add_day(&mut c, 1, 2000.0, Some(2000.0));
+        add_day(&mut c, 2, 2100.0, Some(2000.0));
+        add_day(&mut c, 3, 2500.0, Some(2000.0));
+        assert_eq!(c.completed_streak(day_start(4, 4)), 3);
+    }
+
+    #[test]
+    fn streak_is_not_capped() {
+        let mut c = StreakCounter::new();
+        let first = day_start(1, 4);
+        for day in 0..60 {
+            add_day_starting(&mut c, first + TimeDelta::days(day), 2000.0, Some(2000.0));
+        }
+        assert_eq!(c.completed_streak(first + TimeDelta::days(60)), 60);
+    }
+
+    #[test]
+    fn missed_day_resets_streak() {
+        let mut c = StreakCounter::new();
+        add_day(&mut c, 1, 2000.0, Some(2000.0));
+        add_day(&mut c, 2, 1500.0, Some(2000.0));
+        add_day(&mut c, 3, 2000.0, Some(2000.0));
+        assert_eq!(c.completed_streak(day_start(4, 4)), 1);
+    }
+
+    #[test]
+    fn gap_in_summaries_breaks_streak() {
+        let mut c = StreakCounter::new();
+        add_day(&mut c, 1, 2000.0, Some(2000.0));
+        add_day(&mut c, 3, 2000.0, Some(2000.0));
+        assert_eq!(c.completed_streak(day_start(4, 4)), 1);
+        let mut c = StreakCounter::new();
+        add_day(&mut c, 1, 2000.0, Some(2000.0));
+        add_day(&mut c, 2, 2000.0, Some(2000.0));
+        assert_eq!(c.completed_streak(day_start(4, 4)), 0);
+    }
+
+    #[test]
+    fn today_is_not_counted() {
+        let mut c = StreakCounter::new();
+        add_day(&mut c, 1, 2000.0, Some(2000.0));
+        assert_eq!(c.completed_streak(day_start(1, 4)), 0);
+        assert_eq!(c.completed_streak(day_start(2, 4)), 1);
+    }
+
+    #[test]
+    fn day_start_time_change_keeps_streak() {
+        let mut c = StreakCounter::new();
+        add_day(&mut c, 1, 2000.0, Some(2000.0));
+        add_day_starting(&mut c, day_start(2, 6), 2000.0, Some(2000.0));
+        assert_eq!(c.completed_streak(day_start(3, 2)), 2);
+    }
+
+    #[test]
+    fn no_daily_target_is_not_achieved() {
+        let mut c = StreakCounter::new();
+        add_day(&mut c, 1, 2000.0, Some(2000.0));
+        add_day(&mut c, 2, 2000.0, None);
+        add_day(&mut c, 3, 2000.0, Some(0.0));
+        assert_eq!(c.completed_streak(day_start(4, 4)), 0);
+    }
+
+    #[test]
+    fn clear_resets_streak() {
+        let mut c = StreakCounter::new();
+        add_day(&mut c, 1, 2000.0, Some(2000.0));
+        c.clear();
+        assert_eq!(c.completed_streak(day_start(2, 4)), 0);
+    }
+}
@@ -0,0 +1,89 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+/// Whether the consumption target is a goal to reach (e.g. water) or a ceiling to stay under
+/// (e.g. coffee or alcohol).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TargetDirection {
+    Minimum,
+    Maximum,
+}
+
+impl TargetDirection {
+    /// How far `consumed` is on the good side of `target`. Negative values mean under a minimum
+    /// target or over a maximum target.
+    pub fn margin(&self, consumed: f32, target: f32) -> f32 {
+        match self {
+            TargetDirection::Minimum => consumed - target,
+            TargetDirection::Maximum => target - consumed,
+        }
+    }
+
+    /// True if `consumed` satisfies the target.
+    pub fn is_achieved(&self, consumed: f32, target: f32) -> bool {
+        self.margin(consumed, target) >= 0.0
+    }
+}
+
+impl TryFrom<u8> for TargetDirection {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Minimum),
+            1 => Ok(Self::Maximum),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<TargetDirection> for u8 {
+    fn from(value: TargetDirection) -> Self {
+        match value {
+            TargetDirection::Minimum => 0,
+            TargetDirection::Maximum => 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minimum_target() {
+        assert_eq!(TargetDirection::Minimum.margin(150.0, 200.0), -50.0);
+        assert!(!TargetDirection::Minimum.is_achieved(1999.0, 2000.0));
+        assert!(TargetDirection::Minimum.is_achieved(2000.0, 2000.0));
+    }
+
+    #[test]
+    fn maximum_target() {
+        assert_eq!(TargetDirection::Maximum.margin(150.0, 200.0), 50.0);
+        assert!(TargetDirection::Maximum.is_achieved(0.0, 300.0));
+        assert!(TargetDirection::Maximum.is_achieved(300.0, 300.0));
+        assert!(!TargetDirection::Maximum.is_achieved(301.0, 300.0));
+    }
+
+    #[test]
+    fn setting_round_trip() {
+        for direction in [TargetDirection::Minimum, TargetDirection::Maximum] {
+            assert_eq!(
+                TargetDirection::try_from(u8::from(direction)),
+                Ok(direction)
+            );
+        }
+        assert_eq!(TargetDirection::try_from(2), Err(()));
+    }
+}
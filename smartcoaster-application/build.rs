@@ -37,9 +37,20 @@ fn main() {
         .unwrap()
         .write_all(include_bytes!("memory.x"))
         .unwrap();
+
+    // Generate the shared flash layout. An invalid partition table fails the build here rather
+    // than producing images that disagree on where things are.
+    let layout = smartcoaster_partitions::board_layout();
+    if let Err(e) = layout.validate() {
+        panic!("Invalid flash partition table: {e}");
+    }
     File::create(out.join("memory_common.x"))
         .unwrap()
-        .write_all(include_bytes!("../memory_common.x"))
+        .write_all(layout.linker_script().as_bytes())
+        .unwrap();
+    File::create(out.join("partitions.rs"))
+        .unwrap()
+        .write_all(layout.rust_constants().as_bytes())
         .unwrap();
     println!("cargo:rustc-link-search={}", out.display());
 
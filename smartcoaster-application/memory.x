@@ -1,6 +1,7 @@
 /** *** CRITICAL WARNING ***
  * This file uses values from memory_common.x to ensure alignment between
- * the bootloader and the application.
+ * the bootloader and the application. memory_common.x is generated by the
+ * build script from the partition table in smartcoaster-partitions.
  *
  * Consistency between the bootloader and the application is important
  * for the correct operation of the application, bootloader and update mechanism.
@@ -12,8 +13,8 @@ MEMORY
 {
     BOOT2               : ORIGIN = _flash_start,                        LENGTH = _boot2_size
     BOOTLOADER_STATE    : ORIGIN = _bootloader_state_start,             LENGTH = _bootloader_state_size
-    FLASH               : ORIGIN = _bootloader_active_partition_start,  LENGTH = _bootloader_active_partition_size
-    DFU                 : ORIGIN = _bootloader_update_partition_start,  LENGTH = _bootloader_update_partition_size
+    FLASH               : ORIGIN = _active_start,                       LENGTH = _active_size
+    DFU                 : ORIGIN = _dfu_start,                          LENGTH = _dfu_size
     NVM                 : ORIGIN = _app_nvm_start,                      LENGTH = _app_nvm_total_size
     RAM                 : ORIGIN = _ram_start,                          LENGTH = _ram_size
 }
@@ -13,28 +13,40 @@
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use crate::application::application_state::{
-    ApplicationState, CalibrationStateSubstates, ConfirmationId,
+    ApplicationState, CalibrationStateSubstates, ConfirmationId, FirstRunStep,
 };
 use crate::application::messaging::ApplicationData::MonitoringUpdate;
 use crate::application::messaging::{
     ApplicationChannelPublisher, ApplicationData, ApplicationMessage,
 };
 use crate::drink_monitor::messaging::DrinkMonitorChannelSubscriber;
+use crate::error_log::{log_error, ErrorCode, ErrorLogData, ErrorLogReader, ErrorModule};
 use crate::hmi::messaging::HmiMessage::PushButtonPressed;
 use crate::hmi::messaging::{
     HmiChannelSubscriber, HmiMessage, UiActionChannelSubscriber, UiRequestMessage,
 };
-use crate::storage::settings::SettingsAccessorId;
+use crate::hmi::screens::settings_menu::monitoring_options::MonitoringTargetPeriodOptions;
+use crate::hmi::screens::settings_screens::error_log::ERROR_LOG_SCREEN_RECORDS;
+use crate::storage::factory_reset::{request_factory_reset, FactoryResetOptions};
+use crate::storage::historical::messaging::HistoricalLogChannel;
+use crate::storage::settings::accessor::FlashSettingsAccessor;
+use crate::storage::settings::{SettingValue, SettingsAccessor, SettingsAccessorId};
+use crate::storage::status::{read_partition_status, NvmPartition};
 use crate::weight::WeighingSystem;
-use crate::Heap;
 use defmt::{debug, trace, warn, Debug2Format};
 use embassy_futures::select::{select, select3, Either, Either3};
+use embassy_sync::pubsub::PubSubChannel;
 use embassy_time::{Duration, Instant, Timer};
+use embedded_alloc::LlffHeap as Heap;
+use heapless::Deque;
+
+static ERROR_LOG_READ_CHANNEL: HistoricalLogChannel = PubSubChannel::new();
 
 pub struct ApplicationManager<WS> {
     app_publisher: ApplicationChannelPublisher<'static>,
     weighing_system: WS,
     heap: &'static Heap,
+    error_log_reader: ErrorLogReader,
 }
 
 impl<WS> ApplicationManager<WS>
@@ -52,6 +64,7 @@ where
             app_publisher,
             weighing_system,
             heap,
+            error_log_reader: ErrorLogReader::new(&ERROR_LOG_READ_CHANNEL),
         }
     }
 
@@ -61,6 +74,10 @@ where
             Debug2Format(&hmi_message)
         );
 
+        if hmi_message == HmiMessage::FactoryResetCombination {
+            self.boot_factory_reset().await;
+        }
+
         if self.app_publisher.is_full() {
             warn!("Application channel is full during HMI message send - skipping input");
             return;
@@ -74,7 +91,8 @@ where
         while hmi_subscriber.try_next_message_pure().is_some() {}
     }
 
-    async fn manage_error(&mut self, message: &'static str) -> ! {
+    async fn manage_error(&mut self, code: ErrorCode, message: &'static str) -> ! {
+        log_error(ErrorLogData::new(ErrorModule::Application, code));
         self.app_publisher
             .publish(ApplicationMessage::ApplicationStateUpdate(
                 ApplicationState::ErrorScreenWithMessage(message),
@@ -120,7 +138,11 @@ where
 
         self.clear_out_hmi_rx(&mut hmi_subscriber).await;
 
-        let mut next_state = ApplicationState::Monitoring;
+        let mut next_state = if Self::first_run_setup_required().await {
+            ApplicationState::FirstRunSetup(FirstRunStep::Welcome)
+        } else {
+            ApplicationState::Monitoring
+        };
         loop {
             match next_state {
                 ApplicationState::Startup | ApplicationState::ErrorScreenWithMessage(_) => {}
@@ -180,6 +202,16 @@ where
                         .heap_status_screen(&mut ui_action_receiver, &mut hmi_subscriber)
                         .await
                 }
+                ApplicationState::ErrorLog => {
+                    next_state = self
+                        .error_log_screen(&mut ui_action_receiver, &mut hmi_subscriber)
+                        .await
+                }
+                ApplicationState::NvmStatus => {
+                    next_state = self
+                        .nvm_status_screen(&mut ui_action_receiver, &mut hmi_subscriber)
+                        .await
+                }
                 ApplicationState::Calibration => {
                     match self
                         .weighing_calibration_sequence(&mut hmi_subscriber)
@@ -189,7 +221,11 @@ where
                             self.clear_out_hmi_rx(&mut hmi_subscriber).await;
                         }
                         Err(_) => {
-                            self.manage_error("Scale calibration failed").await;
+                            self.manage_error(
+                                ErrorCode::ScaleCalibrationFailed,
+                                "Scale calibration failed",
+                            )
+                            .await;
                         }
                     }
                     next_state = ApplicationState::Settings;
@@ -208,11 +244,132 @@ where
                         .about_screen(&mut ui_action_receiver, &mut hmi_subscriber)
                         .await;
                 }
+                ApplicationState::FactoryReset => self.factory_reset_screen().await,
+                ApplicationState::FirstRunSetup(_) => {
+                    next_state = self
+                        .first_run_setup(&mut ui_action_receiver, &mut hmi_subscriber)
+                        .await;
+                }
             }
             debug!("Changing to next_state: {:?}", next_state);
         }
     }
 
+    /// Setup is needed after a fresh flash or a factory reset, when the completion flag is missing.
+    /// Devices set up before the flag existed have it added by the settings schema migration.
+    async fn first_run_setup_required() -> bool {
+        FlashSettingsAccessor::new()
+            .get_setting(SettingsAccessorId::SystemSetupComplete)
+            .await
+            .is_none()
+    }
+
+    async fn mark_first_run_setup_complete() {
+        FlashSettingsAccessor::new()
+            .save_setting(
+                SettingsAccessorId::SystemSetupComplete,
+                SettingValue::SmallUInt(1),
+            )
+            .await
+            .unwrap_or_else(|e| warn!("Failed to save setup completion - {}", e));
+    }
+
+    /// Walks through the settings needed before monitoring: date and time, scale calibration,
+    /// target type and value, then the day start time. Calibration is skipped if it was kept over
+    /// a factory reset. Cancelling a step leaves that setting at its default.
+    async fn first_run_setup(
+        &mut self,
+        ui_action_subscriber: &mut UiActionChannelSubscriber<'_>,
+        hmi_subscriber: &mut HmiChannelSubscriber<'_>,
+    ) -> ApplicationState {
+        self.first_run_step_screen(ui_action_subscriber, hmi_subscriber, FirstRunStep::Welcome)
+            .await;
+
+        self.set_date_time_screen(
+            ui_action_subscriber,
+            hmi_subscriber,
+            ApplicationState::SetSystemDateTime,
+        )
+        .await;
+
+        let calibrated = FlashSettingsAccessor::new()
+            .get_setting(SettingsAccessorId::WeighingSystemCalibrationGradient)
+            .await
+            .is_some();
+        if !calibrated {
+            if self
+                .weighing_calibration_sequence(hmi_subscriber)
+                .await
+                .is_err()
+            {
+                self.manage_error(
+                    ErrorCode::ScaleCalibrationFailed,
+                    "Scale calibration failed",
+                )
+                .await;
+            }
+            self.clear_out_hmi_rx(hmi_subscriber).await;
+        }
+
+        let target_period = match self
+            .first_run_step_screen(
+                ui_action_subscriber,
+                hmi_subscriber,
+                FirstRunStep::TargetType,
+            )
+            .await
+        {
+            ApplicationState::FirstRunSetup(FirstRunStep::TargetValue(period)) => period,
+            _ => MonitoringTargetPeriodOptions::Daily,
+        };
+        self.first_run_step_screen(
+            ui_action_subscriber,
+            hmi_subscriber,
+            FirstRunStep::TargetValue(target_period),
+        )
+        .await;
+
+        self.time_entry_screen(
+            ui_action_subscriber,
+            hmi_subscriber,
+            SettingsAccessorId::MonitoringDayStartTime,
+        )
+        .await;
+
+        Self::mark_first_run_setup_complete().await;
+        self.first_run_step_screen(ui_action_subscriber, hmi_subscriber, FirstRunStep::Complete)
+            .await;
+        ApplicationState::Monitoring
+    }
+
+    async fn first_run_step_screen(
+        &mut self,
+        ui_action_subscriber: &mut UiActionChannelSubscriber<'_>,
+        hmi_subscriber: &mut HmiChannelSubscriber<'_>,
+        step: FirstRunStep,
+    ) -> ApplicationState {
+        self.update_application_state(ApplicationState::FirstRunSetup(step))
+            .await;
+        loop {
+            let ui_or_hmi = select(
+                ui_action_subscriber.next_message_pure(),
+                hmi_subscriber.next_message_pure(),
+            )
+            .await;
+
+            match ui_or_hmi {
+                Either::First(ui_action_message) => {
+                    if let UiRequestMessage::ChangeState(new_state) = ui_action_message {
+                        return new_state;
+                    }
+                }
+                Either::Second(hmi_message) => {
+                    self.publish_application_hmi_message(hmi_message).await;
+                }
+            }
+        }
+    }
+
     /// Run the weight scale calibration sequence
     async fn weighing_calibration_sequence(
         &mut self,
@@ -261,6 +418,10 @@ where
 
             match ui_or_hmi {
                 Either3::First(ui_action_message) => {
+                    if let UiRequestMessage::SnoozeReminder() = ui_action_message {
+                        self.app_publisher
+                            .publish_immediate(ApplicationMessage::SnoozeReminder);
+                    }
                     if let UiRequestMessage::ChangeState(new_state) = ui_action_message {
                         return new_state;
                     }
@@ -321,7 +482,10 @@ where
                                 )),
                             );
                         } // just send on weight for now so it updates on screen
-                        Err(_) => self.manage_error("Scale reading failed").await,
+                        Err(_) => {
+                            self.manage_error(ErrorCode::ScaleReadFailed, "Scale reading failed")
+                                .await
+                        }
                     }
                 }
             }
@@ -373,6 +537,8 @@ where
                             );
                         }
                         UiRequestMessage::ClearHistoricalConsumptionLog() => {}
+                        UiRequestMessage::FactoryReset(_) => {}
+                        UiRequestMessage::SnoozeReminder() => {}
                     }
                 }
                 Either::Second(hmi_message) => {
@@ -471,6 +637,113 @@ where
         }
     }
 
+    async fn error_log_screen(
+        &mut self,
+        ui_action_subscriber: &mut UiActionChannelSubscriber<'_>,
+        hmi_subscriber: &mut HmiChannelSubscriber<'_>,
+    ) -> ApplicationState {
+        self.update_application_state(ApplicationState::ErrorLog)
+            .await;
+
+        let mut records = Deque::<_, ERROR_LOG_SCREEN_RECORDS>::new();
+        self.error_log_reader.start_read().await;
+        while let Some(record) = self.error_log_reader.next_record().await {
+            if records.is_full() {
+                records.pop_front();
+            }
+            let _ = records.push_back(record);
+        }
+        for record in records.iter().rev() {
+            self.app_publisher
+                .publish(ApplicationMessage::ApplicationDataUpdate(
+                    ApplicationData::ErrorLogRecord(*record),
+                ))
+                .await;
+        }
+
+        loop {
+            let ui_or_hmi = select(
+                ui_action_subscriber.next_message_pure(),
+                hmi_subscriber.next_message_pure(),
+            )
+            .await;
+
+            match ui_or_hmi {
+                Either::First(ui_action_message) => {
+                    if let UiRequestMessage::ChangeState(new_state) = ui_action_message {
+                        return new_state;
+                    }
+                }
+                Either::Second(hmi_message) => {
+                    self.publish_application_hmi_message(hmi_message).await;
+                }
+            }
+        }
+    }
+
+    /// Shown while the storage task resets the storage. It reboots the device when done.
+    async fn factory_reset_screen(&mut self) -> ! {
+        self.update_application_state(ApplicationState::FactoryReset)
+            .await;
+        loop {
+            Timer::after(Duration::from_secs(1)).await;
+        }
+    }
+
+    /// Starts the reset made with the button and encoder after boot. There is no confirmation, so
+    /// the reset screen is shown before the storage starts being erased.
+    async fn boot_factory_reset(&mut self) -> ! {
+        self.update_application_state(ApplicationState::FactoryReset)
+            .await;
+        request_factory_reset(FactoryResetOptions {
+            keep_calibration: false,
+        });
+        loop {
+            Timer::after(Duration::from_secs(1)).await;
+        }
+    }
+
+    async fn nvm_status_screen(
+        &mut self,
+        ui_action_subscriber: &mut UiActionChannelSubscriber<'_>,
+        hmi_subscriber: &mut HmiChannelSubscriber<'_>,
+    ) -> ApplicationState {
+        self.update_application_state(ApplicationState::NvmStatus)
+            .await;
+
+        for partition in NvmPartition::ALL {
+            match read_partition_status(partition).await {
+                Ok(status) => {
+                    self.app_publisher
+                        .publish(ApplicationMessage::ApplicationDataUpdate(
+                            ApplicationData::NvmPartitionStatus(status),
+                        ))
+                        .await;
+                }
+                Err(e) => warn!("Failed to read status of {}: {}", partition, e),
+            }
+        }
+
+        loop {
+            let ui_or_hmi = select(
+                ui_action_subscriber.next_message_pure(),
+                hmi_subscriber.next_message_pure(),
+            )
+            .await;
+
+            match ui_or_hmi {
+                Either::First(ui_action_message) => {
+                    if let UiRequestMessage::ChangeState(new_state) = ui_action_message {
+                        return new_state;
+                    }
+                }
+                Either::Second(hmi_message) => {
+                    self.publish_application_hmi_message(hmi_message).await;
+                }
+            }
+        }
+    }
+
     async fn time_entry_screen(
         &mut self,
         ui_action_subscriber: &mut UiActionChannelSubscriber<'_>,
@@ -548,6 +821,10 @@ where
                         self.app_publisher
                             .publish_immediate(ApplicationMessage::ClearHistoricalConsumptionLog);
                     }
+                    if let UiRequestMessage::FactoryReset(options) = ui_action_message {
+                        request_factory_reset(options);
+                        return ApplicationState::FactoryReset;
+                    }
                     if let UiRequestMessage::ChangeState(new_state) = ui_action_message {
                         return new_state;
                     }
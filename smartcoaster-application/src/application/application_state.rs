@@ -12,12 +12,25 @@
 // You should have received a copy of the GNU General Public License along with
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use crate::hmi::screens::settings_menu::monitoring_options::MonitoringTargetPeriodOptions;
+use crate::storage::factory_reset::FactoryResetOptions;
 use crate::storage::settings::SettingsAccessorId;
 use defmt::Format;
 
 #[derive(Clone, Copy, Debug, PartialEq, Format)]
 pub enum ConfirmationId {
     ClearHistoricalConsumptionLog,
+    FactoryReset(FactoryResetOptions),
+}
+
+/// Steps of the first run setup that have their own screen. Setting the date and time, calibrating
+/// and choosing the day start time use the same screens as the settings menu.
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub enum FirstRunStep {
+    Welcome,
+    TargetType,
+    TargetValue(MonitoringTargetPeriodOptions),
+    Complete,
 }
 
 #[derive(Debug, Format, Clone, Copy, PartialEq)]
@@ -28,6 +41,8 @@ pub enum ApplicationState {
     Settings,
     Monitoring,
     HeapStatus,
+    ErrorLog,
+    NvmStatus,
     Calibration,
     SetSystemDateTime,
     DateTimeEntry(SettingsAccessorId),
@@ -35,6 +50,8 @@ pub enum ApplicationState {
     TimeEntry(SettingsAccessorId),
     AboutScreen,
     ConfirmationScreen(ConfirmationId),
+    FactoryReset,
+    FirstRunSetup(FirstRunStep),
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -22,10 +22,13 @@ use crate::led::led_control::{LedArrayMode, LedControl};
 use defmt::{trace, warn};
 use embassy_futures::select::{select3, Either3};
 use embassy_sync::pubsub::WaitResult;
-use embassy_time::{Duration, Ticker};
+use embassy_time::{Duration, Instant, Ticker};
 use smart_leds::RGB8;
+use smartcoaster_app_core::reminder::ReminderLevel;
+use smartcoaster_app_core::target_direction::TargetDirection;
 
 const UPDATES_PER_SECOND: u64 = 30;
+const CELEBRATION_SECONDS: u64 = 30;
 
 pub struct LedManager<LC> {
     led_control: LC,
@@ -36,6 +39,10 @@ pub struct LedManager<LC> {
     target_rate: f32,
     monitoring_mode: MonitoringTargetPeriodOptions,
     monitoring_last_hour: bool,
+    target_direction: TargetDirection,
+    reminder_level: ReminderLevel,
+    /// Set while the goal achieved animation is playing.
+    celebration_end: Option<Instant>,
 }
 
 impl<LC> LedManager<LC>
@@ -56,6 +63,9 @@ where
             target_rate: 0.0,
             monitoring_mode: MonitoringTargetPeriodOptions::Hourly,
             monitoring_last_hour: false,
+            target_direction: TargetDirection::Minimum,
+            reminder_level: ReminderLevel::NotDue,
+            celebration_end: None,
         }
     }
 
@@ -70,6 +80,16 @@ where
             .await;
             match timer_or_state_change {
                 Either3::First(_) => {
+                    if self
+                        .celebration_end
+                        .is_some_and(|celebration_end| Instant::now() >= celebration_end)
+                    {
+                        self.celebration_end = None;
+                        if self.application_state == ApplicationState::Monitoring {
+                            self.rate_update(self.consumption_rate, self.target_rate)
+                                .await;
+                        }
+                    }
                     self.led_control.led_update().await;
                 }
                 Either3::Second(message) => match message {
@@ -80,7 +100,7 @@ where
                         ApplicationMessage::ApplicationStateUpdate(new_state) => {
                             self.application_state = new_state;
                             match new_state {
-                                ApplicationState::Startup => {
+                                ApplicationState::Startup | ApplicationState::FactoryReset => {
                                     self.led_control.set_mode(LedArrayMode::Off);
                                 }
                                 ApplicationState::TestScreen => {
@@ -99,12 +119,14 @@ where
                                     })
                                 }
                                 ApplicationState::Monitoring => {}
-                                ApplicationState::Settings => {
+                                ApplicationState::Settings | ApplicationState::FirstRunSetup(_) => {
                                     self.led_control.set_mode(LedArrayMode::StaticColour {
                                         colour: RGB8::new(191, 64, 191),
                                     })
                                 }
                                 ApplicationState::HeapStatus => {}
+                                ApplicationState::ErrorLog => {}
+                                ApplicationState::NvmStatus => {}
                                 ApplicationState::Calibration => {}
                                 ApplicationState::SetSystemDateTime => {}
                                 ApplicationState::NumberEntry(_) => {}
@@ -170,6 +192,33 @@ where
                         DrinkMonitoringUpdate::LastHour(last_hour) => {
                             self.monitoring_last_hour = last_hour;
                         }
+                        DrinkMonitoringUpdate::PlanDifference(_) => {}
+                        DrinkMonitoringUpdate::TargetDirection(direction) => {
+                            self.target_direction = direction;
+                        }
+                        DrinkMonitoringUpdate::ActiveBeverage(_) => {}
+                        DrinkMonitoringUpdate::BeverageTotalConsumed(_, _) => {}
+                        DrinkMonitoringUpdate::BeverageTarget(_, _, _) => {}
+                        DrinkMonitoringUpdate::LastDrinkTime(_) => {}
+                        DrinkMonitoringUpdate::ReminderDue(level) => {
+                            self.reminder_level = level;
+                        }
+                        DrinkMonitoringUpdate::GoalAchieved => {
+                            self.celebration_end =
+                                Some(Instant::now() + Duration::from_secs(CELEBRATION_SECONDS));
+                            if self.application_state == ApplicationState::Monitoring {
+                                self.led_control.set_mode(LedArrayMode::RainbowWheel {
+                                    speed: 1.0,
+                                    repetitions: 1.0,
+                                });
+                            }
+                        }
+                        DrinkMonitoringUpdate::Streak(_) => {}
+                        DrinkMonitoringUpdate::DailyTotal(_, _, _) => {}
+                        DrinkMonitoringUpdate::SevenDayAverage(_) => {}
+                        DrinkMonitoringUpdate::ThirtyDayAverage(_) => {}
+                        DrinkMonitoringUpdate::HourlyConsumption(_, _) => {}
+                        DrinkMonitoringUpdate::DayProgress(_, _, _) => {}
                     }
                     if self.application_state == ApplicationState::Monitoring {
                         self.rate_update(self.consumption_rate, self.target_rate)
@@ -181,11 +230,36 @@ where
     }
 
     async fn rate_update(&mut self, consumption_rate: f32, target_rate: f32) {
+        if self.celebration_end.is_some() {
+            return;
+        }
+
+        // a due reminder takes priority over showing how consumption compares to the target
+        match self.reminder_level {
+            ReminderLevel::NotDue => {}
+            ReminderLevel::Gentle => {
+                self.led_control.set_mode(LedArrayMode::Pulse {
+                    colour: RGB8::new(0, 120, 255), // blue
+                    speed: 0.5,
+                });
+                return;
+            }
+            ReminderLevel::Insistent => {
+                self.led_control.set_mode(LedArrayMode::SingleColourWheel {
+                    colour: RGB8::new(0, 120, 255), // blue
+                    repetitions: 1.0,
+                    speed: 1.5,
+                });
+                return;
+            }
+        }
+
+        // rate_delta is negative when on the wrong side of the target, whichever direction that is
         let rate_delta;
-        if self.monitoring_last_hour {
+        if self.monitoring_last_hour && self.target_direction == TargetDirection::Minimum {
             rate_delta = -target_rate;
         } else {
-            rate_delta = consumption_rate - target_rate;
+            rate_delta = self.target_direction.margin(consumption_rate, target_rate);
         }
 
         trace!("Rate delta = {}", rate_delta);
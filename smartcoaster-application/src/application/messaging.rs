@@ -14,7 +14,9 @@
 
 use crate::application::application_state::{ApplicationState, CalibrationStateSubstates};
 use crate::drink_monitor::messaging::DrinkMonitoringUpdate;
+use crate::error_log::ErrorLogRecord;
 use crate::hmi::messaging::HmiMessage;
+use crate::storage::status::PartitionStatus;
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use embassy_sync::pubsub::{PubSubChannel, Publisher, Subscriber};
 
@@ -24,6 +26,7 @@ pub enum ApplicationMessage {
     ApplicationDataUpdate(ApplicationData),
     HmiInput(HmiMessage),
     ClearHistoricalConsumptionLog,
+    SnoozeReminder,
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -31,6 +34,8 @@ pub enum ApplicationData {
     Weight(f32),
     CalibrationSubstate(CalibrationStateSubstates),
     HeapStatus { used: usize, free: usize },
+    ErrorLogRecord(ErrorLogRecord),
+    NvmPartitionStatus(PartitionStatus),
     LedBrightness(u8),
     DisplayBrightness(u8),
     DisplayTimeout(u8),
@@ -0,0 +1,83 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use defmt::Format;
+use strum::{EnumCount, EnumIter};
+
+/// Type of drink in the vessel on the coaster. Water is the primary beverage and uses the full
+/// daily/hourly target monitoring. The others are tracked against their own daily target.
+#[derive(Clone, Copy, Debug, PartialEq, Format, EnumCount, EnumIter)]
+pub enum BeverageType {
+    Water,
+    Coffee,
+    Tea,
+    Other,
+}
+
+impl BeverageType {
+    pub fn option_strings() -> &'static [&'static str] {
+        &["Water", "Coffee", "Tea", "Other"]
+    }
+
+    pub fn name(&self) -> &'static str {
+        Self::option_strings()[self.index()]
+    }
+
+    pub fn target_title(&self) -> &'static str {
+        match self {
+            BeverageType::Water => "Water Target",
+            BeverageType::Coffee => "Coffee Target",
+            BeverageType::Tea => "Tea Target",
+            BeverageType::Other => "Other Target",
+        }
+    }
+
+    pub fn direction_title(&self) -> &'static str {
+        match self {
+            BeverageType::Water => "Water is",
+            BeverageType::Coffee => "Coffee is",
+            BeverageType::Tea => "Tea is",
+            BeverageType::Other => "Other is",
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        u8::from(*self) as usize
+    }
+}
+
+impl TryFrom<u8> for BeverageType {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Water),
+            1 => Ok(Self::Coffee),
+            2 => Ok(Self::Tea),
+            3 => Ok(Self::Other),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<BeverageType> for u8 {
+    fn from(value: BeverageType) -> Self {
+        match value {
+            BeverageType::Water => 0,
+            BeverageType::Coffee => 1,
+            BeverageType::Tea => 2,
+            BeverageType::Other => 3,
+        }
+    }
+}
@@ -14,8 +14,14 @@
 
 use crate::application::application_state::ApplicationState;
 use crate::application::messaging::{ApplicationChannelSubscriber, ApplicationMessage};
+use crate::drink_monitor::beverage::BeverageType;
 use crate::drink_monitor::log_data::DrinkMonitorLogData;
-use crate::drink_monitor::messaging::{DrinkMonitorChannelPublisher, DrinkMonitoringUpdate};
+use crate::drink_monitor::messaging::{
+    DrinkMonitorChannelPublisher, DrinkMonitoringUpdate, DAILY_TOTAL_HISTORY_DAYS,
+    DAY_PROGRESS_POINTS, HOURLY_HISTORY_HOURS,
+};
+use crate::drink_monitor::rollup_data::DailyRollupLogData;
+use crate::error_log::{log_error, ErrorCode, ErrorLogData, ErrorModule};
 use crate::hmi::screens::settings_menu::monitoring_options::MonitoringTargetPeriodOptions;
 use crate::rtc::accessor::RtcAccessor;
 use crate::storage::historical::accessor::HistoricalLogAccessor;
@@ -26,18 +32,29 @@ use crate::storage::settings::messaging::SettingsMessage;
 use crate::storage::settings::monitor::FlashSettingsMonitor;
 use crate::storage::settings::{SettingValue, SettingsAccessor, SettingsAccessorId};
 use crate::weight::WeighingSystem;
-use chrono::{NaiveDateTime, NaiveTime, TimeDelta, Timelike};
+use chrono::{NaiveDateTime, NaiveTime, TimeDelta};
 use core::cmp::PartialEq;
 use core::ops::Sub;
 use defmt::{debug, error, info, trace, warn, Debug2Format};
 use embassy_futures::select::{select4, Either4};
 use embassy_sync::pubsub::{PubSubChannel, WaitResult};
 use embassy_time::{Duration, Ticker, Timer};
-use heapless::HistoryBuffer;
+use heapless::{HistoryBuffer, Vec};
 use micromath::F32Ext;
+use smartcoaster_app_core::daily_summary::{DailySummary, DailySummaryBuilder, SummaryHistory};
+use smartcoaster_app_core::flight_path::{FlightPath, PacingProfile};
+use smartcoaster_app_core::hourly_history::{hour_start, HourlyHistory};
+use smartcoaster_app_core::monitoring_day;
+use smartcoaster_app_core::reminder::{ReminderLevel, ReminderSchedule};
+use smartcoaster_app_core::streak::StreakCounter;
+use smartcoaster_app_core::target_direction::TargetDirection;
+use strum::{EnumCount, IntoEnumIterator};
 
 static LOG_READ_CHANNEL: HistoricalLogChannel = PubSubChannel::new();
 
+/// Days of daily summaries kept for statistics.
+const SUMMARY_HISTORY_DAYS: usize = 30;
+
 #[derive(Clone, PartialEq, Copy, Debug)]
 pub enum MonitoringStateSubstates {
     WaitingForActivity,
@@ -46,6 +63,24 @@ pub enum MonitoringStateSubstates {
     Error(&'static str),
 }
 
+/// Daily tracking for beverages other than water
+#[derive(Clone, Copy)]
+struct BeverageTracker {
+    total_consumption: f32,
+    daily_consumption_target: u32,
+    target_direction: TargetDirection,
+}
+
+impl BeverageTracker {
+    const fn new() -> Self {
+        Self {
+            total_consumption: 0.0,
+            daily_consumption_target: 0,
+            target_direction: TargetDirection::Maximum,
+        }
+    }
+}
+
 pub struct DrinkMonitoring<WS> {
     weighing_system: WS,
     drink_monitor_publisher: DrinkMonitorChannelPublisher<'static>,
@@ -58,6 +93,26 @@ pub struct DrinkMonitoring<WS> {
     daily_consumption_target_time: NaiveTime,
     monitoring_log: HistoricalLogAccessor,
     last_hour_consumption: f32,
+    day_start_time: NaiveTime,
+    drinking_window_start: Option<NaiveTime>,
+    pacing_profile: PacingProfile,
+    target_direction: TargetDirection,
+    active_beverage: BeverageType,
+    /// Indexed by beverage. Water uses the main monitoring state, so its entry is unused.
+    beverage_trackers: [BeverageTracker; BeverageType::COUNT],
+    reminder_schedule: ReminderSchedule,
+    reminder_level: ReminderLevel,
+    last_drink_time: Option<NaiveDateTime>,
+    reminder_snoozed_until: Option<NaiveDateTime>,
+    /// Consecutive days on target up to, but not including, today, kept from the daily summaries.
+    streak_counter: StreakCounter,
+    rollup_log: HistoricalLogAccessor,
+    /// Water consumption for the current monitoring day, written to the rollup log when the day ends.
+    day_summary: DailySummary,
+    summary_history: SummaryHistory<SUMMARY_HISTORY_DAYS>,
+    /// Water consumption per clock hour, kept up to date as drinks are taken so the log only needs
+    /// reading at boot.
+    hourly_history: HourlyHistory<HOURLY_HISTORY_HOURS>,
 }
 
 impl<WS> DrinkMonitoring<WS>
@@ -65,6 +120,7 @@ where
     WS: WeighingSystem,
 {
     const STABILISED_WEIGHT_MAX_DELTA: f32 = 5.0;
+    const REMINDER_SNOOZE_MINUTES: i64 = 15;
 
     pub fn new(
         drink_monitor_publisher: DrinkMonitorChannelPublisher<'static>,
@@ -79,15 +135,31 @@ where
             daily_consumption_target: 0,
             target_mode: MonitoringTargetPeriodOptions::Hourly,
             monitoring_start_time: rtc_accessor.get_date_time(),
+            hourly_history: HourlyHistory::new(rtc_accessor.get_date_time()),
             rtc_accessor,
             total_consumption: 0.0,
             daily_consumption_target_time: Default::default(),
             monitoring_log: HistoricalLogAccessor::new(log_config::Logs::ConsumptionLog),
             last_hour_consumption: 0.0,
+            day_start_time: NaiveTime::MIN,
+            drinking_window_start: None,
+            pacing_profile: PacingProfile::Linear,
+            target_direction: TargetDirection::Minimum,
+            active_beverage: BeverageType::Water,
+            beverage_trackers: [BeverageTracker::new(); BeverageType::COUNT],
+            reminder_schedule: ReminderSchedule::new(0, NaiveTime::MIN, NaiveTime::MIN),
+            reminder_level: ReminderLevel::NotDue,
+            last_drink_time: None,
+            reminder_snoozed_until: None,
+            streak_counter: StreakCounter::new(),
+            rollup_log: HistoricalLogAccessor::new(log_config::Logs::DailyRollupLog),
+            day_summary: DailySummary::new(NaiveDateTime::default()),
+            summary_history: SummaryHistory::new(),
         }
     }
 
-    async fn manage_error(&mut self, message: &'static str) -> ! {
+    async fn manage_error(&mut self, code: ErrorCode, message: &'static str) -> ! {
+        log_error(ErrorLogData::new(ErrorModule::DrinkMonitor, code));
         self.drink_monitor_publisher
             .publish(DrinkMonitoringUpdate::UpdateMonitoringSubstate(
                 MonitoringStateSubstates::Error(message),
@@ -113,8 +185,11 @@ where
         match self.weighing_system.get_reading().await {
             Ok(w) => w,
             Err(_) => {
-                self.manage_error("Scale reading failed. Restart device")
-                    .await
+                self.manage_error(
+                    ErrorCode::ScaleReadFailed,
+                    "Scale reading failed. Restart device",
+                )
+                .await
             }
         }
     }
@@ -166,18 +241,27 @@ where
             .await;
     }
 
-    async fn update_day_average_consumption_rate(&mut self) -> f32 {
-        // daily reset logic
-        let current_date_time = self.rtc_accessor.get_date_time();
-        let current_date = NaiveDateTime::from(current_date_time.date());
-        if self.monitoring_start_time != current_date {
-            self.monitoring_start_time = current_date;
+    /// Closes the day and starts a new one if `now` is past the end of the current monitoring day.
+    /// This must be done before a drink is added so the drink counts towards the day it was taken.
+    async fn roll_over_day(&mut self, now: NaiveDateTime) {
+        let current_day_start = monitoring_day::day_start_for(now, self.day_start_time);
+        if self.monitoring_start_time != current_day_start {
+            self.close_day_summary().await;
+            self.monitoring_start_time = current_day_start;
             self.total_consumption = 0.0;
+            self.day_summary = DailySummary::new(current_day_start);
+            self.reset_beverage_totals().await;
+            self.send_daily_statistics().await;
         }
+    }
+
+    async fn update_day_average_consumption_rate(&mut self) -> f32 {
+        let current_date_time = self.rtc_accessor.get_date_time();
+        self.roll_over_day(current_date_time).await;
 
         // calculate the rate and notify the system
         let elapsed_time_in_hours = f32::max(
-            current_date_time.num_seconds_from_midnight() as f32 / 3600.0,
+            monitoring_day::hours_since_day_start(current_date_time, self.day_start_time),
             1.0, // this avoids getting meaningless consumption rate numbers when we are less than 1 hour from the start point
         );
         let consumption_rate = self.total_consumption / elapsed_time_in_hours;
@@ -236,7 +320,9 @@ where
                             error!("Failed to decode data from log");
                         })
                         .unwrap();
-                    last_hour_consumption += log_entry.get_last_consumption();
+                    if log_entry.get_beverage() == BeverageType::Water {
+                        last_hour_consumption += log_entry.get_last_consumption();
+                    }
                     trace!(
                         "{} - consumption was {}",
                         Debug2Format(&entry.timestamp),
@@ -250,9 +336,9 @@ where
     async fn update_targets(&mut self) {
         match self.target_mode {
             MonitoringTargetPeriodOptions::Daily => {
-                let current_time = self.rtc_accessor.get_date_time().time();
-                let time_left = self.daily_consumption_target_time - current_time;
-                let mut hours_left = time_left.num_minutes() as f32 / 60.0;
+                let current_date_time = self.rtc_accessor.get_date_time();
+                let flight_path = self.flight_path(current_date_time);
+                let mut hours_left = flight_path.hours_until_end(current_date_time);
                 if hours_left <= 1.0 {
                     hours_left = 1.0;
                 }
@@ -260,17 +346,19 @@ where
                     .await;
 
                 self.hourly_consumption_target =
-                    (self.daily_consumption_target as f32 - self.total_consumption) / hours_left;
-                if self.hourly_consumption_target < 0.0 {
-                    self.hourly_consumption_target = 0.0;
-                }
+                    flight_path.hourly_target(current_date_time, self.total_consumption);
+                let plan_difference =
+                    flight_path.difference_from_plan(current_date_time, self.total_consumption);
                 debug!(
-                    "Hourly target (calculated from daily) is now {} (daily_target = {}, consumed = {}, hours left = {})",
+                    "Hourly target (calculated from daily) is now {} (daily_target = {}, consumed = {}, hours left = {}, plan difference = {})",
                     self.hourly_consumption_target,
-                    self.total_consumption,
                     self.daily_consumption_target,
-                    hours_left
+                    self.total_consumption,
+                    hours_left,
+                    plan_difference
                 );
+                self.send_monitoring_update(DrinkMonitoringUpdate::PlanDifference(plan_difference))
+                    .await;
                 self.send_monitoring_update(DrinkMonitoringUpdate::TargetConsumption(
                     self.daily_consumption_target as f32,
                 ))
@@ -288,9 +376,25 @@ where
         .await;
     }
 
+    /// The daily target flight path for the monitoring day containing `now`. The drinking window
+    /// runs from the drinking start time (or the day start if not set) to the daily target time.
+    fn flight_path(&self, now: NaiveDateTime) -> FlightPath {
+        FlightPath::new(
+            now,
+            self.day_start_time,
+            self.drinking_window_start.unwrap_or(self.day_start_time),
+            self.daily_consumption_target_time,
+            self.pacing_profile,
+            self.daily_consumption_target as f32,
+        )
+    }
+
     async fn update(&mut self, new_consumption: f32) {
-        self.update_targets().await;
+        // rolls the day over first, so the targets are worked out from the current day's total
         self.update_day_average_consumption_rate().await;
+        self.update_targets().await;
+        self.send_monitoring_update(DrinkMonitoringUpdate::Streak(self.streak()))
+            .await;
 
         let snapshot = DrinkMonitorLogData::new(
             self.hourly_consumption_target,
@@ -299,10 +403,419 @@ where
             self.total_consumption,
             self.daily_consumption_target_time,
             new_consumption,
+            BeverageType::Water,
         );
         self.monitoring_log.log_data(snapshot).await;
     }
 
+    /// True once a minimum daily target has been reached today. Maximum targets can only be judged
+    /// once the day is over.
+    fn goal_met_today(&self) -> bool {
+        self.target_mode == MonitoringTargetPeriodOptions::Daily
+            && self.target_direction == TargetDirection::Minimum
+            && self.daily_consumption_target > 0
+            && self
+                .target_direction
+                .is_achieved(self.total_consumption, self.daily_consumption_target as f32)
+    }
+
+    /// Consecutive days on target, including today once the target has been reached.
+    fn streak(&self) -> u32 {
+        self.streak_counter
+            .completed_streak(self.monitoring_start_time)
+            + self.goal_met_today() as u32
+    }
+
+    async fn record_water_consumption(&mut self, consumption: f32) {
+        let now = self.rtc_accessor.get_date_time();
+        self.roll_over_day(now).await;
+        let goal_was_met = self.goal_met_today();
+        self.total_consumption += consumption;
+        self.day_summary.add_drink(now, consumption);
+        self.update_hourly_history().await;
+        self.hourly_history.add(now, consumption);
+        self.send_monitoring_update(DrinkMonitoringUpdate::HourlyConsumption(
+            0,
+            self.hourly_history.hours_ago(0),
+        ))
+        .await;
+        self.send_day_progress_point(now).await;
+        if !goal_was_met && self.goal_met_today() {
+            info!(
+                "Daily target of {} ml achieved - streak now {} days",
+                self.daily_consumption_target,
+                self.streak()
+            );
+            self.send_monitoring_update(DrinkMonitoringUpdate::GoalAchieved)
+                .await;
+        }
+
+        // this is not strictly accurate, but only for up to a minute and makes things more responsive,
+        // with less penalty caused by reading the flash for the history
+        self.last_hour_consumption += consumption;
+        self.send_monitoring_update(DrinkMonitoringUpdate::LastHourConsumptionRate(
+            self.last_hour_consumption,
+        ))
+        .await;
+
+        self.update(consumption).await;
+
+        self.send_monitoring_update(DrinkMonitoringUpdate::TotalConsumed(self.total_consumption))
+            .await;
+        debug!("Total consumption = {} ml", self.total_consumption);
+    }
+
+    async fn send_hourly_history(&mut self) {
+        for hours_ago in 0..HOURLY_HISTORY_HOURS {
+            self.send_monitoring_update(DrinkMonitoringUpdate::HourlyConsumption(
+                hours_ago as u8,
+                self.hourly_history.hours_ago(hours_ago),
+            ))
+            .await;
+        }
+    }
+
+    /// Moves the hourly history on to the current hour, sending the whole history if it changed.
+    async fn update_hourly_history(&mut self) {
+        if self
+            .hourly_history
+            .advance(self.rtc_accessor.get_date_time())
+        {
+            self.send_hourly_history().await;
+            self.send_day_progress().await;
+        }
+    }
+
+    /// Cumulative consumption and target at `point` hours into the monitoring day. Consumption is
+    /// taken from the hourly history, so only matches the total exactly when the day starts on the
+    /// hour.
+    fn day_progress_at(&self, point: usize, now: NaiveDateTime) -> (Option<f32>, f32) {
+        let day_start = self.monitoring_start_time;
+        let time = day_start + TimeDelta::hours(point as i64);
+        let consumed = if point == 0 || time - TimeDelta::hours(1) < now {
+            Some(
+                self.hourly_history
+                    .consumed_between(hour_start(day_start), time),
+            )
+        } else {
+            None
+        };
+        let target = match self.target_mode {
+            MonitoringTargetPeriodOptions::Daily => {
+                self.flight_path(now).planned_consumption_at(time)
+            }
+            MonitoringTargetPeriodOptions::Hourly => self.hourly_consumption_target * point as f32,
+        };
+        (consumed, target)
+    }
+
+    /// Sends the whole of today's progress for the sparkline.
+    async fn send_day_progress(&mut self) {
+        let now = self.rtc_accessor.get_date_time();
+        for point in 0..DAY_PROGRESS_POINTS {
+            let (consumed, target) = self.day_progress_at(point, now);
+            self.send_monitoring_update(DrinkMonitoringUpdate::DayProgress(
+                point as u8,
+                consumed,
+                target,
+            ))
+            .await;
+        }
+    }
+
+    /// Sends the progress point covering `now`, which is the one changed by a drink.
+    async fn send_day_progress_point(&mut self, now: NaiveDateTime) {
+        let hours_into_day = (now - self.monitoring_start_time).num_hours() as usize + 1;
+        if hours_into_day < DAY_PROGRESS_POINTS {
+            let (consumed, target) = self.day_progress_at(hours_into_day, now);
+            self.send_monitoring_update(DrinkMonitoringUpdate::DayProgress(
+                hours_into_day as u8,
+                consumed,
+                target,
+            ))
+            .await;
+        }
+    }
+
+    /// Builds the hourly history from the log.
+    async fn initialise_hourly_history(&mut self) {
+        self.hourly_history = HourlyHistory::new(self.rtc_accessor.get_date_time());
+
+        debug!("Retrieving logs for hourly history");
+        self.monitoring_log
+            .get_log_data_after_timestamp(self.hourly_history.oldest_hour(), &LOG_READ_CHANNEL)
+            .await;
+
+        let mut log_subscriber = LOG_READ_CHANNEL.subscriber().unwrap();
+        loop {
+            match log_subscriber.next_message_pure().await {
+                HistoricalLogMessage::Error() => {
+                    error!("Log retrieval error when building hourly history");
+                    break;
+                }
+                HistoricalLogMessage::EndOfRead() => break,
+                HistoricalLogMessage::Record(entry) => {
+                    let log_entry = DrinkMonitorLogData::from_bytes(&entry.data)
+                        .map_err(|_| {
+                            error!("Failed to decode data from log");
+                        })
+                        .unwrap();
+                    if log_entry.get_beverage() == BeverageType::Water {
+                        self.hourly_history
+                            .add(entry.timestamp, log_entry.get_last_consumption());
+                    }
+                }
+            }
+        }
+        self.send_hourly_history().await;
+    }
+
+    /// Daily target for the day summary, which is only set when monitoring a daily target.
+    fn summary_daily_target(&self) -> Option<f32> {
+        if self.target_mode == MonitoringTargetPeriodOptions::Daily {
+            Some(self.daily_consumption_target as f32)
+        } else {
+            None
+        }
+    }
+
+    /// Completes the summary for the day that is ending and stores it in the rollup log.
+    async fn close_day_summary(&mut self) {
+        self.day_summary
+            .complete(self.summary_daily_target(), self.target_direction);
+        let summary = self.day_summary;
+        self.store_daily_summary(&summary).await;
+    }
+
+    async fn store_daily_summary(&mut self, summary: &DailySummary) {
+        debug!(
+            "Storing summary for day starting {}: {} ml from {} drinks, target achieved = {}",
+            Debug2Format(&summary.day_start),
+            summary.total_consumption.round(),
+            summary.drink_count,
+            summary.target_achieved
+        );
+        self.rollup_log
+            .log_data(DailyRollupLogData::from(summary))
+            .await;
+        self.summary_history.add(*summary);
+        self.streak_counter.add_day(summary);
+    }
+
+    /// Sends the previous days' totals and the average daily totals.
+    async fn send_daily_statistics(&mut self) {
+        let today_start =
+            monitoring_day::day_start_for(self.rtc_accessor.get_date_time(), self.day_start_time);
+        for days_ago in 1..=DAILY_TOTAL_HISTORY_DAYS as u32 {
+            let (total, target_achieved) = self
+                .summary_history
+                .day(today_start, days_ago)
+                .map_or((0.0, false), |summary| {
+                    (summary.total_consumption, summary.target_achieved)
+                });
+            self.send_monitoring_update(DrinkMonitoringUpdate::DailyTotal(
+                days_ago as u8,
+                total,
+                target_achieved,
+            ))
+            .await;
+        }
+        self.send_monitoring_update(DrinkMonitoringUpdate::SevenDayAverage(
+            self.summary_history.average_total(today_start, 7),
+        ))
+        .await;
+        self.send_monitoring_update(DrinkMonitoringUpdate::ThirtyDayAverage(
+            self.summary_history
+                .average_total(today_start, SUMMARY_HISTORY_DAYS as u32),
+        ))
+        .await;
+    }
+
+    /// Loads the daily summaries and the streak from the rollup log. The whole log is read so the
+    /// streak is only limited by the log's size. Days that ended while the coaster was off have no
+    /// summary, so these are rebuilt from the consumption log.
+    async fn initialise_daily_summaries(&mut self) {
+        let now = self.rtc_accessor.get_date_time();
+        let today_start = monitoring_day::day_start_for(now, self.day_start_time);
+        let history_start = today_start - TimeDelta::days(SUMMARY_HISTORY_DAYS as i64);
+        self.summary_history.clear();
+        self.streak_counter.clear();
+
+        debug!("Retrieving daily summaries");
+        self.rollup_log
+            .get_log_data_after_timestamp(NaiveDateTime::MIN, &LOG_READ_CHANNEL)
+            .await;
+
+        let mut log_subscriber = LOG_READ_CHANNEL.subscriber().unwrap();
+        loop {
+            match log_subscriber.next_message_pure().await {
+                HistoricalLogMessage::Error() => {
+                    error!("Log retrieval error when loading daily summaries");
+                    break;
+                }
+                HistoricalLogMessage::EndOfRead() => break,
+                HistoricalLogMessage::Record(entry) => {
+                    match DailyRollupLogData::from_bytes(&entry.data)
+                        .and_then(|log_entry| log_entry.get_summary())
+                    {
+                        Ok(summary) => {
+                            self.summary_history.add(summary);
+                            self.streak_counter.add_day(&summary);
+                        }
+                        Err(e) => error!("Failed to decode daily summary: {}", Debug2Format(&e)),
+                    }
+                }
+            }
+        }
+        drop(log_subscriber);
+
+        let backfill_start = match self.summary_history.latest_day() {
+            Some(latest_day) => monitoring_day::day_end_for(latest_day, self.day_start_time),
+            None => history_start,
+        };
+        if backfill_start < today_start {
+            self.backfill_daily_summaries(backfill_start, now).await;
+        }
+        info!(
+            "Loaded daily summaries - 7 day average = {} ml, streak before today = {} days",
+            self.summary_history
+                .average_total(today_start, 7)
+                .unwrap_or(0.0)
+                .round(),
+            self.streak_counter.completed_streak(today_start)
+        );
+        self.send_daily_statistics().await;
+    }
+
+    /// Builds the summaries for days from `start` up to the start of today from the consumption
+    /// log and stores them.
+    async fn backfill_daily_summaries(&mut self, start: NaiveDateTime, now: NaiveDateTime) {
+        let mut summary_builder =
+            DailySummaryBuilder::new(self.day_start_time, self.target_direction);
+        // summaries can't be stored until the read has finished as the log store is busy
+        let mut summaries = Vec::<DailySummary, SUMMARY_HISTORY_DAYS>::new();
+
+        debug!("Rebuilding daily summaries from {}", Debug2Format(&start));
+        self.monitoring_log
+            .get_log_data_after_timestamp(start, &LOG_READ_CHANNEL)
+            .await;
+
+        let mut log_subscriber = LOG_READ_CHANNEL.subscriber().unwrap();
+        loop {
+            match log_subscriber.next_message_pure().await {
+                HistoricalLogMessage::Error() => {
+                    error!("Log retrieval error when rebuilding daily summaries");
+                    break;
+                }
+                HistoricalLogMessage::EndOfRead() => break,
+                HistoricalLogMessage::Record(entry) => {
+                    let log_entry = DrinkMonitorLogData::from_bytes(&entry.data)
+                        .map_err(|_| {
+                            error!("Failed to decode data from log");
+                        })
+                        .unwrap();
+                    if log_entry.get_beverage() != BeverageType::Water {
+                        continue;
+                    }
+                    if let Some(summary) = summary_builder.add_record(
+                        entry.timestamp,
+                        log_entry.get_last_consumption(),
+                        log_entry
+                            .get_daily_consumption_target()
+                            .map(|target| target as f32),
+                    ) {
+                        let _ = summaries.push(summary);
+                    }
+                }
+            }
+        }
+        drop(log_subscriber);
+
+        if let Some(summary) = summary_builder.finish(now) {
+            let _ = summaries.push(summary);
+        }
+        info!("Rebuilt {} daily summaries", summaries.len());
+        for summary in summaries.iter() {
+            self.store_daily_summary(summary).await;
+        }
+    }
+
+    /// Adds consumption of a beverage other than water to its daily total and logs it.
+    async fn record_beverage_consumption(&mut self, beverage: BeverageType, consumption: f32) {
+        self.roll_over_day(self.rtc_accessor.get_date_time()).await;
+        let tracker = &mut self.beverage_trackers[beverage.index()];
+        tracker.total_consumption += consumption;
+        let total_consumption = tracker.total_consumption;
+        // logged with the monitoring mode and hourly target in use, as water drinks are
+        let snapshot = DrinkMonitorLogData::new(
+            self.hourly_consumption_target,
+            tracker.daily_consumption_target,
+            self.target_mode,
+            total_consumption,
+            self.daily_consumption_target_time,
+            consumption,
+            beverage,
+        );
+        self.monitoring_log.log_data(snapshot).await;
+
+        self.send_monitoring_update(DrinkMonitoringUpdate::BeverageTotalConsumed(
+            beverage,
+            total_consumption,
+        ))
+        .await;
+        debug!("{} total consumption = {} ml", beverage, total_consumption);
+    }
+
+    async fn reset_beverage_totals(&mut self) {
+        for beverage in BeverageType::iter().filter(|b| *b != BeverageType::Water) {
+            self.beverage_trackers[beverage.index()].total_consumption = 0.0;
+            self.send_monitoring_update(DrinkMonitoringUpdate::BeverageTotalConsumed(
+                beverage, 0.0,
+            ))
+            .await;
+        }
+    }
+
+    async fn send_beverage_target(&mut self, beverage: BeverageType) {
+        let tracker = self.beverage_trackers[beverage.index()];
+        self.send_monitoring_update(DrinkMonitoringUpdate::BeverageTarget(
+            beverage,
+            tracker.daily_consumption_target as f32,
+            tracker.target_direction,
+        ))
+        .await;
+    }
+
+    /// Retrieves the active beverage and the targets for beverages other than water from settings.
+    async fn load_beverage_settings(&mut self, settings: &FlashSettingsAccessor) {
+        let active_setting = settings
+            .get_setting(SettingsAccessorId::MonitoringActiveBeverage)
+            .await;
+        if let Some(SettingValue::SmallUInt(beverage_id)) = active_setting {
+            self.active_beverage = beverage_id.try_into().unwrap_or(BeverageType::Water);
+        }
+        self.send_monitoring_update(DrinkMonitoringUpdate::ActiveBeverage(self.active_beverage))
+            .await;
+
+        for beverage in BeverageType::iter().filter(|b| *b != BeverageType::Water) {
+            let tracker = &mut self.beverage_trackers[beverage.index()];
+            if let Some(SettingValue::UInt(target)) = settings
+                .get_setting(SettingsAccessorId::BeverageTargetDaily(beverage))
+                .await
+            {
+                tracker.daily_consumption_target = target;
+            }
+            if let Some(SettingValue::SmallUInt(direction_id)) = settings
+                .get_setting(SettingsAccessorId::BeverageTargetDirection(beverage))
+                .await
+            {
+                tracker.target_direction =
+                    direction_id.try_into().unwrap_or(TargetDirection::Maximum);
+            }
+            self.send_beverage_target(beverage).await;
+        }
+    }
+
     /// Sets internal monitoring mode state and retrieves associated target values.
     async fn change_monitoring_mode(
         &mut self,
@@ -322,8 +835,8 @@ where
                 let hourly_setting = settings
                     .get_setting(SettingsAccessorId::MonitoringTargetHourly)
                     .await;
-                if let Some(SettingValue::UInt(hourly_target)) = hourly_setting {
-                    self.hourly_consumption_target = hourly_target as f32;
+                if let Some(SettingValue::Float(hourly_target)) = hourly_setting {
+                    self.hourly_consumption_target = hourly_target;
                 } else {
                     warn!(
                         "Unable to get expected data for hourly target: {}",
@@ -355,17 +868,154 @@ where
                         Debug2Format(&target_time_setting)
                     );
                 }
+
+                let window_start_setting = settings
+                    .get_setting(SettingsAccessorId::MonitoringDrinkingWindowStart)
+                    .await;
+                if let Some(SettingValue::Time(window_start)) = window_start_setting {
+                    self.drinking_window_start = Some(window_start);
+                } else {
+                    debug!("No drinking start time set, using day start");
+                    self.drinking_window_start = None;
+                }
+
+                let pacing_setting = settings
+                    .get_setting(SettingsAccessorId::MonitoringPacingProfile)
+                    .await;
+                if let Some(SettingValue::SmallUInt(profile_id)) = pacing_setting {
+                    self.pacing_profile = profile_id.try_into().unwrap_or(PacingProfile::Linear);
+                } else {
+                    debug!("No pacing profile set, using linear");
+                    self.pacing_profile = PacingProfile::Linear;
+                }
             }
         }
         self.update(0.0).await;
     }
 
+    /// Retrieves the reminder interval and quiet hours from settings. Reminders are off and there
+    /// are no quiet hours if they have not been set.
+    async fn load_reminder_schedule(&mut self, settings: &FlashSettingsAccessor) {
+        let interval_minutes = match settings
+            .get_setting(SettingsAccessorId::MonitoringReminderInterval)
+            .await
+        {
+            Some(SettingValue::SmallUInt(minutes)) => minutes,
+            _ => 0,
+        };
+        let quiet_start = match settings
+            .get_setting(SettingsAccessorId::MonitoringQuietStart)
+            .await
+        {
+            Some(SettingValue::Time(time)) => time,
+            _ => NaiveTime::MIN,
+        };
+        let quiet_end = match settings
+            .get_setting(SettingsAccessorId::MonitoringQuietEnd)
+            .await
+        {
+            Some(SettingValue::Time(time)) => time,
+            _ => NaiveTime::MIN,
+        };
+        self.reminder_schedule = ReminderSchedule::new(interval_minutes, quiet_start, quiet_end);
+        debug!(
+            "Reminder schedule: {}",
+            Debug2Format(&self.reminder_schedule)
+        );
+    }
+
+    /// Works out whether a reminder to drink is due and notifies the system when that changes.
+    /// With no drink yet today the interval counts from the start of the day.
+    async fn update_reminder(&mut self) {
+        let now = self.rtc_accessor.get_date_time();
+        let snoozed = self
+            .reminder_snoozed_until
+            .is_some_and(|snoozed_until| now < snoozed_until);
+        let level = if snoozed {
+            ReminderLevel::NotDue
+        } else {
+            let last_drink = self
+                .last_drink_time
+                .map_or(self.monitoring_start_time, |t| {
+                    t.max(self.monitoring_start_time)
+                });
+            self.reminder_schedule
+                .level_for_target(self.target_direction, now, last_drink)
+        };
+
+        if level != self.reminder_level {
+            debug!("Reminder level now {}", Debug2Format(&level));
+            self.reminder_level = level;
+            self.send_monitoring_update(DrinkMonitoringUpdate::ReminderDue(level))
+                .await;
+        }
+    }
+
+    async fn snooze_reminder(&mut self) {
+        let now = self.rtc_accessor.get_date_time();
+        self.reminder_snoozed_until = Some(now + TimeDelta::minutes(Self::REMINDER_SNOOZE_MINUTES));
+        info!(
+            "Reminder snoozed for {} minutes",
+            Self::REMINDER_SNOOZE_MINUTES
+        );
+        self.update_reminder().await;
+    }
+
+    async fn record_drink_time(&mut self, time: NaiveDateTime) {
+        self.last_drink_time = Some(time);
+        self.reminder_snoozed_until = None;
+        self.send_monitoring_update(DrinkMonitoringUpdate::LastDrinkTime(time))
+            .await;
+        self.update_reminder().await;
+    }
+
+    /// Retrieves the target direction from settings and notifies the system. Defaults to treating
+    /// the target as a minimum.
+    async fn load_target_direction(&mut self, settings: &FlashSettingsAccessor) {
+        let direction_setting = settings
+            .get_setting(SettingsAccessorId::MonitoringTargetDirection)
+            .await;
+        if let Some(SettingValue::SmallUInt(direction_id)) = direction_setting {
+            self.target_direction = direction_id.try_into().unwrap_or(TargetDirection::Minimum);
+        } else {
+            debug!("No target direction set, using minimum");
+            self.target_direction = TargetDirection::Minimum;
+        }
+        self.send_monitoring_update(DrinkMonitoringUpdate::TargetDirection(
+            self.target_direction,
+        ))
+        .await;
+    }
+
+    /// Retrieves the day start time from settings. Midnight is used if it has not been set.
+    async fn load_day_start_time(&mut self, settings: &FlashSettingsAccessor) {
+        let day_start_setting = settings
+            .get_setting(SettingsAccessorId::MonitoringDayStartTime)
+            .await;
+        if let Some(SettingValue::Time(day_start_time)) = day_start_setting {
+            self.day_start_time = day_start_time;
+        } else {
+            warn!(
+                "No saved day start time found: {}. Using midnight",
+                Debug2Format(&day_start_setting)
+            );
+            self.day_start_time = NaiveTime::MIN;
+        }
+        self.monitoring_start_time =
+            monitoring_day::day_start_for(self.rtc_accessor.get_date_time(), self.day_start_time);
+    }
+
+    /// Rebuilds the total consumption for the current monitoring day from the log.
     async fn initialise_total_consumption(&mut self) {
-        let current_date = self.rtc_accessor.get_date_time().date();
-        let day_start = NaiveDateTime::from(current_date);
+        let day_start =
+            monitoring_day::day_start_for(self.rtc_accessor.get_date_time(), self.day_start_time);
+        self.monitoring_start_time = day_start;
 
         debug!("Retrieving logs for today's consumption");
         let mut total_consumption = 0.0;
+        let mut day_summary = DailySummary::new(day_start);
+        let mut beverage_totals = [0.0; BeverageType::COUNT];
+        let mut last_drink_time = None;
         let mut total_entries = 0;
         trace!("Requesting log entries for hourly consumption rate calculation");
         self.monitoring_log
@@ -380,11 +1030,25 @@ where
             match retrieved_log_message {
                 HistoricalLogMessage::Error() => {
                     self.total_consumption = total_consumption;
+                    self.day_summary = day_summary;
                     error!("Log retrieval error when calculating historical consumption. Assuming {} ml", total_consumption.round());
                     break 'process_log_data;
                 }
                 HistoricalLogMessage::EndOfRead() => {
                     self.total_consumption = total_consumption;
+                    self.day_summary = day_summary;
+                    for beverage in BeverageType::iter().filter(|b| *b != BeverageType::Water) {
+                        self.beverage_trackers[beverage.index()].total_consumption =
+                            beverage_totals[beverage.index()];
+                        self.send_monitoring_update(DrinkMonitoringUpdate::BeverageTotalConsumed(
+                            beverage,
+                            beverage_totals[beverage.index()],
+                        ))
+                        .await;
+                    }
+                    if let Some(last_drink_time) = last_drink_time {
+                        self.record_drink_time(last_drink_time).await;
+                    }
                     info!(
                         "Looking at {} log entries - consumption since day start = {} ml",
                         total_entries,
@@ -399,7 +1063,19 @@ where
                             error!("Failed to decode data from log");
                         })
                         .unwrap();
-                    total_consumption += log_entry.get_last_consumption();
+                    if log_entry.get_last_consumption() > 0.0 {
+                        last_drink_time = Some(entry.timestamp);
+                    }
+                    match log_entry.get_beverage() {
+                        BeverageType::Water => {
+                            total_consumption += log_entry.get_last_consumption();
+                            day_summary
+                                .add_drink(entry.timestamp, log_entry.get_last_consumption());
+                        }
+                        beverage => {
+                            beverage_totals[beverage.index()] += log_entry.get_last_consumption()
+                        }
+                    }
                     trace!(
                         "{} - consumption was {}",
                         Debug2Format(&entry.timestamp),
@@ -424,6 +1100,10 @@ where
         let mut settings_monitor = FlashSettingsMonitor::new();
 
         // initialise from stored settings
+        self.load_day_start_time(&settings).await;
+        self.load_target_direction(&settings).await;
+        self.load_beverage_settings(&settings).await;
+        self.load_reminder_schedule(&settings).await;
         let mode_from_settings = settings
             .get_setting(SettingsAccessorId::MonitoringTargetType)
             .await;
@@ -443,8 +1123,12 @@ where
         self.initialise_total_consumption().await;
         self.send_monitoring_update(DrinkMonitoringUpdate::TotalConsumed(self.total_consumption))
             .await;
+        self.initialise_daily_summaries().await;
+        self.initialise_hourly_history().await;
+        self.send_day_progress().await;
         self.update_hourly_consumption_rate().await;
         self.update(0.0).await;
+        self.update_reminder().await;
 
         loop {
             let weight_update_or_consumption_tick_or_app_data = select4(
@@ -463,34 +1147,28 @@ where
                         self.update_monitoring_substate(MonitoringStateSubstates::VesselPlaced)
                             .await;
                         let consumption = f32::max(0.0, vessel_placed_weight - new_stable_weight);
-                        self.total_consumption += consumption;
-
-                        // this is not strictly accurate, but only for up to a minute and makes things more responsive,
-                        // with less penalty caused by reading the flash for the history
-                        self.last_hour_consumption += consumption;
-                        self.send_monitoring_update(
-                            DrinkMonitoringUpdate::LastHourConsumptionRate(
-                                self.last_hour_consumption,
-                            ),
-                        )
-                        .await;
-
-                        self.update(consumption).await;
-
-                        self.send_monitoring_update(DrinkMonitoringUpdate::Consumption(f32::max(
-                            0.0,
+                        match self.active_beverage {
+                            BeverageType::Water => self.record_water_consumption(consumption).await,
+                            beverage => {
+                                self.record_beverage_consumption(beverage, consumption)
+                                    .await
+                            }
+                        }
+                        self.send_monitoring_update(DrinkMonitoringUpdate::Consumption(
                             consumption,
-                        )))
-                        .await;
-                        self.send_monitoring_update(DrinkMonitoringUpdate::TotalConsumed(
-                            self.total_consumption,
                         ))
                         .await;
+                        if consumption > 0.0 {
+                            self.record_drink_time(self.rtc_accessor.get_date_time())
+                                .await;
+                        }
 
                         vessel_placed_weight = new_stable_weight;
                         trace!("New placed weight {}", vessel_placed_weight);
-                        debug!("Consumption = {} ml", consumption);
-                        debug!("Total consumption = {} ml", self.total_consumption);
+                        debug!(
+                            "Consumption = {} ml of {}",
+                            consumption, self.active_beverage
+                        );
                     } else if stable_delta < -MINIMUM_DELTA_FOR_STATE_CHANGE {
                         self.update_monitoring_substate(MonitoringStateSubstates::VesselRemoved)
                             .await;
@@ -501,7 +1179,10 @@ where
                 Either4::Second(_) => {
                     // Periodic update
                     self.update_hourly_consumption_rate().await;
+                    // after the update so any change of day has been made first
                     self.update(0.0).await;
+                    self.update_hourly_history().await;
+                    self.update_reminder().await;
                 }
                 Either4::Third(message) => match message {
                     WaitResult::Lagged(missed) => {
@@ -516,11 +1197,36 @@ where
                             )
                         {
                             self.update(0.0).await;
+                            if let Some(last_drink_time) = self.last_drink_time {
+                                self.send_monitoring_update(DrinkMonitoringUpdate::LastDrinkTime(
+                                    last_drink_time,
+                                ))
+                                .await;
+                            }
+                            self.send_monitoring_update(DrinkMonitoringUpdate::ReminderDue(
+                                self.reminder_level,
+                            ))
+                            .await;
+                            self.send_daily_statistics().await;
+                            self.send_hourly_history().await;
+                            self.send_day_progress().await;
+                        }
+                        if app_message == ApplicationMessage::SnoozeReminder {
+                            self.snooze_reminder().await;
                         }
                         if app_message == ApplicationMessage::ClearHistoricalConsumptionLog {
                             self.monitoring_log.clear_log().await;
+                            self.rollup_log.clear_log().await;
                             self.last_hour_consumption = 0.0;
                             self.total_consumption = 0.0;
+                            self.streak_counter.clear();
+                            self.day_summary = DailySummary::new(self.monitoring_start_time);
+                            self.summary_history.clear();
+                            self.send_daily_statistics().await;
+                            self.hourly_history.clear();
+                            self.send_hourly_history().await;
+                            self.send_day_progress().await;
+                            self.reset_beverage_totals().await;
                             self.send_monitoring_update(
                                 DrinkMonitoringUpdate::LastHourConsumptionRate(
                                     self.last_hour_consumption,
@@ -544,8 +1250,8 @@ where
                     let mut do_update = false;
                     match changed_setting.setting_id {
                         SettingsAccessorId::MonitoringTargetHourly => {
-                            if let SettingValue::UInt(new_hourly_target) = changed_setting.value {
-                                self.hourly_consumption_target = new_hourly_target as f32;
+                            if let SettingValue::Float(new_hourly_target) = changed_setting.value {
+                                self.hourly_consumption_target = new_hourly_target;
                                 debug!("Hourly target is now {}", new_hourly_target);
                                 do_update = true;
                             } else {
@@ -578,6 +1284,124 @@ where
                                 );
                             }
                         }
+                        SettingsAccessorId::MonitoringDrinkingWindowStart => {
+                            if let SettingValue::Time(window_start) = changed_setting.value {
+                                self.drinking_window_start = Some(window_start);
+                                debug!(
+                                    "Drinking start time is now {}",
+                                    Debug2Format(&window_start)
+                                );
+                                do_update = true;
+                            } else {
+                                warn!(
+                                    "Unable to get expected data for drinking start time: {}",
+                                    Debug2Format(&changed_setting.value)
+                                );
+                            }
+                        }
+                        SettingsAccessorId::MonitoringPacingProfile => {
+                            if let SettingValue::SmallUInt(profile_id) = changed_setting.value {
+                                self.pacing_profile =
+                                    profile_id.try_into().unwrap_or(PacingProfile::Linear);
+                                debug!(
+                                    "Pacing profile is now {}",
+                                    Debug2Format(&self.pacing_profile)
+                                );
+                                do_update = true;
+                            } else {
+                                warn!(
+                                    "Unexpected MonitoringPacingProfile setting value: {}",
+                                    Debug2Format(&changed_setting.value)
+                                );
+                            }
+                        }
+                        SettingsAccessorId::MonitoringTargetDirection => {
+                            if let SettingValue::SmallUInt(direction_id) = changed_setting.value {
+                                self.target_direction =
+                                    direction_id.try_into().unwrap_or(TargetDirection::Minimum);
+                                debug!(
+                                    "Target direction is now {}",
+                                    Debug2Format(&self.target_direction)
+                                );
+                                self.send_monitoring_update(
+                                    DrinkMonitoringUpdate::TargetDirection(self.target_direction),
+                                )
+                                .await;
+                                self.update_reminder().await;
+                                do_update = true;
+                            } else {
+                                warn!(
+                                    "Unexpected MonitoringTargetDirection setting value: {}",
+                                    Debug2Format(&changed_setting.value)
+                                );
+                            }
+                        }
+                        SettingsAccessorId::MonitoringReminderInterval
+                        | SettingsAccessorId::MonitoringQuietStart
+                        | SettingsAccessorId::MonitoringQuietEnd => {
+                            self.load_reminder_schedule(&settings).await;
+                            self.update_reminder().await;
+                        }
+                        SettingsAccessorId::MonitoringActiveBeverage => {
+                            if let SettingValue::SmallUInt(beverage_id) = changed_setting.value {
+                                self.active_beverage =
+                                    beverage_id.try_into().unwrap_or(BeverageType::Water);
+                                debug!("Active beverage is now {}", self.active_beverage);
+                                self.send_monitoring_update(DrinkMonitoringUpdate::ActiveBeverage(
+                                    self.active_beverage,
+                                ))
+                                .await;
+                            } else {
+                                warn!(
+                                    "Unexpected MonitoringActiveBeverage setting value: {}",
+                                    Debug2Format(&changed_setting.value)
+                                );
+                            }
+                        }
+                        SettingsAccessorId::BeverageTargetDaily(beverage) => {
+                            if let SettingValue::UInt(target) = changed_setting.value {
+                                self.beverage_trackers[beverage.index()].daily_consumption_target =
+                                    target;
+                                self.send_beverage_target(beverage).await;
+                            } else {
+                                warn!(
+                                    "Unexpected BeverageTargetDaily setting value: {}",
+                                    Debug2Format(&changed_setting.value)
+                                );
+                            }
+                        }
+                        SettingsAccessorId::BeverageTargetDirection(beverage) => {
+                            if let SettingValue::SmallUInt(direction_id) = changed_setting.value {
+                                self.beverage_trackers[beverage.index()].target_direction =
+                                    direction_id.try_into().unwrap_or(TargetDirection::Maximum);
+                                self.send_beverage_target(beverage).await;
+                            } else {
+                                warn!(
+                                    "Unexpected BeverageTargetDirection setting value: {}",
+                                    Debug2Format(&changed_setting.value)
+                                );
+                            }
+                        }
+                        SettingsAccessorId::MonitoringDayStartTime => {
+                            if let SettingValue::Time(day_start_time) = changed_setting.value {
+                                self.day_start_time = day_start_time;
+                                debug!("Day start time is now {}", Debug2Format(&day_start_time));
+                                // the day boundary has moved so today's total needs rebuilding.
+                                // The streak is kept from completed days so is unchanged.
+                                self.initialise_total_consumption().await;
+                                self.send_monitoring_update(DrinkMonitoringUpdate::TotalConsumed(
+                                    self.total_consumption,
+                                ))
+                                .await;
+                                self.send_daily_statistics().await;
+                                do_update = true;
+                            } else {
+                                warn!(
+                                    "Unable to get expected data for day start time: {}",
+                                    Debug2Format(&changed_setting.value)
+                                );
+                            }
+                        }
                         SettingsAccessorId::MonitoringTargetType => {
                             if let SettingValue::SmallUInt(mode_id) = changed_setting.value {
                                 let new_mode = mode_id.try_into().unwrap();
@@ -595,6 +1419,7 @@ where
                     if do_update {
                         debug!("Updating after settings change");
                         self.update(0.0).await;
+                        self.send_day_progress().await;
                     }
                 }
             }
@@ -12,13 +12,21 @@
 // You should have received a copy of the GNU General Public License along with
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use crate::drink_monitor::beverage::BeverageType;
 use crate::hmi::screens::settings_menu::monitoring_options::MonitoringTargetPeriodOptions;
+use crate::storage::historical::record::{LogRecordType, RecordHeader};
 use crate::storage::historical::{LogEncodeDecode, LogEncodeDecodeError};
 use crate::storage::StoredDataValue;
 use chrono::NaiveTime;
 use defmt::{error, trace, warn, Debug2Format};
 use sequential_storage::map::{SerializationError, Value};
 
+/// Layout version written in the header of consumption records.
+///
+/// - 0: no header, six values. Written before beverages were tracked, so the beverage is water.
+/// - 1: record header followed by all seven values.
+pub const CONSUMPTION_RECORD_VERSION: u8 = 1;
+
 pub struct DrinkMonitorLogData {
     hourly_consumption_target: StoredDataValue,
     daily_consumption_target: StoredDataValue,
@@ -26,6 +34,7 @@ pub struct DrinkMonitorLogData {
     total_consumption: StoredDataValue,
     daily_consumption_target_time: StoredDataValue,
     last_consumption: StoredDataValue,
+    beverage: StoredDataValue,
 }
 
 impl DrinkMonitorLogData {
@@ -36,6 +45,7 @@ impl DrinkMonitorLogData {
         total_consumption: f32,
         daily_consumption_target_time: NaiveTime,
         last_consumption: f32,
+        beverage: BeverageType,
     ) -> Self {
         Self {
             hourly_consumption_target: StoredDataValue::Float(hourly_consumption_target),
@@ -44,6 +54,97 @@ impl DrinkMonitorLogData {
             total_consumption: StoredDataValue::Float(total_consumption),
             daily_consumption_target_time: StoredDataValue::Time(daily_consumption_target_time),
             last_consumption: StoredDataValue::Float(last_consumption),
+            beverage: StoredDataValue::SmallUInt(beverage.into()),
+        }
+    }
+
+    fn encode_value(
+        value: &StoredDataValue,
+        buf: &mut [u8],
+    ) -> Result<usize, LogEncodeDecodeError> {
+        value.serialize_into(buf).map_err(|e| {
+            if e == SerializationError::BufferTooSmall {
+                LogEncodeDecodeError::BufferTooSmall
+            } else {
+                LogEncodeDecodeError::EncodeFailed
+            }
+        })
+    }
+
+    /// Decodes the values at the start of `buf` into `values`, giving the number of bytes used.
+    fn decode_values(
+        buf: &[u8],
+        values: &mut [StoredDataValue],
+    ) -> Result<usize, LogEncodeDecodeError> {
+        let mut data_start = 0;
+        for value in values.iter_mut() {
+            if data_start >= buf.len() {
+                error!("Not enough bytes to decode - got {}", buf.len());
+                return Err(LogEncodeDecodeError::BufferTooSmall);
+            }
+            *value = StoredDataValue::deserialize_from(&buf[data_start..]).map_err(|e| {
+                error!(
+                    "Unable to decode data {} - start: {} - bytes: {}",
+                    e,
+                    data_start,
+                    &buf[data_start..]
+                );
+                LogEncodeDecodeError::DecodeFailed
+            })?;
+            data_start += value.get_serialization_buffer_size();
+        }
+        Ok(data_start)
+    }
+
+    /// Version 0 records have no header and hold the first six values only.
+    fn decode_v0(buf: &[u8]) -> Result<Self, LogEncodeDecodeError> {
+        let mut values = [StoredDataValue::Default; 7];
+        let data_start = Self::decode_values(buf, &mut values[..6])?;
+        values[6] = StoredDataValue::SmallUInt(BeverageType::Water.into());
+        trace!("Decoded {} bytes", data_start);
+        Ok(Self::from_values(values))
+    }
+
+    /// Version 1 records hold all seven values after the header. Fields added by later versions
+    /// are appended, so anything after the known values is ignored.
+    fn decode_v1(buf: &[u8]) -> Result<Self, LogEncodeDecodeError> {
+        let mut values = [StoredDataValue::Default; 7];
+        let data_start = Self::decode_values(buf, &mut values)?;
+        trace!("Decoded {} bytes", data_start);
+        Ok(Self::from_values(values))
+    }
+
+    fn from_values(values: [StoredDataValue; 7]) -> Self {
+        let [hourly_consumption_target, daily_consumption_target, target_mode, total_consumption, daily_consumption_target_time, last_consumption, beverage] =
+            values;
+        Self {
+            hourly_consumption_target,
+            daily_consumption_target,
+            target_mode,
+            total_consumption,
+            daily_consumption_target_time,
+            last_consumption,
+            beverage,
+        }
+    }
+
+    /// Records written before beverage tracking was added decode as water.
+    pub fn get_beverage(&self) -> BeverageType {
+        match self.beverage {
+            StoredDataValue::SmallUInt(beverage_id) => {
+                beverage_id.try_into().unwrap_or_else(|_| {
+                    warn!("Unknown beverage ID in log: {}", beverage_id);
+                    BeverageType::Water
+                })
+            }
+            StoredDataValue::Default => BeverageType::Water,
+            _ => {
+                warn!(
+                    "Unexpected stored data for beverage: {}",
+                    Debug2Format(&self.beverage)
+                );
+                BeverageType::Water
+            }
         }
     }
 
@@ -58,6 +159,39 @@ impl DrinkMonitorLogData {
             0.0
         }
     }
+
+    pub fn get_total_consumption(&self) -> f32 {
+        if let StoredDataValue::Float(total_consumption) = self.total_consumption {
+            total_consumption
+        } else {
+            warn!(
+                "Unexpected stored data for total_consumption: {}",
+                Debug2Format(&self.total_consumption)
+            );
+            0.0
+        }
+    }
+
+    /// The daily target at the time of the snapshot, or `None` if a daily target was not being
+    /// monitored.
+    pub fn get_daily_consumption_target(&self) -> Option<u32> {
+        let daily_mode = u8::from(MonitoringTargetPeriodOptions::Daily);
+        match (self.target_mode, self.daily_consumption_target) {
+            (StoredDataValue::SmallUInt(mode), StoredDataValue::UInt(target))
+                if mode == daily_mode =>
+            {
+                Some(target)
+            }
+            (StoredDataValue::SmallUInt(_), _) => None,
+            _ => {
+                warn!(
+                    "Unexpected stored data for target_mode: {}",
+                    Debug2Format(&self.target_mode)
+                );
+                None
+            }
+        }
+    }
 }
 
 impl Default for DrinkMonitorLogData {
@@ -69,74 +203,27 @@ impl Default for DrinkMonitorLogData {
             total_consumption: StoredDataValue::Float(f32::default()),
             daily_consumption_target_time: StoredDataValue::Time(NaiveTime::default()),
             last_consumption: StoredDataValue::Float(f32::default()),
+            beverage: StoredDataValue::SmallUInt(BeverageType::Water.into()),
         }
     }
 }
 
 impl LogEncodeDecode for DrinkMonitorLogData {
     fn encode(&self, buf: &mut [u8]) -> Result<usize, LogEncodeDecodeError> {
-        let mut data_size = 0;
-        data_size += self
-            .hourly_consumption_target
-            .serialize_into(&mut buf[data_size..])
-            .map_err(|e| {
-                if e == SerializationError::BufferTooSmall {
-                    LogEncodeDecodeError::BufferTooSmall
-                } else {
-                    LogEncodeDecodeError::EncodeFailed
-                }
-            })?;
-        data_size += self
-            .daily_consumption_target
-            .serialize_into(&mut buf[data_size..])
-            .map_err(|e| {
-                if e == SerializationError::BufferTooSmall {
-                    LogEncodeDecodeError::BufferTooSmall
-                } else {
-                    LogEncodeDecodeError::EncodeFailed
-                }
-            })?;
-        data_size += self
-            .target_mode
-            .serialize_into(&mut buf[data_size..])
-            .map_err(|e| {
-                if e == SerializationError::BufferTooSmall {
-                    LogEncodeDecodeError::BufferTooSmall
-                } else {
-                    LogEncodeDecodeError::EncodeFailed
-                }
-            })?;
-        data_size += self
-            .total_consumption
-            .serialize_into(&mut buf[data_size..])
-            .map_err(|e| {
-                if e == SerializationError::BufferTooSmall {
-                    LogEncodeDecodeError::BufferTooSmall
-                } else {
-                    LogEncodeDecodeError::EncodeFailed
-                }
-            })?;
-        data_size += self
-            .daily_consumption_target_time
-            .serialize_into(&mut buf[data_size..])
-            .map_err(|e| {
-                if e == SerializationError::BufferTooSmall {
-                    LogEncodeDecodeError::BufferTooSmall
-                } else {
-                    LogEncodeDecodeError::EncodeFailed
-                }
-            })?;
-        data_size += self
-            .last_consumption
-            .serialize_into(&mut buf[data_size..])
-            .map_err(|e| {
-                if e == SerializationError::BufferTooSmall {
-                    LogEncodeDecodeError::BufferTooSmall
-                } else {
-                    LogEncodeDecodeError::EncodeFailed
-                }
-            })?;
-
+        let mut data_size =
+            RecordHeader::new(LogRecordType::Consumption, CONSUMPTION_RECORD_VERSION)
+                .encode(buf)?;
+        for value in [
+            &self.hourly_consumption_target,
+            &self.daily_consumption_target,
+            &self.target_mode,
+            &self.total_consumption,
+            &self.daily_consumption_target_time,
+            &self.last_consumption,
+            &self.beverage,
+        ] {
+            data_size += Self::encode_value(value, &mut buf[data_size..])?;
+        }
         Ok(data_size)
     }
 
@@ -144,113 +231,14 @@ impl LogEncodeDecode for DrinkMonitorLogData {
     where
         Self: Sized,
     {
-        let expected_bytes = 30;
-        if buf.len() < expected_bytes {
-            error!(
-                "Not enough bytes to decode - got {} expected {}",
-                buf.len(),
-                expected_bytes
-            );
-            return Err(LogEncodeDecodeError::BufferTooSmall);
-        }
         trace!("buffer: {}", &buf);
-
-        let mut s = Self::default();
-        let mut data_start: usize = 0;
-        let mut element_size = s.hourly_consumption_target.get_serialization_buffer_size();
-        s.hourly_consumption_target =
-            StoredDataValue::deserialize_from(&buf[data_start..data_start + element_size])
-                .map_err(|e| {
-                    error!(
-                        "Unable to decode data {} - start: {} - element_size: {} - bytes: {}",
-                        e,
-                        data_start,
-                        element_size,
-                        &buf[data_start..data_start + element_size]
-                    );
-                    LogEncodeDecodeError::DecodeFailed
-                })?;
-        data_start += element_size;
-
-        element_size = s.daily_consumption_target.get_serialization_buffer_size();
-        s.daily_consumption_target =
-            StoredDataValue::deserialize_from(&buf[data_start..data_start + element_size])
-                .map_err(|e| {
-                    error!(
-                        "Unable to decode data {} - start: {} - element_size: {} - bytes: {}",
-                        e,
-                        data_start,
-                        element_size,
-                        &buf[data_start..data_start + element_size]
-                    );
-                    LogEncodeDecodeError::DecodeFailed
-                })?;
-        data_start += element_size;
-
-        element_size = s.target_mode.get_serialization_buffer_size();
-        s.target_mode =
-            StoredDataValue::deserialize_from(&buf[data_start..data_start + element_size])
-                .map_err(|e| {
-                    error!(
-                        "Unable to decode data {} - start: {} - element_size: {} - bytes: {}",
-                        e,
-                        data_start,
-                        element_size,
-                        &buf[data_start..data_start + element_size]
-                    );
-                    LogEncodeDecodeError::DecodeFailed
-                })?;
-        data_start += element_size;
-
-        element_size = s.total_consumption.get_serialization_buffer_size();
-        s.total_consumption =
-            StoredDataValue::deserialize_from(&buf[data_start..data_start + element_size])
-                .map_err(|e| {
-                    error!(
-                        "Unable to decode data {} - start: {} - element_size: {} - bytes: {}",
-                        e,
-                        data_start,
-                        element_size,
-                        &buf[data_start..data_start + element_size]
-                    );
-                    LogEncodeDecodeError::DecodeFailed
-                })?;
-        data_start += element_size;
-
-        element_size = s
-            .daily_consumption_target_time
-            .get_serialization_buffer_size();
-        s.daily_consumption_target_time =
-            StoredDataValue::deserialize_from(&buf[data_start..data_start + element_size])
-                .map_err(|e| {
-                    error!(
-                        "Unable to decode data {} - start: {} - element_size: {} - bytes: {}",
-                        e,
-                        data_start,
-                        element_size,
-                        &buf[data_start..data_start + element_size]
-                    );
-                    LogEncodeDecodeError::DecodeFailed
-                })?;
-        data_start += element_size;
-
-        element_size = s.last_consumption.get_serialization_buffer_size();
-        s.last_consumption =
-            StoredDataValue::deserialize_from(&buf[data_start..data_start + element_size])
-                .map_err(|e| {
-                    error!(
-                        "Unable to decode data {} - start: {} - element_size: {} - bytes: {}",
-                        e,
-                        data_start,
-                        element_size,
-                        &buf[data_start..data_start + element_size]
-                    );
-                    LogEncodeDecodeError::DecodeFailed
-                })?;
-        data_start += element_size;
-
-        trace!("Decoded {} bytes", data_start);
-
-        Ok(s)
+        match RecordHeader::split_record(
+            buf,
+            LogRecordType::Consumption,
+            CONSUMPTION_RECORD_VERSION,
+        )? {
+            (0, body) => Self::decode_v0(body),
+            (_, body) => Self::decode_v1(body),
+        }
     }
 }
@@ -12,10 +12,14 @@
 // You should have received a copy of the GNU General Public License along with
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use crate::drink_monitor::beverage::BeverageType;
 use crate::drink_monitor::drink_monitoring::MonitoringStateSubstates;
 use crate::hmi::screens::settings_menu::monitoring_options::MonitoringTargetPeriodOptions;
+use chrono::NaiveDateTime;
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use embassy_sync::pubsub::{PubSubChannel, Publisher, Subscriber};
+use smartcoaster_app_core::reminder::ReminderLevel;
+use smartcoaster_app_core::target_direction::TargetDirection;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum DrinkMonitoringUpdate {
@@ -28,8 +32,50 @@ pub enum DrinkMonitoringUpdate {
     TargetMode(MonitoringTargetPeriodOptions),
     UpdateMonitoringSubstate(MonitoringStateSubstates),
     LastHour(bool),
+    /// Amount consumed ahead of the daily target flight path (ml). Negative when behind.
+    PlanDifference(f32),
+    TargetDirection(TargetDirection),
+    /// Beverage that consumption is currently attributed to.
+    ActiveBeverage(BeverageType),
+    /// Total consumed today for a beverage (ml).
+    BeverageTotalConsumed(BeverageType, f32),
+    /// Daily target (ml) and its direction for a beverage other than water.
+    BeverageTarget(BeverageType, f32, TargetDirection),
+    /// Time of the most recent drink of any beverage.
+    LastDrinkTime(NaiveDateTime),
+    /// Sent when the time-since-last-drink reminder level changes.
+    ReminderDue(ReminderLevel),
+    /// Sent once when the daily target is reached.
+    GoalAchieved,
+    /// Consecutive days on target, including today once the target is reached.
+    Streak(u32),
+    /// Total consumed (ml) on a previous day and whether the target was achieved, identified by
+    /// the number of days ago, from 1 to `DAILY_TOTAL_HISTORY_DAYS`. Days without a summary are
+    /// reported as zero.
+    DailyTotal(u8, f32, bool),
+    /// Average daily total (ml) over the previous 7 days, or `None` without any history.
+    SevenDayAverage(Option<f32>),
+    /// Average daily total (ml) over the previous 30 days, or `None` without any history.
+    ThirtyDayAverage(Option<f32>),
+    /// Water consumed (ml) during a clock hour, identified by the number of hours ago from 0 (the
+    /// current hour) to `HOURLY_HISTORY_HOURS - 1`.
+    HourlyConsumption(u8, f32),
+    /// Cumulative water consumption (ml) and target consumption (ml) at a point through the
+    /// monitoring day, identified by the hours since the day started from 0 to
+    /// `DAY_PROGRESS_POINTS - 1`. Consumption is `None` for points that have not been reached yet.
+    DayProgress(u8, Option<f32>, f32),
 }
 
+/// Number of previous days reported with `DrinkMonitoringUpdate::DailyTotal`.
+pub const DAILY_TOTAL_HISTORY_DAYS: usize = 7;
+
+/// Number of hours reported with `DrinkMonitoringUpdate::HourlyConsumption`.
+pub const HOURLY_HISTORY_HOURS: usize = 24;
+
+/// Number of points reported with `DrinkMonitoringUpdate::DayProgress`, one for each hour of the day
+/// including both the start and end.
+pub const DAY_PROGRESS_POINTS: usize = 25;
+
 const CHANNEL_DEPTH: usize = 10;
 const CHANNEL_SUBS: usize = 2;
 const CHANNEL_PUBS: usize = 1;
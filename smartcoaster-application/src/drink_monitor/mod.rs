@@ -12,6 +12,8 @@
 // You should have received a copy of the GNU General Public License along with
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
+pub mod beverage;
 pub mod drink_monitoring;
-mod log_data;
+pub mod log_data;
 pub mod messaging;
+pub mod rollup_data;
@@ -0,0 +1,165 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::storage::historical::record::{LogRecordType, RecordHeader};
+use crate::storage::historical::{LogEncodeDecode, LogEncodeDecodeError};
+use crate::storage::StoredDataValue;
+use chrono::NaiveDateTime;
+use defmt::{error, trace, warn, Debug2Format};
+use sequential_storage::map::{SerializationError, Value};
+use smartcoaster_app_core::daily_summary::DailySummary;
+
+/// Layout version written in the header of daily rollup records.
+///
+/// - 0: no header, six values.
+/// - 1: record header followed by the six values.
+pub const ROLLUP_RECORD_VERSION: u8 = 1;
+
+/// Daily summary record stored in the daily rollup log.
+pub struct DailyRollupLogData {
+    day_start: StoredDataValue,
+    total_consumption: StoredDataValue,
+    drink_count: StoredDataValue,
+    first_drink: StoredDataValue,
+    last_drink: StoredDataValue,
+    target_achieved: StoredDataValue,
+}
+
+impl DailyRollupLogData {
+    fn optional_date_time(value: StoredDataValue) -> Option<NaiveDateTime> {
+        match value {
+            StoredDataValue::DateTime(date_time) => Some(date_time),
+            StoredDataValue::Default => None,
+            _ => {
+                warn!(
+                    "Unexpected stored data for drink time: {}",
+                    Debug2Format(&value)
+                );
+                None
+            }
+        }
+    }
+
+    pub fn get_summary(&self) -> Result<DailySummary, LogEncodeDecodeError> {
+        let StoredDataValue::DateTime(day_start) = self.day_start else {
+            error!(
+                "Unexpected stored data for day_start: {}",
+                Debug2Format(&self.day_start)
+            );
+            return Err(LogEncodeDecodeError::DecodeFailed);
+        };
+        let mut summary = DailySummary::new(day_start);
+        if let StoredDataValue::Float(total_consumption) = self.total_consumption {
+            summary.total_consumption = total_consumption;
+        }
+        if let StoredDataValue::UInt(drink_count) = self.drink_count {
+            summary.drink_count = drink_count;
+        }
+        summary.first_drink = Self::optional_date_time(self.first_drink);
+        summary.last_drink = Self::optional_date_time(self.last_drink);
+        summary.target_achieved = self.target_achieved == StoredDataValue::SmallUInt(1);
+        Ok(summary)
+    }
+
+    fn encode_value(
+        value: &StoredDataValue,
+        buf: &mut [u8],
+    ) -> Result<usize, LogEncodeDecodeError> {
+        value.serialize_into(buf).map_err(|e| {
+            if e == SerializationError::BufferTooSmall {
+                LogEncodeDecodeError::BufferTooSmall
+            } else {
+                LogEncodeDecodeError::EncodeFailed
+            }
+        })
+    }
+
+    /// Decodes the value at the start of `buf`, returning it with the number of bytes it used.
+    fn decode_value(buf: &[u8]) -> Result<(StoredDataValue, usize), LogEncodeDecodeError> {
+        let value = StoredDataValue::deserialize_from(buf).map_err(|e| {
+            error!("Unable to decode data {} - bytes: {}", e, buf);
+            LogEncodeDecodeError::DecodeFailed
+        })?;
+        Ok((value, value.get_serialization_buffer_size()))
+    }
+}
+
+impl From<&DailySummary> for DailyRollupLogData {
+    fn from(summary: &DailySummary) -> Self {
+        Self {
+            day_start: StoredDataValue::DateTime(summary.day_start),
+            total_consumption: StoredDataValue::Float(summary.total_consumption),
+            drink_count: StoredDataValue::UInt(summary.drink_count),
+            first_drink: summary
+                .first_drink
+                .map_or(StoredDataValue::Default, StoredDataValue::DateTime),
+            last_drink: summary
+                .last_drink
+                .map_or(StoredDataValue::Default, StoredDataValue::DateTime),
+            target_achieved: StoredDataValue::SmallUInt(summary.target_achieved as u8),
+        }
+    }
+}
+
+impl LogEncodeDecode for DailyRollupLogData {
+    fn encode(&self, buf: &mut [u8]) -> Result<usize, LogEncodeDecodeError> {
+        let mut data_size =
+            RecordHeader::new(LogRecordType::DailyRollup, ROLLUP_RECORD_VERSION).encode(buf)?;
+        for value in [
+            &self.day_start,
+            &self.total_consumption,
+            &self.drink_count,
+            &self.first_drink,
+            &self.last_drink,
+            &self.target_achieved,
+        ] {
+            data_size += Self::encode_value(value, &mut buf[data_size..])?;
+        }
+        Ok(data_size)
+    }
+
+    fn from_bytes(buf: &[u8]) -> Result<Self, LogEncodeDecodeError>
+    where
+        Self: Sized,
+    {
+        // versions 0 and 1 differ only in the header, and later versions append fields
+        let (_, buf) =
+            RecordHeader::split_record(buf, LogRecordType::DailyRollup, ROLLUP_RECORD_VERSION)?;
+
+        // drink times are not stored when there were no drinks, so fields are variable length
+        let mut data_start = 0;
+        let mut values = [StoredDataValue::Default; 6];
+        for value in values.iter_mut() {
+            if data_start >= buf.len() {
+                error!("Not enough bytes to decode - got {}", buf.len());
+                return Err(LogEncodeDecodeError::BufferTooSmall);
+            }
+            let (decoded, size) = Self::decode_value(&buf[data_start..])?;
+            *value = decoded;
+            data_start += size;
+        }
+        trace!("Decoded {} bytes", data_start);
+
+        let [day_start, total_consumption, drink_count, first_drink, last_drink, target_achieved] =
+            values;
+        Ok(Self {
+            day_start,
+            total_consumption,
+            drink_count,
+            first_drink,
+            last_drink,
+            target_achieved,
+        })
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::error_log::{ErrorCode, ErrorModule, ERROR_VALUE_COUNT};
+use crate::storage::historical::record::{LogRecordType, RecordHeader};
+use crate::storage::historical::{LogEncodeDecode, LogEncodeDecodeError};
+use crate::storage::StoredDataValue;
+use defmt::{error, warn, Debug2Format};
+use sequential_storage::map::{SerializationError, Value};
+
+/// Layout version written in the header of error records.
+///
+/// - 0: no header, module, code and values.
+/// - 1: record header followed by module, code and values.
+pub const ERROR_RECORD_VERSION: u8 = 1;
+
+/// Error record stored in the error log. The timestamp is stored by the log itself.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ErrorLogData {
+    pub module: ErrorModule,
+    pub code: ErrorCode,
+    pub values: [Option<u32>; ERROR_VALUE_COUNT],
+}
+
+impl ErrorLogData {
+    pub fn new(module: ErrorModule, code: ErrorCode) -> Self {
+        Self {
+            module,
+            code,
+            values: [None; ERROR_VALUE_COUNT],
+        }
+    }
+
+    /// Adds a value to the record. Values beyond [`ERROR_VALUE_COUNT`] are dropped.
+    pub fn with_value(mut self, value: u32) -> Self {
+        match self.values.iter_mut().find(|v| v.is_none()) {
+            Some(free_value) => *free_value = Some(value),
+            None => warn!("No space for error value {}", value),
+        }
+        self
+    }
+
+    fn encode_value(
+        value: &StoredDataValue,
+        buf: &mut [u8],
+    ) -> Result<usize, LogEncodeDecodeError> {
+        value.serialize_into(buf).map_err(|e| {
+            if e == SerializationError::BufferTooSmall {
+                LogEncodeDecodeError::BufferTooSmall
+            } else {
+                LogEncodeDecodeError::EncodeFailed
+            }
+        })
+    }
+
+    /// Decodes the value at the start of `buf`, returning it with the number of bytes it used.
+    fn decode_value(buf: &[u8]) -> Result<(StoredDataValue, usize), LogEncodeDecodeError> {
+        let value = StoredDataValue::deserialize_from(buf).map_err(|e| {
+            error!("Unable to decode data {} - bytes: {}", e, buf);
+            LogEncodeDecodeError::DecodeFailed
+        })?;
+        Ok((value, value.get_serialization_buffer_size()))
+    }
+}
+
+impl LogEncodeDecode for ErrorLogData {
+    fn encode(&self, buf: &mut [u8]) -> Result<usize, LogEncodeDecodeError> {
+        let mut data_size =
+            RecordHeader::new(LogRecordType::Error, ERROR_RECORD_VERSION).encode(buf)?;
+        data_size += Self::encode_value(
+            &StoredDataValue::SmallUInt(self.module.into()),
+            &mut buf[data_size..],
+        )?;
+        data_size += Self::encode_value(
+            &StoredDataValue::SmallUInt(self.code.into()),
+            &mut buf[data_size..],
+        )?;
+        for value in self.values {
+            let value = value.map_or(StoredDataValue::Default, StoredDataValue::UInt);
+            data_size += Self::encode_value(&value, &mut buf[data_size..])?;
+        }
+        Ok(data_size)
+    }
+
+    fn from_bytes(buf: &[u8]) -> Result<Self, LogEncodeDecodeError>
+    where
+        Self: Sized,
+    {
+        // versions 0 and 1 differ only in the header, and later versions append fields
+        let (_, buf) = RecordHeader::split_record(buf, LogRecordType::Error, ERROR_RECORD_VERSION)?;
+
+        // missing values are not stored, so fields are variable length
+        let mut data_start = 0;
+        let mut fields = [StoredDataValue::Default; 2 + ERROR_VALUE_COUNT];
+        for field in fields.iter_mut() {
+            if data_start >= buf.len() {
+                error!("Not enough bytes to decode - got {}", buf.len());
+                return Err(LogEncodeDecodeError::BufferTooSmall);
+            }
+            let (decoded, size) = Self::decode_value(&buf[data_start..])?;
+            *field = decoded;
+            data_start += size;
+        }
+
+        let (StoredDataValue::SmallUInt(module), StoredDataValue::SmallUInt(code)) =
+            (fields[0], fields[1])
+        else {
+            error!(
+                "Unexpected stored data for error module and code: {}, {}",
+                Debug2Format(&fields[0]),
+                Debug2Format(&fields[1])
+            );
+            return Err(LogEncodeDecodeError::DecodeFailed);
+        };
+
+        let mut data = Self::new(module.into(), code.into());
+        for (value, field) in data.values.iter_mut().zip(&fields[2..]) {
+            *value = match field {
+                StoredDataValue::UInt(v) => Some(*v),
+                StoredDataValue::Default => None,
+                _ => {
+                    warn!(
+                        "Unexpected stored data for error value: {}",
+                        Debug2Format(field)
+                    );
+                    None
+                }
+            };
+        }
+        Ok(data)
+    }
+}
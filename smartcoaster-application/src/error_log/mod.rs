@@ -0,0 +1,247 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Persistent log of errors and notable system events, such as firmware roll backs and watchdog
+//! resets, so they can be looked at after the device has been restarted.
+//!
+//! Errors are queued with [`log_error`], which can be called from anywhere, and written to flash
+//! by the storage task. Records are read back with an [`ErrorLogReader`].
+
+mod log_data;
+
+pub use log_data::{ErrorLogData, ERROR_RECORD_VERSION};
+
+use crate::storage::historical::accessor::HistoricalLogAccessor;
+use crate::storage::historical::log_config::Logs;
+use crate::storage::historical::messaging::{
+    HistoricalLogChannel, HistoricalLogChannelSubscriber, HistoricalLogMessage,
+};
+use crate::storage::historical::LogEncodeDecode;
+use crate::storage::signal::signal_storage_work;
+use chrono::NaiveDateTime;
+use defmt::{error, warn, Debug2Format, Format};
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::channel::Channel;
+
+/// Optional values stored with each error, e.g. the key of a setting that could not be saved.
+pub const ERROR_VALUE_COUNT: usize = 2;
+
+const ERROR_QUEUE_DEPTH: usize = 8;
+
+static ERROR_LOG_QUEUE: Channel<CriticalSectionRawMutex, ErrorLogData, ERROR_QUEUE_DEPTH> =
+    Channel::new();
+
+/// Part of the system an error was raised by.
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub enum ErrorModule {
+    System,
+    Application,
+    DrinkMonitor,
+    Storage,
+    /// Written by firmware that knows of more modules than this one, e.g. before a roll back.
+    Unknown(u8),
+}
+
+impl ErrorModule {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorModule::System => "System",
+            ErrorModule::Application => "Application",
+            ErrorModule::DrinkMonitor => "Drink monitor",
+            ErrorModule::Storage => "Storage",
+            ErrorModule::Unknown(_) => "Unknown",
+        }
+    }
+}
+
+impl From<u8> for ErrorModule {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::System,
+            1 => Self::Application,
+            2 => Self::DrinkMonitor,
+            3 => Self::Storage,
+            _ => Self::Unknown(value),
+        }
+    }
+}
+
+impl From<ErrorModule> for u8 {
+    fn from(value: ErrorModule) -> Self {
+        match value {
+            ErrorModule::System => 0,
+            ErrorModule::Application => 1,
+            ErrorModule::DrinkMonitor => 2,
+            ErrorModule::Storage => 3,
+            ErrorModule::Unknown(value) => value,
+        }
+    }
+}
+
+/// What went wrong, or what happened for events that are not errors.
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub enum ErrorCode {
+    ScaleReadFailed,
+    ScaleCalibrationFailed,
+    SettingSaveFailed,
+    LogWriteFailed,
+    LogClearFailed,
+    FirmwareUpdated,
+    FirmwareRolledBack,
+    WatchdogReset,
+    FactoryResetFailed,
+    SettingsMigrationFailed,
+    /// Written by firmware that knows of more codes than this one, e.g. before a roll back.
+    Unknown(u8),
+}
+
+impl ErrorCode {
+    pub fn description(&self) -> &'static str {
+        match self {
+            ErrorCode::ScaleReadFailed => "Scale read failed",
+            ErrorCode::ScaleCalibrationFailed => "Calibration failed",
+            ErrorCode::SettingSaveFailed => "Setting save failed",
+            ErrorCode::LogWriteFailed => "Log write failed",
+            ErrorCode::LogClearFailed => "Log clear failed",
+            ErrorCode::FirmwareUpdated => "Firmware updated",
+            ErrorCode::FirmwareRolledBack => "Firmware rolled back",
+            ErrorCode::WatchdogReset => "Watchdog reset",
+            ErrorCode::FactoryResetFailed => "Factory reset failed",
+            ErrorCode::SettingsMigrationFailed => "Settings migration failed",
+            ErrorCode::Unknown(_) => "Unknown error",
+        }
+    }
+}
+
+impl From<u8> for ErrorCode {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::ScaleReadFailed,
+            1 => Self::ScaleCalibrationFailed,
+            2 => Self::SettingSaveFailed,
+            3 => Self::LogWriteFailed,
+            4 => Self::LogClearFailed,
+            5 => Self::FirmwareUpdated,
+            6 => Self::FirmwareRolledBack,
+            7 => Self::WatchdogReset,
+            8 => Self::FactoryResetFailed,
+            9 => Self::SettingsMigrationFailed,
+            _ => Self::Unknown(value),
+        }
+    }
+}
+
+impl From<ErrorCode> for u8 {
+    fn from(value: ErrorCode) -> Self {
+        match value {
+            ErrorCode::ScaleReadFailed => 0,
+            ErrorCode::ScaleCalibrationFailed => 1,
+            ErrorCode::SettingSaveFailed => 2,
+            ErrorCode::LogWriteFailed => 3,
+            ErrorCode::LogClearFailed => 4,
+            ErrorCode::FirmwareUpdated => 5,
+            ErrorCode::FirmwareRolledBack => 6,
+            ErrorCode::WatchdogReset => 7,
+            ErrorCode::FactoryResetFailed => 8,
+            ErrorCode::SettingsMigrationFailed => 9,
+            ErrorCode::Unknown(value) => value,
+        }
+    }
+}
+
+/// An error read back from the log with the time it was written.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ErrorLogRecord {
+    pub timestamp: NaiveDateTime,
+    pub data: ErrorLogData,
+}
+
+/// Queues an error to be written to the error log. Does not wait, so it can be called from any
+/// context, including while storage is locked.
+pub fn log_error(data: ErrorLogData) {
+    error!(
+        "{} error logged: {}",
+        data.module.name(),
+        data.code.description()
+    );
+    if ERROR_LOG_QUEUE.try_send(data).is_err() {
+        warn!("Error log queue full - error not stored");
+    }
+    signal_storage_work();
+}
+
+/// Writes the queued errors to the error log. The errors are timestamped as they are written.
+pub async fn process_error_log_queue(error_log: &mut HistoricalLogAccessor) {
+    while let Ok(data) = ERROR_LOG_QUEUE.try_receive() {
+        error_log.log_data(data).await;
+    }
+}
+
+/// Reads the error log through a log read channel, which must not be used by anything else.
+pub struct ErrorLogReader {
+    error_log: HistoricalLogAccessor,
+    log_channel: &'static HistoricalLogChannel,
+    log_subscriber: Option<HistoricalLogChannelSubscriber<'static>>,
+}
+
+impl ErrorLogReader {
+    pub fn new(log_channel: &'static HistoricalLogChannel) -> Self {
+        Self {
+            error_log: HistoricalLogAccessor::new(Logs::ErrorLog),
+            log_channel,
+            log_subscriber: None,
+        }
+    }
+
+    /// Starts reading the whole log, oldest record first.
+    pub async fn start_read(&mut self) {
+        // subscribe before queueing the read so no records are missed
+        self.log_subscriber = self.log_channel.subscriber().ok();
+        if self.log_subscriber.is_none() {
+            error!("Unable to subscribe to error log read channel");
+            return;
+        }
+        self.error_log
+            .get_log_data_after_timestamp(NaiveDateTime::MIN, self.log_channel)
+            .await;
+    }
+
+    /// Returns the next record, or `None` when the whole log has been read. Records that cannot be
+    /// decoded are skipped.
+    pub async fn next_record(&mut self) -> Option<ErrorLogRecord> {
+        let log_subscriber = self.log_subscriber.as_mut()?;
+        loop {
+            match log_subscriber.next_message_pure().await {
+                HistoricalLogMessage::Error() => {
+                    error!("Log retrieval error when reading error log");
+                    break;
+                }
+                HistoricalLogMessage::EndOfRead() => break,
+                HistoricalLogMessage::Record(entry) => {
+                    match ErrorLogData::from_bytes(&entry.data) {
+                        Ok(data) => {
+                            return Some(ErrorLogRecord {
+                                timestamp: entry.timestamp,
+                                data,
+                            })
+                        }
+                        Err(e) => error!("Failed to decode error log entry: {}", Debug2Format(&e)),
+                    }
+                }
+            }
+        }
+        self.log_subscriber = None;
+        None
+    }
+}
@@ -12,73 +12,82 @@
 // You should have received a copy of the GNU General Public License along with
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use crate::application::application_state::{ApplicationState, ConfirmationId};
+use crate::application::application_state::ApplicationState;
 use crate::application::messaging::{
     ApplicationChannelSubscriber, ApplicationData, ApplicationMessage,
 };
-use crate::hmi::messaging::{HmiMessage, UiActionChannelPublisher, UiRequestMessage};
+use crate::hmi::messaging::{HmiMessage, UiActionChannelPublisher};
 use crate::hmi::rotary_encoder::Direction;
-use crate::hmi::screens::monitoring::MonitoringScreen;
-use crate::hmi::screens::settings_menu::monitoring_options::MonitoringTargetPeriodOptions;
-use crate::hmi::screens::settings_menu::SettingMenu;
-use crate::hmi::screens::settings_screens::about::AboutScreen;
-use crate::hmi::screens::settings_screens::calibration::CalibrationScreens;
-use crate::hmi::screens::settings_screens::confirmation::ConfirmationScreen;
-use crate::hmi::screens::settings_screens::heap_status::HeapStatusScreen;
-use crate::hmi::screens::settings_screens::set_date_time::SetDateTimeScreen;
-use crate::hmi::screens::settings_screens::set_number::SetNumberScreen;
-use crate::hmi::screens::settings_screens::set_time::SetTimeScreen;
-use crate::hmi::screens::settings_screens::test_mode::TestModeScreen;
-use crate::hmi::screens::{draw_message_screen, UiDrawer, UiInput, UiInputHandler};
+use crate::hmi::screen_manager::ScreenManager;
+use crate::hmi::screens::UiInput;
 use crate::rtc::accessor::RtcAccessor;
 use crate::storage::settings::{SettingValue, SettingsAccessor, SettingsAccessorId};
-use chrono::{NaiveDateTime, NaiveTime};
+use core::fmt::Debug;
 use defmt::{debug, error, trace, warn, Debug2Format};
 use embassy_futures::select::{select3, Either3};
 use embassy_sync::pubsub::WaitResult;
 use embassy_time::{Duration, Instant, Ticker};
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::DrawTarget;
 use sh1106::mode::GraphicsMode;
 
 const DEFAULT_BRIGHTNESS: u8 = 128;
 const DEFAULT_DISPLAY_TIMEOUT_MINUTES: u8 = 15;
 
-pub struct DisplayManager<'a, DI, SA>
+/// A buffered display that the screens are drawn to. Nothing is shown until the buffer is flushed.
+pub trait HmiDisplay: DrawTarget<Color = BinaryColor, Error: Debug> {
+    fn init(&mut self) -> Result<(), ()>;
+    fn flush(&mut self) -> Result<(), ()>;
+    fn clear_buffer(&mut self);
+    fn set_contrast(&mut self, contrast: u8) -> Result<(), ()>;
+}
+
+impl<DI> HmiDisplay for GraphicsMode<DI>
 where
     DI: sh1106::interface::DisplayInterface,
+{
+    fn init(&mut self) -> Result<(), ()> {
+        GraphicsMode::init(self).map_err(|_| ())
+    }
+
+    fn flush(&mut self) -> Result<(), ()> {
+        GraphicsMode::flush(self).map_err(|_| ())
+    }
+
+    fn clear_buffer(&mut self) {
+        GraphicsMode::clear(self)
+    }
+
+    fn set_contrast(&mut self, contrast: u8) -> Result<(), ()> {
+        GraphicsMode::set_contrast(self, contrast).map_err(|_| ())
+    }
+}
+
+pub struct DisplayManager<'a, D, SA>
+where
+    D: HmiDisplay,
     SA: SettingsAccessor,
 {
     app_channel_subscriber: ApplicationChannelSubscriber<'static>,
-    ui_action_publisher: UiActionChannelPublisher<'static>,
-    display: GraphicsMode<DI>,
+    display: D,
+    screens: ScreenManager<'a, SA>,
 
-    display_state: ApplicationState,
     last_display_update: Instant,
     display_timeout: Duration,
 
-    settings_screen: SettingMenu<'a, SA>,
-    test_mode_screen: TestModeScreen,
-    monitoring_screen: MonitoringScreen<'a, SA>,
-    heap_status_screen: HeapStatusScreen,
-    calibration_screens: CalibrationScreens,
-    set_date_time_screen: SetDateTimeScreen,
-    number_setting_screen: SetNumberScreen,
-    time_entry_screen: SetTimeScreen,
-    confirmation_screen: ConfirmationScreen,
-    about_screen: AboutScreen,
-
     settings: &'a SA,
     rtc_accessor: RtcAccessor,
 }
 
-impl<'a, DI, SA> DisplayManager<'a, DI, SA>
+impl<'a, D, SA> DisplayManager<'a, D, SA>
 where
-    DI: sh1106::interface::DisplayInterface,
+    D: HmiDisplay,
     SA: SettingsAccessor,
 {
     const FRAME_TIMING_MS: u32 = 1000 / 30;
 
     pub async fn new(
-        mut display: GraphicsMode<DI>,
+        mut display: D,
         app_channel_subscriber: ApplicationChannelSubscriber<'static>,
         ui_action_publisher: UiActionChannelPublisher<'static>,
         settings: &'a SA,
@@ -93,40 +102,12 @@ where
 
         let mut s = Self {
             app_channel_subscriber,
-            ui_action_publisher,
             display,
+            screens: ScreenManager::new(ui_action_publisher, settings).await,
 
-            display_state: ApplicationState::Startup,
             last_display_update: Instant::MIN,
             display_timeout: Duration::from_secs(30 * 60),
 
-            settings_screen: SettingMenu::new(settings).await,
-            test_mode_screen: TestModeScreen::new(),
-            monitoring_screen: MonitoringScreen::new(settings).await,
-            heap_status_screen: HeapStatusScreen::new(),
-            calibration_screens: CalibrationScreens::new(),
-            set_date_time_screen: SetDateTimeScreen::new("Default", NaiveDateTime::default(), None),
-            number_setting_screen: SetNumberScreen::new(
-                // set some default values, these are changed as required
-                "Default",
-                "X",
-                0,
-                0,
-                1000,
-                SettingsAccessorId::MonitoringTargetDaily,
-            ),
-            time_entry_screen: SetTimeScreen::new(
-                "Default",
-                NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
-                SettingsAccessorId::MonitoringDailyTargetTime,
-            ),
-            confirmation_screen: ConfirmationScreen::new(
-                "Default",
-                "Default",
-                UiRequestMessage::ClearHistoricalConsumptionLog(),
-            ),
-            about_screen: AboutScreen::new(),
-
             settings,
             rtc_accessor,
         };
@@ -171,109 +152,9 @@ where
         Duration::from_secs(display_timeout as u64 * 60)
     }
 
-    async fn setup_system_date_time_setting(&mut self) {
-        self.set_date_time_screen = SetDateTimeScreen::new(
-            "System Date & Time",
-            self.rtc_accessor.get_date_time(),
-            None,
-        )
-    }
-
-    async fn setup_monitoring_target_time_selection(&mut self) {
-        let value = if let SettingValue::Time(value) = self
-            .settings
-            .get_setting(SettingsAccessorId::MonitoringDailyTargetTime)
-            .await
-            .unwrap_or(SettingValue::Time(
-                NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
-            )) {
-            value
-        } else {
-            NaiveTime::from_hms_opt(0, 0, 0).unwrap()
-        };
-
-        self.time_entry_screen = SetTimeScreen::new(
-            "Daily Target Time",
-            value,
-            SettingsAccessorId::MonitoringDailyTargetTime,
-        )
-    }
-
-    async fn setup_consumption_log_reset_confirmation(&mut self) {
-        self.confirmation_screen = ConfirmationScreen::new(
-            "",
-            "Are you sure you wish to clear the historical consumption data?",
-            UiRequestMessage::ClearHistoricalConsumptionLog(),
-        )
-    }
-
-    async fn setup_monitoring_target_value_selection(&mut self) {
-        let monitoring_target_id = if let SettingValue::SmallUInt(value) = self
-            .settings
-            .get_setting(SettingsAccessorId::MonitoringTargetType)
-            .await
-            .unwrap_or(SettingValue::SmallUInt(0))
-        {
-            value
-        } else {
-            0u8
-        };
-        let monitoring_target =
-            MonitoringTargetPeriodOptions::try_from(monitoring_target_id as usize).unwrap();
-
-        let accessor_id = match monitoring_target {
-            MonitoringTargetPeriodOptions::Daily => SettingsAccessorId::MonitoringTargetDaily,
-            MonitoringTargetPeriodOptions::Hourly => SettingsAccessorId::MonitoringTargetHourly,
-        };
-
-        let properties = accessor_id.get_numeric_properties().unwrap();
-        let value = if let SettingValue::UInt(value) = self
-            .settings
-            .get_setting(accessor_id)
-            .await
-            .unwrap_or(SettingValue::UInt(0))
-        {
-            value
-        } else {
-            0u32
-        };
-
-        self.number_setting_screen = SetNumberScreen::new(
-            monitoring_target.title(),
-            monitoring_target.units(),
-            value,
-            properties.minimum_value,
-            properties.maximum_value,
-            accessor_id,
-        );
-    }
-
     pub async fn set_display_state(&mut self, display_state: ApplicationState) {
-        debug!("Display state: {:?}", display_state);
-
-        if let ApplicationState::NumberEntry(setting_id) = display_state {
-            if setting_id == SettingsAccessorId::MonitoringTargetDaily {
-                self.setup_monitoring_target_value_selection().await
-            }
-        }
-        if let ApplicationState::TimeEntry(setting_id) = display_state {
-            if setting_id == SettingsAccessorId::MonitoringDailyTargetTime {
-                self.setup_monitoring_target_time_selection().await
-            }
-        }
-        if let ApplicationState::ConfirmationScreen(confirmation_id) = display_state {
-            if confirmation_id == ConfirmationId::ClearHistoricalConsumptionLog {
-                self.setup_consumption_log_reset_confirmation().await;
-            }
-        }
-
-        if let ApplicationState::SetSystemDateTime = display_state {
-            self.setup_system_date_time_setting().await;
-        }
-
-        self.display_state = display_state;
         let dt = self.rtc_accessor.get_date_time();
-        self.route_ui_input(UiInput::DateTimeUpdate(dt)).await;
+        self.screens.set_display_state(display_state, dt).await;
         self.update_now().await;
     }
 
@@ -286,108 +167,14 @@ where
     }
 
     async fn update_now(&mut self) {
-        self.display.clear();
-        match self.display_state {
-            ApplicationState::Startup => {
-                draw_message_screen(&mut self.display, "Starting up...").unwrap()
-            }
-
-            ApplicationState::ErrorScreenWithMessage(s) => {
-                draw_message_screen(&mut self.display, s).unwrap()
-            }
-
-            ApplicationState::TestScreen => self.test_mode_screen.draw(&mut self.display).unwrap(),
-            ApplicationState::Settings => self.settings_screen.draw(&mut self.display).unwrap(),
-            ApplicationState::Monitoring => self.monitoring_screen.draw(&mut self.display).unwrap(),
-            ApplicationState::HeapStatus => {
-                self.heap_status_screen.draw(&mut self.display).unwrap()
-            }
-            ApplicationState::Calibration => {
-                self.calibration_screens.draw(&mut self.display).unwrap()
-            }
-            ApplicationState::SetSystemDateTime | ApplicationState::DateTimeEntry(_) => {
-                self.set_date_time_screen.draw(&mut self.display).unwrap()
-            }
-            ApplicationState::NumberEntry(_) => {
-                self.number_setting_screen.draw(&mut self.display).unwrap()
-            }
-            ApplicationState::AboutScreen => {
-                self.about_screen.update_pre_draw_actions(&self.display);
-                self.about_screen.draw(&mut self.display).unwrap()
-            }
-            ApplicationState::TimeEntry(_) => {
-                self.time_entry_screen.draw(&mut self.display).unwrap()
-            }
-            ApplicationState::ConfirmationScreen(_) => {
-                self.confirmation_screen.draw(&mut self.display).unwrap()
-            }
-        }
-
+        self.display.clear_buffer();
+        self.screens.draw(&mut self.display).unwrap();
         self.display
             .flush()
             .unwrap_or_else(|_| error!("Display flush failed"));
         self.last_display_update = Instant::now();
     }
 
-    async fn route_ui_input(&mut self, input: UiInput) {
-        trace!("Route UI input received: {}", Debug2Format(&input));
-        match self.display_state {
-            ApplicationState::Startup => {}
-            ApplicationState::ErrorScreenWithMessage(_) => {}
-
-            ApplicationState::Settings => {
-                self.settings_screen
-                    .ui_input_handler(input, &self.ui_action_publisher)
-                    .await
-            }
-            ApplicationState::TestScreen => {
-                self.test_mode_screen
-                    .ui_input_handler(input, &self.ui_action_publisher)
-                    .await
-            }
-            ApplicationState::Monitoring => {
-                self.monitoring_screen
-                    .ui_input_handler(input, &self.ui_action_publisher)
-                    .await
-            }
-            ApplicationState::HeapStatus => {
-                self.heap_status_screen
-                    .ui_input_handler(input, &self.ui_action_publisher)
-                    .await
-            }
-            ApplicationState::Calibration => {
-                self.calibration_screens
-                    .ui_input_handler(input, &self.ui_action_publisher)
-                    .await
-            }
-            ApplicationState::SetSystemDateTime | ApplicationState::DateTimeEntry(_) => {
-                self.set_date_time_screen
-                    .ui_input_handler(input, &self.ui_action_publisher)
-                    .await
-            }
-            ApplicationState::NumberEntry(_) => {
-                self.number_setting_screen
-                    .ui_input_handler(input, &self.ui_action_publisher)
-                    .await
-            }
-            ApplicationState::TimeEntry(_) => {
-                self.time_entry_screen
-                    .ui_input_handler(input, &self.ui_action_publisher)
-                    .await
-            }
-            ApplicationState::ConfirmationScreen(_) => {
-                self.confirmation_screen
-                    .ui_input_handler(input, &self.ui_action_publisher)
-                    .await;
-            }
-            ApplicationState::AboutScreen => {
-                self.about_screen
-                    .ui_input_handler(input, &self.ui_action_publisher)
-                    .await
-            }
-        }
-    }
-
     pub async fn run(&mut self) {
         self.update_screen().await;
         let mut update_ticker = Ticker::every(Duration::from_millis(200));
@@ -411,6 +198,7 @@ where
                         trace!("App message: {}", Debug2Format(&message));
                         match message {
                             ApplicationMessage::ClearHistoricalConsumptionLog => {}
+                            ApplicationMessage::SnoozeReminder => {}
                             ApplicationMessage::HmiInput(hmi_message) => {
                                 last_activity = Instant::now();
                                 match hmi_message {
@@ -418,13 +206,16 @@ where
                                         trace!("Encoder update: {:?}", Debug2Format(&direction));
                                         match direction {
                                             Direction::Clockwise => {
-                                                self.route_ui_input(UiInput::EncoderClockwise).await
+                                                self.screens
+                                                    .route_ui_input(UiInput::EncoderClockwise)
+                                                    .await
                                             }
                                             Direction::CounterClockwise => {
-                                                self.route_ui_input(
-                                                    UiInput::EncoderCounterClockwise,
-                                                )
-                                                .await
+                                                self.screens
+                                                    .route_ui_input(
+                                                        UiInput::EncoderCounterClockwise,
+                                                    )
+                                                    .await
                                             }
                                             Direction::None => {}
                                         }
@@ -432,12 +223,20 @@ where
                                     HmiMessage::PushButtonPressed(is_pressed) => {
                                         trace!("Button pressed {:?} ", is_pressed);
                                         match is_pressed {
-                                            true => self.route_ui_input(UiInput::ButtonPress).await,
+                                            true => {
+                                                self.screens
+                                                    .route_ui_input(UiInput::ButtonPress)
+                                                    .await
+                                            }
                                             false => {
-                                                self.route_ui_input(UiInput::ButtonRelease).await
+                                                self.screens
+                                                    .route_ui_input(UiInput::ButtonRelease)
+                                                    .await
                                             }
                                         }
                                     }
+                                    // handled by the application manager
+                                    HmiMessage::FactoryResetCombination => {}
                                 }
                             }
                             ApplicationMessage::ApplicationStateUpdate(new_state) => {
@@ -490,7 +289,8 @@ where
                                         if let ApplicationData::MonitoringUpdate(_) = data_update {
                                             last_activity = Instant::now();
                                         }
-                                        self.route_ui_input(UiInput::ApplicationData(data_update))
+                                        self.screens
+                                            .route_ui_input(UiInput::ApplicationData(data_update))
                                             .await;
                                     }
                                 }
@@ -503,9 +303,11 @@ where
                 }
                 Either3::Third(dt) => {
                     trace!("RTC update");
-                    if self.display_state != ApplicationState::SetSystemDateTime {
+                    if self.screens.display_state() != ApplicationState::SetSystemDateTime {
                         trace!("DateTime update");
-                        self.route_ui_input(UiInput::DateTimeUpdate(dt)).await;
+                        self.screens
+                            .route_ui_input(UiInput::DateTimeUpdate(dt))
+                            .await;
                     }
                 }
             }
@@ -515,7 +317,7 @@ where
                         "Display timeout reached - turning display off - {} sec",
                         self.display_timeout.as_secs()
                     );
-                    self.display.clear();
+                    self.display.clear_buffer();
                     self.display
                         .flush()
                         .unwrap_or_else(|_| warn!("Display flush failed"));
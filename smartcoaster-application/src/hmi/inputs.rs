@@ -15,19 +15,58 @@
 use crate::hmi::debouncer::Debouncer;
 use crate::hmi::messaging::HmiChannelPublisher;
 use crate::hmi::messaging::HmiMessage;
-use crate::hmi::rotary_encoder::RotaryEncoder;
+use crate::hmi::rotary_encoder::{Direction, RotaryEncoder};
 use defmt::{trace, Debug2Format};
 use embassy_futures::select::{select, Either};
 use embassy_rp::gpio::Level;
+use embassy_time::{Duration, Instant};
 
 const PRESSED_LEVEL: Level = Level::Low;
 
+/// Holding the button down at power on enters the bootloader's DFU mode, so the factory reset
+/// combination is made once the application is running: hold the button and turn the encoder
+/// anticlockwise this many steps within the window after boot. The application manager shows the
+/// reset screen and then starts the reset.
+const FACTORY_RESET_STEPS: u8 = 5;
+const FACTORY_RESET_WINDOW: Duration = Duration::from_secs(10);
+
+struct FactoryResetCombination {
+    button_held: bool,
+    steps: u8,
+}
+
+impl FactoryResetCombination {
+    fn new() -> Self {
+        Self {
+            button_held: false,
+            steps: 0,
+        }
+    }
+
+    fn button(&mut self, pressed: bool) {
+        self.button_held = pressed;
+        self.steps = 0;
+    }
+
+    /// Returns true when the combination has been completed.
+    fn encoder(&mut self, direction: Direction) -> bool {
+        if !self.button_held || direction != Direction::CounterClockwise {
+            self.steps = 0;
+            return false;
+        }
+        self.steps += 1;
+        self.steps == FACTORY_RESET_STEPS
+    }
+}
+
 pub async fn hmi_input_handler(
     hmi_event_channel: HmiChannelPublisher<'_>,
     mut debounced_btn: Debouncer<'_>,
     rotary_encoder: &mut impl RotaryEncoder,
 ) {
     let mut next_btn_level = PRESSED_LEVEL; // assumes we start unpressed
+    let mut factory_reset_combination = FactoryResetCombination::new();
+    let boot_time = Instant::now();
     loop {
         trace!("Waiting for input");
         let hmi_io_event = select(
@@ -39,10 +78,16 @@ pub async fn hmi_input_handler(
         match hmi_io_event {
             Either::First(encoder_moved) => {
                 trace!("Encoder moved: {}", Debug2Format(&encoder_moved));
-                hmi_event_channel.publish_immediate(HmiMessage::EncoderUpdate(encoder_moved))
+                hmi_event_channel.publish_immediate(HmiMessage::EncoderUpdate(encoder_moved));
+                if factory_reset_combination.encoder(encoder_moved)
+                    && boot_time.elapsed() < FACTORY_RESET_WINDOW
+                {
+                    hmi_event_channel.publish_immediate(HmiMessage::FactoryResetCombination);
+                }
             }
             Either::Second(_) => {
                 trace!("Button pressed: {}", Debug2Format(&next_btn_level));
+                factory_reset_combination.button(next_btn_level == PRESSED_LEVEL);
                 hmi_event_channel.publish_immediate(HmiMessage::PushButtonPressed(
                     next_btn_level == PRESSED_LEVEL,
                 ));
@@ -14,6 +14,7 @@
 
 use crate::application::application_state::ApplicationState;
 use crate::hmi::rotary_encoder::Direction;
+use crate::storage::factory_reset::FactoryResetOptions;
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use embassy_sync::pubsub::{PubSubChannel, Publisher, Subscriber};
 
@@ -21,6 +22,8 @@ use embassy_sync::pubsub::{PubSubChannel, Publisher, Subscriber};
 pub enum HmiMessage {
     EncoderUpdate(Direction),
     PushButtonPressed(bool),
+    /// The factory reset combination was made after boot, see `hmi::inputs`.
+    FactoryResetCombination,
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -30,6 +33,8 @@ pub enum UiRequestMessage {
     ChangeDisplayBrightness(u8),
     ChangeDisplayTimeout(u8),
     ClearHistoricalConsumptionLog(),
+    FactoryReset(FactoryResetOptions),
+    SnoozeReminder(),
 }
 
 const CHANNEL_DEPTH: usize = 20;
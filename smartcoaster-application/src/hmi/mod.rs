@@ -13,9 +13,12 @@
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
 pub mod rotary_encoder;
+#[cfg(target_os = "none")]
 pub mod debouncer;
 pub mod display;
+#[cfg(target_os = "none")]
 pub mod inputs;
 pub mod messaging;
-pub(crate) mod screens;
+pub mod screen_manager;
+pub mod screens;
 
@@ -12,10 +12,15 @@
 // You should have received a copy of the GNU General Public License along with
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
+#[cfg(target_os = "none")]
 use crate::hmi::debouncer::Debouncer;
+#[cfg(target_os = "none")]
 use embassy_futures::join::join;
+#[cfg(target_os = "none")]
 use embassy_futures::select::{select, Either};
+#[cfg(target_os = "none")]
 use embassy_rp::gpio::{Input, Level};
+#[cfg(target_os = "none")]
 use embassy_time::Duration;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -41,11 +46,13 @@ pub trait RotaryEncoder {
     async fn state_change(&mut self) -> Direction;
 }
 
+#[cfg(target_os = "none")]
 pub struct DebouncedRotaryEncoder<'a> {
     debounced_dt: Debouncer<'a>,
     debounced_clk: Debouncer<'a>,
 }
 
+#[cfg(target_os = "none")]
 impl<'a> DebouncedRotaryEncoder<'a> {
     pub fn new(pin_dt: Input<'a>, pin_clk: Input<'a>, debounce_duration: Duration) -> Self {
         Self {
@@ -55,6 +62,7 @@ impl<'a> DebouncedRotaryEncoder<'a> {
     }
 }
 
+#[cfg(target_os = "none")]
 impl RotaryEncoder for DebouncedRotaryEncoder<'_> {
     async fn state_change(&mut self) -> Direction {
         let mut clk_level: Level;
@@ -108,12 +116,14 @@ impl RotaryEncoder for DebouncedRotaryEncoder<'_> {
     }
 }
 
+#[cfg(target_os = "none")]
 #[allow(dead_code)]
 pub struct RawRotaryEncoder<'a> {
     dt: Input<'a>,
     clk: Input<'a>,
 }
 
+#[cfg(target_os = "none")]
 #[allow(dead_code)]
 impl<'a> RawRotaryEncoder<'a> {
     fn new(pin_dt: Input<'a>, pin_clk: Input<'a>) -> Self {
@@ -137,6 +147,7 @@ impl<'a> RawRotaryEncoder<'a> {
     }
 }
 
+#[cfg(target_os = "none")]
 #[allow(dead_code)]
 impl<'a> RotaryEncoder for RawRotaryEncoder<'a> {
     async fn state_change(&mut self) -> Direction {
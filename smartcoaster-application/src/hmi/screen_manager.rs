@@ -0,0 +1,422 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Owns every HMI screen and selects which one is shown and receives input based on the
+//! application state. It has no knowledge of the display hardware so it can drive any
+//! `DrawTarget`.
+
+use crate::application::application_state::{ApplicationState, ConfirmationId, FirstRunStep};
+use crate::drink_monitor::beverage::BeverageType;
+use crate::hmi::messaging::{UiActionChannelPublisher, UiRequestMessage};
+use crate::hmi::screens::monitoring::{read_custom_layout, MonitoringScreen};
+use crate::hmi::screens::settings_menu::monitoring_options::MonitoringTargetPeriodOptions;
+use crate::hmi::screens::settings_menu::SettingMenu;
+use crate::hmi::screens::settings_screens::about::AboutScreen;
+use crate::hmi::screens::settings_screens::calibration::CalibrationScreens;
+use crate::hmi::screens::settings_screens::confirmation::ConfirmationScreen;
+use crate::hmi::screens::settings_screens::error_log::ErrorLogScreen;
+use crate::hmi::screens::settings_screens::first_run::FirstRunScreen;
+use crate::hmi::screens::settings_screens::heap_status::HeapStatusScreen;
+use crate::hmi::screens::settings_screens::nvm_status::NvmStatusScreen;
+use crate::hmi::screens::settings_screens::set_date_time::SetDateTimeScreen;
+use crate::hmi::screens::settings_screens::set_number::SetNumberScreen;
+use crate::hmi::screens::settings_screens::set_time::SetTimeScreen;
+use crate::hmi::screens::settings_screens::test_mode::TestModeScreen;
+use crate::hmi::screens::{draw_message_screen, UiDrawer, UiInput, UiInputHandler};
+use crate::storage::factory_reset::FactoryResetOptions;
+use crate::storage::settings::{SettingValue, SettingsAccessor, SettingsAccessorId};
+use chrono::{NaiveDateTime, NaiveTime};
+use defmt::{debug, trace, Debug2Format};
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::pixelcolor::BinaryColor;
+
+pub struct ScreenManager<'a, SA>
+where
+    SA: SettingsAccessor,
+{
+    ui_action_publisher: UiActionChannelPublisher<'static>,
+    display_state: ApplicationState,
+
+    settings_screen: SettingMenu<'a, SA>,
+    test_mode_screen: TestModeScreen,
+    monitoring_screen: MonitoringScreen<'a, SA>,
+    heap_status_screen: HeapStatusScreen,
+    error_log_screen: ErrorLogScreen,
+    nvm_status_screen: NvmStatusScreen,
+    calibration_screens: CalibrationScreens,
+    set_date_time_screen: SetDateTimeScreen,
+    number_setting_screen: SetNumberScreen,
+    time_entry_screen: SetTimeScreen,
+    confirmation_screen: ConfirmationScreen,
+    about_screen: AboutScreen,
+    first_run_screen: FirstRunScreen,
+
+    settings: &'a SA,
+}
+
+impl<'a, SA> ScreenManager<'a, SA>
+where
+    SA: SettingsAccessor,
+{
+    pub async fn new(
+        ui_action_publisher: UiActionChannelPublisher<'static>,
+        settings: &'a SA,
+    ) -> Self {
+        Self {
+            ui_action_publisher,
+            display_state: ApplicationState::Startup,
+
+            settings_screen: SettingMenu::new(settings).await,
+            test_mode_screen: TestModeScreen::new(),
+            monitoring_screen: MonitoringScreen::new(settings).await,
+            heap_status_screen: HeapStatusScreen::new(),
+            error_log_screen: ErrorLogScreen::new(),
+            nvm_status_screen: NvmStatusScreen::new(),
+            calibration_screens: CalibrationScreens::new(),
+            set_date_time_screen: SetDateTimeScreen::new("Default", NaiveDateTime::default(), None),
+            number_setting_screen: SetNumberScreen::new(
+                // set some default values, these are changed as required
+                "Default",
+                "X",
+                0,
+                0,
+                1000,
+                SettingsAccessorId::MonitoringTargetDaily,
+            ),
+            time_entry_screen: SetTimeScreen::new(
+                "Default",
+                NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+                SettingsAccessorId::MonitoringDailyTargetTime,
+            ),
+            confirmation_screen: ConfirmationScreen::new(
+                "Default",
+                "Default",
+                UiRequestMessage::ClearHistoricalConsumptionLog(),
+            ),
+            about_screen: AboutScreen::new(),
+            first_run_screen: FirstRunScreen::new(FirstRunStep::Welcome),
+
+            settings,
+        }
+    }
+
+    pub fn display_state(&self) -> ApplicationState {
+        self.display_state
+    }
+
+    async fn setup_system_date_time_setting(&mut self, now: NaiveDateTime) {
+        self.set_date_time_screen = SetDateTimeScreen::new("System Date & Time", now, None)
+    }
+
+    async fn setup_time_selection(&mut self, label: &'static str, setting_id: SettingsAccessorId) {
+        let value = if let SettingValue::Time(value) = self
+            .settings
+            .get_setting(setting_id)
+            .await
+            .unwrap_or(SettingValue::Time(
+                NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+            )) {
+            value
+        } else {
+            NaiveTime::from_hms_opt(0, 0, 0).unwrap()
+        };
+
+        self.time_entry_screen = SetTimeScreen::new(label, value, setting_id)
+    }
+
+    async fn setup_consumption_log_reset_confirmation(&mut self) {
+        self.confirmation_screen = ConfirmationScreen::new(
+            "",
+            "Are you sure you wish to clear the historical consumption data?",
+            UiRequestMessage::ClearHistoricalConsumptionLog(),
+        )
+    }
+
+    async fn setup_factory_reset_confirmation(&mut self, options: FactoryResetOptions) {
+        let message = if options.keep_calibration {
+            "Erase all settings except calibration, and all history?"
+        } else {
+            "Erase all settings and history?"
+        };
+        self.confirmation_screen =
+            ConfirmationScreen::new("", message, UiRequestMessage::FactoryReset(options))
+    }
+
+    async fn setup_monitoring_target_value_selection(&mut self) {
+        let monitoring_target_id = if let SettingValue::SmallUInt(value) = self
+            .settings
+            .get_setting(SettingsAccessorId::MonitoringTargetType)
+            .await
+            .unwrap_or(SettingValue::SmallUInt(0))
+        {
+            value
+        } else {
+            0u8
+        };
+        let monitoring_target =
+            MonitoringTargetPeriodOptions::try_from(monitoring_target_id as usize).unwrap();
+        self.setup_target_period_value_selection(monitoring_target)
+            .await;
+    }
+
+    async fn setup_target_period_value_selection(
+        &mut self,
+        monitoring_target: MonitoringTargetPeriodOptions,
+    ) {
+        let accessor_id = match monitoring_target {
+            MonitoringTargetPeriodOptions::Daily => SettingsAccessorId::MonitoringTargetDaily,
+            MonitoringTargetPeriodOptions::Hourly => SettingsAccessorId::MonitoringTargetHourly,
+        };
+
+        let properties = accessor_id.get_numeric_properties().unwrap();
+        let value = match self.settings.get_setting(accessor_id).await {
+            Some(SettingValue::UInt(value)) => value,
+            Some(SettingValue::Float(value)) => value as u32,
+            _ => 0u32,
+        };
+
+        self.number_setting_screen = SetNumberScreen::new(
+            monitoring_target.title(),
+            monitoring_target.units(),
+            value,
+            properties.minimum_value,
+            properties.maximum_value,
+            accessor_id,
+        );
+    }
+
+    async fn setup_beverage_target_value_selection(&mut self, beverage: BeverageType) {
+        let accessor_id = SettingsAccessorId::BeverageTargetDaily(beverage);
+        let properties = accessor_id.get_numeric_properties().unwrap();
+        let value = if let SettingValue::UInt(value) = self
+            .settings
+            .get_setting(accessor_id)
+            .await
+            .unwrap_or(SettingValue::UInt(0))
+        {
+            value
+        } else {
+            0u32
+        };
+
+        self.number_setting_screen = SetNumberScreen::new(
+            beverage.target_title(),
+            MonitoringTargetPeriodOptions::Daily.units(),
+            value,
+            properties.minimum_value,
+            properties.maximum_value,
+            accessor_id,
+        );
+    }
+
+    pub async fn set_display_state(&mut self, display_state: ApplicationState, now: NaiveDateTime) {
+        debug!("Display state: {:?}", display_state);
+
+        if let ApplicationState::NumberEntry(setting_id) = display_state {
+            match setting_id {
+                SettingsAccessorId::MonitoringTargetDaily => {
+                    self.setup_monitoring_target_value_selection().await
+                }
+                SettingsAccessorId::BeverageTargetDaily(beverage) => {
+                    self.setup_beverage_target_value_selection(beverage).await
+                }
+                _ => {}
+            }
+        }
+        if let ApplicationState::TimeEntry(setting_id) = display_state {
+            match setting_id {
+                SettingsAccessorId::MonitoringDailyTargetTime => {
+                    self.setup_time_selection("Daily Target Time", setting_id)
+                        .await
+                }
+                SettingsAccessorId::MonitoringDrinkingWindowStart => {
+                    self.setup_time_selection("Drinking Start Time", setting_id)
+                        .await
+                }
+                SettingsAccessorId::MonitoringDayStartTime => {
+                    self.setup_time_selection("Day Start Time", setting_id)
+                        .await
+                }
+                SettingsAccessorId::MonitoringQuietStart => {
+                    self.setup_time_selection("Quiet Start", setting_id).await
+                }
+                SettingsAccessorId::MonitoringQuietEnd => {
+                    self.setup_time_selection("Quiet End", setting_id).await
+                }
+                _ => {}
+            }
+        }
+        if let ApplicationState::ConfirmationScreen(confirmation_id) = display_state {
+            match confirmation_id {
+                ConfirmationId::ClearHistoricalConsumptionLog => {
+                    self.setup_consumption_log_reset_confirmation().await
+                }
+                ConfirmationId::FactoryReset(options) => {
+                    self.setup_factory_reset_confirmation(options).await
+                }
+            }
+        }
+
+        if let ApplicationState::FirstRunSetup(step) = display_state {
+            match step {
+                // the chosen target type may not have been saved yet, so it is passed on
+                FirstRunStep::TargetValue(period) => {
+                    self.setup_target_period_value_selection(period).await
+                }
+                _ => self.first_run_screen = FirstRunScreen::new(step),
+            }
+        }
+
+        if let ApplicationState::ErrorLog = display_state {
+            // the records are sent again each time the screen is entered
+            self.error_log_screen = ErrorLogScreen::new();
+        }
+
+        if let ApplicationState::NvmStatus = display_state {
+            // usage is read again each time the screen is entered
+            self.nvm_status_screen = NvmStatusScreen::new();
+        }
+
+        if let ApplicationState::SetSystemDateTime = display_state {
+            self.setup_system_date_time_setting(now).await;
+        }
+
+        if let ApplicationState::Monitoring = display_state {
+            // the layout may have been changed in the settings menu or over USB
+            self.monitoring_screen
+                .set_custom_layout(read_custom_layout(self.settings).await);
+        }
+
+        self.display_state = display_state;
+        self.route_ui_input(UiInput::DateTimeUpdate(now)).await;
+    }
+
+    /// Draws the screen for the current state.
+    pub fn draw<D>(&mut self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        match self.display_state {
+            ApplicationState::Startup => draw_message_screen(display, "Starting up...")?,
+            ApplicationState::FactoryReset => {
+                draw_message_screen(display, "Factory reset in progress...")?
+            }
+
+            ApplicationState::ErrorScreenWithMessage(s) => draw_message_screen(display, s)?,
+
+            ApplicationState::TestScreen => self.test_mode_screen.draw(display)?,
+            ApplicationState::Settings => self.settings_screen.draw(display)?,
+            ApplicationState::Monitoring => self.monitoring_screen.draw(display)?,
+            ApplicationState::HeapStatus => self.heap_status_screen.draw(display)?,
+            ApplicationState::ErrorLog => self.error_log_screen.draw(display)?,
+            ApplicationState::NvmStatus => self.nvm_status_screen.draw(display)?,
+            ApplicationState::Calibration => self.calibration_screens.draw(display)?,
+            ApplicationState::SetSystemDateTime | ApplicationState::DateTimeEntry(_) => {
+                self.set_date_time_screen.draw(display)?
+            }
+            ApplicationState::NumberEntry(_) => self.number_setting_screen.draw(display)?,
+            ApplicationState::AboutScreen => {
+                self.about_screen.update_pre_draw_actions(display);
+                self.about_screen.draw(display)?
+            }
+            ApplicationState::TimeEntry(_) => self.time_entry_screen.draw(display)?,
+            ApplicationState::ConfirmationScreen(_) => self.confirmation_screen.draw(display)?,
+            ApplicationState::FirstRunSetup(FirstRunStep::TargetValue(_)) => {
+                self.number_setting_screen.draw(display)?
+            }
+            ApplicationState::FirstRunSetup(_) => self.first_run_screen.draw(display)?,
+        }
+        Ok(())
+    }
+
+    pub async fn route_ui_input(&mut self, input: UiInput) {
+        trace!("Route UI input received: {}", Debug2Format(&input));
+        match self.display_state {
+            ApplicationState::Startup => {}
+            ApplicationState::ErrorScreenWithMessage(_) => {}
+            ApplicationState::FactoryReset => {}
+
+            ApplicationState::Settings => {
+                self.settings_screen
+                    .ui_input_handler(input, &self.ui_action_publisher)
+                    .await
+            }
+            ApplicationState::TestScreen => {
+                self.test_mode_screen
+                    .ui_input_handler(input, &self.ui_action_publisher)
+                    .await
+            }
+            ApplicationState::Monitoring => {
+                self.monitoring_screen
+                    .ui_input_handler(input, &self.ui_action_publisher)
+                    .await
+            }
+            ApplicationState::HeapStatus => {
+                self.heap_status_screen
+                    .ui_input_handler(input, &self.ui_action_publisher)
+                    .await
+            }
+            ApplicationState::ErrorLog => {
+                self.error_log_screen
+                    .ui_input_handler(input, &self.ui_action_publisher)
+                    .await
+            }
+            ApplicationState::NvmStatus => {
+                self.nvm_status_screen
+                    .ui_input_handler(input, &self.ui_action_publisher)
+                    .await
+            }
+            ApplicationState::Calibration => {
+                self.calibration_screens
+                    .ui_input_handler(input, &self.ui_action_publisher)
+                    .await
+            }
+            ApplicationState::SetSystemDateTime | ApplicationState::DateTimeEntry(_) => {
+                self.set_date_time_screen
+                    .ui_input_handler(input, &self.ui_action_publisher)
+                    .await
+            }
+            ApplicationState::NumberEntry(_) => {
+                self.number_setting_screen
+                    .ui_input_handler(input, &self.ui_action_publisher)
+                    .await
+            }
+            ApplicationState::TimeEntry(_) => {
+                self.time_entry_screen
+                    .ui_input_handler(input, &self.ui_action_publisher)
+                    .await
+            }
+            ApplicationState::ConfirmationScreen(_) => {
+                self.confirmation_screen
+                    .ui_input_handler(input, &self.ui_action_publisher)
+                    .await;
+            }
+            ApplicationState::AboutScreen => {
+                self.about_screen
+                    .ui_input_handler(input, &self.ui_action_publisher)
+                    .await
+            }
+            ApplicationState::FirstRunSetup(FirstRunStep::TargetValue(_)) => {
+                self.number_setting_screen
+                    .ui_input_handler(input, &self.ui_action_publisher)
+                    .await
+            }
+            ApplicationState::FirstRunSetup(_) => {
+                self.first_run_screen
+                    .ui_input_handler(input, &self.ui_action_publisher)
+                    .await
+            }
+        }
+    }
+}
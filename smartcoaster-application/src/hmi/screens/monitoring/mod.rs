@@ -13,34 +13,63 @@
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
 mod monitoring_screen_1;
+mod monitoring_screen_10;
 mod monitoring_screen_2;
 mod monitoring_screen_3;
 mod monitoring_screen_4;
+mod monitoring_screen_5;
+mod monitoring_screen_6;
+mod monitoring_screen_7;
+mod monitoring_screen_8;
+mod monitoring_screen_9;
+mod monitoring_screen_custom;
 mod monitoring_screen_debug;
 mod top_status_bar;
 
+pub(crate) use monitoring_screen_custom::read_custom_layout;
+
 use crate::application::application_state::ApplicationState;
 use crate::application::messaging::ApplicationData;
+use crate::drink_monitor::beverage::BeverageType;
 use crate::drink_monitor::drink_monitoring::MonitoringStateSubstates;
-use crate::drink_monitor::messaging::DrinkMonitoringUpdate;
+use crate::drink_monitor::messaging::{
+    DrinkMonitoringUpdate, DAILY_TOTAL_HISTORY_DAYS, DAY_PROGRESS_POINTS, HOURLY_HISTORY_HOURS,
+};
 use crate::hmi::messaging::{UiActionChannelPublisher, UiRequestMessage};
 use crate::hmi::screens::monitoring::monitoring_screen_1::MonitoringScreen1;
+use crate::hmi::screens::monitoring::monitoring_screen_10::MonitoringScreen10;
 use crate::hmi::screens::monitoring::monitoring_screen_2::MonitoringScreen2;
 use crate::hmi::screens::monitoring::monitoring_screen_3::MonitoringScreen3;
 use crate::hmi::screens::monitoring::monitoring_screen_4::MonitoringScreen4;
+use crate::hmi::screens::monitoring::monitoring_screen_5::MonitoringScreen5;
+use crate::hmi::screens::monitoring::monitoring_screen_6::MonitoringScreen6;
+use crate::hmi::screens::monitoring::monitoring_screen_7::MonitoringScreen7;
+use crate::hmi::screens::monitoring::monitoring_screen_8::MonitoringScreen8;
+use crate::hmi::screens::monitoring::monitoring_screen_9::MonitoringScreen9;
+use crate::hmi::screens::monitoring::monitoring_screen_custom::MonitoringScreenCustom;
 use crate::hmi::screens::monitoring::monitoring_screen_debug::MonitoringScreenDebug;
 use crate::hmi::screens::monitoring::top_status_bar::TopStatusBar;
 use crate::hmi::screens::settings_menu::monitoring_options::MonitoringTargetPeriodOptions;
 use crate::hmi::screens::{draw_message_screen, UiDrawer, UiInput, UiInputHandler};
 use crate::storage::settings::{SettingValue, SettingsAccessor, SettingsAccessorId};
-use chrono::NaiveDateTime;
+use chrono::{NaiveDateTime, TimeDelta};
+use core::fmt::Write;
 use embedded_graphics::draw_target::DrawTarget;
 use embedded_graphics::geometry::{AnchorX, AnchorY, Point};
 use embedded_graphics::image::Image;
+use embedded_graphics::mono_font::ascii::FONT_6X10;
+use embedded_graphics::mono_font::MonoTextStyleBuilder;
 use embedded_graphics::pixelcolor::BinaryColor;
 use embedded_graphics::prelude::{Dimensions, DrawTargetExt, OriginDimensions};
+use embedded_graphics::text::renderer::TextRenderer;
+use embedded_graphics::text::{Alignment, Baseline, Text, TextStyleBuilder};
 use embedded_graphics::Drawable;
 use embedded_icon::NewIcon;
+use heapless::String;
+use smartcoaster_app_core::reminder::ReminderLevel;
+use smartcoaster_app_core::target_direction::TargetDirection;
+use smartcoaster_widgets::grid::GridLayout;
+use strum::EnumCount;
 
 struct MonitoringData {
     last_consumption: f32,
@@ -51,6 +80,61 @@ struct MonitoringData {
     target_mode: MonitoringTargetPeriodOptions,
     last_hour_consumption_rate: f32,
     last_hour: bool,
+    plan_difference: f32,
+    target_direction: TargetDirection,
+    active_beverage: BeverageType,
+    /// Indexed by beverage. The water entries are unused as water is covered by the fields above.
+    beverage_totals: [f32; BeverageType::COUNT],
+    beverage_targets: [f32; BeverageType::COUNT],
+    beverage_directions: [TargetDirection; BeverageType::COUNT],
+    last_drink_time: Option<NaiveDateTime>,
+    reminder_level: ReminderLevel,
+    streak: u32,
+    /// Indexed by days ago, starting with yesterday.
+    daily_totals: [f32; DAILY_TOTAL_HISTORY_DAYS],
+    daily_target_achieved: [bool; DAILY_TOTAL_HISTORY_DAYS],
+    average_7_day: Option<f32>,
+    average_30_day: Option<f32>,
+    /// Indexed by hours ago, starting with the current hour.
+    hourly_consumption: [f32; HOURLY_HISTORY_HOURS],
+    /// Indexed by hours since the day started.
+    day_progress_consumed: [Option<f32>; DAY_PROGRESS_POINTS],
+    day_progress_target: [f32; DAY_PROGRESS_POINTS],
+}
+
+impl MonitoringData {
+    /// Copies the day progress points reached so far into `buffer`. Only those points have a
+    /// consumption value.
+    fn day_progress_so_far<'b>(&self, buffer: &'b mut [f32; DAY_PROGRESS_POINTS]) -> &'b [f32] {
+        let mut count = 0;
+        for value in self.day_progress_consumed.iter().map_while(|value| *value) {
+            buffer[count] = value;
+            count += 1;
+        }
+        &buffer[..count]
+    }
+
+    /// Target for the whole day. With an hourly target this is what the hourly target adds up to
+    /// over the day.
+    fn day_target(&self) -> f32 {
+        match self.target_mode {
+            MonitoringTargetPeriodOptions::Daily => self.day_target_consumption,
+            MonitoringTargetPeriodOptions::Hourly => {
+                self.day_progress_target[DAY_PROGRESS_POINTS - 1]
+            }
+        }
+    }
+
+    /// How much should have been consumed by now to be on the daily target flight path. Only
+    /// known with a daily target.
+    fn expected_by_now(&self) -> Option<f32> {
+        match self.target_mode {
+            MonitoringTargetPeriodOptions::Daily => {
+                Some(self.day_total_consumed - self.plan_difference)
+            }
+            MonitoringTargetPeriodOptions::Hourly => None,
+        }
+    }
 }
 
 trait MonitoringScreenContent<D>
@@ -69,9 +153,20 @@ static SCREEN_LAYOUT_1: MonitoringScreen1 = MonitoringScreen1 {};
 static SCREEN_LAYOUT_2: MonitoringScreen2 = MonitoringScreen2 {};
 static SCREEN_LAYOUT_3: MonitoringScreen3 = MonitoringScreen3 {};
 static SCREEN_LAYOUT_4: MonitoringScreen4 = MonitoringScreen4 {};
+static SCREEN_LAYOUT_5: MonitoringScreen5 = MonitoringScreen5 {};
+static SCREEN_LAYOUT_6: MonitoringScreen6 = MonitoringScreen6 {};
+static SCREEN_LAYOUT_7: MonitoringScreen7 = MonitoringScreen7 {};
+static SCREEN_LAYOUT_8: MonitoringScreen8 = MonitoringScreen8 {};
+static SCREEN_LAYOUT_9: MonitoringScreen9 = MonitoringScreen9 {};
+static SCREEN_LAYOUT_10: MonitoringScreen10 = MonitoringScreen10 {};
 static SCREEN_LAYOUT_DEBUG: MonitoringScreenDebug = MonitoringScreenDebug {};
 
-const MAX_SCREENS: u8 = 5;
+/// Number of monitoring screens, including the custom and debug screens.
+pub const MAX_SCREENS: u8 = 12;
+/// The debug screen is always the last screen.
+pub const DEBUG_SCREEN_INDEX: u8 = MAX_SCREENS - 1;
+/// The custom screen holds its layout so is created when drawn rather than held in a static.
+const CUSTOM_SCREEN_INDEX: u8 = 10;
 fn get_screen_layout<D>(index: &u8) -> &dyn MonitoringScreenContent<D>
 where
     D: DrawTarget<Color = BinaryColor>,
@@ -81,7 +176,13 @@ where
         1 => &SCREEN_LAYOUT_2,
         2 => &SCREEN_LAYOUT_3,
         3 => &SCREEN_LAYOUT_4,
-        4 => &SCREEN_LAYOUT_DEBUG,
+        4 => &SCREEN_LAYOUT_5,
+        5 => &SCREEN_LAYOUT_6,
+        6 => &SCREEN_LAYOUT_7,
+        7 => &SCREEN_LAYOUT_8,
+        8 => &SCREEN_LAYOUT_9,
+        9 => &SCREEN_LAYOUT_10,
+        DEBUG_SCREEN_INDEX => &SCREEN_LAYOUT_DEBUG,
         _ => &SCREEN_LAYOUT_1,
     }
 }
@@ -93,6 +194,11 @@ pub struct MonitoringScreen<'a, SA> {
     active_screen_index: u8,
     datetime: NaiveDateTime,
     settings: &'a SA,
+    /// Set while the goal achieved screen is showing.
+    celebration_end: Option<NaiveDateTime>,
+    /// Set while the confirmation of a snoozed reminder is showing.
+    snooze_confirmation_end: Option<NaiveDateTime>,
+    custom_layout: GridLayout,
 }
 
 impl<'a, SA> MonitoringScreen<'a, SA>
@@ -109,6 +215,7 @@ where
         } else {
             0
         };
+        let custom_layout = read_custom_layout(settings).await;
 
         Self {
             monitoring_data: MonitoringData {
@@ -120,7 +227,26 @@ where
                 target_mode: MonitoringTargetPeriodOptions::Daily,
                 last_hour_consumption_rate: 0.0,
                 last_hour: false,
+                plan_difference: 0.0,
+                target_direction: TargetDirection::Minimum,
+                active_beverage: BeverageType::Water,
+                beverage_totals: [0.0; BeverageType::COUNT],
+                beverage_targets: [0.0; BeverageType::COUNT],
+                beverage_directions: [TargetDirection::Maximum; BeverageType::COUNT],
+                last_drink_time: None,
+                reminder_level: ReminderLevel::NotDue,
+                streak: 0,
+                daily_totals: [0.0; DAILY_TOTAL_HISTORY_DAYS],
+                daily_target_achieved: [false; DAILY_TOTAL_HISTORY_DAYS],
+                average_7_day: None,
+                average_30_day: None,
+                hourly_consumption: [0.0; HOURLY_HISTORY_HOURS],
+                day_progress_consumed: [None; DAY_PROGRESS_POINTS],
+                day_progress_target: [0.0; DAY_PROGRESS_POINTS],
             },
+            celebration_end: None,
+            snooze_confirmation_end: None,
+            custom_layout,
             state: MonitoringStateSubstates::WaitingForActivity,
             active_screen_index,
             datetime: NaiveDateTime::default(),
@@ -128,6 +254,10 @@ where
         }
     }
 
+    pub fn set_custom_layout(&mut self, layout: GridLayout) {
+        self.custom_layout = layout;
+    }
+
     fn process_application_data(&mut self, data: ApplicationData) {
         if let ApplicationData::MonitoringUpdate(update) = data {
             match update {
@@ -159,6 +289,65 @@ where
                 DrinkMonitoringUpdate::LastHour(last_hour) => {
                     self.monitoring_data.last_hour = last_hour;
                 }
+                DrinkMonitoringUpdate::PlanDifference(plan_difference) => {
+                    self.monitoring_data.plan_difference = plan_difference;
+                }
+                DrinkMonitoringUpdate::TargetDirection(target_direction) => {
+                    self.monitoring_data.target_direction = target_direction;
+                }
+                DrinkMonitoringUpdate::ActiveBeverage(beverage) => {
+                    self.monitoring_data.active_beverage = beverage;
+                }
+                DrinkMonitoringUpdate::BeverageTotalConsumed(beverage, total) => {
+                    self.monitoring_data.beverage_totals[beverage.index()] = total;
+                }
+                DrinkMonitoringUpdate::BeverageTarget(beverage, target, direction) => {
+                    self.monitoring_data.beverage_targets[beverage.index()] = target;
+                    self.monitoring_data.beverage_directions[beverage.index()] = direction;
+                }
+                DrinkMonitoringUpdate::LastDrinkTime(last_drink_time) => {
+                    self.monitoring_data.last_drink_time = Some(last_drink_time);
+                }
+                DrinkMonitoringUpdate::ReminderDue(reminder_level) => {
+                    self.monitoring_data.reminder_level = reminder_level;
+                }
+                DrinkMonitoringUpdate::GoalAchieved => {
+                    self.celebration_end =
+                        Some(self.datetime + TimeDelta::seconds(Self::CELEBRATION_SECONDS));
+                }
+                DrinkMonitoringUpdate::Streak(streak) => {
+                    self.monitoring_data.streak = streak;
+                }
+                DrinkMonitoringUpdate::DailyTotal(days_ago, total, target_achieved) => {
+                    if let Some(index) = (days_ago as usize)
+                        .checked_sub(1)
+                        .filter(|index| *index < DAILY_TOTAL_HISTORY_DAYS)
+                    {
+                        self.monitoring_data.daily_totals[index] = total;
+                        self.monitoring_data.daily_target_achieved[index] = target_achieved;
+                    }
+                }
+                DrinkMonitoringUpdate::SevenDayAverage(average) => {
+                    self.monitoring_data.average_7_day = average;
+                }
+                DrinkMonitoringUpdate::ThirtyDayAverage(average) => {
+                    self.monitoring_data.average_30_day = average;
+                }
+                DrinkMonitoringUpdate::HourlyConsumption(hours_ago, consumption) => {
+                    if let Some(hourly_consumption) = self
+                        .monitoring_data
+                        .hourly_consumption
+                        .get_mut(hours_ago as usize)
+                    {
+                        *hourly_consumption = consumption;
+                    }
+                }
+                DrinkMonitoringUpdate::DayProgress(point, consumed, target) => {
+                    if (point as usize) < DAY_PROGRESS_POINTS {
+                        self.monitoring_data.day_progress_consumed[point as usize] = consumed;
+                        self.monitoring_data.day_progress_target[point as usize] = target;
+                    }
+                }
             }
         }
     }
@@ -208,16 +397,111 @@ where
                         .unwrap();
                 }
             }
-            UiInput::ButtonPress => ui_action_publisher
-                .publish_immediate(UiRequestMessage::ChangeState(ApplicationState::Settings)),
+            UiInput::ButtonPress => {
+                if self.is_celebrating() {
+                    self.celebration_end = None;
+                } else if self.is_confirming_snooze() {
+                    self.snooze_confirmation_end = None;
+                } else if self.is_showing_reminder() {
+                    ui_action_publisher.publish_immediate(UiRequestMessage::SnoozeReminder());
+                    self.snooze_confirmation_end =
+                        Some(self.datetime + TimeDelta::seconds(Self::SNOOZE_CONFIRMATION_SECONDS));
+                } else {
+                    ui_action_publisher.publish_immediate(UiRequestMessage::ChangeState(
+                        ApplicationState::Settings,
+                    ))
+                }
+            }
             UiInput::ButtonRelease => {}
             UiInput::ApplicationData(data) => self.process_application_data(data),
-            UiInput::DateTimeUpdate(dt) => self.datetime = dt,
+            UiInput::DateTimeUpdate(dt) => {
+                self.datetime = dt;
+                if !self.is_celebrating() {
+                    self.celebration_end = None;
+                }
+                if !self.is_confirming_snooze() {
+                    self.snooze_confirmation_end = None;
+                }
+            }
         }
     }
 }
 
 impl<'a, SA> MonitoringScreen<'a, SA> {
+    const CELEBRATION_SECONDS: i64 = 30;
+    const SNOOZE_CONFIRMATION_SECONDS: i64 = 3;
+
+    fn is_celebrating(&self) -> bool {
+        self.celebration_end
+            .is_some_and(|celebration_end| self.datetime < celebration_end)
+    }
+
+    fn is_confirming_snooze(&self) -> bool {
+        self.snooze_confirmation_end
+            .is_some_and(|confirmation_end| self.datetime < confirmation_end)
+    }
+
+    /// The reminder is only drawn over the monitoring screens, so a button press only snoozes it
+    /// when one of those is showing.
+    fn is_showing_reminder(&self) -> bool {
+        matches!(
+            self.state,
+            MonitoringStateSubstates::VesselRemoved | MonitoringStateSubstates::VesselPlaced
+        ) && !self.is_celebrating()
+            && !self.is_confirming_snooze()
+            && self.monitoring_data.reminder_level != ReminderLevel::NotDue
+    }
+
+    fn draw_celebration<D>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let left_icon_area_width = display.bounding_box().size.width / 3;
+        let mut left_icon_display = display.cropped(
+            &display
+                .bounding_box()
+                .resized_width(left_icon_area_width, AnchorX::Left),
+        );
+
+        let icon = embedded_icon::mdi::size32px::Trophy::new(BinaryColor::On);
+        let mut icon_location = left_icon_display.bounding_box().center();
+        icon_location.x -= (icon.size().width / 2) as i32;
+        icon_location.y -= (icon.size().height / 2) as i32;
+        Image::new(&icon, icon_location).draw(&mut left_icon_display)?;
+
+        let mut right_text_display = display.cropped(&display.bounding_box().resized_width(
+            display.bounding_box().size.width - left_icon_area_width,
+            AnchorX::Right,
+        ));
+
+        let mut message = String::<60>::new();
+        write!(
+            message,
+            "Target\nreached!\n{:.0} ml\n{} day streak",
+            self.monitoring_data.day_total_consumed, self.monitoring_data.streak
+        )
+        .unwrap();
+        self.draw_centred_lines(&mut right_text_display, &message)
+    }
+
+    fn draw_centred_lines<D>(&self, display: &mut D, message: &str) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let text_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X10)
+            .text_color(BinaryColor::On)
+            .build();
+        let centred_text_style = TextStyleBuilder::new()
+            .alignment(Alignment::Center)
+            .baseline(Baseline::Middle)
+            .build();
+        let mut position = display.bounding_box().center();
+        position.y -= (message.lines().count() as i32 - 1) * text_style.line_height() as i32 / 2;
+        Text::with_text_style(message, position, text_style, centred_text_style).draw(display)?;
+        Ok(())
+    }
+
     fn draw_waiting_content<D>(&self, display: &mut D) -> Result<(), D::Error>
     where
         D: DrawTarget<Color = BinaryColor>,
@@ -249,6 +533,47 @@ impl<'a, SA> MonitoringScreen<'a, SA> {
         Ok(())
     }
 
+    fn draw_reminder<D>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let left_icon_area_width = display.bounding_box().size.width / 3;
+        let mut left_icon_display = display.cropped(
+            &display
+                .bounding_box()
+                .resized_width(left_icon_area_width, AnchorX::Left),
+        );
+
+        let icon = embedded_icon::mdi::size32px::BellRing::new(BinaryColor::On);
+        let mut icon_location = left_icon_display.bounding_box().center();
+        icon_location.x -= (icon.size().width / 2) as i32;
+        icon_location.y -= (icon.size().height / 2) as i32;
+        Image::new(&icon, icon_location).draw(&mut left_icon_display)?;
+
+        let mut right_text_display = display.cropped(&display.bounding_box().resized_width(
+            display.bounding_box().size.width - left_icon_area_width,
+            AnchorX::Right,
+        ));
+
+        let mut message = String::<60>::new();
+        match self.monitoring_data.last_drink_time {
+            Some(last_drink_time) => {
+                let minutes = (self.datetime - last_drink_time).num_minutes().max(0);
+                write!(message, "Last drink\n{} min ago", minutes).unwrap();
+            }
+            None => write!(message, "No drink\nyet today").unwrap(),
+        }
+        write!(message, "\nPress to\nsnooze").unwrap();
+        self.draw_centred_lines(&mut right_text_display, &message)
+    }
+
+    fn draw_snooze_confirmation<D>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        self.draw_centred_lines(display, "Reminder\nsnoozed")
+    }
+
     fn draw_error<D>(&self, display: &mut D, message: &str) -> Result<(), D::Error>
     where
         D: DrawTarget<Color = BinaryColor>,
@@ -306,6 +631,30 @@ impl<'a, SA> UiDrawer for MonitoringScreen<'a, SA> {
             MonitoringStateSubstates::WaitingForActivity => {
                 self.draw_waiting_content(&mut content_display)?
             }
+            MonitoringStateSubstates::VesselRemoved | MonitoringStateSubstates::VesselPlaced
+                if self.is_celebrating() =>
+            {
+                self.draw_celebration(&mut content_display)?
+            }
+            MonitoringStateSubstates::VesselRemoved | MonitoringStateSubstates::VesselPlaced
+                if self.is_confirming_snooze() =>
+            {
+                self.draw_snooze_confirmation(&mut content_display)?
+            }
+            MonitoringStateSubstates::VesselRemoved | MonitoringStateSubstates::VesselPlaced
+                if self.is_showing_reminder() =>
+            {
+                self.draw_reminder(&mut content_display)?
+            }
+            MonitoringStateSubstates::VesselRemoved | MonitoringStateSubstates::VesselPlaced
+                if self.active_screen_index == CUSTOM_SCREEN_INDEX =>
+            {
+                MonitoringScreenCustom::new(self.custom_layout, self.datetime).draw_content(
+                    &mut content_display,
+                    self.state,
+                    &self.monitoring_data,
+                )?
+            }
             MonitoringStateSubstates::VesselRemoved | MonitoringStateSubstates::VesselPlaced => {
                 let active_screen = get_screen_layout(&self.active_screen_index);
                 active_screen.draw_content(
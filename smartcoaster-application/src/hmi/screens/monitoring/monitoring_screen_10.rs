@@ -0,0 +1,120 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::drink_monitor::drink_monitoring::MonitoringStateSubstates;
+use crate::hmi::screens::monitoring::monitoring_screen_4::MonitoringScreen4;
+use crate::hmi::screens::monitoring::{MonitoringData, MonitoringScreenContent};
+use core::fmt::Write;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Dimensions, Point, Size};
+use embedded_graphics::mono_font::ascii::{FONT_6X10, FONT_8X13_BOLD};
+use embedded_graphics::mono_font::MonoTextStyleBuilder;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::text::{Alignment, Baseline, Text, TextStyleBuilder};
+use embedded_graphics::Drawable;
+use heapless::String;
+use smartcoaster_app_core::target_direction::TargetDirection;
+use smartcoaster_widgets::progress_bar::ProgressBar;
+
+/// How much to drink to stay on track alongside a progress bar towards the daily target. The tick
+/// on the bar shows where consumption should be by now.
+pub struct MonitoringScreen10 {}
+
+impl<D> MonitoringScreenContent<D> for MonitoringScreen10
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    fn draw_content(
+        &self,
+        display: &mut D,
+        _state: MonitoringStateSubstates,
+        data: &MonitoringData,
+    ) -> Result<(), D::Error> {
+        let value_char_style = MonoTextStyleBuilder::new()
+            .font(&FONT_8X13_BOLD)
+            .text_color(BinaryColor::On)
+            .build();
+        let label_char_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X10)
+            .text_color(BinaryColor::On)
+            .build();
+        let centre_text_style = TextStyleBuilder::new()
+            .alignment(Alignment::Center)
+            .baseline(Baseline::Top)
+            .build();
+        let centre_x = (display.bounding_box().size.width / 2) as i32;
+
+        let mut string_buffer = String::<30>::new();
+        let footer = match data.target_direction {
+            TargetDirection::Minimum => {
+                let amount = MonitoringScreen4::calculate_drink_to_stay_on_target(data);
+                write!(string_buffer, "Drink {} ml", amount).unwrap();
+                "to stay on track"
+            }
+            TargetDirection::Maximum => {
+                let budget_remaining = MonitoringScreen4::calculate_budget_remaining(data);
+                write!(string_buffer, "{} ml", budget_remaining.abs()).unwrap();
+                if budget_remaining >= 0 {
+                    "left in budget"
+                } else {
+                    "over budget"
+                }
+            }
+        };
+        Text::with_text_style(
+            string_buffer.as_str(),
+            Point::new(centre_x, 0),
+            value_char_style,
+            centre_text_style,
+        )
+        .draw(display)?;
+        Text::with_text_style(
+            footer,
+            Point::new(centre_x, 13),
+            label_char_style,
+            centre_text_style,
+        )
+        .draw(display)?;
+
+        let margin = 4;
+        let mut progress_bar = ProgressBar::new(
+            Point::new(margin, 26),
+            Size::new(display.bounding_box().size.width - 2 * margin as u32, 14),
+            data.day_total_consumed,
+            data.day_target(),
+        );
+        if let Some(expected) = data.expected_by_now() {
+            progress_bar = progress_bar.with_marker(expected);
+        }
+        progress_bar.draw(display)?;
+
+        string_buffer.clear();
+        write!(
+            string_buffer,
+            "{:.0} / {:.0} ml",
+            data.day_total_consumed,
+            data.day_target()
+        )
+        .unwrap();
+        Text::with_text_style(
+            string_buffer.as_str(),
+            Point::new(centre_x, 42),
+            label_char_style,
+            centre_text_style,
+        )
+        .draw(display)?;
+
+        Ok(())
+    }
+}
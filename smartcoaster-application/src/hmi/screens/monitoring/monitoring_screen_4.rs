@@ -14,6 +14,7 @@
 
 use crate::drink_monitor::drink_monitoring::MonitoringStateSubstates;
 use crate::hmi::screens::monitoring::{MonitoringData, MonitoringScreenContent};
+use crate::hmi::screens::settings_menu::monitoring_options::MonitoringTargetPeriodOptions;
 use core::cmp::max;
 use core::fmt::Write;
 use embedded_graphics::draw_target::{DrawTarget, DrawTargetExt};
@@ -29,11 +30,12 @@ use embedded_graphics::text::{Alignment, Baseline, Text, TextStyleBuilder};
 use embedded_graphics::Drawable;
 use embedded_icon::NewIcon;
 use heapless::String;
+use smartcoaster_app_core::target_direction::TargetDirection;
 
 pub struct MonitoringScreen4 {}
 
 impl MonitoringScreen4 {
-    fn calculate_drink_to_stay_on_target(monitoring_data: &MonitoringData) -> i32 {
+    pub(super) fn calculate_drink_to_stay_on_target(monitoring_data: &MonitoringData) -> i32 {
         if monitoring_data.last_hour {
             return monitoring_data.target_rate as i32;
         }
@@ -42,6 +44,18 @@ impl MonitoringScreen4 {
             (monitoring_data.target_rate - monitoring_data.last_hour_consumption_rate) as i32,
         )
     }
+
+    /// Budget left when the target is a maximum. Negative once the budget has been exceeded.
+    pub(super) fn calculate_budget_remaining(monitoring_data: &MonitoringData) -> i32 {
+        match monitoring_data.target_mode {
+            MonitoringTargetPeriodOptions::Daily => {
+                (monitoring_data.day_target_consumption - monitoring_data.day_total_consumed) as i32
+            }
+            MonitoringTargetPeriodOptions::Hourly => {
+                (monitoring_data.target_rate - monitoring_data.last_hour_consumption_rate) as i32
+            }
+        }
+    }
 }
 
 impl<D> MonitoringScreenContent<D> for MonitoringScreen4
@@ -94,8 +108,23 @@ where
         .into_styled(base_style)
         .draw(&mut left_icon_display)?;
 
-        // show how much is needed to drink to stay on track
-        let amount_to_stay_on_target = Self::calculate_drink_to_stay_on_target(data);
+        // show how much is needed to drink to stay on track, or how much is left to drink when the
+        // target is a ceiling
+        let (heading, amount, footer) = match data.target_direction {
+            TargetDirection::Minimum => (
+                "Drink",
+                Self::calculate_drink_to_stay_on_target(data),
+                "to stay on\ntrack",
+            ),
+            TargetDirection::Maximum => {
+                let budget_remaining = Self::calculate_budget_remaining(data);
+                if budget_remaining >= 0 {
+                    ("", budget_remaining, "left in\nbudget")
+                } else {
+                    ("", -budget_remaining, "over\nbudget")
+                }
+            }
+        };
 
         let mut string_buffer = String::<20>::new();
 
@@ -125,11 +154,11 @@ where
         pos.y -= (value_char_style.line_height() / 2) as i32
             + (label_char_style.line_height() / 2) as i32
             + 3;
-        Text::with_text_style("Drink", pos, label_char_style, centre_text_style)
+        Text::with_text_style(heading, pos, label_char_style, centre_text_style)
             .draw(&mut right_display_area)?;
 
         string_buffer.clear();
-        write!(string_buffer, "{} ml", amount_to_stay_on_target).unwrap();
+        write!(string_buffer, "{} ml", amount).unwrap();
         pos = right_display_area.bounding_box().center();
 
         Text::with_text_style(
@@ -142,13 +171,8 @@ where
 
         pos.y += (value_char_style.line_height() / 2) as i32
             + (label_char_style.line_height() / 2) as i32;
-        Text::with_text_style(
-            "to stay on\ntrack",
-            pos,
-            label_char_style,
-            centre_text_style,
-        )
-        .draw(&mut right_display_area)?;
+        Text::with_text_style(footer, pos, label_char_style, centre_text_style)
+            .draw(&mut right_display_area)?;
 
         Ok(())
     }
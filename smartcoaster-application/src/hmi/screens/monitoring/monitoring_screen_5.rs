@@ -0,0 +1,119 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::drink_monitor::beverage::BeverageType;
+use crate::drink_monitor::drink_monitoring::MonitoringStateSubstates;
+use crate::hmi::screens::monitoring::{MonitoringData, MonitoringScreenContent};
+use crate::hmi::screens::settings_menu::monitoring_options::MonitoringTargetPeriodOptions;
+use core::fmt::Write;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::Point;
+use embedded_graphics::mono_font::ascii::FONT_6X10;
+use embedded_graphics::mono_font::MonoTextStyleBuilder;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::text::renderer::TextRenderer;
+use embedded_graphics::text::{Alignment, Baseline, Text, TextStyleBuilder};
+use embedded_graphics::Drawable;
+use heapless::String;
+use smartcoaster_app_core::target_direction::TargetDirection;
+use strum::{EnumCount, IntoEnumIterator};
+
+/// Lists today's total against target for each beverage, marking the active beverage.
+pub struct MonitoringScreen5 {}
+
+impl MonitoringScreen5 {
+    fn direction_label(direction: TargetDirection) -> &'static str {
+        match direction {
+            TargetDirection::Minimum => "min",
+            TargetDirection::Maximum => "max",
+        }
+    }
+}
+
+impl<D> MonitoringScreenContent<D> for MonitoringScreen5
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    fn draw_content(
+        &self,
+        display: &mut D,
+        _state: MonitoringStateSubstates,
+        data: &MonitoringData,
+    ) -> Result<(), D::Error> {
+        let mut string_buffer = String::<100>::new();
+        let text_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X10)
+            .text_color(BinaryColor::On)
+            .build();
+        let left_text_style = TextStyleBuilder::new()
+            .alignment(Alignment::Left)
+            .baseline(Baseline::Middle)
+            .build();
+
+        for beverage in BeverageType::iter() {
+            let marker = if beverage == data.active_beverage {
+                '>'
+            } else {
+                ' '
+            };
+            let (total, target, direction) = match beverage {
+                // water is tracked by the main monitoring, which only has a daily target in daily mode
+                BeverageType::Water => (
+                    data.day_total_consumed,
+                    if data.target_mode == MonitoringTargetPeriodOptions::Daily {
+                        Some(data.day_target_consumption)
+                    } else {
+                        None
+                    },
+                    data.target_direction,
+                ),
+                _ => (
+                    data.beverage_totals[beverage.index()],
+                    Some(data.beverage_targets[beverage.index()]),
+                    data.beverage_directions[beverage.index()],
+                ),
+            };
+            match target {
+                Some(target) => writeln!(
+                    string_buffer,
+                    "{}{:<7}{:.0}/{:.0} {}",
+                    marker,
+                    beverage.name(),
+                    total,
+                    target,
+                    Self::direction_label(direction)
+                ),
+                None => writeln!(
+                    string_buffer,
+                    "{}{:<7}{:.0} ml",
+                    marker,
+                    beverage.name(),
+                    total
+                ),
+            }
+            .unwrap();
+        }
+
+        let start_y_pos = display.bounding_box().center().y
+            - (BeverageType::COUNT as i32 - 1) * text_style.line_height() as i32 / 2;
+        Text::with_text_style(
+            string_buffer.trim_end(),
+            Point::new(2, start_y_pos),
+            text_style,
+            left_text_style,
+        )
+        .draw(display)?;
+        Ok(())
+    }
+}
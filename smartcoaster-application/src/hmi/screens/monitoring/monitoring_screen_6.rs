@@ -0,0 +1,100 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::drink_monitor::drink_monitoring::MonitoringStateSubstates;
+use crate::hmi::screens::monitoring::{MonitoringData, MonitoringScreenContent};
+use core::fmt::Write;
+use embedded_graphics::draw_target::{DrawTarget, DrawTargetExt};
+use embedded_graphics::geometry::{AnchorX, Dimensions, OriginDimensions};
+use embedded_graphics::image::Image;
+use embedded_graphics::mono_font::ascii::{FONT_6X10, FONT_8X13_BOLD};
+use embedded_graphics::mono_font::MonoTextStyleBuilder;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::text::renderer::TextRenderer;
+use embedded_graphics::text::{Alignment, Baseline, Text, TextStyleBuilder};
+use embedded_graphics::Drawable;
+use embedded_icon::NewIcon;
+use heapless::String;
+
+/// Shows the number of consecutive days on which the daily target has been achieved.
+pub struct MonitoringScreen6 {}
+
+impl<D> MonitoringScreenContent<D> for MonitoringScreen6
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    fn draw_content(
+        &self,
+        display: &mut D,
+        _state: MonitoringStateSubstates,
+        data: &MonitoringData,
+    ) -> Result<(), D::Error> {
+        let main_area_display = display;
+
+        let left_icon_area_width = main_area_display.bounding_box().size.width / 3;
+        let mut left_icon_display = main_area_display.cropped(
+            &main_area_display
+                .bounding_box()
+                .resized_width(left_icon_area_width, AnchorX::Left),
+        );
+
+        let icon = embedded_icon::mdi::size32px::Trophy::new(BinaryColor::On);
+        let mut icon_location = left_icon_display.bounding_box().center();
+        icon_location.x -= (icon.size().width / 2) as i32;
+        icon_location.y -= (icon.size().height / 2) as i32;
+        Image::new(&icon, icon_location).draw(&mut left_icon_display)?;
+
+        let mut right_display_area =
+            main_area_display.cropped(&main_area_display.bounding_box().resized_width(
+                2 * main_area_display.bounding_box().size.width / 3,
+                AnchorX::Right,
+            ));
+
+        let value_char_style = MonoTextStyleBuilder::new()
+            .font(&FONT_8X13_BOLD)
+            .text_color(BinaryColor::On)
+            .build();
+        let label_char_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X10)
+            .text_color(BinaryColor::On)
+            .build();
+        let centre_text_style = TextStyleBuilder::new()
+            .alignment(Alignment::Center)
+            .baseline(Baseline::Bottom)
+            .build();
+
+        let mut string_buffer = String::<20>::new();
+        write!(string_buffer, "{}", data.streak).unwrap();
+        let mut pos = right_display_area.bounding_box().center();
+        Text::with_text_style(
+            string_buffer.as_str(),
+            pos,
+            value_char_style,
+            centre_text_style,
+        )
+        .draw(&mut right_display_area)?;
+
+        pos.y += (value_char_style.line_height() / 2) as i32
+            + (label_char_style.line_height() / 2) as i32;
+        let label = if data.streak == 1 {
+            "day on\ntarget"
+        } else {
+            "days on\ntarget"
+        };
+        Text::with_text_style(label, pos, label_char_style, centre_text_style)
+            .draw(&mut right_display_area)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::drink_monitor::drink_monitoring::MonitoringStateSubstates;
+use crate::drink_monitor::messaging::DAILY_TOTAL_HISTORY_DAYS;
+use crate::hmi::screens::monitoring::{MonitoringData, MonitoringScreenContent};
+use core::fmt::Write;
+use embedded_graphics::draw_target::{DrawTarget, DrawTargetExt};
+use embedded_graphics::geometry::{AnchorX, Dimensions, Point, Size};
+use embedded_graphics::mono_font::ascii::FONT_6X10;
+use embedded_graphics::mono_font::MonoTextStyleBuilder;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::primitives::{Line, Primitive, PrimitiveStyle, Rectangle};
+use embedded_graphics::text::renderer::TextRenderer;
+use embedded_graphics::text::{Alignment, Baseline, Text, TextStyleBuilder};
+use embedded_graphics::Drawable;
+use heapless::String;
+
+/// Bar chart of the daily totals over the past week, with the 7 and 30 day averages. Days on which
+/// the target was achieved are filled.
+pub struct MonitoringScreen7 {}
+
+impl MonitoringScreen7 {
+    fn write_average(string_buffer: &mut String<40>, label: &str, average: Option<f32>) {
+        match average {
+            Some(average) => writeln!(string_buffer, "{}\n{:.0}", label, average),
+            None => writeln!(string_buffer, "{}\n--", label),
+        }
+        .unwrap();
+    }
+}
+
+impl<D> MonitoringScreenContent<D> for MonitoringScreen7
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    fn draw_content(
+        &self,
+        display: &mut D,
+        _state: MonitoringStateSubstates,
+        data: &MonitoringData,
+    ) -> Result<(), D::Error> {
+        let main_area_display = display;
+        let bar_width: u32 = 9;
+        let bar_spacing: u32 = 3;
+        let chart_margin: u32 = 2;
+        let chart_width =
+            DAILY_TOTAL_HISTORY_DAYS as u32 * (bar_width + bar_spacing) + chart_margin;
+        let mut chart_display = main_area_display.cropped(
+            &main_area_display
+                .bounding_box()
+                .resized_width(chart_width, AnchorX::Left),
+        );
+
+        // bars are drawn oldest first, scaled so the largest day fills the chart
+        let chart_height = chart_display.bounding_box().size.height - 2 * chart_margin;
+        let baseline_y = (chart_display.bounding_box().size.height - chart_margin) as i32;
+        let largest_total = data
+            .daily_totals
+            .iter()
+            .fold(0.0, |largest: f32, total| largest.max(*total));
+        let filled_style = PrimitiveStyle::with_fill(BinaryColor::On);
+        let outline_style = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+        for (position, days_ago) in (1..=DAILY_TOTAL_HISTORY_DAYS).rev().enumerate() {
+            let total = data.daily_totals[days_ago - 1];
+            if total <= 0.0 || largest_total <= 0.0 {
+                continue;
+            }
+            let bar_height = u32::max(1, (total / largest_total * chart_height as f32) as u32);
+            let bar_x = (chart_margin + position as u32 * (bar_width + bar_spacing)) as i32;
+            let style = if data.daily_target_achieved[days_ago - 1] {
+                filled_style
+            } else {
+                outline_style
+            };
+            Rectangle::new(
+                Point::new(bar_x, baseline_y - bar_height as i32),
+                Size::new(bar_width, bar_height),
+            )
+            .into_styled(style)
+            .draw(&mut chart_display)?;
+        }
+        Line::new(
+            Point::new(0, baseline_y),
+            Point::new(chart_width as i32 - 1, baseline_y),
+        )
+        .into_styled(outline_style)
+        .draw(&mut chart_display)?;
+
+        let mut averages_display =
+            main_area_display.cropped(&main_area_display.bounding_box().resized_width(
+                main_area_display.bounding_box().size.width - chart_width,
+                AnchorX::Right,
+            ));
+        let mut string_buffer = String::<40>::new();
+        Self::write_average(&mut string_buffer, "7d avg", data.average_7_day);
+        Self::write_average(&mut string_buffer, "30d avg", data.average_30_day);
+
+        let text_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X10)
+            .text_color(BinaryColor::On)
+            .build();
+        let centred_text_style = TextStyleBuilder::new()
+            .alignment(Alignment::Center)
+            .baseline(Baseline::Middle)
+            .build();
+        let message = string_buffer.trim_end();
+        let mut position = averages_display.bounding_box().center();
+        position.y -= (message.lines().count() as i32 - 1) * text_style.line_height() as i32 / 2;
+        Text::with_text_style(message, position, text_style, centred_text_style)
+            .draw(&mut averages_display)?;
+        Ok(())
+    }
+}
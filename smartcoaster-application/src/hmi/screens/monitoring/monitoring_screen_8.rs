@@ -0,0 +1,146 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::drink_monitor::drink_monitoring::MonitoringStateSubstates;
+use crate::drink_monitor::messaging::HOURLY_HISTORY_HOURS;
+use crate::hmi::screens::monitoring::{MonitoringData, MonitoringScreenContent};
+use core::fmt::Write;
+use embedded_graphics::draw_target::{DrawTarget, DrawTargetExt};
+use embedded_graphics::geometry::{AnchorY, Dimensions, Point, Size};
+use embedded_graphics::mono_font::ascii::FONT_6X10;
+use embedded_graphics::mono_font::MonoTextStyleBuilder;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::primitives::{Line, Primitive, PrimitiveStyle, Rectangle};
+use embedded_graphics::text::{Baseline, Text};
+use embedded_graphics::Drawable;
+use heapless::String;
+
+/// Bar graph of water consumption for each of the past hours, oldest on the left, with the target
+/// rate shown as a dashed line.
+pub struct MonitoringScreen8 {}
+
+impl MonitoringScreen8 {
+    const BAR_WIDTH: u32 = 4;
+    const BAR_SPACING: u32 = 1;
+    const DASH_LENGTH: i32 = 2;
+}
+
+impl<D> MonitoringScreenContent<D> for MonitoringScreen8
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    fn draw_content(
+        &self,
+        display: &mut D,
+        _state: MonitoringStateSubstates,
+        data: &MonitoringData,
+    ) -> Result<(), D::Error> {
+        let main_area_display = display;
+        let text_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X10)
+            .text_color(BinaryColor::On)
+            .build();
+
+        // scale so the larger of the busiest hour and the target fills the graph
+        let largest_value = data
+            .hourly_consumption
+            .iter()
+            .fold(data.target_rate, |largest: f32, total| largest.max(*total));
+
+        let mut string_buffer = String::<30>::new();
+        write!(
+            string_buffer,
+            "{}h  max {:.0} ml",
+            HOURLY_HISTORY_HOURS, largest_value
+        )
+        .unwrap();
+        Text::with_baseline(
+            string_buffer.as_str(),
+            Point::new(2, 0),
+            text_style,
+            Baseline::Top,
+        )
+        .draw(main_area_display)?;
+
+        let label_height = 11;
+        let mut graph_display =
+            main_area_display.cropped(&main_area_display.bounding_box().resized_height(
+                main_area_display.bounding_box().size.height - label_height,
+                AnchorY::Bottom,
+            ));
+        let graph_size = graph_display.bounding_box().size;
+        let graph_width = HOURLY_HISTORY_HOURS as u32 * (Self::BAR_WIDTH + Self::BAR_SPACING);
+        let graph_left = ((graph_size.width - graph_width) / 2) as i32;
+        let baseline_y = graph_size.height as i32 - 1;
+        let bar_max_height = graph_size.height - 1;
+        let scale = if largest_value > 0.0 {
+            bar_max_height as f32 / largest_value
+        } else {
+            0.0
+        };
+
+        // bar heights by position, oldest first
+        let mut bar_heights = [0u32; HOURLY_HISTORY_HOURS];
+        for (position, hours_ago) in (0..HOURLY_HISTORY_HOURS).rev().enumerate() {
+            bar_heights[position] = (data.hourly_consumption[hours_ago] * scale) as u32;
+        }
+
+        let filled_style = PrimitiveStyle::with_fill(BinaryColor::On);
+        let line_style = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+        for (position, bar_height) in bar_heights.iter().enumerate() {
+            if *bar_height == 0 {
+                continue;
+            }
+            let bar_x =
+                graph_left + (position as u32 * (Self::BAR_WIDTH + Self::BAR_SPACING)) as i32;
+            Rectangle::new(
+                Point::new(bar_x, baseline_y - *bar_height as i32),
+                Size::new(Self::BAR_WIDTH, *bar_height),
+            )
+            .into_styled(filled_style)
+            .draw(&mut graph_display)?;
+        }
+        Line::new(
+            Point::new(graph_left, baseline_y),
+            Point::new(graph_left + graph_width as i32 - 1, baseline_y),
+        )
+        .into_styled(line_style)
+        .draw(&mut graph_display)?;
+
+        if data.target_rate > 0.0 {
+            let target_height = (data.target_rate * scale) as i32;
+            let target_y = baseline_y - target_height;
+            for dash_x in (graph_left..graph_left + graph_width as i32)
+                .step_by(2 * Self::DASH_LENGTH as usize)
+            {
+                // the dash is drawn inverted where it crosses a bar so that it stays visible
+                let position =
+                    ((dash_x - graph_left) as u32 / (Self::BAR_WIDTH + Self::BAR_SPACING)) as usize;
+                let colour = if bar_heights[position] as i32 > target_height {
+                    BinaryColor::Off
+                } else {
+                    BinaryColor::On
+                };
+                Line::new(
+                    Point::new(dash_x, target_y),
+                    Point::new(dash_x + Self::DASH_LENGTH - 1, target_y),
+                )
+                .into_styled(PrimitiveStyle::with_stroke(colour, 1))
+                .draw(&mut graph_display)?;
+            }
+        }
+
+        Ok(())
+    }
+}
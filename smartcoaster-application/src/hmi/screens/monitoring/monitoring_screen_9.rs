@@ -0,0 +1,82 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::drink_monitor::drink_monitoring::MonitoringStateSubstates;
+use crate::drink_monitor::messaging::DAY_PROGRESS_POINTS;
+use crate::hmi::screens::monitoring::{MonitoringData, MonitoringScreenContent};
+use core::fmt::Write;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Dimensions, Point, Size};
+use embedded_graphics::mono_font::ascii::FONT_6X10;
+use embedded_graphics::mono_font::MonoTextStyleBuilder;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::text::{Baseline, Text};
+use embedded_graphics::Drawable;
+use heapless::String;
+use smartcoaster_widgets::sparkline::Sparkline;
+
+/// Today's cumulative consumption plotted against the target for the day.
+pub struct MonitoringScreen9 {}
+
+impl<D> MonitoringScreenContent<D> for MonitoringScreen9
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    fn draw_content(
+        &self,
+        display: &mut D,
+        _state: MonitoringStateSubstates,
+        data: &MonitoringData,
+    ) -> Result<(), D::Error> {
+        let text_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X10)
+            .text_color(BinaryColor::On)
+            .build();
+
+        let mut string_buffer = String::<30>::new();
+        write!(
+            string_buffer,
+            "Today {:.0}/{:.0} ml",
+            data.day_total_consumed,
+            data.day_progress_target[DAY_PROGRESS_POINTS - 1]
+        )
+        .unwrap();
+        Text::with_baseline(
+            string_buffer.as_str(),
+            Point::new(2, 0),
+            text_style,
+            Baseline::Top,
+        )
+        .draw(display)?;
+
+        let mut consumed = [0.0; DAY_PROGRESS_POINTS];
+        let consumed = data.day_progress_so_far(&mut consumed);
+
+        let label_height = 11;
+        let margin = 2;
+        let display_size = display.bounding_box().size;
+        Sparkline::new(
+            Point::new(margin, label_height),
+            Size::new(
+                display_size.width - 2 * margin as u32,
+                display_size.height - label_height as u32 - margin as u32,
+            ),
+            consumed,
+            &data.day_progress_target,
+        )
+        .draw(display)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,200 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::drink_monitor::drink_monitoring::MonitoringStateSubstates;
+use crate::drink_monitor::messaging::DAY_PROGRESS_POINTS;
+use crate::hmi::screens::monitoring::monitoring_screen_4::MonitoringScreen4;
+use crate::hmi::screens::monitoring::{MonitoringData, MonitoringScreenContent};
+use crate::hmi::screens::settings_menu::monitoring_options::MonitoringTargetPeriodOptions;
+use crate::storage::settings::{SettingValue, SettingsAccessor, SettingsAccessorId};
+use chrono::{NaiveDateTime, Timelike};
+use core::fmt::Write;
+use defmt::warn;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Dimensions, Point, Size};
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::Drawable;
+use heapless::String;
+use smartcoaster_app_core::target_direction::TargetDirection;
+use smartcoaster_widgets::grid::{GridLayout, WidgetKind};
+use smartcoaster_widgets::progress_bar::ProgressBar;
+use smartcoaster_widgets::sparkline::Sparkline;
+use smartcoaster_widgets::value::ValueWidget;
+
+/// Reads the custom screen layout from the settings, falling back to the default layout.
+pub async fn read_custom_layout<SA: SettingsAccessor>(settings: &SA) -> GridLayout {
+    let packed = match settings
+        .get_setting(SettingsAccessorId::MonitoringCustomScreen)
+        .await
+    {
+        Some(SettingValue::UInt(packed)) => Some(packed),
+        Some(_) => {
+            warn!("Unable to retrieve custom screen layout");
+            None
+        }
+        None => None,
+    };
+    GridLayout::from_stored(packed)
+}
+
+/// Screen built from widgets placed on a grid, as chosen in the settings menu.
+pub struct MonitoringScreenCustom {
+    layout: GridLayout,
+    now: NaiveDateTime,
+}
+
+impl MonitoringScreenCustom {
+    const MARGIN: u32 = 2;
+
+    pub fn new(layout: GridLayout, now: NaiveDateTime) -> Self {
+        Self { layout, now }
+    }
+
+    fn draw_widget<D>(
+        &self,
+        display: &mut D,
+        kind: WidgetKind,
+        area: Rectangle,
+        data: &MonitoringData,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let mut value = String::<20>::new();
+        let label = match kind {
+            WidgetKind::Empty => return Ok(()),
+            WidgetKind::ProgressBar => return self.draw_progress_bar(display, area, data),
+            WidgetKind::Sparkline => return self.draw_sparkline(display, area, data),
+            WidgetKind::Clock => {
+                write!(value, "{:02}:{:02}", self.now.hour(), self.now.minute()).unwrap();
+                "Time"
+            }
+            WidgetKind::TotalConsumed => {
+                write!(value, "{:.0}ml", data.day_total_consumed).unwrap();
+                "Today"
+            }
+            WidgetKind::Target => {
+                match data.target_mode {
+                    MonitoringTargetPeriodOptions::Daily => {
+                        write!(value, "{:.0}ml", data.day_target_consumption).unwrap()
+                    }
+                    MonitoringTargetPeriodOptions::Hourly => {
+                        write!(value, "{:.0}ml/h", data.target_rate).unwrap()
+                    }
+                }
+                "Target"
+            }
+            WidgetKind::LastDrink => {
+                match data.last_drink_time {
+                    Some(last_drink_time) => {
+                        let minutes = (self.now - last_drink_time).num_minutes().max(0);
+                        if minutes < 60 {
+                            write!(value, "{}m", minutes).unwrap();
+                        } else {
+                            write!(value, "{}h{:02}m", minutes / 60, minutes % 60).unwrap();
+                        }
+                    }
+                    None => write!(value, "-").unwrap(),
+                }
+                "Last drink"
+            }
+            WidgetKind::HourlyRate => {
+                write!(value, "{:.0}ml/h", data.day_consumption_rate).unwrap();
+                "Average"
+            }
+            WidgetKind::DrinkToStayOnTarget => match data.target_direction {
+                TargetDirection::Minimum => {
+                    let amount = MonitoringScreen4::calculate_drink_to_stay_on_target(data);
+                    write!(value, "{}ml", amount).unwrap();
+                    "Drink"
+                }
+                TargetDirection::Maximum => {
+                    let budget_remaining = MonitoringScreen4::calculate_budget_remaining(data);
+                    write!(value, "{}ml", budget_remaining.abs()).unwrap();
+                    if budget_remaining >= 0 {
+                        "Budget left"
+                    } else {
+                        "Over budget"
+                    }
+                }
+            },
+        };
+        ValueWidget::new(area, label, &value).draw(display)
+    }
+
+    fn draw_progress_bar<D>(
+        &self,
+        display: &mut D,
+        area: Rectangle,
+        data: &MonitoringData,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let bar_height = u32::min(14, area.size.height.saturating_sub(2 * Self::MARGIN));
+        let bar_width = area.size.width.saturating_sub(2 * Self::MARGIN);
+        let bar = Rectangle::with_center(area.center(), Size::new(bar_width, bar_height));
+        let mut progress_bar = ProgressBar::new(
+            bar.top_left,
+            bar.size,
+            data.day_total_consumed,
+            data.day_target(),
+        );
+        if let Some(expected) = data.expected_by_now() {
+            progress_bar = progress_bar.with_marker(expected);
+        }
+        progress_bar.draw(display)
+    }
+
+    fn draw_sparkline<D>(
+        &self,
+        display: &mut D,
+        area: Rectangle,
+        data: &MonitoringData,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let mut consumed = [0.0; DAY_PROGRESS_POINTS];
+        let consumed = data.day_progress_so_far(&mut consumed);
+        Sparkline::new(
+            area.top_left + Point::new(Self::MARGIN as i32, Self::MARGIN as i32),
+            Size::new(
+                area.size.width.saturating_sub(2 * Self::MARGIN),
+                area.size.height.saturating_sub(2 * Self::MARGIN),
+            ),
+            consumed,
+            &data.day_progress_target,
+        )
+        .draw(display)
+    }
+}
+
+impl<D> MonitoringScreenContent<D> for MonitoringScreenCustom
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    fn draw_content(
+        &self,
+        display: &mut D,
+        _state: MonitoringStateSubstates,
+        data: &MonitoringData,
+    ) -> Result<(), D::Error> {
+        for (kind, area) in self.layout.regions(display.bounding_box()) {
+            self.draw_widget(display, kind, area, data)?;
+        }
+        Ok(())
+    }
+}
@@ -14,6 +14,7 @@
 
 use crate::drink_monitor::drink_monitoring::MonitoringStateSubstates;
 use crate::hmi::screens::monitoring::{MonitoringData, MonitoringScreenContent};
+use crate::hmi::screens::settings_menu::monitoring_options::MonitoringTargetPeriodOptions;
 use core::fmt::Write;
 use embedded_graphics::draw_target::DrawTarget;
 use embedded_graphics::geometry::Point;
@@ -71,7 +72,10 @@ where
         )
         .unwrap();
         writeln!(string_buffer, "Last drink: {:.0} ml", data.last_consumption).unwrap();
-        write!(string_buffer, "Total: {:.0} ml", data.day_total_consumed).unwrap();
+        writeln!(string_buffer, "Total: {:.0} ml", data.day_total_consumed).unwrap();
+        if data.target_mode == MonitoringTargetPeriodOptions::Daily {
+            write!(string_buffer, "Plan: {:+.0} ml", data.plan_difference).unwrap();
+        }
         Text::with_text_style(
             string_buffer.as_str(),
             centre_point,
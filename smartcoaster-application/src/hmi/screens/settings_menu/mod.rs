@@ -13,11 +13,15 @@
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use crate::application::application_state::{ApplicationState, ConfirmationId};
+use crate::drink_monitor::beverage::BeverageType;
 use crate::hmi::messaging::{UiActionChannelPublisher, UiRequestMessage};
+use crate::hmi::screens::monitoring::read_custom_layout;
 use crate::hmi::screens::settings_menu::display_options::{
     DisplayBrightnessOptions, DisplayTimeoutOptions,
 };
 use crate::hmi::screens::{UiDrawer, UiInput, UiInputHandler};
+use crate::storage::factory_reset::FactoryResetOptions;
+use crate::storage::restart::request_restart;
 use crate::storage::settings::{SettingValue, SettingsAccessor, SettingsAccessorId};
 use defmt::debug;
 use defmt::{warn, Debug2Format};
@@ -30,10 +34,14 @@ use embedded_graphics::Drawable;
 use led_brightness_options::LedBrightnessOptions;
 use simple_embedded_graphics_menu::items::SelectedData;
 use simple_embedded_graphics_menu::{Menu, MenuStyle};
+use smartcoaster_app_core::target_direction::TargetDirection;
+use strum::IntoEnumIterator;
+use system_options::UsbDriveOptions;
 
 mod display_options;
 mod led_brightness_options;
 pub mod monitoring_options;
+pub mod system_options;
 
 #[derive(Copy, Clone, Debug)]
 pub enum SettingMenuIdentifier {
@@ -41,6 +49,11 @@ pub enum SettingMenuIdentifier {
     Root,
     EnterTestScreen,
     EnterHeapStatusScreen,
+    EnterErrorLogScreen,
+    EnterNvmStatusScreen,
+    Restart,
+    FactoryReset,
+    FactoryResetKeepCalibration,
     DoCalibration,
     SetLedBrightness,
     DisplayBrightness,
@@ -51,6 +64,18 @@ pub enum SettingMenuIdentifier {
     AboutScreen,
     SetDailyTargetTime,
     ClearHistoricalMonitoringData,
+    SetDayStartTime,
+    SetDrinkingWindowStart,
+    SetPacingProfile,
+    SetTargetDirection,
+    SetActiveBeverage,
+    SetBeverageTarget(BeverageType),
+    SetBeverageDirection(BeverageType),
+    SetReminderInterval,
+    SetQuietStart,
+    SetQuietEnd,
+    SetCustomScreenWidget(usize),
+    SetUsbDrive,
 }
 
 pub struct SettingMenu<'a, SA>
@@ -78,6 +103,29 @@ where
         menu: &mut Menu<'_, BinaryColor, SettingMenuIdentifier>,
         settings: &SA,
     ) {
+        let active_beverage: u8 = {
+            if let Some(result) = settings
+                .get_setting(SettingsAccessorId::MonitoringActiveBeverage)
+                .await
+            {
+                match result {
+                    SettingValue::SmallUInt(v) => v,
+                    _ => {
+                        warn!("Unable to retrieve active beverage");
+                        0
+                    }
+                }
+            } else {
+                0
+            }
+        };
+        menu.add_selector(
+            "Drinking",
+            SettingMenuIdentifier::SetActiveBeverage,
+            BeverageType::option_strings(),
+            Some(active_beverage as usize),
+        );
+
         let monitoring_target_type: u8 = {
             if let Some(result) = settings
                 .get_setting(SettingsAccessorId::MonitoringTargetType)
@@ -102,14 +150,141 @@ where
         );
 
         menu.add_action("Target", SettingMenuIdentifier::SetMonitoringTargetValue);
+
+        let target_direction: u8 = {
+            if let Some(result) = settings
+                .get_setting(SettingsAccessorId::MonitoringTargetDirection)
+                .await
+            {
+                match result {
+                    SettingValue::SmallUInt(v) => v,
+                    _ => {
+                        warn!("Unable to retrieve target direction");
+                        0
+                    }
+                }
+            } else {
+                0
+            }
+        };
+        menu.add_selector(
+            "Target is",
+            SettingMenuIdentifier::SetTargetDirection,
+            monitoring_options::MonitoringTargetDirectionOptions::option_strings(),
+            Some(target_direction as usize),
+        );
+
+        menu.add_action(
+            "Drinking Start Time",
+            SettingMenuIdentifier::SetDrinkingWindowStart,
+        );
         menu.add_action(
             "Daily Target Time",
             SettingMenuIdentifier::SetDailyTargetTime,
         );
+
+        let pacing_profile: u8 = {
+            if let Some(result) = settings
+                .get_setting(SettingsAccessorId::MonitoringPacingProfile)
+                .await
+            {
+                match result {
+                    SettingValue::SmallUInt(v) => v,
+                    _ => {
+                        warn!("Unable to retrieve pacing profile");
+                        0
+                    }
+                }
+            } else {
+                0
+            }
+        };
+        menu.add_selector(
+            "Pacing",
+            SettingMenuIdentifier::SetPacingProfile,
+            monitoring_options::MonitoringPacingOptions::option_strings(),
+            Some(pacing_profile as usize),
+        );
+        menu.add_action("Day Start Time", SettingMenuIdentifier::SetDayStartTime);
         menu.add_action(
             "Clear logged data",
             SettingMenuIdentifier::ClearHistoricalMonitoringData,
         );
+
+        menu.add_section("Reminders", SettingMenuIdentifier::None);
+        let reminder_interval: u8 = {
+            if let Some(result) = settings
+                .get_setting(SettingsAccessorId::MonitoringReminderInterval)
+                .await
+            {
+                match result {
+                    SettingValue::SmallUInt(v) => v,
+                    _ => {
+                        warn!("Unable to retrieve reminder interval");
+                        0
+                    }
+                }
+            } else {
+                0
+            }
+        };
+        menu.add_selector(
+            "Remind every",
+            SettingMenuIdentifier::SetReminderInterval,
+            monitoring_options::MonitoringReminderIntervalOptions::option_strings(),
+            Some(
+                monitoring_options::MonitoringReminderIntervalOptions::minutes_to_option_index(
+                    reminder_interval,
+                ),
+            ),
+        );
+        menu.add_action("Quiet Start", SettingMenuIdentifier::SetQuietStart);
+        menu.add_action("Quiet End", SettingMenuIdentifier::SetQuietEnd);
+
+        menu.add_section("Custom screen", SettingMenuIdentifier::None);
+        let custom_screen_layout = read_custom_layout(settings).await;
+        for (cell, title) in monitoring_options::CustomScreenWidgetOptions::cell_titles()
+            .iter()
+            .enumerate()
+        {
+            menu.add_selector(
+                title,
+                SettingMenuIdentifier::SetCustomScreenWidget(cell),
+                monitoring_options::CustomScreenWidgetOptions::option_strings(),
+                Some(u8::from(custom_screen_layout.cell(cell)) as usize),
+            );
+        }
+
+        menu.add_section("Other drinks", SettingMenuIdentifier::None);
+        for beverage in BeverageType::iter().filter(|b| *b != BeverageType::Water) {
+            menu.add_action(
+                beverage.target_title(),
+                SettingMenuIdentifier::SetBeverageTarget(beverage),
+            );
+
+            let beverage_direction: u8 = {
+                if let Some(result) = settings
+                    .get_setting(SettingsAccessorId::BeverageTargetDirection(beverage))
+                    .await
+                {
+                    match result {
+                        SettingValue::SmallUInt(v) => v,
+                        _ => {
+                            warn!("Unable to retrieve {} target direction", beverage);
+                            TargetDirection::Maximum.into()
+                        }
+                    }
+                } else {
+                    TargetDirection::Maximum.into()
+                }
+            };
+            menu.add_selector(
+                beverage.direction_title(),
+                SettingMenuIdentifier::SetBeverageDirection(beverage),
+                monitoring_options::MonitoringTargetDirectionOptions::option_strings(),
+                Some(beverage_direction as usize),
+            );
+        }
         menu.add_back("Back", SettingMenuIdentifier::None);
     }
 
@@ -202,9 +377,40 @@ where
 
         menu.add_section("System", SettingMenuIdentifier::None);
         menu.add_action("Set Date/Time", SettingMenuIdentifier::SetDateTime);
+        {
+            let usb_drive: u8 = match settings
+                .get_setting(SettingsAccessorId::SystemUsbDrive)
+                .await
+            {
+                Some(SettingValue::SmallUInt(v)) => v,
+                Some(_) => {
+                    warn!("Unable to retrieve USB drive setting");
+                    UsbDriveOptions::OFF
+                }
+                None => UsbDriveOptions::OFF,
+            };
+
+            menu.add_selector(
+                "USB Drive",
+                SettingMenuIdentifier::SetUsbDrive,
+                UsbDriveOptions::option_strings(),
+                Some(UsbDriveOptions::value_to_option_index(usb_drive)),
+            );
+        }
         menu.add_action("Calibration", SettingMenuIdentifier::DoCalibration);
         menu.add_action("Test Mode", SettingMenuIdentifier::EnterTestScreen);
         menu.add_action("Heap Status", SettingMenuIdentifier::EnterHeapStatusScreen);
+        menu.add_action("Error Log", SettingMenuIdentifier::EnterErrorLogScreen);
+        menu.add_action(
+            "Storage Status",
+            SettingMenuIdentifier::EnterNvmStatusScreen,
+        );
+        menu.add_action("Restart", SettingMenuIdentifier::Restart);
+        menu.add_action("Factory Reset", SettingMenuIdentifier::FactoryReset);
+        menu.add_action(
+            "Reset, Keep Calib.",
+            SettingMenuIdentifier::FactoryResetKeepCalibration,
+        );
         menu.add_back("Back", SettingMenuIdentifier::None);
     }
 
@@ -241,7 +447,7 @@ where
     }
 
     async fn process_multi_options(
-        &self,
+        &mut self,
         ui_action_publisher: &UiActionChannelPublisher<'static>,
         id: SettingMenuIdentifier,
         option_id: usize,
@@ -275,28 +481,144 @@ where
                         warn!("Failed to store monitoring type: {:?}", Debug2Format(&e))
                     });
             }
+            SettingMenuIdentifier::SetPacingProfile => {
+                let profile =
+                    monitoring_options::MonitoringPacingOptions::option_index_to_profile(option_id);
+                self.settings_accessor
+                    .save_setting(
+                        SettingsAccessorId::MonitoringPacingProfile,
+                        SettingValue::SmallUInt(profile.into()),
+                    )
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!("Failed to store pacing profile: {:?}", Debug2Format(&e))
+                    });
+            }
+            SettingMenuIdentifier::SetTargetDirection => {
+                let direction =
+                    monitoring_options::MonitoringTargetDirectionOptions::option_index_to_direction(
+                        option_id,
+                    );
+                self.settings_accessor
+                    .save_setting(
+                        SettingsAccessorId::MonitoringTargetDirection,
+                        SettingValue::SmallUInt(direction.into()),
+                    )
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!("Failed to store target direction: {:?}", Debug2Format(&e))
+                    });
+            }
+            SettingMenuIdentifier::SetActiveBeverage => {
+                self.settings_accessor
+                    .save_setting(
+                        SettingsAccessorId::MonitoringActiveBeverage,
+                        SettingValue::SmallUInt(option_id as u8),
+                    )
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!("Failed to store active beverage: {:?}", Debug2Format(&e))
+                    });
+            }
+            SettingMenuIdentifier::SetBeverageDirection(beverage) => {
+                let direction =
+                    monitoring_options::MonitoringTargetDirectionOptions::option_index_to_direction(
+                        option_id,
+                    );
+                self.settings_accessor
+                    .save_setting(
+                        SettingsAccessorId::BeverageTargetDirection(beverage),
+                        SettingValue::SmallUInt(direction.into()),
+                    )
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!(
+                            "Failed to store {} target direction: {:?}",
+                            beverage,
+                            Debug2Format(&e)
+                        )
+                    });
+            }
+            SettingMenuIdentifier::SetReminderInterval => {
+                let minutes =
+                    monitoring_options::MonitoringReminderIntervalOptions::option_index_to_minutes(
+                        option_id,
+                    );
+                self.settings_accessor
+                    .save_setting(
+                        SettingsAccessorId::MonitoringReminderInterval,
+                        SettingValue::SmallUInt(minutes),
+                    )
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!("Failed to store reminder interval: {:?}", Debug2Format(&e))
+                    });
+            }
+            SettingMenuIdentifier::SetCustomScreenWidget(cell) => {
+                // read back each time, as the layout can also be set over USB
+                let mut custom_screen_layout = read_custom_layout(self.settings_accessor).await;
+                custom_screen_layout.set_cell(
+                    cell,
+                    monitoring_options::CustomScreenWidgetOptions::option_index_to_widget(
+                        option_id,
+                    ),
+                );
+                self.settings_accessor
+                    .save_setting(
+                        SettingsAccessorId::MonitoringCustomScreen,
+                        SettingValue::UInt(custom_screen_layout.into()),
+                    )
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!(
+                            "Failed to store custom screen layout: {:?}",
+                            Debug2Format(&e)
+                        )
+                    });
+            }
             SettingMenuIdentifier::DisplayTimeout => {
                 // TODO change this to a direct setting write and then publish change through settings channel
                 ui_action_publisher.publish_immediate(UiRequestMessage::ChangeDisplayTimeout(
                     DisplayTimeoutOptions::option_index_to_minutes(option_id),
                 ))
             }
+            SettingMenuIdentifier::SetUsbDrive => {
+                self.settings_accessor
+                    .save_setting(
+                        SettingsAccessorId::SystemUsbDrive,
+                        SettingValue::SmallUInt(UsbDriveOptions::option_index_to_value(option_id)),
+                    )
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!("Failed to store USB drive setting: {:?}", Debug2Format(&e))
+                    });
+            }
 
             SettingMenuIdentifier::None => {}
             SettingMenuIdentifier::Root => {}
             SettingMenuIdentifier::EnterTestScreen => {}
             SettingMenuIdentifier::EnterHeapStatusScreen => {}
+            SettingMenuIdentifier::EnterErrorLogScreen => {}
+            SettingMenuIdentifier::EnterNvmStatusScreen => {}
+            SettingMenuIdentifier::Restart => {}
+            SettingMenuIdentifier::FactoryReset => {}
+            SettingMenuIdentifier::FactoryResetKeepCalibration => {}
             SettingMenuIdentifier::DoCalibration => {}
             SettingMenuIdentifier::SetDateTime => {}
             SettingMenuIdentifier::SetMonitoringTargetValue => {}
             SettingMenuIdentifier::AboutScreen => {}
             SettingMenuIdentifier::SetDailyTargetTime => {}
             SettingMenuIdentifier::ClearHistoricalMonitoringData => {}
+            SettingMenuIdentifier::SetDayStartTime => {}
+            SettingMenuIdentifier::SetDrinkingWindowStart => {}
+            SettingMenuIdentifier::SetBeverageTarget(_) => {}
+            SettingMenuIdentifier::SetQuietStart => {}
+            SettingMenuIdentifier::SetQuietEnd => {}
         }
     }
 
     async fn process_selection(
-        &self,
+        &mut self,
         selection_data: SelectedData<SettingMenuIdentifier>,
         ui_action_publisher: &UiActionChannelPublisher<'static>,
     ) {
@@ -312,6 +634,16 @@ where
                         ApplicationState::HeapStatus,
                     ));
                 }
+                SettingMenuIdentifier::EnterErrorLogScreen => {
+                    ui_action_publisher.publish_immediate(UiRequestMessage::ChangeState(
+                        ApplicationState::ErrorLog,
+                    ));
+                }
+                SettingMenuIdentifier::EnterNvmStatusScreen => {
+                    ui_action_publisher.publish_immediate(UiRequestMessage::ChangeState(
+                        ApplicationState::NvmStatus,
+                    ));
+                }
                 SettingMenuIdentifier::DoCalibration => ui_action_publisher.publish_immediate(
                     UiRequestMessage::ChangeState(ApplicationState::Calibration),
                 ),
@@ -330,12 +662,53 @@ where
                         ApplicationState::TimeEntry(SettingsAccessorId::MonitoringDailyTargetTime),
                     ))
                 }
+                SettingMenuIdentifier::SetDrinkingWindowStart => ui_action_publisher
+                    .publish_immediate(UiRequestMessage::ChangeState(ApplicationState::TimeEntry(
+                        SettingsAccessorId::MonitoringDrinkingWindowStart,
+                    ))),
+                SettingMenuIdentifier::SetDayStartTime => {
+                    ui_action_publisher.publish_immediate(UiRequestMessage::ChangeState(
+                        ApplicationState::TimeEntry(SettingsAccessorId::MonitoringDayStartTime),
+                    ))
+                }
+                SettingMenuIdentifier::SetQuietStart => {
+                    ui_action_publisher.publish_immediate(UiRequestMessage::ChangeState(
+                        ApplicationState::TimeEntry(SettingsAccessorId::MonitoringQuietStart),
+                    ))
+                }
+                SettingMenuIdentifier::SetQuietEnd => {
+                    ui_action_publisher.publish_immediate(UiRequestMessage::ChangeState(
+                        ApplicationState::TimeEntry(SettingsAccessorId::MonitoringQuietEnd),
+                    ))
+                }
+                SettingMenuIdentifier::SetBeverageTarget(beverage) => ui_action_publisher
+                    .publish_immediate(UiRequestMessage::ChangeState(
+                        ApplicationState::NumberEntry(SettingsAccessorId::BeverageTargetDaily(
+                            beverage,
+                        )),
+                    )),
                 SettingMenuIdentifier::ClearHistoricalMonitoringData => ui_action_publisher
                     .publish_immediate(UiRequestMessage::ChangeState(
                         ApplicationState::ConfirmationScreen(
                             ConfirmationId::ClearHistoricalConsumptionLog,
                         ),
                     )),
+                SettingMenuIdentifier::Restart => request_restart(),
+                SettingMenuIdentifier::FactoryReset => ui_action_publisher.publish_immediate(
+                    UiRequestMessage::ChangeState(ApplicationState::ConfirmationScreen(
+                        ConfirmationId::FactoryReset(FactoryResetOptions {
+                            keep_calibration: false,
+                        }),
+                    )),
+                ),
+                SettingMenuIdentifier::FactoryResetKeepCalibration => ui_action_publisher
+                    .publish_immediate(UiRequestMessage::ChangeState(
+                        ApplicationState::ConfirmationScreen(ConfirmationId::FactoryReset(
+                            FactoryResetOptions {
+                                keep_calibration: true,
+                            },
+                        )),
+                    )),
                 _ => {}
             },
             SelectedData::MultiOption { id, option_id } => {
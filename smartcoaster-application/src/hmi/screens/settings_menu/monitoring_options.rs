@@ -12,7 +12,12 @@
 // You should have received a copy of the GNU General Public License along with
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+use defmt::Format;
+use smartcoaster_app_core::flight_path::PacingProfile;
+use smartcoaster_app_core::target_direction::TargetDirection;
+use smartcoaster_widgets::grid::WidgetKind;
+
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
 pub enum MonitoringTargetPeriodOptions {
     Daily,
     Hourly,
@@ -70,3 +75,83 @@ impl From<MonitoringTargetPeriodOptions> for u8 {
         }
     }
 }
+
+pub struct MonitoringPacingOptions {}
+
+impl MonitoringPacingOptions {
+    pub fn option_strings() -> &'static [&'static str] {
+        &["Linear", "Front-loaded"]
+    }
+
+    pub fn option_index_to_profile(index: usize) -> PacingProfile {
+        PacingProfile::try_from(index as u8).unwrap_or(PacingProfile::Linear)
+    }
+}
+
+pub struct MonitoringTargetDirectionOptions {}
+
+impl MonitoringTargetDirectionOptions {
+    pub fn option_strings() -> &'static [&'static str] {
+        &["Minimum", "Maximum"]
+    }
+
+    pub fn option_index_to_direction(index: usize) -> TargetDirection {
+        TargetDirection::try_from(index as u8).unwrap_or(TargetDirection::Minimum)
+    }
+}
+
+pub struct MonitoringReminderIntervalOptions {}
+
+impl MonitoringReminderIntervalOptions {
+    /// Minutes between reminders. Zero turns reminders off.
+    const OPTION_LIST: [u8; 6] = [0, 30, 45, 60, 90, 120];
+
+    pub fn option_strings() -> &'static [&'static str] {
+        &["Off", "30 min", "45 min", "60 min", "90 min", "120 min"]
+    }
+
+    pub fn option_index_to_minutes(index: usize) -> u8 {
+        Self::OPTION_LIST.get(index).copied().unwrap_or(0)
+    }
+
+    pub fn minutes_to_option_index(minutes: u8) -> usize {
+        Self::OPTION_LIST
+            .iter()
+            .position(|&m| m == minutes)
+            .unwrap_or(0)
+    }
+}
+
+pub struct CustomScreenWidgetOptions {}
+
+impl CustomScreenWidgetOptions {
+    /// Option order matches the stored widget value.
+    pub fn option_strings() -> &'static [&'static str] {
+        &[
+            "Empty",
+            "Clock",
+            "Total",
+            "Target",
+            "Last drink",
+            "Hourly rate",
+            "Progress bar",
+            "Drink to target",
+            "Sparkline",
+        ]
+    }
+
+    pub fn option_index_to_widget(index: usize) -> WidgetKind {
+        WidgetKind::try_from(index as u8).unwrap_or(WidgetKind::Empty)
+    }
+
+    pub fn cell_titles() -> &'static [&'static str] {
+        &[
+            "Top left",
+            "Top middle",
+            "Top right",
+            "Bottom left",
+            "Bottom middle",
+            "Bottom right",
+        ]
+    }
+}
@@ -0,0 +1,40 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+/// Whether the coaster shows up as a USB drive holding its data. The USB device is set up at boot,
+/// so a change applies from the next restart.
+pub struct UsbDriveOptions {}
+
+impl UsbDriveOptions {
+    pub const OFF: u8 = 0;
+    pub const ON: u8 = 1;
+
+    pub fn option_strings() -> &'static [&'static str] {
+        &["Off", "On"]
+    }
+
+    pub fn option_index_to_value(index: usize) -> u8 {
+        match index {
+            1 => Self::ON,
+            _ => Self::OFF,
+        }
+    }
+
+    pub fn value_to_option_index(value: u8) -> usize {
+        match value {
+            Self::OFF => 0,
+            _ => 1,
+        }
+    }
+}
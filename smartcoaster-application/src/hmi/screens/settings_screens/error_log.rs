@@ -0,0 +1,144 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::application::application_state::ApplicationState;
+use crate::application::messaging::ApplicationData;
+use crate::error_log::ErrorLogRecord;
+use crate::hmi::messaging::{UiActionChannelPublisher, UiRequestMessage};
+use crate::hmi::screens::UiInput;
+use crate::hmi::screens::UiInputHandler;
+use crate::hmi::screens::{draw_message_screen, UiDrawer};
+use chrono::{Datelike, Timelike};
+use core::fmt::Write;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::Point;
+use embedded_graphics::mono_font::ascii::FONT_6X10;
+use embedded_graphics::mono_font::MonoTextStyleBuilder;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::text::{Baseline, Text};
+use embedded_graphics::Drawable;
+use heapless::{String, Vec};
+
+/// Most recent errors shown on the screen.
+pub const ERROR_LOG_SCREEN_RECORDS: usize = 16;
+
+/// Shows the most recent errors one at a time, newest first. The encoder moves between them.
+pub struct ErrorLogScreen {
+    records: Vec<ErrorLogRecord, ERROR_LOG_SCREEN_RECORDS>,
+    selected: usize,
+}
+
+impl ErrorLogScreen {
+    pub fn new() -> Self {
+        Self {
+            records: Vec::new(),
+            selected: 0,
+        }
+    }
+
+    fn process_app_data(&mut self, data: ApplicationData) {
+        match data {
+            ApplicationData::ErrorLogRecord(record) => {
+                // records arrive newest first so any beyond the screen's capacity are the oldest
+                let _ = self.records.push(record);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl UiInputHandler for ErrorLogScreen {
+    async fn ui_input_handler(
+        &mut self,
+        input: UiInput,
+        ui_channel_publisher: &UiActionChannelPublisher<'_>,
+    ) {
+        match input {
+            UiInput::EncoderClockwise => {
+                if self.selected + 1 < self.records.len() {
+                    self.selected += 1;
+                }
+            }
+            UiInput::EncoderCounterClockwise => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            UiInput::ButtonPress => ui_channel_publisher
+                .publish_immediate(UiRequestMessage::ChangeState(ApplicationState::Settings)),
+            UiInput::ApplicationData(data) => {
+                self.process_app_data(data);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl UiDrawer for ErrorLogScreen {
+    fn draw<D>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let Some(record) = self.records.get(self.selected) else {
+            return draw_message_screen(display, "No errors logged");
+        };
+
+        let text_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X10)
+            .text_color(BinaryColor::On)
+            .build();
+        let line_height = 12;
+        let mut line_string = String::<40>::new();
+        let mut y = 0;
+        let mut draw_line = |line: &str, display: &mut D| {
+            let result = Text::with_baseline(line, Point::new(0, y), text_style, Baseline::Top)
+                .draw(display);
+            y += line_height;
+            result.map(|_| ())
+        };
+
+        write!(
+            &mut line_string,
+            "Error {} of {}",
+            self.selected + 1,
+            self.records.len()
+        )
+        .unwrap();
+        draw_line(&line_string, display)?;
+
+        line_string.clear();
+        let timestamp = record.timestamp;
+        write!(
+            &mut line_string,
+            "{}-{:02}-{:02} {:02}:{:02}:{:02}",
+            timestamp.year(),
+            timestamp.month(),
+            timestamp.day(),
+            timestamp.hour(),
+            timestamp.minute(),
+            timestamp.second()
+        )
+        .unwrap();
+        draw_line(&line_string, display)?;
+
+        draw_line(record.data.module.name(), display)?;
+        draw_line(record.data.code.description(), display)?;
+
+        line_string.clear();
+        for value in record.data.values.iter().flatten() {
+            let separator = if line_string.is_empty() { "Info:" } else { "," };
+            write!(&mut line_string, "{} {}", separator, value).unwrap();
+        }
+        draw_line(&line_string, display)?;
+        Ok(())
+    }
+}
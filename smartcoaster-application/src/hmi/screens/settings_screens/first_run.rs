@@ -0,0 +1,177 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Screens for the steps of the first run setup that are not shared with the settings menu.
+
+use crate::application::application_state::{ApplicationState, FirstRunStep};
+use crate::hmi::messaging::{UiActionChannelPublisher, UiRequestMessage};
+use crate::hmi::screens::settings_menu::monitoring_options::MonitoringTargetPeriodOptions;
+use crate::hmi::screens::{draw_message_screen, UiDrawer, UiInput, UiInputHandler};
+use crate::storage::settings::accessor::FlashSettingsAccessor;
+use crate::storage::settings::{SettingValue, SettingsAccessor, SettingsAccessorId};
+use defmt::{error, Debug2Format};
+use embedded_graphics::mono_font::ascii::{FONT_6X10, FONT_6X13_BOLD, FONT_8X13};
+use embedded_graphics::mono_font::MonoTextStyleBuilder;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::{DrawTarget, Point};
+use embedded_graphics::text::renderer::TextRenderer;
+use embedded_graphics::text::{Alignment, Baseline, Text, TextStyleBuilder};
+use embedded_graphics::Drawable;
+
+pub struct FirstRunScreen {
+    step: FirstRunStep,
+    target_period: MonitoringTargetPeriodOptions,
+}
+
+impl FirstRunScreen {
+    pub fn new(step: FirstRunStep) -> Self {
+        Self {
+            step,
+            target_period: MonitoringTargetPeriodOptions::Daily,
+        }
+    }
+
+    fn toggle_target_period(&mut self) {
+        self.target_period = match self.target_period {
+            MonitoringTargetPeriodOptions::Daily => MonitoringTargetPeriodOptions::Hourly,
+            MonitoringTargetPeriodOptions::Hourly => MonitoringTargetPeriodOptions::Daily,
+        };
+    }
+
+    fn draw_target_type<D>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let label_char_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X13_BOLD)
+            .text_color(BinaryColor::On)
+            .build();
+        let active_element_style = MonoTextStyleBuilder::new()
+            .font(&FONT_8X13)
+            .text_color(BinaryColor::Off)
+            .background_color(BinaryColor::On)
+            .build();
+        let text_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X10)
+            .text_color(BinaryColor::On)
+            .build();
+        let centred_style = TextStyleBuilder::new()
+            .alignment(Alignment::Center)
+            .baseline(Baseline::Top)
+            .build();
+
+        let centre_x = (display.bounding_box().size.width / 2) as i32;
+        Text::with_text_style(
+            "Target Type",
+            Point::new(centre_x, 0),
+            label_char_style,
+            centred_style,
+        )
+        .draw(display)?;
+
+        let mut next_point = Point::new(centre_x, display.bounding_box().center().y);
+        next_point.y -= active_element_style.line_height() as i32;
+        for (index, option) in MonitoringTargetPeriodOptions::option_strings()
+            .iter()
+            .enumerate()
+        {
+            let option_style = if u8::from(self.target_period) as usize == index {
+                active_element_style
+            } else {
+                text_style
+            };
+            Text::with_text_style(option, next_point, option_style, centred_style).draw(display)?;
+            next_point.y += active_element_style.line_height() as i32;
+        }
+
+        Text::with_text_style(
+            "Press to select",
+            Point::new(centre_x, display.bounding_box().size.height as i32),
+            text_style,
+            TextStyleBuilder::new()
+                .alignment(Alignment::Center)
+                .baseline(Baseline::Bottom)
+                .build(),
+        )
+        .draw(display)?;
+        Ok(())
+    }
+}
+
+impl UiInputHandler for FirstRunScreen {
+    async fn ui_input_handler(
+        &mut self,
+        input: UiInput,
+        ui_action_publisher: &UiActionChannelPublisher<'_>,
+    ) {
+        match input {
+            UiInput::EncoderClockwise | UiInput::EncoderCounterClockwise => {
+                if self.step == FirstRunStep::TargetType {
+                    self.toggle_target_period();
+                }
+            }
+            UiInput::ButtonPress => match self.step {
+                FirstRunStep::Welcome => {
+                    ui_action_publisher.publish_immediate(UiRequestMessage::ChangeState(
+                        ApplicationState::SetSystemDateTime,
+                    ));
+                }
+                FirstRunStep::TargetType => {
+                    let settings_accessor = FlashSettingsAccessor::new();
+                    settings_accessor
+                        .save_setting(
+                            SettingsAccessorId::MonitoringTargetType,
+                            SettingValue::SmallUInt(self.target_period.into()),
+                        )
+                        .await
+                        .unwrap_or_else(|e| {
+                            error!("Failed to save setting value - {}", Debug2Format(&e))
+                        });
+                    ui_action_publisher.publish_immediate(UiRequestMessage::ChangeState(
+                        ApplicationState::FirstRunSetup(FirstRunStep::TargetValue(
+                            self.target_period,
+                        )),
+                    ));
+                }
+                FirstRunStep::TargetValue(_) => {}
+                FirstRunStep::Complete => {
+                    ui_action_publisher.publish_immediate(UiRequestMessage::ChangeState(
+                        ApplicationState::Monitoring,
+                    ));
+                }
+            },
+            _ => {}
+        }
+    }
+}
+
+impl UiDrawer for FirstRunScreen {
+    fn draw<D>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        match self.step {
+            FirstRunStep::Welcome => draw_message_screen(
+                display,
+                "Welcome! Let's set up your coaster. Press to start.",
+            ),
+            FirstRunStep::TargetType => self.draw_target_type(display),
+            // the target value is entered on the number entry screen
+            FirstRunStep::TargetValue(_) => Ok(()),
+            FirstRunStep::Complete => {
+                draw_message_screen(display, "Setup complete. Press to start monitoring.")
+            }
+        }
+    }
+}
@@ -15,7 +15,10 @@
 pub mod about;
 pub mod calibration;
 pub mod confirmation;
+pub mod error_log;
+pub mod first_run;
 pub mod heap_status;
+pub mod nvm_status;
 pub mod set_date_time;
 pub mod set_number;
 pub mod set_time;
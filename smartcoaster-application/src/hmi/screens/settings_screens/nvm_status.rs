@@ -0,0 +1,196 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::application::application_state::ApplicationState;
+use crate::application::messaging::ApplicationData;
+use crate::hmi::messaging::{UiActionChannelPublisher, UiRequestMessage};
+use crate::hmi::screens::UiInput;
+use crate::hmi::screens::UiInputHandler;
+use crate::hmi::screens::{draw_message_screen, UiDrawer};
+use crate::storage::status::{NvmPartition, PartitionStatus};
+use chrono::{Datelike, NaiveDateTime, Timelike};
+use core::fmt::Write;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::Point;
+use embedded_graphics::mono_font::ascii::FONT_6X10;
+use embedded_graphics::mono_font::MonoTextStyleBuilder;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::text::{Baseline, Text};
+use embedded_graphics::Drawable;
+use heapless::{String, Vec};
+
+/// Shows the usage of one NVM partition at a time, then how often the pages of each have been
+/// erased. The encoder moves between pages.
+pub struct NvmStatusScreen {
+    statuses: Vec<PartitionStatus, { NvmPartition::COUNT }>,
+    selected: usize,
+}
+
+impl NvmStatusScreen {
+    pub fn new() -> Self {
+        Self {
+            statuses: Vec::new(),
+            selected: 0,
+        }
+    }
+
+    /// Partitions, plus the wear page once erase counts have been read.
+    fn page_count(&self) -> usize {
+        let has_wear = self.statuses.iter().any(|status| status.wear.is_some());
+        self.statuses.len() + usize::from(has_wear)
+    }
+
+    fn process_app_data(&mut self, data: ApplicationData) {
+        match data {
+            ApplicationData::NvmPartitionStatus(status) => {
+                let _ = self.statuses.push(status);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn write_timestamp(line_string: &mut String<40>, label: &str, timestamp: NaiveDateTime) {
+    write!(
+        line_string,
+        "{} {:02}-{:02}-{:02} {:02}:{:02}",
+        label,
+        timestamp.year() % 100,
+        timestamp.month(),
+        timestamp.day(),
+        timestamp.hour(),
+        timestamp.minute()
+    )
+    .unwrap();
+}
+
+impl UiInputHandler for NvmStatusScreen {
+    async fn ui_input_handler(
+        &mut self,
+        input: UiInput,
+        ui_channel_publisher: &UiActionChannelPublisher<'_>,
+    ) {
+        match input {
+            UiInput::EncoderClockwise => {
+                if self.selected + 1 < self.page_count() {
+                    self.selected += 1;
+                }
+            }
+            UiInput::EncoderCounterClockwise => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            UiInput::ButtonPress => ui_channel_publisher
+                .publish_immediate(UiRequestMessage::ChangeState(ApplicationState::Settings)),
+            UiInput::ApplicationData(data) => {
+                self.process_app_data(data);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl UiDrawer for NvmStatusScreen {
+    fn draw<D>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        if self.statuses.is_empty() {
+            return draw_message_screen(display, "Reading storage...");
+        }
+
+        let text_style = MonoTextStyleBuilder::new()
+            .font(&FONT_6X10)
+            .text_color(BinaryColor::On)
+            .build();
+        let line_height = 12;
+        let mut line_string = String::<40>::new();
+        let mut y = 0;
+        let mut draw_line = |line: &str, display: &mut D| {
+            let result = Text::with_baseline(line, Point::new(0, y), text_style, Baseline::Top)
+                .draw(display);
+            y += line_height;
+            result.map(|_| ())
+        };
+
+        let Some(status) = self.statuses.get(self.selected) else {
+            write!(
+                &mut line_string,
+                "Page erases {}/{}",
+                self.selected + 1,
+                self.page_count()
+            )
+            .unwrap();
+            draw_line(&line_string, display)?;
+            for status in &self.statuses {
+                if let Some(wear) = status.wear {
+                    line_string.clear();
+                    write!(
+                        &mut line_string,
+                        "{} max {}",
+                        status.partition.name(),
+                        wear.max_page_erases
+                    )
+                    .unwrap();
+                    draw_line(&line_string, display)?;
+                }
+            }
+            return Ok(());
+        };
+
+        write!(
+            &mut line_string,
+            "{} {}/{}",
+            status.partition.name(),
+            self.selected + 1,
+            self.page_count()
+        )
+        .unwrap();
+        draw_line(&line_string, display)?;
+
+        line_string.clear();
+        write!(&mut line_string, "Used {}/{}", status.used, status.capacity).unwrap();
+        draw_line(&line_string, display)?;
+
+        line_string.clear();
+        if let Some(live_settings) = status.live_settings {
+            write!(
+                &mut line_string,
+                "{} set, {} items",
+                live_settings, status.record_count
+            )
+            .unwrap();
+            return draw_line(&line_string, display);
+        }
+        match status.days_until_wrap() {
+            Some(days) => write!(
+                &mut line_string,
+                "{} recs, wrap ~{}d",
+                status.record_count, days
+            ),
+            None => write!(&mut line_string, "{} records", status.record_count),
+        }
+        .unwrap();
+        draw_line(&line_string, display)?;
+
+        if let (Some(oldest), Some(newest)) = (status.oldest, status.newest) {
+            line_string.clear();
+            write_timestamp(&mut line_string, "Old", oldest);
+            draw_line(&line_string, display)?;
+            line_string.clear();
+            write_timestamp(&mut line_string, "New", newest);
+            draw_line(&line_string, display)?;
+        }
+        Ok(())
+    }
+}
@@ -16,7 +16,7 @@ use crate::application::application_state::ApplicationState;
 use crate::hmi::messaging::{UiActionChannelPublisher, UiRequestMessage};
 use crate::hmi::screens::{UiDrawer, UiInput, UiInputHandler};
 use crate::storage::settings::accessor::FlashSettingsAccessor;
-use crate::storage::settings::{SettingValue, SettingsAccessor, SettingsAccessorId};
+use crate::storage::settings::{SettingsAccessor, SettingsAccessorId};
 use core::cmp::{max, min, PartialEq};
 use core::fmt::Write;
 use defmt::error;
@@ -148,7 +148,10 @@ impl UiInputHandler for SetNumberScreen {
                 Element::Save => {
                     let settings_accessor = FlashSettingsAccessor::new();
                     settings_accessor
-                        .save_setting(self.setting_id_to_save, SettingValue::UInt(self.value))
+                        .save_setting(
+                            self.setting_id_to_save,
+                            self.setting_id_to_save.numeric_value(self.value),
+                        )
                         .await
                         .unwrap_or_else(|e| {
                             error!("Failed to save setting value - {}", Debug2Format(&e))
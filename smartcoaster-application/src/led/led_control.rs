@@ -12,17 +12,28 @@
 // You should have received a copy of the GNU General Public License along with
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
+#[cfg(target_os = "none")]
 use crate::storage::settings::{SettingValue, SettingsAccessor, SettingsAccessorId};
+#[cfg(target_os = "none")]
 use defmt::{warn, Debug2Format};
+#[cfg(target_os = "none")]
 use embassy_rp::pio::Instance;
+#[cfg(target_os = "none")]
 use embassy_rp::pio_programs::ws2812::PioWs2812;
+#[cfg(target_os = "none")]
 use embassy_time::Instant;
+#[cfg(target_os = "none")]
 use micromath::F32Ext;
+#[cfg(target_os = "none")]
 use smart_leds::hsv::{hsv2rgb, Hsv};
-use smart_leds::{brightness, gamma, RGB8};
+use smart_leds::RGB8;
+#[cfg(target_os = "none")]
+use smart_leds::{brightness, gamma};
 
+#[cfg(target_os = "none")]
 const DEFAULT_BRIGHTNESS: u8 = 128;
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum LedArrayMode {
     Off,
     RainbowWheel {
@@ -45,6 +56,7 @@ pub enum LedArrayMode {
 }
 
 /// Input a position 0 to 255 to get a color value associated with that position.
+#[cfg(target_os = "none")]
 fn rainbow_wheel(wheel_pos: u8) -> RGB8 {
     hsv2rgb(Hsv {
         hue: wheel_pos,
@@ -55,6 +67,7 @@ fn rainbow_wheel(wheel_pos: u8) -> RGB8 {
 
 /// Input a position in range 0-255 to get the brightness of the colour associated with that
 /// position.
+#[cfg(target_os = "none")]
 fn single_colour_wheel(colour: RGB8, wheel_pos: u8) -> RGB8 {
     let intensity = match wheel_pos < 128 {
         true => 255 - (wheel_pos * 2),
@@ -77,6 +90,7 @@ pub trait LedControl {
     async fn led_update(&mut self);
 }
 
+#[cfg(target_os = "none")]
 pub struct LedController<'a, const LED_COUNT: usize, P: Instance, const S: usize, SA>
 where
     SA: SettingsAccessor,
@@ -94,6 +108,7 @@ where
     settings: SA,
 }
 
+#[cfg(target_os = "none")]
 impl<'a, const LED_COUNT: usize, P, const S: usize, SA> LedControl
     for LedController<'a, LED_COUNT, P, S, SA>
 where
@@ -191,6 +206,7 @@ where
     }
 }
 
+#[cfg(target_os = "none")]
 impl<'a, const LED_COUNT: usize, P, const S: usize, SA> LedController<'a, LED_COUNT, P, S, SA>
 where
     P: Instance,
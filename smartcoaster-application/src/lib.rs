@@ -0,0 +1,34 @@
+#![no_std]
+#![allow(async_fn_in_trait)]
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Application logic for the smart coaster. The firmware binary wires these modules up to the
+//! RP2040 peripherals. Modules that touch the RP2040 directly are only built for the device
+//! (`target_os = "none"`) so the rest of the application, including the HMI screens, can be built
+//! and exercised on a host.
+
+pub mod application;
+pub mod drink_monitor;
+pub mod error_log;
+pub mod hmi;
+pub mod led;
+pub mod rtc;
+pub mod storage;
+#[cfg(target_os = "none")]
+pub mod usb;
+pub mod weight;
+
+// Flash size and NVM partition ranges, generated from the partition table in smartcoaster-partitions
+include!(concat!(env!("OUT_DIR"), "/partitions.rs"));
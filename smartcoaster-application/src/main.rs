@@ -24,18 +24,14 @@ compile_error!("Check if https://github.com/embassy-rs/embassy/issues/1634 has b
 #[cfg(not(any(feature = "pcb_rev1")))]
 compile_error!("no board configured - use feature \"pcb_rev1\"");
 
-mod application;
-mod drink_monitor;
-mod hmi;
-mod led;
-mod rtc;
-pub mod storage;
-mod weight;
+use smartcoaster_application::{
+    FLASH_SIZE, NVM_PARTITION_RANGE, SETTINGS_NVM_FLASH_OFFSET_RANGE, application, drink_monitor,
+    error_log, hmi, led, rtc, storage, usb, weight,
+};
 
 use core::cell::RefCell;
-use core::ops::Range;
 use embassy_executor::{Executor, InterruptExecutor, SendSpawner, Spawner};
-use embassy_time::{Duration, Timer};
+use embassy_time::Duration;
 #[allow(unused_imports)]
 use {defmt_rtt as _, panic_probe as _};
 
@@ -57,7 +53,7 @@ use embassy_rp::multicore::{Stack, spawn_core1};
 use embassy_rp::peripherals::{FLASH, I2C0, I2C1, PIO0};
 use embassy_rp::pio::Pio;
 use embassy_rp::pio_programs::ws2812::{PioWs2812, PioWs2812Program};
-use embassy_rp::watchdog::Watchdog;
+use embassy_rp::watchdog::{ResetReason, Watchdog};
 use embassy_rp::{bind_interrupts, flash, interrupt, peripherals, pio};
 use embassy_sync::pubsub::PubSubChannel;
 use hmi::debouncer::Debouncer;
@@ -82,12 +78,17 @@ use crate::drink_monitor::drink_monitoring::DrinkMonitoring;
 use crate::drink_monitor::messaging::{
     DrinkMonitorChannel, DrinkMonitorChannelPublisher, DrinkMonitorChannelSubscriber,
 };
+use crate::error_log::{ErrorCode, ErrorLogData, ErrorModule, log_error};
 use crate::rtc::{RtcControl, SystemRtc};
+use crate::storage::factory_reset;
+use crate::storage::historical::accessor::HistoricalLogAccessor;
+use crate::storage::historical::log_config::Logs;
 use crate::storage::storage_manager::BlockingFlash;
+use crate::usb::host_link::HostLink;
 use core::ptr::addr_of_mut;
 use cortex_m_rt::entry;
 use ds323x::Ds323x;
-use embassy_boot_rp::{AlignedBuffer, BlockingFirmwareUpdater, FirmwareUpdaterConfig};
+use embassy_boot_rp::{AlignedBuffer, BlockingFirmwareUpdater, FirmwareUpdaterConfig, State};
 use embassy_rp::interrupt::{InterruptExt, Priority};
 use embassy_sync::blocking_mutex::Mutex;
 use embassy_sync::blocking_mutex::raw::{CriticalSectionRawMutex, NoopRawMutex};
@@ -107,19 +108,6 @@ static DRINK_MONITOR_CHANNEL: DrinkMonitorChannel = PubSubChannel::new();
 const CORE1_STACK_SIZE: usize = 16 * 1024;
 const HEAP_SIZE: usize = 16 * 1024;
 
-// Ensure this matches memory.x
-const FLASH_SIZE: usize = 16 * 1024 * 1024; // TODO centralise this to make common across bootloader and application
-const _NVM_PAGE_SIZE: usize = 256;
-
-// Application NVM for settings and activity log is at the end of flash
-const SETTINGS_SIZE: usize = 0x2000;
-const ACTIVITY_LOG_SIZE: usize = 0x8000;
-const NVM_PARTITION_SIZE: usize = SETTINGS_SIZE + ACTIVITY_LOG_SIZE;
-const NVM_PARTITION_RANGE: Range<u32> = (FLASH_SIZE - NVM_PARTITION_SIZE) as u32..FLASH_SIZE as u32;
-const ACTIVITY_LOG_NVM_FLASH_OFFSET_RANGE: Range<u32> = 0..ACTIVITY_LOG_SIZE as u32;
-const SETTINGS_NVM_FLASH_OFFSET_RANGE: Range<u32> =
-    ACTIVITY_LOG_SIZE as u32..NVM_PARTITION_SIZE as u32;
-
 #[cfg(feature = "pcb_rev1")]
 const LED_COUNT: usize = 12;
 
@@ -154,6 +142,9 @@ assign_resources! {
         sda_pin: PIN_2,
         scl_pin: PIN_3,
     }
+    usb: UsbResources {
+        usb: USB,
+    }
 }
 
 struct Core0HighPrioResources {
@@ -165,6 +156,7 @@ struct Core0HighPrioResources {
 
 struct Core0LowPrioResources {
     storage: StorageResources,
+    usb: UsbResources,
 }
 
 struct Core1Resources {
@@ -217,13 +209,20 @@ fn main() -> ! {
     };
     let core0_low_prio_resources = Core0LowPrioResources {
         storage: resources.storage,
+        usb: resources.usb,
     };
     let core1_resources = Core1Resources {
         display_i2c: resources.display_i2c,
     };
 
     // Override bootloader watchdog
-    let _watchdog = Watchdog::new(p.WATCHDOG);
+    let watchdog = Watchdog::new(p.WATCHDOG);
+    if watchdog.reset_reason() == Some(ResetReason::TimedOut) {
+        log_error(ErrorLogData::new(
+            ErrorModule::System,
+            ErrorCode::WatchdogReset,
+        ));
+    }
 
     #[cfg(feature = "multicore")]
     {
@@ -253,6 +252,8 @@ fn main() -> ! {
 fn core0_low_prio_main(spawner: Spawner, resources: Core0LowPrioResources) {
     info!("Spawning storage task");
     spawner.must_spawn(storage_task(resources.storage));
+    info!("Spawning USB task");
+    spawner.must_spawn(usb_task(resources.usb, spawner));
 }
 
 fn core0_high_prio_main(spawner: SendSpawner, resources: Core0HighPrioResources) {
@@ -340,6 +341,17 @@ async fn storage_task(storage_resources: StorageResources) {
     info!("Marking boot ok");
     let mut aligned = AlignedBuffer([0; 1]);
     let mut updater = BlockingFirmwareUpdater::new(config, &mut aligned.0);
+    match updater.get_state() {
+        Ok(State::Swap) => log_error(ErrorLogData::new(
+            ErrorModule::System,
+            ErrorCode::FirmwareUpdated,
+        )),
+        Ok(State::Revert) => log_error(ErrorLogData::new(
+            ErrorModule::System,
+            ErrorCode::FirmwareRolledBack,
+        )),
+        _ => {}
+    }
     updater
         .mark_booted()
         .expect("Unable to mark boot successful");
@@ -358,13 +370,43 @@ async fn storage_task(storage_resources: StorageResources) {
 
     storage::settings::accessor::initialise_settings().await;
 
+    let mut error_log = HistoricalLogAccessor::new(Logs::ErrorLog);
     loop {
-        Timer::after(Duration::from_millis(200)).await;
+        // anything queued before this point, e.g. errors logged during boot, has signalled already
+        let save_due = storage::settings::accessor::next_settings_save_due()
+            .await
+            .into_iter()
+            .chain(storage::storage_manager::next_erase_counts_save_due().await)
+            .min();
+        storage::signal::wait_for_storage_work(save_due).await;
+        if let Some(options) = factory_reset::take_factory_reset_request() {
+            if factory_reset::factory_reset(options).await.is_ok() {
+                info!("Rebooting after factory reset");
+                cortex_m::peripheral::SCB::sys_reset();
+            }
+            log_error(ErrorLogData::new(
+                ErrorModule::Storage,
+                ErrorCode::FactoryResetFailed,
+            ));
+        }
         storage::settings::accessor::process_save_queue().await;
+        error_log::process_error_log_queue(&mut error_log).await;
         storage::historical::manager::process_log_queues().await;
+        storage::storage_manager::save_erase_counts().await;
+        if storage::restart::take_restart_request() {
+            storage::restart::prepare_for_restart().await;
+            info!("Restarting");
+            cortex_m::peripheral::SCB::sys_reset();
+        }
     }
 }
 
+#[embassy_executor::task]
+async fn usb_task(usb_resources: UsbResources, spawner: Spawner) {
+    let mut host_link = HostLink::new();
+    host_link.start(usb_resources.usb, spawner).await;
+}
+
 #[embassy_executor::task]
 async fn hmi_input_task(
     hmi_input_pins: HmiInputPins,
@@ -0,0 +1,57 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Stands in for the DS3231 when the application is built for a host. Time runs from a given start
+//! point at the rate of the embassy time driver.
+
+use crate::rtc::signal::{RTC_SET_TIME, RTC_TIME_UPDATE};
+use chrono::{NaiveDateTime, TimeDelta};
+use defmt::{debug, Debug2Format};
+use embassy_time::{Duration, Instant, Ticker};
+
+pub struct HostRtc {
+    set_dt: NaiveDateTime,
+    set_at: Instant,
+}
+
+impl HostRtc {
+    /// Starts the clock at `start`. The time is published straight away so anything that reads the
+    /// time before the first tick gets `start` rather than the default time.
+    pub fn new(start: NaiveDateTime) -> Self {
+        RTC_TIME_UPDATE.sender().send(start);
+        Self {
+            set_dt: start,
+            set_at: Instant::now(),
+        }
+    }
+
+    /// Current time, in whole seconds like the DS3231.
+    pub fn now(&self) -> NaiveDateTime {
+        self.set_dt + TimeDelta::seconds(self.set_at.elapsed().as_secs() as i64)
+    }
+
+    pub async fn run(&mut self) {
+        let sender = RTC_TIME_UPDATE.sender();
+        let mut one_second_ticker = Ticker::every(Duration::from_secs(1));
+        loop {
+            one_second_ticker.next().await;
+            if let Some(new_time) = RTC_SET_TIME.try_take() {
+                self.set_dt = new_time;
+                self.set_at = Instant::now();
+                debug!("Time set to {}", Debug2Format(&new_time));
+            }
+            sender.send(self.now());
+        }
+    }
+}
@@ -0,0 +1,311 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Read-only FAT16 volume laid out on the fly, so files can be presented over USB mass storage
+//! without holding an image of the volume. Each sector is built when it is read: the boot sector,
+//! FATs and root directory from the list of files, and file data by the caller.
+//!
+//! Files are stored one after another in contiguous clusters, in the order given, and all sit in
+//! the root directory with a long name entry ahead of their 8.3 entry.
+
+use chrono::{Datelike, NaiveDateTime, Timelike};
+
+pub const SECTOR_SIZE: usize = 512;
+/// 4MB, the smallest size with enough clusters to be FAT16 at one sector per cluster. Hosts tell
+/// FAT12 and FAT16 apart by the cluster count alone.
+pub const SECTOR_COUNT: u32 = 8192;
+
+const RESERVED_SECTORS: u32 = 1;
+const FAT_COUNT: u32 = 2;
+const FAT_SECTORS: u32 = 32;
+const ROOT_ENTRY_COUNT: usize = 16;
+const DIR_ENTRY_SIZE: usize = 32;
+const ROOT_DIR_SECTORS: u32 = (ROOT_ENTRY_COUNT * DIR_ENTRY_SIZE / SECTOR_SIZE) as u32;
+const FIRST_FAT_SECTOR: u32 = RESERVED_SECTORS;
+const ROOT_DIR_SECTOR: u32 = FIRST_FAT_SECTOR + FAT_COUNT * FAT_SECTORS;
+const FIRST_DATA_SECTOR: u32 = ROOT_DIR_SECTOR + ROOT_DIR_SECTORS;
+/// Clusters are numbered from 2.
+const FIRST_CLUSTER: u32 = 2;
+const CLUSTER_SIZE: u32 = SECTOR_SIZE as u32;
+const CLUSTER_COUNT: u32 = SECTOR_COUNT - FIRST_DATA_SECTOR;
+const FAT_ENTRIES_PER_SECTOR: u32 = (SECTOR_SIZE / 2) as u32;
+/// Largest total size of the files that fits in the volume.
+pub const MAX_DATA_SIZE: u32 = CLUSTER_COUNT * CLUSTER_SIZE;
+
+const MEDIA_DESCRIPTOR: u8 = 0xF8;
+const END_OF_CHAIN: u16 = 0xFFFF;
+const VOLUME_LABEL: &[u8; 11] = b"COASTER    ";
+const VOLUME_ID: u32 = 0x5343_0001;
+
+const ATTR_READ_ONLY: u8 = 0x01;
+const ATTR_VOLUME_ID: u8 = 0x08;
+const ATTR_ARCHIVE: u8 = 0x20;
+const ATTR_LONG_NAME: u8 = 0x0F;
+/// UTF-16 characters held by each long name entry.
+const LONG_NAME_CHARS: usize = 13;
+
+/// A file in the volume. Its data is supplied by the caller when its sectors are read.
+#[derive(Clone, Copy, Debug)]
+pub struct FatFile {
+    /// Long name, ASCII. The 8.3 name is made from it.
+    pub name: &'static str,
+    pub size: u32,
+}
+
+/// What a sector read from the volume holds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SectorContent {
+    /// Boot sector, FAT or directory, already filled in.
+    Metadata,
+    /// Data of file `file` starting at byte `offset`, to be filled in by the caller.
+    FileData { file: usize, offset: u32 },
+    /// Past the end of the files or the volume, left zeroed.
+    Unused,
+}
+
+pub struct FatVolume<const FILES: usize> {
+    files: [FatFile; FILES],
+    modified: NaiveDateTime,
+}
+
+impl<const FILES: usize> FatVolume<FILES> {
+    /// Lays out `files`, which are all shown as modified at `modified`. Files that do not fit in
+    /// the volume are cut short.
+    pub fn new(mut files: [FatFile; FILES], modified: NaiveDateTime) -> Self {
+        let mut space = MAX_DATA_SIZE;
+        for file in files.iter_mut() {
+            file.size = file.size.min(space);
+            space -= Self::cluster_count(file.size) * CLUSTER_SIZE;
+        }
+        Self { files, modified }
+    }
+
+    pub fn files(&self) -> &[FatFile; FILES] {
+        &self.files
+    }
+
+    /// Fills `buf` with sector `lba` of the volume. File data is left for the caller to fill in,
+    /// with the file and offset to read given back.
+    pub fn read_sector(&self, lba: u32, buf: &mut [u8; SECTOR_SIZE]) -> SectorContent {
+        buf.fill(0);
+        match lba {
+            0 => {
+                self.boot_sector(buf);
+                SectorContent::Metadata
+            }
+            FIRST_FAT_SECTOR..ROOT_DIR_SECTOR => {
+                self.fat_sector((lba - FIRST_FAT_SECTOR) % FAT_SECTORS, buf);
+                SectorContent::Metadata
+            }
+            ROOT_DIR_SECTOR..FIRST_DATA_SECTOR => {
+                self.root_dir_sector(buf);
+                SectorContent::Metadata
+            }
+            FIRST_DATA_SECTOR..SECTOR_COUNT => {
+                let cluster = FIRST_CLUSTER + lba - FIRST_DATA_SECTOR;
+                match self.file_at_cluster(cluster) {
+                    Some((file, first_cluster)) => SectorContent::FileData {
+                        file,
+                        offset: (cluster - first_cluster) * CLUSTER_SIZE,
+                    },
+                    None => SectorContent::Unused,
+                }
+            }
+            _ => SectorContent::Unused,
+        }
+    }
+
+    fn cluster_count(size: u32) -> u32 {
+        size.div_ceil(CLUSTER_SIZE)
+    }
+
+    /// First cluster of each file, or `None` for empty files, which have no clusters.
+    fn first_clusters(&self) -> impl Iterator<Item = Option<u32>> + '_ {
+        self.files.iter().scan(FIRST_CLUSTER, |next_cluster, file| {
+            let clusters = Self::cluster_count(file.size);
+            let first_cluster = (clusters > 0).then_some(*next_cluster);
+            *next_cluster += clusters;
+            Some(first_cluster)
+        })
+    }
+
+    /// The file holding `cluster`, with the first cluster of that file.
+    fn file_at_cluster(&self, cluster: u32) -> Option<(usize, u32)> {
+        self.first_clusters().zip(&self.files).enumerate().find_map(
+            |(index, (first_cluster, file))| {
+                let first_cluster = first_cluster?;
+                (first_cluster..first_cluster + Self::cluster_count(file.size))
+                    .contains(&cluster)
+                    .then_some((index, first_cluster))
+            },
+        )
+    }
+
+    fn boot_sector(&self, buf: &mut [u8; SECTOR_SIZE]) {
+        // jump over the BPB to a boot loop, not that anything should boot from this
+        buf[0..3].copy_from_slice(&[0xEB, 0x3C, 0x90]);
+        buf[3..11].copy_from_slice(b"MSWIN4.1");
+        buf[11..13].copy_from_slice(&(SECTOR_SIZE as u16).to_le_bytes());
+        buf[13] = (CLUSTER_SIZE / SECTOR_SIZE as u32) as u8;
+        buf[14..16].copy_from_slice(&(RESERVED_SECTORS as u16).to_le_bytes());
+        buf[16] = FAT_COUNT as u8;
+        buf[17..19].copy_from_slice(&(ROOT_ENTRY_COUNT as u16).to_le_bytes());
+        buf[19..21].copy_from_slice(&(SECTOR_COUNT as u16).to_le_bytes());
+        buf[21] = MEDIA_DESCRIPTOR;
+        buf[22..24].copy_from_slice(&(FAT_SECTORS as u16).to_le_bytes());
+        // sectors per track and heads, unused but some hosts want them set
+        buf[24..26].copy_from_slice(&32u16.to_le_bytes());
+        buf[26..28].copy_from_slice(&2u16.to_le_bytes());
+        buf[36] = 0x80;
+        buf[38] = 0x29;
+        buf[39..43].copy_from_slice(&VOLUME_ID.to_le_bytes());
+        buf[43..54].copy_from_slice(VOLUME_LABEL);
+        buf[54..62].copy_from_slice(b"FAT16   ");
+        buf[62] = 0xF4; // hlt
+        buf[63..65].copy_from_slice(&[0xEB, 0xFD]); // jmp to hlt
+        buf[510..512].copy_from_slice(&[0x55, 0xAA]);
+    }
+
+    fn fat_sector(&self, fat_sector: u32, buf: &mut [u8; SECTOR_SIZE]) {
+        let first_entry = fat_sector * FAT_ENTRIES_PER_SECTOR;
+        for (entry, bytes) in (first_entry..).zip(buf.chunks_exact_mut(2)) {
+            let value = match entry {
+                0 => u16::from_le_bytes([MEDIA_DESCRIPTOR, 0xFF]),
+                1 => END_OF_CHAIN,
+                cluster => match self.file_at_cluster(cluster) {
+                    Some((file, first_cluster)) => {
+                        let last_cluster =
+                            first_cluster + Self::cluster_count(self.files[file].size) - 1;
+                        if cluster == last_cluster {
+                            END_OF_CHAIN
+                        } else {
+                            (cluster + 1) as u16
+                        }
+                    }
+                    None => 0,
+                },
+            };
+            bytes.copy_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    /// Files whose entries do not fit in the root directory are left out.
+    fn root_dir_sector(&self, buf: &mut [u8; SECTOR_SIZE]) {
+        let mut entries = buf.chunks_exact_mut(DIR_ENTRY_SIZE);
+        if let Some(entry) = entries.next() {
+            entry[0..11].copy_from_slice(VOLUME_LABEL);
+            entry[11] = ATTR_VOLUME_ID;
+            self.write_timestamp(entry);
+        }
+
+        for (file, first_cluster) in self.files.iter().zip(self.first_clusters()) {
+            let short_name = short_name(file.name);
+            let long_entry_count = file.name.len().div_ceil(LONG_NAME_CHARS);
+            if entries.len() < long_entry_count + 1 {
+                break;
+            }
+
+            // long name entries come last part first
+            for part in (0..long_entry_count).rev() {
+                let entry = entries.next().unwrap();
+                let mut order = part as u8 + 1;
+                if part == long_entry_count - 1 {
+                    order |= 0x40;
+                }
+                write_long_name_entry(
+                    entry,
+                    order,
+                    &file.name.as_bytes()[part * LONG_NAME_CHARS..],
+                    &short_name,
+                );
+            }
+
+            let entry = entries.next().unwrap();
+            entry[0..11].copy_from_slice(&short_name);
+            entry[11] = ATTR_READ_ONLY | ATTR_ARCHIVE;
+            self.write_timestamp(entry);
+            let first_cluster = first_cluster.unwrap_or(0) as u16;
+            entry[26..28].copy_from_slice(&first_cluster.to_le_bytes());
+            entry[28..32].copy_from_slice(&file.size.to_le_bytes());
+        }
+    }
+
+    /// Sets the created, accessed and modified times of a directory entry.
+    fn write_timestamp(&self, entry: &mut [u8]) {
+        let (date, time) = fat_date_time(self.modified);
+        entry[14..16].copy_from_slice(&time.to_le_bytes());
+        entry[16..18].copy_from_slice(&date.to_le_bytes());
+        entry[18..20].copy_from_slice(&date.to_le_bytes());
+        entry[22..24].copy_from_slice(&time.to_le_bytes());
+        entry[24..26].copy_from_slice(&date.to_le_bytes());
+    }
+}
+
+/// 8.3 name for `name`, in upper case with the base name cut to fit and marked with `~1`. The
+/// long name entry holds the real name.
+fn short_name(name: &str) -> [u8; 11] {
+    let (base, extension) = name.rsplit_once('.').unwrap_or((name, ""));
+    let mut short_name = [b' '; 11];
+    let base_len = if base.len() > 8 || extension.len() > 3 {
+        short_name[6..8].copy_from_slice(b"~1");
+        6
+    } else {
+        8
+    };
+    for (short, c) in short_name[..base_len].iter_mut().zip(base.bytes()) {
+        *short = c.to_ascii_uppercase();
+    }
+    for (short, c) in short_name[8..].iter_mut().zip(extension.bytes()) {
+        *short = c.to_ascii_uppercase();
+    }
+    short_name
+}
+
+/// Checksum of the 8.3 name a long name entry belongs to.
+fn short_name_checksum(short_name: &[u8; 11]) -> u8 {
+    short_name
+        .iter()
+        .fold(0u8, |sum, &c| sum.rotate_right(1).wrapping_add(c))
+}
+
+/// Writes a long name entry holding up to 13 characters from the start of `name`.
+fn write_long_name_entry(entry: &mut [u8], order: u8, name: &[u8], short_name: &[u8; 11]) {
+    // the name is ended with a null if it does not fill the entry, then padded
+    let character = |index: usize| -> u16 {
+        match index.cmp(&name.len()) {
+            core::cmp::Ordering::Less => name[index] as u16,
+            core::cmp::Ordering::Equal => 0,
+            core::cmp::Ordering::Greater => 0xFFFF,
+        }
+    };
+    const CHARACTER_OFFSETS: [usize; LONG_NAME_CHARS] =
+        [1, 3, 5, 7, 9, 14, 16, 18, 20, 22, 24, 28, 30];
+
+    entry[0] = order;
+    for (index, offset) in CHARACTER_OFFSETS.into_iter().enumerate() {
+        entry[offset..offset + 2].copy_from_slice(&character(index).to_le_bytes());
+    }
+    entry[11] = ATTR_LONG_NAME;
+    entry[13] = short_name_checksum(short_name);
+}
+
+/// FAT date and time of `timestamp`. FAT dates start in 1980, earlier times are shown as then.
+fn fat_date_time(timestamp: NaiveDateTime) -> (u16, u16) {
+    let year = timestamp.year().clamp(1980, 2107) as u16;
+    let date = ((year - 1980) << 9) | ((timestamp.month() as u16) << 5) | timestamp.day() as u16;
+    let time = ((timestamp.hour() as u16) << 11)
+        | ((timestamp.minute() as u16) << 5)
+        | (timestamp.second() as u16 / 2);
+    (date, time)
+}
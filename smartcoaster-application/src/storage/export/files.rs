@@ -0,0 +1,160 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Contents of the exported files: the consumption and error logs as CSV, one row per log entry,
+//! and the settings as JSON.
+
+use crate::drink_monitor::log_data::DrinkMonitorLogData;
+use crate::error_log::{ErrorLogData, ERROR_VALUE_COUNT};
+use crate::storage::historical::accessor::RetrievedLogEntry;
+use crate::storage::historical::LogEncodeDecode;
+use crate::storage::settings::settings_store::StoredSettings;
+use crate::storage::settings::{SettingValue, StorageError};
+use crate::storage::storage_manager::StorageManager;
+use chrono::{Datelike, NaiveDateTime, Timelike};
+use core::fmt::Write;
+use heapless::String;
+use strum::IntoEnumIterator;
+
+/// Longest CSV row, with room to spare.
+pub const ROW_CAPACITY: usize = 128;
+/// Holds every setting with the longest name and value.
+pub const SETTINGS_JSON_CAPACITY: usize = 2048;
+
+pub type Row = String<ROW_CAPACITY>;
+
+pub const HISTORY_CSV_HEADER: &str = "time,beverage,consumed_ml,day_total_ml,daily_target_ml\r\n";
+
+/// Error values are numbered from 1 in the header.
+pub fn errors_csv_header() -> Row {
+    let mut header = Row::new();
+    let _ = header.push_str("time,module,error");
+    for value in 1..=ERROR_VALUE_COUNT {
+        let _ = write!(header, ",value_{}", value);
+    }
+    let _ = header.push_str("\r\n");
+    header
+}
+
+/// Formats a consumption log entry, as stored with its timestamp, into `row`. Entries that cannot
+/// be read give an empty row.
+pub fn history_row(entry: &[u8], row: &mut Row) {
+    row.clear();
+    let Ok(entry) = RetrievedLogEntry::from_buffer(entry) else {
+        return;
+    };
+    let Ok(data) = DrinkMonitorLogData::from_bytes(&entry.data) else {
+        return;
+    };
+
+    write_timestamp(row, entry.timestamp);
+    let _ = write!(
+        row,
+        ",{},{:.0},{:.0},",
+        data.get_beverage().name(),
+        data.get_last_consumption(),
+        data.get_total_consumption()
+    );
+    if let Some(target) = data.get_daily_consumption_target() {
+        let _ = write!(row, "{}", target);
+    }
+    let _ = row.push_str("\r\n");
+}
+
+/// Formats an error log entry, as stored with its timestamp, into `row`. Entries that cannot be
+/// read give an empty row.
+pub fn error_row(entry: &[u8], row: &mut Row) {
+    row.clear();
+    let Ok(entry) = RetrievedLogEntry::from_buffer(entry) else {
+        return;
+    };
+    let Ok(data) = ErrorLogData::from_bytes(&entry.data) else {
+        return;
+    };
+
+    write_timestamp(row, entry.timestamp);
+    let _ = write!(row, ",{},{}", data.module.name(), data.code.description());
+    for value in data.values {
+        let _ = row.push(',');
+        if let Some(value) = value {
+            let _ = write!(row, "{}", value);
+        }
+    }
+    let _ = row.push_str("\r\n");
+}
+
+/// Spreadsheets read this format as a date and time.
+fn write_timestamp(row: &mut Row, timestamp: NaiveDateTime) {
+    let _ = write!(
+        row,
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        timestamp.year(),
+        timestamp.month(),
+        timestamp.day(),
+        timestamp.hour(),
+        timestamp.minute(),
+        timestamp.second()
+    );
+}
+
+/// Reads the settings from `storage` into a JSON object keyed by setting name. Settings that have
+/// never been saved are left out.
+pub async fn settings_json(
+    storage: &mut impl StorageManager,
+) -> Result<String<SETTINGS_JSON_CAPACITY>, StorageError> {
+    let mut json = String::new();
+    let _ = json.push('{');
+    let mut first = true;
+    for setting in StoredSettings::iter() {
+        let Some(value) = storage
+            .read_key_value_pair::<SettingValue>(setting.key())
+            .await?
+        else {
+            continue;
+        };
+        let name: &'static str = (&setting).into();
+        let _ = write!(json, "{}\r\n  \"{}\": ", if first { "" } else { "," }, name);
+        write_json_value(&mut json, &value);
+        first = false;
+    }
+    let _ = json.push_str("\r\n}\r\n");
+    Ok(json)
+}
+
+fn write_json_value(json: &mut String<SETTINGS_JSON_CAPACITY>, value: &SettingValue) {
+    let _ = match value {
+        SettingValue::Float(v) if v.is_finite() => write!(json, "{}", v),
+        SettingValue::SmallUInt(v) => write!(json, "{}", v),
+        SettingValue::UInt(v) => write!(json, "{}", v),
+        SettingValue::Time(v) => write!(
+            json,
+            "\"{:02}:{:02}:{:02}\"",
+            v.hour(),
+            v.minute(),
+            v.second()
+        ),
+        SettingValue::DateTime(v) => write!(
+            json,
+            "\"{:04}-{:02}-{:02} {:02}:{:02}:{:02}\"",
+            v.year(),
+            v.month(),
+            v.day(),
+            v.hour(),
+            v.minute(),
+            v.second()
+        ),
+        // JSON has no infinity or NaN
+        SettingValue::Float(_) | SettingValue::Default => write!(json, "null"),
+    };
+}
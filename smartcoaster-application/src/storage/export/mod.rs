@@ -0,0 +1,305 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Coaster data as files on a read-only FAT volume, for reading on a computer without any special
+//! software. The volume holds `history.csv` from the consumption log, `errors.csv` from the error
+//! log and `settings.json`, and is built a few sectors at a time as it is read, so only the
+//! settings and those sectors are held in RAM.
+//!
+//! File sizes are fixed when the volume is made, as hosts cache them. Log entries written since
+//! are left out. If a log wraps while the volume is in use its oldest rows are lost, and the end
+//! of the file is padded with blank lines.
+
+pub mod fat;
+pub mod files;
+
+use crate::storage::export::fat::{FatFile, FatVolume, SectorContent, SECTOR_SIZE};
+use crate::storage::export::files::{Row, SETTINGS_JSON_CAPACITY};
+use crate::storage::historical::index::{visit_log_items_from, LogIndex};
+use crate::storage::historical::log_config::Logs;
+use crate::storage::settings::StorageError;
+use crate::storage::storage_manager::{StorageManager, StoredLogConfig};
+use chrono::NaiveDateTime;
+use core::ops::{ControlFlow, Range};
+use heapless::String;
+
+pub const HISTORY_FILE_NAME: &str = "history.csv";
+pub const SETTINGS_FILE_NAME: &str = "settings.json";
+pub const ERRORS_FILE_NAME: &str = "errors.csv";
+
+const FILE_COUNT: usize = 3;
+const HISTORY_FILE: usize = 0;
+const SETTINGS_FILE: usize = 1;
+const ERRORS_FILE: usize = 2;
+
+/// Bytes of a log file formatted on each read. A read goes through the page holding its first row
+/// from the start of the page, so formatting several sectors at once cuts the entries read more
+/// than once by a host reading through the file.
+const READ_AHEAD_SIZE: usize = 8 * SECTOR_SIZE;
+
+/// A log exported as CSV, with a header row followed by a row for each entry.
+struct CsvLog {
+    config: StoredLogConfig,
+    header: Row,
+    format_row: fn(&[u8], &mut Row),
+    /// Entries in the log when the volume was made.
+    entry_count: usize,
+    /// Pages of the log, so a read can start from the page holding the cursor rather than the
+    /// oldest entry.
+    pages: LogIndex,
+    /// Index and file offset of the last row read. Rows are only found by reading on from the
+    /// first row or from here, so a host reading through the file reads each page of the log once
+    /// or twice rather than the whole log for every few sectors.
+    cursor: (usize, u32),
+}
+
+impl CsvLog {
+    fn new(log: Logs, header: Row, format_row: fn(&[u8], &mut Row)) -> Self {
+        Self {
+            config: log.get_config(),
+            cursor: (0, header.len() as u32),
+            header,
+            format_row,
+            entry_count: 0,
+            pages: LogIndex::new(),
+        }
+    }
+
+    /// Takes the entries now in the log as the contents of the file, giving the file size.
+    async fn snapshot(&mut self, storage: &mut impl StorageManager) -> Result<u32, StorageError> {
+        let mut size = self.header.len() as u32;
+        let mut row = Row::new();
+        let format_row = self.format_row;
+        self.entry_count = storage
+            .for_each_log_item(&self.config, |entry| {
+                format_row(entry, &mut row);
+                size += row.len() as u32;
+            })
+            .await?;
+        self.cursor = (0, self.header.len() as u32);
+        self.pages.invalidate();
+        Ok(size)
+    }
+
+    /// Fills `buf` with the file from `offset`. `buf` must not run past the end of the file.
+    async fn read(
+        &mut self,
+        storage: &mut impl StorageManager,
+        offset: u32,
+        buf: &mut [u8],
+    ) -> Result<(), StorageError> {
+        copy_overlap(self.header.as_bytes(), 0, offset, buf);
+        if offset < self.cursor.1 {
+            self.cursor = (0, self.header.len() as u32);
+        }
+
+        let mut rows = RowPlacer::new(self, offset, buf);
+        if !rows.is_done() {
+            let mut index = self.cursor.0;
+            visit_log_items_from(storage, &self.config, &mut self.pages, index, |entry| {
+                rows.place(index, entry);
+                index += 1;
+                if rows.is_done() {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            })
+            .await?;
+        }
+        self.cursor = rows.cursor;
+
+        // rows lost since the snapshot
+        let (position, end) = (rows.position, rows.end());
+        if position < end {
+            rows.buf[position.saturating_sub(offset) as usize..].fill(b'\n');
+        }
+        Ok(())
+    }
+}
+
+/// Formats the rows of log entries, given in order, into the part of a file held in `buf`.
+struct RowPlacer<'a> {
+    buf: &'a mut [u8],
+    offset: u32,
+    format_row: fn(&[u8], &mut Row),
+    row: Row,
+    /// Index of the first entry to place and of the first entry left out of the file.
+    entries: Range<usize>,
+    /// Index and file offset of the next row placed.
+    next: usize,
+    position: u32,
+    cursor: (usize, u32),
+}
+
+impl<'a> RowPlacer<'a> {
+    /// Places rows from the cursor of `log`.
+    fn new(log: &CsvLog, offset: u32, buf: &'a mut [u8]) -> Self {
+        Self {
+            buf,
+            offset,
+            format_row: log.format_row,
+            row: Row::new(),
+            entries: log.cursor.0..log.entry_count,
+            next: log.cursor.0,
+            position: log.cursor.1,
+            cursor: log.cursor,
+        }
+    }
+
+    fn end(&self) -> u32 {
+        self.offset + self.buf.len() as u32
+    }
+
+    fn is_done(&self) -> bool {
+        self.position >= self.end() || self.next >= self.entries.end
+    }
+
+    /// Places the row for the entry at `index` in the log, if it is in `buf`.
+    fn place(&mut self, index: usize, entry: &[u8]) {
+        if !self.entries.contains(&index) || self.position >= self.end() {
+            return;
+        }
+        (self.format_row)(entry, &mut self.row);
+        copy_overlap(self.row.as_bytes(), self.position, self.offset, self.buf);
+        self.cursor = (index, self.position);
+        self.next = index + 1;
+        self.position += self.row.len() as u32;
+    }
+}
+
+/// Copies the part of `data`, which sits at `position` in a file, that overlaps `buf`, which holds
+/// the file from `offset`.
+fn copy_overlap(data: &[u8], position: u32, offset: u32, buf: &mut [u8]) {
+    let start = position.max(offset);
+    let end = (position + data.len() as u32).min(offset + buf.len() as u32);
+    if start < end {
+        buf[(start - offset) as usize..(end - offset) as usize]
+            .copy_from_slice(&data[(start - position) as usize..(end - position) as usize]);
+    }
+}
+
+/// Part of a log file formatted ahead of the sectors read.
+struct ReadAhead {
+    file: usize,
+    /// File offsets of the bytes held.
+    range: Range<u32>,
+    data: [u8; READ_AHEAD_SIZE],
+}
+
+impl ReadAhead {
+    const fn new() -> Self {
+        Self {
+            file: 0,
+            range: 0..0,
+            data: [0; READ_AHEAD_SIZE],
+        }
+    }
+
+    fn holds(&self, file: usize, offset: u32, len: usize) -> bool {
+        self.file == file && self.range.start <= offset && offset + len as u32 <= self.range.end
+    }
+
+    fn bytes(&self) -> &[u8] {
+        &self.data[..(self.range.end - self.range.start) as usize]
+    }
+}
+
+pub struct ExportVolume {
+    fat: FatVolume<FILE_COUNT>,
+    history: CsvLog,
+    errors: CsvLog,
+    settings: String<SETTINGS_JSON_CAPACITY>,
+    ahead: ReadAhead,
+}
+
+impl ExportVolume {
+    pub const SECTOR_SIZE: usize = SECTOR_SIZE;
+    pub const SECTOR_COUNT: u32 = fat::SECTOR_COUNT;
+
+    /// Makes a volume of the data now in `storage`, with the files dated `created`.
+    pub async fn new(
+        storage: &mut impl StorageManager,
+        created: NaiveDateTime,
+    ) -> Result<Self, StorageError> {
+        let mut history = CsvLog::new(
+            Logs::ConsumptionLog,
+            Row::try_from(files::HISTORY_CSV_HEADER).unwrap_or_default(),
+            files::history_row,
+        );
+        let mut errors = CsvLog::new(Logs::ErrorLog, files::errors_csv_header(), files::error_row);
+        let settings = files::settings_json(storage).await?;
+
+        let mut files = [FatFile { name: "", size: 0 }; FILE_COUNT];
+        files[HISTORY_FILE] = FatFile {
+            name: HISTORY_FILE_NAME,
+            size: history.snapshot(storage).await?,
+        };
+        files[SETTINGS_FILE] = FatFile {
+            name: SETTINGS_FILE_NAME,
+            size: settings.len() as u32,
+        };
+        files[ERRORS_FILE] = FatFile {
+            name: ERRORS_FILE_NAME,
+            size: errors.snapshot(storage).await?,
+        };
+
+        Ok(Self {
+            fat: FatVolume::new(files, created),
+            history,
+            errors,
+            settings,
+            ahead: ReadAhead::new(),
+        })
+    }
+
+    /// Fills `buf` with sector `lba` of the volume.
+    pub async fn read_sector(
+        &mut self,
+        storage: &mut impl StorageManager,
+        lba: u32,
+        buf: &mut [u8; SECTOR_SIZE],
+    ) -> Result<(), StorageError> {
+        let SectorContent::FileData { file, offset } = self.fat.read_sector(lba, buf) else {
+            return Ok(());
+        };
+        let file_size = self.fat.files()[file].size;
+        let len = ((file_size - offset) as usize).min(SECTOR_SIZE);
+        let buf = &mut buf[..len];
+        match file {
+            HISTORY_FILE | ERRORS_FILE => {
+                let ahead = &mut self.ahead;
+                if !ahead.holds(file, offset, len) {
+                    let log = match file {
+                        HISTORY_FILE => &mut self.history,
+                        _ => &mut self.errors,
+                    };
+                    let ahead_len = ((file_size - offset) as usize).min(READ_AHEAD_SIZE);
+                    ahead.range = 0..0;
+                    log.read(storage, offset, &mut ahead.data[..ahead_len])
+                        .await?;
+                    ahead.file = file;
+                    ahead.range = offset..offset + ahead_len as u32;
+                }
+                copy_overlap(ahead.bytes(), ahead.range.start, offset, buf);
+                Ok(())
+            }
+            SETTINGS_FILE => {
+                copy_overlap(self.settings.as_bytes(), 0, offset, buf);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
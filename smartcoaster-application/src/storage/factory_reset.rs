@@ -0,0 +1,100 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Factory reset. The settings and every log are erased so the device starts again as if newly
+//! flashed, optionally keeping the scale calibration. A reset can be requested from the settings
+//! menu, over USB or with the button combination at boot; the storage task carries it out and then
+//! reboots the device.
+
+use crate::storage::historical::log_config::Logs;
+use crate::storage::settings::accessor::flush_settings;
+use crate::storage::settings::schema::{keys, write_schema_version, SettingKey};
+use crate::storage::settings::{SettingValue, StorageError};
+use crate::storage::signal::signal_storage_work;
+use crate::storage::storage_manager::{StorageManager, NV_STORAGE};
+use defmt::{info, warn, Format};
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::signal::Signal;
+
+static FACTORY_RESET_REQUEST: Signal<CriticalSectionRawMutex, FactoryResetOptions> = Signal::new();
+
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub struct FactoryResetOptions {
+    /// Keep the tare offset, calibration gradient and bits to discard so the scale does not need
+    /// calibrating again.
+    pub keep_calibration: bool,
+}
+
+/// Asks the storage task to carry out a factory reset.
+pub fn request_factory_reset(options: FactoryResetOptions) {
+    info!("Factory reset requested: {}", options);
+    FACTORY_RESET_REQUEST.signal(options);
+    signal_storage_work();
+}
+
+/// Returns the pending factory reset request, if there is one.
+pub fn take_factory_reset_request() -> Option<FactoryResetOptions> {
+    FACTORY_RESET_REQUEST.try_take()
+}
+
+const CALIBRATION_KEYS: [SettingKey; 3] = [
+    keys::WEIGHING_SYSTEM_TARE_OFFSET,
+    keys::WEIGHING_SYSTEM_CALIBRATION_GRADIENT,
+    keys::WEIGHING_SYSTEM_BITS_TO_DISCARD,
+];
+
+/// Erases the settings and all logs in `storage`.
+pub async fn reset_storage(
+    storage: &mut impl StorageManager,
+    options: FactoryResetOptions,
+) -> Result<(), StorageError> {
+    let mut calibration = [None; 3];
+    if options.keep_calibration {
+        for (value, key) in calibration.iter_mut().zip(CALIBRATION_KEYS) {
+            *value = storage.read_key_value_pair::<SettingValue>(key).await?;
+        }
+    }
+
+    storage.clear_data().await?;
+    write_schema_version(storage).await?;
+    for log in [Logs::ConsumptionLog, Logs::DailyRollupLog, Logs::ErrorLog] {
+        storage.clear_log_data(&log.get_config()).await?;
+    }
+
+    for (value, key) in calibration.into_iter().zip(CALIBRATION_KEYS) {
+        if let Some(value) = value {
+            storage.save_key_value_pair(key, value).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Factory resets the NVM storage. The device should be rebooted straight after so nothing still
+/// holding settings or log state from before the reset writes it back.
+pub async fn factory_reset(options: FactoryResetOptions) -> Result<(), StorageError> {
+    // settings saves still held back, e.g. a calibration that is being kept
+    flush_settings().await;
+    let mut storage = NV_STORAGE.lock().await;
+    reset_storage(&mut *storage, options)
+        .await
+        .inspect_err(|e| {
+            warn!("Factory reset failed: {}", e);
+        })?;
+    // the erase counts were cleared with the settings, put them back
+    if let Err(e) = storage.save_erase_counts().await {
+        warn!("Unable to save NVM erase counts: {}", e);
+    }
+    info!("Factory reset complete");
+    Ok(())
+}
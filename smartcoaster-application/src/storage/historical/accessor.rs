@@ -16,6 +16,7 @@ use crate::rtc::accessor::RtcAccessor;
 use crate::storage::historical::log_config::Logs;
 use crate::storage::historical::manager::{DATA_BUFFER_SIZE, LOG_STORE, MAX_READ_CHUNK_SIZE};
 use crate::storage::historical::messaging::HistoricalLogChannel;
+use crate::storage::historical::timestamp::decode_timestamp;
 use crate::storage::historical::{LogEncodeDecode, SimpleLogEntry};
 use crate::storage::settings::StorageError;
 use crate::storage::storage_manager::StoredLogConfig;
@@ -31,37 +32,7 @@ pub struct RetrievedLogEntry {
 
 impl RetrievedLogEntry {
     pub fn from_buffer(buffer: &[u8]) -> Result<Self, StorageError> {
-        let timestamp;
-        // Extract date components
-        let year_lower = buffer[0] as u16;
-        let year_upper = buffer[1] as u16;
-        let year = year_lower | (year_upper << 8);
-        let month = buffer[2] as u32;
-        let day = buffer[3] as u32;
-
-        // Extract time components
-        let hour = buffer[4] as u32;
-        let minute = buffer[5] as u32;
-        let second = buffer[6] as u32;
-
-        // Extract nanoseconds (stored in 3 bytes)
-        let mut nano_bytes = [0u8; 4];
-        nano_bytes[0..3].copy_from_slice(&buffer[7..10]);
-        let nano = u32::from_le_bytes(nano_bytes);
-
-        // Create NaiveDate and NaiveTime
-        match chrono::NaiveDate::from_ymd_opt(year as i32, month, day) {
-            Some(date) => {
-                match chrono::NaiveTime::from_hms_nano_opt(hour, minute, second, nano) {
-                    Some(time) => {
-                        // Combine into a NaiveDateTime
-                        timestamp = NaiveDateTime::new(date, time);
-                    }
-                    None => return Err(StorageError::DecodeError),
-                }
-            }
-            None => return Err(StorageError::DecodeError),
-        }
+        let (timestamp, timestamp_size) = decode_timestamp(buffer)?;
 
         // create result, copying in the data
         let mut s = Self {
@@ -69,8 +40,8 @@ impl RetrievedLogEntry {
             data: [0; DATA_BUFFER_SIZE],
         };
 
-        let data_len = s.data.len() - 10;
-        s.data[0..data_len].copy_from_slice(&buffer[10..]);
+        let data_len = buffer.len().min(DATA_BUFFER_SIZE) - timestamp_size;
+        s.data[0..data_len].copy_from_slice(&buffer[timestamp_size..timestamp_size + data_len]);
         Ok(s)
     }
 }
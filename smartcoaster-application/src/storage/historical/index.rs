@@ -0,0 +1,651 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Index of the pages of a log, used to find where reads from a given time start.
+//!
+//! sequential-storage can only iterate a log from its oldest entry, so finding a start point by
+//! reading the log costs a pass over every entry before it. The log is written a page at a time
+//! though, so the index keeps the timestamp of the first entry in each page and the number of
+//! entries in it, oldest page first. A binary search of the pages gives the one the start point is
+//! in, and only that page is read to find it (see `storage::log_pages`). A read goes on from there
+//! a chunk at a time, keeping its place as a page and the number of entries into it. That stays
+//! put as entries are added, and is only lost when the page is reused.
+//!
+//! The index is kept up to date without reading the whole log again. Before each use the first
+//! entry of the oldest page is checked, dropping the page if it has been reused, and the page after
+//! the newest is checked for entries, adding it if it has been written to. A page's entries are
+//! counted when a newer page is added, as it is full then.
+//!
+//! The binary search relies on the entries being in time order, which they are not if the clock
+//! was set back while the log was written. The index notes the newest page holding an entry older
+//! than the one before it, and searches read every page until that page has been reused.
+
+use crate::storage::historical::manager::DATA_BUFFER_SIZE;
+use crate::storage::historical::timestamp::decode_timestamp;
+use crate::storage::log_pages::LogPages;
+use crate::storage::settings::StorageError;
+use crate::storage::storage_manager::{StorageManager, StoredLogConfig};
+use chrono::NaiveDateTime;
+use core::ops::ControlFlow;
+use defmt::{debug, trace, warn, Debug2Format};
+use heapless::Vec;
+
+#[derive(Debug, Clone, Copy)]
+struct PageSample {
+    page: usize,
+    /// Timestamp of the first entry in the page, which changes when the page is reused.
+    timestamp: NaiveDateTime,
+    /// Entries in the page. For the newest page, which can still be written to, only those there
+    /// when it was indexed.
+    entries: usize,
+}
+
+/// Where a read of a log has got to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogPosition {
+    /// Entries after the oldest entry in the log, used when the log cannot be indexed.
+    FromOldest(usize),
+    /// Entries into `page`, whose first entry is from `first`.
+    InPage {
+        page: usize,
+        first: NaiveDateTime,
+        skip: usize,
+    },
+}
+
+impl LogPosition {
+    fn skip(&self) -> usize {
+        match *self {
+            LogPosition::FromOldest(skip) | LogPosition::InPage { skip, .. } => skip,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct LogIndex {
+    /// Pages holding entries, oldest first.
+    pages: Vec<PageSample, { LogPages::MAX_PAGES }>,
+    /// Newest page holding an entry older than the one before it.
+    unordered_page: Option<usize>,
+    /// Timestamp of the newest entry added.
+    newest: Option<NaiveDateTime>,
+    /// An entry older than the one before it has been added since the index was updated.
+    added_out_of_order: bool,
+    valid: bool,
+}
+
+impl LogIndex {
+    /// Creates an index that is built on first use.
+    pub const fn new() -> Self {
+        Self {
+            pages: Vec::new(),
+            unordered_page: None,
+            newest: None,
+            added_out_of_order: false,
+            valid: false,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+
+    /// Whether the entries are in time order, so the pages can be searched by time.
+    pub fn is_ordered(&self) -> bool {
+        self.unordered_page.is_none() && !self.added_out_of_order
+    }
+
+    /// Forces the index to be rebuilt from the log on next use.
+    pub fn invalidate(&mut self) {
+        self.valid = false;
+    }
+
+    /// Sets the index up for an empty log.
+    pub fn clear(&mut self) {
+        self.pages.clear();
+        self.unordered_page = None;
+        self.newest = None;
+        self.added_out_of_order = false;
+        self.valid = true;
+    }
+
+    /// Records an entry added to the end of the log. Only its timestamp is needed, the page it
+    /// went in is found when the index is next used.
+    pub fn append(&mut self, timestamp: NaiveDateTime) {
+        if self.newest.is_some_and(|newest| timestamp < newest) {
+            self.added_out_of_order = true;
+        }
+        self.newest = Some(timestamp);
+    }
+
+    /// Position of the oldest entry in the log.
+    pub fn start(&self) -> LogPosition {
+        match self.pages.first() {
+            Some(oldest) => LogPosition::InPage {
+                page: oldest.page,
+                first: oldest.timestamp,
+                skip: 0,
+            },
+            None => LogPosition::FromOldest(0),
+        }
+    }
+
+    /// Position of the entry `count` entries after the oldest in the log.
+    pub fn position_at(&self, count: usize) -> LogPosition {
+        let mut position = self.start();
+        self.advance(&mut position, count);
+        position
+    }
+
+    /// Number of entries in the log before `position`.
+    pub fn position_in_log(&self, position: &LogPosition) -> usize {
+        match *position {
+            LogPosition::FromOldest(skip) => skip,
+            LogPosition::InPage { page, first, skip } => {
+                let before = self.find(page, first).unwrap_or(0);
+                self.pages[..before]
+                    .iter()
+                    .map(|sample| sample.entries)
+                    .sum::<usize>()
+                    + skip
+            }
+        }
+    }
+
+    /// Moves `position` on by `count` entries, on to the next page if it passes the end of one.
+    pub fn advance(&self, position: &mut LogPosition, count: usize) {
+        match position {
+            LogPosition::FromOldest(skip) => *skip += count,
+            LogPosition::InPage { page, first, skip } => {
+                *skip += count;
+                let Some(mut index) = self.find(*page, *first) else {
+                    return;
+                };
+                // the newest page can still be written to, so a position past its end stays in it
+                while index + 1 < self.pages.len() && *skip >= self.pages[index].entries {
+                    *skip -= self.pages[index].entries;
+                    index += 1;
+                }
+                *page = self.pages[index].page;
+                *first = self.pages[index].timestamp;
+            }
+        }
+    }
+
+    fn find(&self, page: usize, first: NaiveDateTime) -> Option<usize> {
+        self.pages
+            .iter()
+            .position(|sample| sample.page == page && sample.timestamp == first)
+    }
+
+    /// Gives the pages a read from `position` goes through, leaving out those before it. If the
+    /// entries at `position` have been lost, as their page has been reused, it is moved to the
+    /// oldest entry.
+    fn pages_from(&self, position: &mut LogPosition) -> LogPages {
+        if let LogPosition::FromOldest(skip) = *position {
+            *position = self.position_at(skip);
+        }
+        let LogPosition::InPage { page, first, .. } = *position else {
+            return LogPages::ALL;
+        };
+        let Some(index) = self.find(page, first) else {
+            warn!(
+                "Log page {} reused during a read, going on from the oldest entry",
+                page
+            );
+            *position = self.start();
+            return LogPages::ALL;
+        };
+        self.pages[..index]
+            .iter()
+            .fold(LogPages::ALL, |pages, sample| pages.without(sample.page))
+    }
+
+    /// Brings the index up to date with the log, building it if need be.
+    async fn update(&mut self, storage: &mut impl StorageManager, config: &StoredLogConfig) {
+        if self.valid {
+            match self.refresh(storage, config).await {
+                Ok(true) => return,
+                Ok(false) => trace!("Log index out of date, rebuilding"),
+                Err(e) => warn!("Unable to update log index: {}", e),
+            }
+        }
+        if let Err(e) = self.rebuild(storage, config).await {
+            warn!("Unable to index log: {}", e);
+            self.pages.clear();
+            self.invalidate();
+        }
+    }
+
+    /// Drops the pages reused since they were indexed and adds those written to. Gives false if the
+    /// index has to be rebuilt instead.
+    async fn refresh(
+        &mut self,
+        storage: &mut impl StorageManager,
+        config: &StoredLogConfig,
+    ) -> Result<bool, StorageError> {
+        // pages are reused oldest first, and a reused page starts with a different entry
+        while let Some(oldest) = self.pages.first().copied() {
+            if first_timestamp(storage, config, oldest.page).await? == Some(oldest.timestamp) {
+                break;
+            }
+            trace!("Log page {} reused", oldest.page);
+            self.pages.remove(0);
+            if self.unordered_page == Some(oldest.page) {
+                self.unordered_page = None;
+            }
+        }
+        let Some(mut newest) = self.pages.last().copied() else {
+            // nothing is known about where the log goes on from
+            return Ok(false);
+        };
+
+        // pages are written in turn round the storage range
+        let page_count = LogPages::page_count(&config.storage_range);
+        loop {
+            let next = (newest.page + 1) % page_count;
+            if self.pages.iter().any(|sample| sample.page == next) {
+                break;
+            }
+            let Some(timestamp) = first_timestamp(storage, config, next).await? else {
+                break;
+            };
+            // the page before is full, so its entries are counted now
+            let mut first_item = [0u8; DATA_BUFFER_SIZE];
+            let Some(scan) = scan_page(storage, config, newest.page, &mut first_item).await? else {
+                return Ok(false);
+            };
+            if scan.first != newest.timestamp {
+                return Ok(false);
+            }
+            if let Some(sample) = self.pages.last_mut() {
+                sample.entries = scan.entries;
+            }
+            if !scan.ordered {
+                self.unordered_page = Some(newest.page);
+            }
+            if timestamp < scan.last {
+                self.unordered_page = Some(next);
+            }
+            newest = PageSample {
+                page: next,
+                timestamp,
+                entries: 0,
+            };
+            if self.pages.push(newest).is_err() {
+                return Ok(false);
+            }
+        }
+
+        if self.added_out_of_order {
+            // the entry is in the newest page, or one counted above
+            self.unordered_page = Some(newest.page);
+            self.added_out_of_order = false;
+        }
+        Ok(true)
+    }
+
+    /// Rebuilds the index with a single pass over the log, a page at a time. The pages are kept in
+    /// the order they were written, which starts from the page holding the oldest entry.
+    async fn rebuild(
+        &mut self,
+        storage: &mut impl StorageManager,
+        config: &StoredLogConfig,
+    ) -> Result<(), StorageError> {
+        self.clear();
+        self.valid = false;
+
+        let mut oldest_entry = [[0u8; DATA_BUFFER_SIZE]; 1];
+        let count = storage
+            .get_log_items(config, 0, 1, &mut oldest_entry)
+            .await?;
+        if count == 0 {
+            self.valid = true;
+            return Ok(());
+        }
+
+        let mut oldest_page = None;
+        let mut ends = Vec::<(NaiveDateTime, bool), { LogPages::MAX_PAGES }>::new();
+        let mut first_item = [0u8; DATA_BUFFER_SIZE];
+        for page in 0..LogPages::page_count(&config.storage_range) {
+            let Some(scan) = scan_page(storage, config, page, &mut first_item).await? else {
+                continue;
+            };
+            if oldest_page.is_none() && first_item == oldest_entry[0] {
+                oldest_page = Some(self.pages.len());
+            }
+            let _ = self.pages.push(PageSample {
+                page,
+                timestamp: scan.first,
+                entries: scan.entries,
+            });
+            let _ = ends.push((scan.last, scan.ordered));
+        }
+
+        let Some(oldest_page) = oldest_page else {
+            warn!("Oldest log entry not found in any page, log not indexed");
+            return Err(StorageError::RetrieveError);
+        };
+        self.pages.rotate_left(oldest_page);
+        ends.rotate_left(oldest_page);
+        let mut previous_last = None;
+        for (sample, (last, ordered)) in self.pages.iter().zip(ends.iter()) {
+            if !ordered || previous_last.is_some_and(|previous| sample.timestamp < previous) {
+                self.unordered_page = Some(sample.page);
+            }
+            previous_last = Some(*last);
+        }
+        self.newest = previous_last;
+        self.valid = true;
+        debug!(
+            "Log index rebuilt - {} pages, in order {}",
+            self.pages.len(),
+            self.is_ordered()
+        );
+        Ok(())
+    }
+
+    /// Finds the start of a read from `target` from the page it is in. Gives `None` if the page
+    /// no longer starts with the entry it did when it was indexed.
+    async fn seek(
+        &self,
+        storage: &mut impl StorageManager,
+        config: &StoredLogConfig,
+        target: NaiveDateTime,
+    ) -> Result<Option<LogPosition>, StorageError> {
+        let after = self
+            .pages
+            .partition_point(|sample| sample.timestamp < target);
+        let Some(sample_index) = after.checked_sub(1) else {
+            // nothing indexed is from before target, so the read is of the whole log
+            return Ok(Some(self.start()));
+        };
+
+        let sample = self.pages[sample_index];
+        let mut first = None;
+        let mut skip = 0;
+        let mut decoded = Ok(());
+        storage
+            .visit_log_items_in_pages(config, LogPages::only(sample.page), 0, |item| {
+                match decode_timestamp(item) {
+                    Ok((timestamp, _)) => {
+                        first.get_or_insert(timestamp);
+                        if timestamp >= target {
+                            return ControlFlow::Break(());
+                        }
+                        skip += 1;
+                        ControlFlow::Continue(())
+                    }
+                    Err(e) => {
+                        decoded = Err(e);
+                        ControlFlow::Break(())
+                    }
+                }
+            })
+            .await?;
+        decoded?;
+        if first != Some(sample.timestamp) {
+            return Ok(None);
+        }
+
+        let mut position = LogPosition::InPage {
+            page: sample.page,
+            first: sample.timestamp,
+            skip: 0,
+        };
+        self.advance(&mut position, skip);
+        Ok(Some(position))
+    }
+}
+
+impl Default for LogIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Finds where a read of the entries in the log at or after `target` starts. When the entries are
+/// in time order everything from there onwards is after `target`.
+pub async fn find_log_start(
+    storage: &mut impl StorageManager,
+    config: &StoredLogConfig,
+    index: &mut LogIndex,
+    target: NaiveDateTime,
+) -> Result<LogPosition, StorageError> {
+    trace!("Searching for {}", Debug2Format(&target));
+    index.update(storage, config).await;
+    if index.is_valid() && index.is_ordered() {
+        if let Some(start) = index.seek(storage, config, target).await? {
+            return Ok(start);
+        }
+        // the page has changed since it was indexed, which the update should have found
+        trace!("Log index out of date, rebuilding");
+        index.invalidate();
+        index.update(storage, config).await;
+        if index.is_valid() && index.is_ordered() {
+            if let Some(start) = index.seek(storage, config, target).await? {
+                return Ok(start);
+            }
+        }
+    }
+
+    if index.is_valid() {
+        // the entries are out of time order, so every page is searched
+        return scan_pages(storage, config, index, target).await;
+    }
+    // the log could not be indexed, so is searched from the oldest entry
+    let mut decoded = Ok(());
+    let mut start = 0;
+    storage
+        .visit_log_items(config, 0, |item| match decode_timestamp(item) {
+            Ok((timestamp, _)) if timestamp >= target => ControlFlow::Break(()),
+            Ok(_) => {
+                start += 1;
+                ControlFlow::Continue(())
+            }
+            Err(e) => {
+                decoded = Err(e);
+                ControlFlow::Break(())
+            }
+        })
+        .await?;
+    decoded?;
+    Ok(LogPosition::FromOldest(start))
+}
+
+/// Passes the entries in a log from `position` to `visit`, oldest first, until it breaks, reading
+/// only the pages from the one `position` is in. Moves `position` on past the entries visited. If
+/// the entries at `position` have been lost since, as their page has been reused, the visit starts
+/// from the oldest entry instead. Gives the number of entries visited.
+pub async fn visit_log_from(
+    storage: &mut impl StorageManager,
+    config: &StoredLogConfig,
+    index: &mut LogIndex,
+    position: &mut LogPosition,
+    visit: impl FnMut(&[u8]) -> ControlFlow<()>,
+) -> Result<usize, StorageError> {
+    index.update(storage, config).await;
+    visit_updated_log_from(storage, config, index, position, visit).await
+}
+
+/// As [`visit_log_from`], from the entry `start` entries after the oldest in the log.
+pub async fn visit_log_items_from(
+    storage: &mut impl StorageManager,
+    config: &StoredLogConfig,
+    index: &mut LogIndex,
+    start: usize,
+    visit: impl FnMut(&[u8]) -> ControlFlow<()>,
+) -> Result<usize, StorageError> {
+    index.update(storage, config).await;
+    let mut position = index.position_at(start);
+    visit_updated_log_from(storage, config, index, &mut position, visit).await
+}
+
+/// Copies up to `buf.len()` entries from `position` into `buf`, moving `position` on past them.
+/// Gives the number of entries copied.
+pub async fn read_log_items(
+    storage: &mut impl StorageManager,
+    config: &StoredLogConfig,
+    index: &mut LogIndex,
+    position: &mut LogPosition,
+    buf: &mut [[u8; DATA_BUFFER_SIZE]],
+) -> Result<usize, StorageError> {
+    let mut count = 0;
+    if buf.is_empty() {
+        return Ok(count);
+    }
+    visit_log_from(storage, config, index, position, |item| {
+        copy_item(item, &mut buf[count]);
+        count += 1;
+        if count < buf.len() {
+            ControlFlow::Continue(())
+        } else {
+            ControlFlow::Break(())
+        }
+    })
+    .await?;
+    Ok(count)
+}
+
+async fn visit_updated_log_from(
+    storage: &mut impl StorageManager,
+    config: &StoredLogConfig,
+    index: &LogIndex,
+    position: &mut LogPosition,
+    visit: impl FnMut(&[u8]) -> ControlFlow<()>,
+) -> Result<usize, StorageError> {
+    let pages = index.pages_from(position);
+    let count = storage
+        .visit_log_items_in_pages(config, pages, position.skip(), visit)
+        .await?;
+    index.advance(position, count);
+    Ok(count)
+}
+
+/// Reads every page in turn, giving the position of the first entry at or after `target`.
+async fn scan_pages(
+    storage: &mut impl StorageManager,
+    config: &StoredLogConfig,
+    index: &LogIndex,
+    target: NaiveDateTime,
+) -> Result<LogPosition, StorageError> {
+    let mut position = index.start();
+    for sample in &index.pages {
+        let mut skip = 0;
+        let mut found = false;
+        let mut decoded = Ok(());
+        storage
+            .visit_log_items_in_pages(config, LogPages::only(sample.page), 0, |item| {
+                match decode_timestamp(item) {
+                    Ok((timestamp, _)) if timestamp >= target => {
+                        found = true;
+                        ControlFlow::Break(())
+                    }
+                    Ok(_) => {
+                        skip += 1;
+                        ControlFlow::Continue(())
+                    }
+                    Err(e) => {
+                        decoded = Err(e);
+                        ControlFlow::Break(())
+                    }
+                }
+            })
+            .await?;
+        decoded?;
+        position = LogPosition::InPage {
+            page: sample.page,
+            first: sample.timestamp,
+            skip,
+        };
+        if found {
+            break;
+        }
+    }
+    Ok(position)
+}
+
+/// What a pass over the entries in a page found.
+struct PageScan {
+    first: NaiveDateTime,
+    last: NaiveDateTime,
+    entries: usize,
+    /// Whether every entry is at or after the one before it.
+    ordered: bool,
+}
+
+/// Reads the entries in `page`, copying the first into `first_item`. Gives `None` if the page
+/// holds no entries.
+async fn scan_page(
+    storage: &mut impl StorageManager,
+    config: &StoredLogConfig,
+    page: usize,
+    first_item: &mut [u8; DATA_BUFFER_SIZE],
+) -> Result<Option<PageScan>, StorageError> {
+    let mut scan: Option<PageScan> = None;
+    let mut decoded = Ok(());
+    storage
+        .visit_log_items_in_pages(config, LogPages::only(page), 0, |item| {
+            let timestamp = match decode_timestamp(item) {
+                Ok((timestamp, _)) => timestamp,
+                Err(e) => {
+                    decoded = Err(e);
+                    return ControlFlow::Break(());
+                }
+            };
+            match &mut scan {
+                Some(scan) => {
+                    scan.ordered &= timestamp >= scan.last;
+                    scan.last = timestamp;
+                    scan.entries += 1;
+                }
+                None => {
+                    copy_item(item, first_item);
+                    scan = Some(PageScan {
+                        first: timestamp,
+                        last: timestamp,
+                        entries: 1,
+                        ordered: true,
+                    });
+                }
+            }
+            ControlFlow::Continue(())
+        })
+        .await?;
+    decoded?;
+    Ok(scan)
+}
+
+/// Timestamp of the first entry in `page`, or `None` if it holds no entries.
+async fn first_timestamp(
+    storage: &mut impl StorageManager,
+    config: &StoredLogConfig,
+    page: usize,
+) -> Result<Option<NaiveDateTime>, StorageError> {
+    let mut first = None;
+    storage
+        .visit_log_items_in_pages(config, LogPages::only(page), 0, |item| {
+            first = Some(decode_timestamp(item));
+            ControlFlow::Break(())
+        })
+        .await?;
+    Ok(first.transpose()?.map(|(timestamp, _)| timestamp))
+}
+
+/// Copies a log entry into `buf`, zero padding it.
+fn copy_item(item: &[u8], buf: &mut [u8; DATA_BUFFER_SIZE]) {
+    let len = item.len().min(DATA_BUFFER_SIZE);
+    buf.fill(0);
+    buf[..len].copy_from_slice(&item[..len]);
+}
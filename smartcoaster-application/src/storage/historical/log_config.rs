@@ -13,10 +13,14 @@
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use crate::storage::storage_manager::StoredLogConfig;
-use crate::ACTIVITY_LOG_NVM_FLASH_OFFSET_RANGE;
+use crate::{
+    ACTIVITY_LOG_NVM_FLASH_OFFSET_RANGE, DAILY_ROLLUP_LOG_NVM_FLASH_OFFSET_RANGE,
+    ERROR_LOG_NVM_FLASH_OFFSET_RANGE,
+};
 
 pub enum Logs {
     ConsumptionLog,
+    DailyRollupLog,
     ErrorLog,
 }
 
@@ -27,9 +31,14 @@ impl Logs {
                 storage_range: ACTIVITY_LOG_NVM_FLASH_OFFSET_RANGE,
                 allow_overwrite_old: true,
             },
-            Logs::ErrorLog => {
-                todo!("Error log not configured yet")
-            }
+            Logs::DailyRollupLog => StoredLogConfig {
+                storage_range: DAILY_ROLLUP_LOG_NVM_FLASH_OFFSET_RANGE,
+                allow_overwrite_old: true,
+            },
+            Logs::ErrorLog => StoredLogConfig {
+                storage_range: ERROR_LOG_NVM_FLASH_OFFSET_RANGE,
+                allow_overwrite_old: true,
+            },
         }
     }
 }
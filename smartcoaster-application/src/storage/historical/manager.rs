@@ -12,19 +12,25 @@
 // You should have received a copy of the GNU General Public License along with
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use crate::error_log::{log_error, ErrorCode, ErrorLogData, ErrorModule};
 use crate::storage::historical::accessor::RetrievedLogEntry;
+use crate::storage::historical::index::{find_log_start, read_log_items, LogIndex, LogPosition};
+use crate::storage::historical::log_config::Logs;
 use crate::storage::historical::messaging::{
     HistoricalLogChannel, HistoricalLogChannelPublisher, HistoricalLogMessage,
 };
+use crate::storage::historical::timestamp::{decode_timestamp, encode_timestamp};
 use crate::storage::historical::LogEncodeDecode;
 use crate::storage::settings::StorageError;
+use crate::storage::signal::signal_storage_work;
 use crate::storage::storage_manager::{StorageManager, StoredLogConfig, NV_STORAGE};
-use chrono::{Datelike, NaiveDateTime, Timelike};
+use chrono::NaiveDateTime;
 use core::fmt::Debug;
 use defmt::{debug, error, info, trace, Debug2Format};
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use embassy_sync::mutex::Mutex;
 use heapless::spsc::Queue;
+use heapless::LinearMap;
 
 pub static LOG_STORE: LogManagerMutex = Mutex::new(HistoricalLogManager::new());
 
@@ -33,9 +39,13 @@ pub type LogManagerMutex = Mutex<CriticalSectionRawMutex, HistoricalLogManager>;
 pub const DATA_BUFFER_SIZE: usize = 64;
 pub const MAX_READ_CHUNK_SIZE: usize = 5;
 
+/// A log read, served a chunk per pass of the storage task. Its position is kept between chunks,
+/// so each chunk reads on from the last without searching the log again.
 struct ReadLogQueueEntry {
     config: StoredLogConfig,
     start_timestamp: NaiveDateTime,
+    /// Where the next chunk starts, once the start has been found.
+    position: Option<LogPosition>,
     log_channel: &'static HistoricalLogChannel,
 }
 
@@ -48,6 +58,7 @@ impl ReadLogQueueEntry {
         Self {
             config: config.clone(),
             start_timestamp,
+            position: None,
             log_channel,
         }
     }
@@ -56,6 +67,7 @@ impl ReadLogQueueEntry {
 #[derive(Debug)]
 struct WriteLogQueueEntry {
     config: StoredLogConfig,
+    timestamp: NaiveDateTime,
     data: [u8; DATA_BUFFER_SIZE],
     entry_size: usize,
     clear: bool,
@@ -70,22 +82,9 @@ impl WriteLogQueueEntry {
     ) -> Result<Self, StorageError> {
         let mut data = [0; DATA_BUFFER_SIZE];
 
-        // Date part
-        data[0] = timestamp.year() as u8; // Lower 8 bits of year
-        data[1] = (timestamp.year() >> 8) as u8; // Upper 8 bits of year
-        data[2] = timestamp.month() as u8;
-        data[3] = timestamp.day() as u8;
-
-        // Time part
-        data[4] = timestamp.hour() as u8;
-        data[5] = timestamp.minute() as u8;
-        data[6] = timestamp.second() as u8;
-
-        // Nanoseconds
-        let nanos = timestamp.nanosecond();
-        data[7..10].copy_from_slice(&nanos.to_le_bytes()[..3]); // Using only 3 bytes for nanos
-
-        let ts_length = 10;
+        let ts_length = encode_timestamp(timestamp, &mut data);
+        // keep the timestamp as it reads back, to the second
+        let (timestamp, _) = decode_timestamp(&data)?;
 
         let entry_size = ts_length
             + entry.encode(&mut data[ts_length..]).map_err(|_| {
@@ -95,6 +94,7 @@ impl WriteLogQueueEntry {
 
         Ok(Self {
             config: config.clone(),
+            timestamp,
             data,
             entry_size,
             clear,
@@ -102,9 +102,15 @@ impl WriteLogQueueEntry {
     }
 }
 
+/// Number of logs an index is kept for, one per entry in `Logs`.
+const INDEXED_LOGS: usize = 4;
+
 pub struct HistoricalLogManager {
     log_write_queue: Queue<WriteLogQueueEntry, 8>,
     log_read_queue: Queue<ReadLogQueueEntry, 4>,
+    /// Read part way through, carried on before the next queued read is started.
+    log_read: Option<ReadLogQueueEntry>,
+    log_indexes: LinearMap<u32, LogIndex, INDEXED_LOGS>,
 }
 
 impl HistoricalLogManager {
@@ -112,6 +118,8 @@ impl HistoricalLogManager {
         Self {
             log_write_queue: Queue::new(),
             log_read_queue: Queue::new(),
+            log_read: None,
+            log_indexes: LinearMap::new(),
         }
     }
 
@@ -128,6 +136,7 @@ impl HistoricalLogManager {
             error!("Error writing log queue entry: {:?}", Debug2Format(&e));
             StorageError::SaveError
         })?;
+        signal_storage_work();
         Ok(())
     }
 
@@ -143,6 +152,7 @@ impl HistoricalLogManager {
             error!("Error writing read log queue request entry");
             StorageError::RetrieveError
         })?;
+        signal_storage_work();
         Ok(())
     }
 
@@ -152,8 +162,15 @@ impl HistoricalLogManager {
             if queue_entry.clear {
                 info!("Clearing log data");
                 let mut storage = NV_STORAGE.lock().await;
-                storage.clear_log_data(&queue_entry.config).await?;
+                storage
+                    .clear_log_data(&queue_entry.config)
+                    .await
+                    .inspect_err(|_| {
+                        Self::log_storage_error(&queue_entry.config, ErrorCode::LogClearFailed)
+                    })?;
+                self.log_index(&queue_entry.config).clear();
             } else {
+                let config = queue_entry.config.clone();
                 self.write_entry(queue_entry.config, queue_entry.data, queue_entry.entry_size)
                     .await
                     .map_err(|e| {
@@ -161,24 +178,112 @@ impl HistoricalLogManager {
                             "Error while processing log queue entry {:?}",
                             Debug2Format(&e)
                         );
+                        Self::log_storage_error(&config, ErrorCode::LogWriteFailed);
                         StorageError::SaveError
                     })?;
+                self.log_index(&config).append(queue_entry.timestamp);
             }
         }
         Ok(())
     }
 
-    pub async fn process_read_queue(&mut self) -> Result<(), StorageError> {
-        while let Some(queue_entry) = self.log_read_queue.dequeue() {
-            trace!("Processing log read queue request");
-            let _ = Self::get_entries_after_timestamp_to_channel(
-                &queue_entry.config,
-                queue_entry.start_timestamp,
-                queue_entry.log_channel.publisher().unwrap(),
-            )
-            .await?;
+    /// Records a failure on a log in the error log, unless it is the error log that failed.
+    fn log_storage_error(config: &StoredLogConfig, code: ErrorCode) {
+        if config.storage_range != Logs::ErrorLog.get_config().storage_range {
+            log_error(
+                ErrorLogData::new(ErrorModule::Storage, code)
+                    .with_value(config.storage_range.start),
+            );
+        }
+    }
+
+    /// Index for the log, kept by the start of its storage range. Starts out invalid, so it is
+    /// built from the log on first use.
+    fn log_index(&mut self, config: &StoredLogConfig) -> &mut LogIndex {
+        let key = config.storage_range.start;
+        if !self.log_indexes.contains_key(&key) {
+            if self.log_indexes.insert(key, LogIndex::new()).is_err() {
+                error!("No space to index log at 0x{:x}", key);
+                // forget the other indexes, they are rebuilt when next used
+                self.log_indexes.clear();
+                let _ = self.log_indexes.insert(key, LogIndex::new());
+            }
+        }
+        self.log_indexes.get_mut(&key).unwrap()
+    }
+
+    /// Takes the read part way through, or else the next read request, and reads its next chunk of
+    /// entries into `items`, giving it with the number of entries read.
+    async fn read_next_chunk(
+        &mut self,
+        items: &mut [[u8; DATA_BUFFER_SIZE]; MAX_READ_CHUNK_SIZE],
+    ) -> Option<(ReadLogQueueEntry, usize)> {
+        let mut queue_entry = match self.log_read.take() {
+            Some(queue_entry) => queue_entry,
+            None => {
+                let queue_entry = self.log_read_queue.dequeue()?;
+                trace!("Processing log read queue request");
+                queue_entry
+            }
+        };
+
+        let index = self.log_index(&queue_entry.config);
+        let mut storage = NV_STORAGE.lock().await;
+        let mut position = match queue_entry.position {
+            Some(position) => position,
+            None => {
+                trace!(
+                    "Getting log entries after {:?}",
+                    Debug2Format(&queue_entry.start_timestamp)
+                );
+                let start = find_log_start(
+                    &mut *storage,
+                    &queue_entry.config,
+                    index,
+                    queue_entry.start_timestamp,
+                )
+                .await
+                .unwrap_or_else(|e| {
+                    error!("Unable to search log: {}, reading all entries", e);
+                    index.invalidate();
+                    LogPosition::FromOldest(0)
+                });
+                trace!(
+                    "Found start point - skipped {} entries",
+                    index.position_in_log(&start)
+                );
+                start
+            }
+        };
+
+        let count = read_log_items(
+            &mut *storage,
+            &queue_entry.config,
+            index,
+            &mut position,
+            items,
+        )
+        .await
+        .unwrap_or_else(|e| {
+            error!("Unable to read log: {}", e);
+            0
+        });
+        queue_entry.position = Some(position);
+        trace!("Retrieved {} entries", count);
+        Some((queue_entry, count))
+    }
+
+    /// Keeps a read that has more entries to send for the next pass of the storage task.
+    fn continue_read(&mut self, queue_entry: ReadLogQueueEntry) {
+        self.log_read = Some(queue_entry);
+        signal_storage_work();
+    }
+
+    /// Signals the storage task to start the next read, if there is one, once a read is done.
+    fn end_read(&self) {
+        if !self.log_read_queue.is_empty() {
+            signal_storage_work();
         }
-        Ok(())
     }
 
     async fn write_entry(
@@ -204,107 +309,66 @@ impl HistoricalLogManager {
         Ok(())
     }
 
-    async fn get_entries_after_timestamp_to_channel(
-        config: &StoredLogConfig,
-        target_timestamp: NaiveDateTime,
+    /// Sends a chunk of entries to the channel, ending the read with `EndOfRead` once there are no
+    /// more. Gives false if the read has ended.
+    async fn send_chunk_to_channel(
+        items: &[[u8; DATA_BUFFER_SIZE]],
         publisher: HistoricalLogChannelPublisher<'_>,
-    ) -> Result<usize, StorageError> {
-        trace!(
-            "Getting log entries after {:?}",
-            Debug2Format(&target_timestamp)
-        );
-        let mut temp_buffer = [[0u8; DATA_BUFFER_SIZE]; MAX_READ_CHUNK_SIZE];
-        let mut storage = NV_STORAGE.lock().await;
-        trace!("Got storage lock");
-
-        // find starting point - this is a naive straight iteration. Could be improved by doing a
-        // bisecting search
-        let mut chunk_start = 0;
-        'find_start: loop {
-            let retrieved_count = storage
-                .get_log_items(config, chunk_start, MAX_READ_CHUNK_SIZE, &mut temp_buffer)
-                .await
-                .unwrap_or(0);
-            trace!(
-                "Start search - retrieved {} items, starting at {}",
-                retrieved_count,
-                chunk_start
-            );
-            if retrieved_count == 0 {
-                break 'find_start;
-            }
-
-            chunk_start += retrieved_count;
-            // search backwards in the chunk gives us early exit opportunity
-            let mut found_entries_after_start = false;
-            for chunk_idx in (0..retrieved_count).rev() {
-                let entry = RetrievedLogEntry::from_buffer(&temp_buffer[chunk_idx])?;
-                if entry.timestamp < target_timestamp {
-                    if found_entries_after_start {
-                        break 'find_start; // we found the start point in this chunk
-                    } else {
-                        trace!("Last entry in chunk is prior to target - early exit");
-                        continue 'find_start; // early exit
-                    }
-                } else {
-                    found_entries_after_start = true
+    ) -> bool {
+        for buffer in items {
+            match RetrievedLogEntry::from_buffer(buffer) {
+                Ok(entry) => {
+                    trace!("Sending record");
+                    publisher.publish(HistoricalLogMessage::Record(entry)).await;
                 }
-                chunk_start -= 1;
-                if chunk_idx == 0 {
-                    trace!("Found start in first entry of chunk");
-                    break 'find_start; // we found the start point in this chunk, it is the first entry
+                Err(e) => {
+                    error!("Error parsing record: {}", Debug2Format(&e));
+                    publisher.publish(HistoricalLogMessage::Error()).await;
+                    return Self::end_of_read(publisher).await;
                 }
             }
         }
-        trace!("Found start point - skipped {} entries", chunk_start);
 
-        let mut total_count = 0;
-        'transfer_entries: loop {
-            // get the log data and push it over the channel
-            let retrieved_count = storage
-                .get_log_items(config, chunk_start, MAX_READ_CHUNK_SIZE, &mut temp_buffer)
-                .await
-                .unwrap_or(0);
-            total_count += retrieved_count;
-            if retrieved_count == 0 {
-                break 'transfer_entries;
-            }
-
-            for chunk_idx in 0..retrieved_count {
-                let entry = RetrievedLogEntry::from_buffer(&temp_buffer[chunk_idx]);
-                if entry.is_err() {
-                    error!(
-                        "Error parsing record: {}",
-                        Debug2Format(&entry.unwrap_err())
-                    );
-                    let message = HistoricalLogMessage::Error();
-                    publisher.publish(message).await;
-                    break 'transfer_entries;
-                }
-
-                trace!("Sending record");
-                let message = HistoricalLogMessage::Record(entry?);
-                publisher.publish(message).await;
-            }
-
-            chunk_start += retrieved_count;
+        if items.len() < MAX_READ_CHUNK_SIZE {
+            return Self::end_of_read(publisher).await;
         }
+        true
+    }
 
+    async fn end_of_read(publisher: HistoricalLogChannelPublisher<'_>) -> bool {
         trace!("Signalling end of data read");
-        let message = HistoricalLogMessage::EndOfRead();
-        publisher.publish(message).await;
-
-        Ok(total_count)
+        publisher.publish(HistoricalLogMessage::EndOfRead()).await;
+        false
     }
 }
 
+/// Processes the queued log writes, then sends one chunk of the current read. A read streams its
+/// entries to a subscriber so can take a while, so it is served a chunk per pass and signals for
+/// another pass until it is done. Neither the log store nor the storage is locked while entries
+/// are published, so other storage work queued in the meantime is not held up behind the read.
+/// Further reads wait until the current one is done.
 pub async fn process_log_queues() {
-    let mut log_store = LOG_STORE.lock().await;
-    log_store.process_write_queue().await.unwrap_or_else(|e| {
-        error!("Error processing write log queue: {}", e);
-    });
-
-    log_store.process_read_queue().await.unwrap_or_else(|e| {
-        error!("Error processing read log queue: {}", e);
-    });
+    let mut items = [[0u8; DATA_BUFFER_SIZE]; MAX_READ_CHUNK_SIZE];
+    let next_read = {
+        let mut log_store = LOG_STORE.lock().await;
+        log_store.process_write_queue().await.unwrap_or_else(|e| {
+            error!("Error processing write log queue: {}", e);
+        });
+        log_store.read_next_chunk(&mut items).await
+    };
+
+    if let Some((queue_entry, count)) = next_read {
+        let more = HistoricalLogManager::send_chunk_to_channel(
+            &items[..count],
+            queue_entry.log_channel.publisher().unwrap(),
+        )
+        .await;
+
+        let mut log_store = LOG_STORE.lock().await;
+        if more {
+            log_store.continue_read(queue_entry);
+        } else {
+            log_store.end_read();
+        }
+    }
 }
@@ -13,9 +13,12 @@
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
 pub mod accessor;
+pub mod index;
 pub mod log_config;
 pub mod manager;
 pub mod messaging;
+pub mod record;
+pub mod timestamp;
 
 use crate::storage::StoredDataValue;
 use defmt::{error, Debug2Format};
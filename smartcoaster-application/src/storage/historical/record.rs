@@ -0,0 +1,127 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Header written at the start of a log record, after the timestamp, so the record can say what it
+//! is and which layout it was written with. Decoders use the version to read records written by
+//! older firmware.
+
+use crate::storage::historical::LogEncodeDecodeError;
+use defmt::{error, trace, Format};
+
+/// First byte of a record header. It is not a `StoredDataValue` type byte, which is what records
+/// written before headers were added start with.
+const RECORD_HEADER_MARKER: u8 = 0xC5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub enum LogRecordType {
+    Consumption,
+    DailyRollup,
+    Error,
+}
+
+impl TryFrom<u8> for LogRecordType {
+    type Error = LogEncodeDecodeError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::Consumption),
+            2 => Ok(Self::DailyRollup),
+            3 => Ok(Self::Error),
+            _ => {
+                error!("Unknown log record type {}", value);
+                Err(LogEncodeDecodeError::DecodeFailed)
+            }
+        }
+    }
+}
+
+impl From<LogRecordType> for u8 {
+    fn from(value: LogRecordType) -> Self {
+        match value {
+            LogRecordType::Consumption => 1,
+            LogRecordType::DailyRollup => 2,
+            LogRecordType::Error => 3,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub struct RecordHeader {
+    pub record_type: LogRecordType,
+    pub version: u8,
+}
+
+impl RecordHeader {
+    pub const SIZE: usize = 3;
+
+    pub fn new(record_type: LogRecordType, version: u8) -> Self {
+        Self {
+            record_type,
+            version,
+        }
+    }
+
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, LogEncodeDecodeError> {
+        if buf.len() < Self::SIZE {
+            return Err(LogEncodeDecodeError::BufferTooSmall);
+        }
+        buf[0] = RECORD_HEADER_MARKER;
+        buf[1] = self.record_type.into();
+        buf[2] = self.version;
+        Ok(Self::SIZE)
+    }
+
+    /// Reads the header at the start of `buf`. Records written before headers were added have
+    /// none, so give `None`.
+    pub fn decode(buf: &[u8]) -> Result<Option<Self>, LogEncodeDecodeError> {
+        if buf.first() != Some(&RECORD_HEADER_MARKER) {
+            return Ok(None);
+        }
+        if buf.len() < Self::SIZE {
+            return Err(LogEncodeDecodeError::BufferTooSmall);
+        }
+        Ok(Some(Self {
+            record_type: buf[1].try_into()?,
+            version: buf[2],
+        }))
+    }
+
+    /// Checks the record at the start of `buf` is a `record_type` record, giving the layout
+    /// version it was written with and the bytes after its header. Records without a header are
+    /// version 0.
+    pub fn split_record(
+        buf: &[u8],
+        record_type: LogRecordType,
+        current_version: u8,
+    ) -> Result<(u8, &[u8]), LogEncodeDecodeError> {
+        match Self::decode(buf)? {
+            None => Ok((0, buf)),
+            Some(header) if header.record_type == record_type => {
+                if header.version > current_version {
+                    trace!(
+                        "{} record version {} is newer than {}, reading known fields",
+                        record_type,
+                        header.version,
+                        current_version
+                    );
+                }
+                Ok((header.version, &buf[Self::SIZE..]))
+            }
+            Some(header) => {
+                error!("Not a {} record: {}", record_type, header);
+                Err(LogEncodeDecodeError::DecodeFailed)
+            }
+        }
+    }
+}
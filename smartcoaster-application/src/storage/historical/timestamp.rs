@@ -0,0 +1,90 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Timestamp written at the start of every log entry.
+//!
+//! Entries are stamped with seconds since [`compact_epoch`] in 4 bytes, with the top bit set.
+//! Entries written by older firmware used 10 bytes: year, month, day, hour, minute, second and
+//! nanoseconds. The fourth byte of those is the day of the month so never has the top bit set,
+//! which is how the two are told apart.
+
+use crate::storage::settings::StorageError;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+use defmt::{error, warn, Debug2Format};
+
+pub const COMPACT_TIMESTAMP_SIZE: usize = 4;
+const LEGACY_TIMESTAMP_SIZE: usize = 10;
+const COMPACT_TIMESTAMP_FLAG: u32 = 0x8000_0000;
+/// Largest timestamp that can be stored, early 2068.
+const COMPACT_TIMESTAMP_MAX_SECONDS: u32 = !COMPACT_TIMESTAMP_FLAG;
+
+/// Compact timestamps count seconds from the start of 2000.
+pub fn compact_epoch() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2000, 1, 1)
+        .unwrap()
+        .and_time(NaiveTime::MIN)
+}
+
+/// Writes `timestamp` to the start of `buf`, giving the number of bytes used. Sub-second precision
+/// is dropped, and times outside the range that can be stored are clamped to it.
+pub fn encode_timestamp(timestamp: NaiveDateTime, buf: &mut [u8]) -> usize {
+    let seconds = (timestamp - compact_epoch()).num_seconds();
+    let seconds = if seconds < 0 || seconds > COMPACT_TIMESTAMP_MAX_SECONDS as i64 {
+        warn!(
+            "Log timestamp {} out of range, clamping",
+            Debug2Format(&timestamp)
+        );
+        seconds.clamp(0, COMPACT_TIMESTAMP_MAX_SECONDS as i64)
+    } else {
+        seconds
+    };
+    let stored = COMPACT_TIMESTAMP_FLAG | seconds as u32;
+    buf[..COMPACT_TIMESTAMP_SIZE].copy_from_slice(&stored.to_le_bytes());
+    COMPACT_TIMESTAMP_SIZE
+}
+
+/// Reads the timestamp at the start of `buf` in either format, giving it with the number of bytes
+/// it used.
+pub fn decode_timestamp(buf: &[u8]) -> Result<(NaiveDateTime, usize), StorageError> {
+    if buf.len() < COMPACT_TIMESTAMP_SIZE {
+        error!("Not enough bytes for a timestamp - got {}", buf.len());
+        return Err(StorageError::DecodeError);
+    }
+    let stored = u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]);
+    if stored & COMPACT_TIMESTAMP_FLAG != 0 {
+        let seconds = stored & !COMPACT_TIMESTAMP_FLAG;
+        let timestamp = compact_epoch() + TimeDelta::seconds(seconds as i64);
+        return Ok((timestamp, COMPACT_TIMESTAMP_SIZE));
+    }
+    Ok((decode_legacy_timestamp(buf)?, LEGACY_TIMESTAMP_SIZE))
+}
+
+fn decode_legacy_timestamp(buf: &[u8]) -> Result<NaiveDateTime, StorageError> {
+    if buf.len() < LEGACY_TIMESTAMP_SIZE {
+        error!(
+            "Not enough bytes for a legacy timestamp - got {}",
+            buf.len()
+        );
+        return Err(StorageError::DecodeError);
+    }
+    let year = u16::from_le_bytes([buf[0], buf[1]]);
+    let month = buf[2] as u32;
+    let day = buf[3] as u32;
+    let nano = u32::from_le_bytes([buf[7], buf[8], buf[9], 0]);
+
+    let date = NaiveDate::from_ymd_opt(year as i32, month, day).ok_or(StorageError::DecodeError)?;
+    let time = NaiveTime::from_hms_nano_opt(buf[4] as u32, buf[5] as u32, buf[6] as u32, nano)
+        .ok_or(StorageError::DecodeError)?;
+    Ok(NaiveDateTime::new(date, time))
+}
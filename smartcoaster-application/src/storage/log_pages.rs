@@ -0,0 +1,157 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Reading a log from part way through without reading the entries before.
+//!
+//! sequential-storage iterates a log from its oldest entry and has no way to start elsewhere. The
+//! log is written a page at a time though, in turn round its storage range, and the iterator
+//! starts from the first written page after an erased one. Flash that shows the pages before a
+//! given page as erased therefore gives a log that starts at that page, and the entries in those
+//! pages are passed over without reading them. Showing a single page the same way gives the
+//! entries in it.
+//!
+//! This relies on how sequential-storage finds its oldest page, so `historical::index` checks
+//! that the entries read through a view are the ones it expects, and reads the whole log if not.
+
+use crate::FLASH_ERASE_SIZE;
+use core::ops::Range;
+use embedded_storage_async::nor_flash::{
+    ErrorType, NorFlash, NorFlashError, NorFlashErrorKind, ReadNorFlash,
+};
+
+const ERASED: u8 = 0xFF;
+
+/// Set of the pages of a log, by their index in the log's storage range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogPages(u32);
+
+impl LogPages {
+    /// Most pages a log can have for them to be chosen from.
+    pub const MAX_PAGES: usize = u32::BITS as usize;
+    pub const ALL: Self = Self(u32::MAX);
+
+    pub const fn only(page: usize) -> Self {
+        Self(1 << page)
+    }
+
+    pub const fn without(self, page: usize) -> Self {
+        Self(self.0 & !(1 << page))
+    }
+
+    pub const fn contains(&self, page: usize) -> bool {
+        self.0 & (1 << page) != 0
+    }
+
+    /// Number of pages in the log with `storage_range`.
+    pub fn page_count(storage_range: &Range<u32>) -> usize {
+        storage_range.len() / FLASH_ERASE_SIZE
+    }
+}
+
+/// Flash where the pages of a log not in `pages` read as erased. It is only for reading: what it
+/// shows is not what is in flash, so writes and erases through it fail rather than change flash,
+/// e.g. if sequential-storage tries to repair what it sees.
+pub struct LogPagesFlash<'a, F> {
+    flash: &'a mut F,
+    storage_range: Range<u32>,
+    pages: LogPages,
+}
+
+impl<'a, F: NorFlash> LogPagesFlash<'a, F> {
+    pub fn new(flash: &'a mut F, storage_range: Range<u32>, pages: LogPages) -> Self {
+        debug_assert!(LogPages::page_count(&storage_range) <= LogPages::MAX_PAGES);
+        Self {
+            flash,
+            storage_range,
+            pages,
+        }
+    }
+
+    fn is_hidden(&self, offset: u32) -> bool {
+        self.storage_range.contains(&offset)
+            && !self
+                .pages
+                .contains((offset - self.storage_range.start) as usize / FLASH_ERASE_SIZE)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogPagesError<E> {
+    Flash(E),
+    /// A write or erase was made through the read only view.
+    ReadOnly,
+}
+
+impl<E: NorFlashError> NorFlashError for LogPagesError<E> {
+    fn kind(&self) -> NorFlashErrorKind {
+        match self {
+            LogPagesError::Flash(e) => e.kind(),
+            LogPagesError::ReadOnly => NorFlashErrorKind::Other,
+        }
+    }
+}
+
+impl<F: NorFlash> ErrorType for LogPagesFlash<'_, F> {
+    type Error = LogPagesError<F::Error>;
+}
+
+impl<F: NorFlash> ReadNorFlash for LogPagesFlash<'_, F> {
+    const READ_SIZE: usize = F::READ_SIZE;
+
+    async fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        if self.pages == LogPages::ALL {
+            return self
+                .flash
+                .read(offset, bytes)
+                .await
+                .map_err(LogPagesError::Flash);
+        }
+
+        // a read can cross into the next page, so each page it covers is dealt with in turn
+        let mut done = 0;
+        while done < bytes.len() {
+            let address = offset + done as u32;
+            let page_end = (address as usize / FLASH_ERASE_SIZE + 1) * FLASH_ERASE_SIZE;
+            let length = (page_end - address as usize).min(bytes.len() - done);
+            let part = &mut bytes[done..done + length];
+            if self.is_hidden(address) {
+                part.fill(ERASED);
+            } else {
+                self.flash
+                    .read(address, part)
+                    .await
+                    .map_err(LogPagesError::Flash)?;
+            }
+            done += length;
+        }
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        self.flash.capacity()
+    }
+}
+
+impl<F: NorFlash> NorFlash for LogPagesFlash<'_, F> {
+    const WRITE_SIZE: usize = F::WRITE_SIZE;
+    const ERASE_SIZE: usize = F::ERASE_SIZE;
+
+    async fn erase(&mut self, _from: u32, _to: u32) -> Result<(), Self::Error> {
+        Err(LogPagesError::ReadOnly)
+    }
+
+    async fn write(&mut self, _offset: u32, _bytes: &[u8]) -> Result<(), Self::Error> {
+        Err(LogPagesError::ReadOnly)
+    }
+}
@@ -15,9 +15,18 @@
 use chrono::{Datelike, Timelike};
 use sequential_storage::map::{SerializationError, Value};
 
+pub mod export;
+pub mod factory_reset;
 pub mod historical;
+pub mod log_pages;
+#[cfg(not(target_os = "none"))]
+pub mod ram_flash;
+pub mod restart;
 pub mod settings;
+pub mod signal;
+pub mod status;
 pub mod storage_manager;
+pub mod wear;
 
 #[repr(u8)]
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -0,0 +1,140 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Flash held in RAM, used in place of the RP2040 flash when the application is built for a host.
+//! Faults can be injected to test how storage copes with power loss and failing flash.
+
+use embedded_storage::nor_flash::{
+    ErrorType, MultiwriteNorFlash, NorFlash, NorFlashErrorKind, ReadNorFlash, check_erase,
+    check_read, check_write,
+};
+
+/// NOR flash of `SIZE` bytes held in RAM. Follows NOR semantics so a write can only clear bits and
+/// an erase sets a whole sector back to `0xFF`. The geometry matches the RP2040 flash.
+pub struct RamFlash<const SIZE: usize> {
+    data: [u8; SIZE],
+    /// Bytes that can be written before power is lost.
+    write_budget: Option<usize>,
+    failing_erases: usize,
+    reads: usize,
+}
+
+impl<const SIZE: usize> RamFlash<SIZE> {
+    /// Creates an erased flash.
+    pub const fn new() -> Self {
+        Self {
+            data: [0xFF; SIZE],
+            write_budget: None,
+            failing_erases: 0,
+            reads: 0,
+        }
+    }
+
+    /// Raw contents of the flash.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Replaces the contents from `offset` with `image`, e.g. flash captured from a device.
+    pub fn load_image(&mut self, offset: usize, image: &[u8]) {
+        self.data[offset..offset + image.len()].copy_from_slice(image);
+    }
+
+    /// Loses power once `bytes` more bytes have been written. The write in progress at that point
+    /// is left part written and fails. Power is then back, so later operations succeed.
+    pub fn lose_power_after(&mut self, bytes: usize) {
+        self.write_budget = Some(bytes);
+    }
+
+    /// Fails the next `count` erases, leaving the flash unchanged.
+    pub fn fail_erases(&mut self, count: usize) {
+        self.failing_erases = count;
+    }
+
+    /// Number of reads made since the flash was created, for measuring how much work storage does.
+    pub fn read_count(&self) -> usize {
+        self.reads
+    }
+
+    /// Inverts a single bit of the stored data, as a cell losing or gaining charge would.
+    pub fn flip_bit(&mut self, offset: usize, bit: u8) {
+        self.data[offset] ^= 1 << bit;
+    }
+}
+
+impl<const SIZE: usize> Default for RamFlash<SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const SIZE: usize> ErrorType for RamFlash<SIZE> {
+    type Error = NorFlashErrorKind;
+}
+
+impl<const SIZE: usize> ReadNorFlash for RamFlash<SIZE> {
+    const READ_SIZE: usize = 1;
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        check_read(self, offset, bytes.len())?;
+        self.reads += 1;
+        let start = offset as usize;
+        bytes.copy_from_slice(&self.data[start..start + bytes.len()]);
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        SIZE
+    }
+}
+
+impl<const SIZE: usize> NorFlash for RamFlash<SIZE> {
+    const WRITE_SIZE: usize = 1;
+    const ERASE_SIZE: usize = 4096;
+
+    fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+        check_erase(self, from, to)?;
+        if self.failing_erases > 0 {
+            self.failing_erases -= 1;
+            return Err(NorFlashErrorKind::Other);
+        }
+        self.data[from as usize..to as usize].fill(0xFF);
+        Ok(())
+    }
+
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+        check_write(self, offset, bytes.len())?;
+        let written = match self.write_budget {
+            Some(budget) if budget < bytes.len() => {
+                self.write_budget = None;
+                budget
+            }
+            Some(budget) => {
+                self.write_budget = Some(budget - bytes.len());
+                bytes.len()
+            }
+            None => bytes.len(),
+        };
+        let start = offset as usize;
+        for (cell, byte) in self.data[start..start + written].iter_mut().zip(bytes) {
+            *cell &= *byte;
+        }
+        if written < bytes.len() {
+            return Err(NorFlashErrorKind::Other);
+        }
+        Ok(())
+    }
+}
+
+impl<const SIZE: usize> MultiwriteNorFlash for RamFlash<SIZE> {}
@@ -0,0 +1,49 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Restarting the device from the settings menu. Settings saves and erase count saves are held back
+//! before being written, so the storage task writes everything still queued and then resets. The
+//! bootloader checks the button as the device starts, so holding it down through the restart
+//! enters DFU mode just as at power on.
+
+use crate::storage::settings::accessor::flush_settings;
+use crate::storage::signal::signal_storage_work;
+use crate::storage::storage_manager::NV_STORAGE;
+use defmt::{info, warn};
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::signal::Signal;
+
+static RESTART_REQUEST: Signal<CriticalSectionRawMutex, ()> = Signal::new();
+
+/// Asks the storage task to write what is queued and restart the device.
+pub fn request_restart() {
+    info!("Restart requested");
+    RESTART_REQUEST.signal(());
+    signal_storage_work();
+}
+
+/// Takes the pending restart request, giving true if there was one.
+pub fn take_restart_request() -> bool {
+    RESTART_REQUEST.try_take().is_some()
+}
+
+/// Writes the queued settings saves and the erase counts now rather than when they are due, so
+/// nothing is lost when the device resets.
+pub async fn prepare_for_restart() {
+    flush_settings().await;
+    let mut storage = NV_STORAGE.lock().await;
+    if let Err(e) = storage.save_erase_counts().await {
+        warn!("Unable to save NVM erase counts: {}", e);
+    }
+}
@@ -12,12 +12,15 @@
 // You should have received a copy of the GNU General Public License along with
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use crate::drink_monitor::beverage::BeverageType;
 use crate::storage::settings::messaging::{SettingData, SettingsMessage};
+use crate::storage::settings::schema::keys;
 use crate::storage::settings::settings_store::{StoredSettings, SETTINGS_STORE};
+pub use crate::storage::settings::settings_store::{SAVE_DEBOUNCE, SAVE_MAX_DELAY};
 use crate::storage::settings::{SettingError, SettingValue, SettingsAccessor, SettingsAccessorId};
 use crate::storage::storage_manager::wait_for_storage_initialisation;
 use defmt::{error, trace};
-use embassy_time::{Duration, Timer};
+use embassy_time::{Duration, Instant, Timer};
 
 pub struct FlashSettingsAccessor {}
 
@@ -33,41 +36,83 @@ impl SettingsAccessor for FlashSettingsAccessor {
     async fn get_setting(&self, setting: SettingsAccessorId) -> Option<SettingValue> {
         wait_for_settings_initialisation().await;
         let settings = SETTINGS_STORE.lock().await;
-        match setting {
-            SettingsAccessorId::SystemLedBrightness => settings.get_setting(
-                StoredSettings::SystemLedBrightness(SettingValue::Default).discriminant(),
-            ),
-            SettingsAccessorId::SystemDisplayBrightness => settings.get_setting(
-                StoredSettings::SystemDisplayBrightness(SettingValue::Default).discriminant(),
-            ),
-            SettingsAccessorId::WeighingSystemTareOffset => settings.get_setting(
-                StoredSettings::WeighingSystemTareOffset(SettingValue::Default).discriminant(),
-            ),
-            SettingsAccessorId::WeighingSystemCalibrationGradient => settings.get_setting(
-                StoredSettings::WeighingSystemCalibrationGradient(SettingValue::Default)
-                    .discriminant(),
-            ),
-            SettingsAccessorId::WeighingSystemBitsToDiscard => settings.get_setting(
-                StoredSettings::WeighingSystemBitsToDiscard(SettingValue::Default).discriminant(),
-            ),
-            SettingsAccessorId::MonitoringTargetType => settings.get_setting(
-                StoredSettings::MonitoringTargetType(SettingValue::Default).discriminant(),
-            ),
-            SettingsAccessorId::MonitoringTargetDaily => settings.get_setting(
-                StoredSettings::MonitoringTargetDaily(SettingValue::Default).discriminant(),
-            ),
-            SettingsAccessorId::DisplayTimeoutMinutes => settings.get_setting(
-                StoredSettings::DisplayTimeoutMinutes(SettingValue::Default).discriminant(),
-            ),
-            SettingsAccessorId::MonitoringDailyTargetTime => settings.get_setting(
-                StoredSettings::MonitoringDailyTargetTime(SettingValue::Default).discriminant(),
-            ),
-            SettingsAccessorId::MonitoringTargetHourly => settings.get_setting(
-                StoredSettings::MonitoringTargetHourly(SettingValue::Default).discriminant(),
-            ),
-            SettingsAccessorId::MonitoringDisplayIndex => settings.get_setting(
-                StoredSettings::MonitoringDisplayIndex(SettingValue::Default).discriminant(),
-            ),
+        match setting.resolve_alias() {
+            SettingsAccessorId::SystemLedBrightness => {
+                settings.get_setting(keys::SYSTEM_LED_BRIGHTNESS)
+            }
+            SettingsAccessorId::SystemDisplayBrightness => {
+                settings.get_setting(keys::SYSTEM_DISPLAY_BRIGHTNESS)
+            }
+            SettingsAccessorId::WeighingSystemTareOffset => {
+                settings.get_setting(keys::WEIGHING_SYSTEM_TARE_OFFSET)
+            }
+            SettingsAccessorId::WeighingSystemCalibrationGradient => {
+                settings.get_setting(keys::WEIGHING_SYSTEM_CALIBRATION_GRADIENT)
+            }
+            SettingsAccessorId::WeighingSystemBitsToDiscard => {
+                settings.get_setting(keys::WEIGHING_SYSTEM_BITS_TO_DISCARD)
+            }
+            SettingsAccessorId::MonitoringTargetType => {
+                settings.get_setting(keys::MONITORING_TARGET_TYPE)
+            }
+            SettingsAccessorId::MonitoringTargetDaily => {
+                settings.get_setting(keys::MONITORING_TARGET_DAILY)
+            }
+            SettingsAccessorId::DisplayTimeoutMinutes => {
+                settings.get_setting(keys::DISPLAY_TIMEOUT_MINUTES)
+            }
+            SettingsAccessorId::MonitoringDailyTargetTime => {
+                settings.get_setting(keys::MONITORING_DAILY_TARGET_TIME)
+            }
+            SettingsAccessorId::MonitoringTargetHourly => {
+                settings.get_setting(keys::MONITORING_TARGET_HOURLY)
+            }
+            SettingsAccessorId::MonitoringDisplayIndex => {
+                settings.get_setting(keys::MONITORING_DISPLAY_INDEX)
+            }
+            SettingsAccessorId::MonitoringDayStartTime => {
+                settings.get_setting(keys::MONITORING_DAY_START_TIME)
+            }
+            SettingsAccessorId::MonitoringDrinkingWindowStart => {
+                settings.get_setting(keys::MONITORING_DRINKING_WINDOW_START)
+            }
+            SettingsAccessorId::MonitoringPacingProfile => {
+                settings.get_setting(keys::MONITORING_PACING_PROFILE)
+            }
+            SettingsAccessorId::MonitoringTargetDirection => {
+                settings.get_setting(keys::MONITORING_TARGET_DIRECTION)
+            }
+            SettingsAccessorId::MonitoringActiveBeverage => {
+                settings.get_setting(keys::MONITORING_ACTIVE_BEVERAGE)
+            }
+            SettingsAccessorId::BeverageTargetDaily(beverage) => match beverage {
+                BeverageType::Water => settings.get_setting(keys::MONITORING_TARGET_DAILY),
+                BeverageType::Coffee => settings.get_setting(keys::COFFEE_TARGET_DAILY),
+                BeverageType::Tea => settings.get_setting(keys::TEA_TARGET_DAILY),
+                BeverageType::Other => settings.get_setting(keys::OTHER_TARGET_DAILY),
+            },
+            SettingsAccessorId::BeverageTargetDirection(beverage) => match beverage {
+                BeverageType::Water => settings.get_setting(keys::MONITORING_TARGET_DIRECTION),
+                BeverageType::Coffee => settings.get_setting(keys::COFFEE_TARGET_DIRECTION),
+                BeverageType::Tea => settings.get_setting(keys::TEA_TARGET_DIRECTION),
+                BeverageType::Other => settings.get_setting(keys::OTHER_TARGET_DIRECTION),
+            },
+            SettingsAccessorId::MonitoringReminderInterval => {
+                settings.get_setting(keys::MONITORING_REMINDER_INTERVAL)
+            }
+            SettingsAccessorId::MonitoringQuietStart => {
+                settings.get_setting(keys::MONITORING_QUIET_START)
+            }
+            SettingsAccessorId::MonitoringQuietEnd => {
+                settings.get_setting(keys::MONITORING_QUIET_END)
+            }
+            SettingsAccessorId::MonitoringCustomScreen => {
+                settings.get_setting(keys::MONITORING_CUSTOM_SCREEN)
+            }
+            SettingsAccessorId::SystemSetupComplete => {
+                settings.get_setting(keys::SYSTEM_SETUP_COMPLETE)
+            }
+            SettingsAccessorId::SystemUsbDrive => settings.get_setting(keys::SYSTEM_USB_DRIVE),
         }
     }
 
@@ -76,6 +121,7 @@ impl SettingsAccessor for FlashSettingsAccessor {
         setting: SettingsAccessorId,
         value: SettingValue,
     ) -> Result<(), Self::Error> {
+        let setting = setting.resolve_alias();
         let setting_obj = match setting {
             SettingsAccessorId::SystemLedBrightness => StoredSettings::SystemLedBrightness(value),
             SettingsAccessorId::SystemDisplayBrightness => {
@@ -106,6 +152,43 @@ impl SettingsAccessor for FlashSettingsAccessor {
             SettingsAccessorId::MonitoringDisplayIndex => {
                 StoredSettings::MonitoringDisplayIndex(value)
             }
+            SettingsAccessorId::MonitoringDayStartTime => {
+                StoredSettings::MonitoringDayStartTime(value)
+            }
+            SettingsAccessorId::MonitoringDrinkingWindowStart => {
+                StoredSettings::MonitoringDrinkingWindowStart(value)
+            }
+            SettingsAccessorId::MonitoringPacingProfile => {
+                StoredSettings::MonitoringPacingProfile(value)
+            }
+            SettingsAccessorId::MonitoringTargetDirection => {
+                StoredSettings::MonitoringTargetDirection(value)
+            }
+            SettingsAccessorId::MonitoringActiveBeverage => {
+                StoredSettings::MonitoringActiveBeverage(value)
+            }
+            SettingsAccessorId::BeverageTargetDaily(beverage) => match beverage {
+                BeverageType::Water => StoredSettings::MonitoringTargetDaily(value),
+                BeverageType::Coffee => StoredSettings::CoffeeTargetDaily(value),
+                BeverageType::Tea => StoredSettings::TeaTargetDaily(value),
+                BeverageType::Other => StoredSettings::OtherTargetDaily(value),
+            },
+            SettingsAccessorId::BeverageTargetDirection(beverage) => match beverage {
+                BeverageType::Water => StoredSettings::MonitoringTargetDirection(value),
+                BeverageType::Coffee => StoredSettings::CoffeeTargetDirection(value),
+                BeverageType::Tea => StoredSettings::TeaTargetDirection(value),
+                BeverageType::Other => StoredSettings::OtherTargetDirection(value),
+            },
+            SettingsAccessorId::MonitoringReminderInterval => {
+                StoredSettings::MonitoringReminderInterval(value)
+            }
+            SettingsAccessorId::MonitoringQuietStart => StoredSettings::MonitoringQuietStart(value),
+            SettingsAccessorId::MonitoringQuietEnd => StoredSettings::MonitoringQuietEnd(value),
+            SettingsAccessorId::MonitoringCustomScreen => {
+                StoredSettings::MonitoringCustomScreen(value)
+            }
+            SettingsAccessorId::SystemSetupComplete => StoredSettings::SystemSetupComplete(value),
+            SettingsAccessorId::SystemUsbDrive => StoredSettings::SystemUsbDrive(value),
         };
 
         let mut settings = SETTINGS_STORE.lock().await;
@@ -141,10 +224,26 @@ pub async fn wait_for_settings_initialisation() {
     }
 }
 
+/// Writes the queued settings saves that are due. Repeated saves to a setting are held back and
+/// written once, see `next_settings_save_due`.
 pub async fn process_save_queue() {
     let mut settings = SETTINGS_STORE.lock().await;
     let _ = settings
-        .process_queued_saves()
+        .process_queued_saves(false)
         .await
         .map_err(|e| error!("Unable to process queued settings_menu saves - {:?}", e));
 }
+
+/// Writes all the queued settings saves now, e.g. before the device resets.
+pub async fn flush_settings() {
+    let mut settings = SETTINGS_STORE.lock().await;
+    let _ = settings
+        .process_queued_saves(true)
+        .await
+        .map_err(|e| error!("Unable to flush queued settings_menu saves - {:?}", e));
+}
+
+/// When the storage task next needs to write a queued settings save.
+pub async fn next_settings_save_due() -> Option<Instant> {
+    SETTINGS_STORE.lock().await.next_save_due()
+}
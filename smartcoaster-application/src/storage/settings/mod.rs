@@ -12,6 +12,7 @@
 // You should have received a copy of the GNU General Public License along with
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use crate::drink_monitor::beverage::BeverageType;
 use crate::storage::StoredDataValue;
 use core::fmt::Debug;
 use core::future::Future;
@@ -20,7 +21,8 @@ use defmt::Format;
 pub mod accessor;
 pub mod messaging;
 pub mod monitor;
-mod settings_store;
+pub mod schema;
+pub(crate) mod settings_store;
 
 #[derive(Debug, Format)]
 pub enum SettingError {
@@ -59,6 +61,20 @@ pub enum SettingsAccessorId {
     MonitoringDailyTargetTime,
     MonitoringTargetHourly,
     MonitoringDisplayIndex,
+    MonitoringDayStartTime,
+    MonitoringDrinkingWindowStart,
+    MonitoringPacingProfile,
+    MonitoringTargetDirection,
+    MonitoringActiveBeverage,
+    BeverageTargetDaily(BeverageType),
+    BeverageTargetDirection(BeverageType),
+    MonitoringReminderInterval,
+    MonitoringQuietStart,
+    MonitoringQuietEnd,
+    MonitoringCustomScreen,
+    SystemSetupComplete,
+    /// Whether the coaster also shows up as a USB drive holding its data. Read when USB starts.
+    SystemUsbDrive,
 }
 
 impl SettingsAccessorId {
@@ -72,9 +88,36 @@ impl SettingsAccessorId {
                 minimum_value: 0,
                 maximum_value: 1000,
             }),
+            SettingsAccessorId::BeverageTargetDaily(_) => Some(NumericSettingProperties::<u32> {
+                minimum_value: 0,
+                maximum_value: 10000,
+            }),
             _ => None,
         }
     }
+
+    /// Stored value of a number entered for a numeric setting. The hourly target is stored as a
+    /// float, the others as unsigned integers.
+    pub fn numeric_value(&self, value: u32) -> SettingValue {
+        match self {
+            SettingsAccessorId::MonitoringTargetHourly => SettingValue::Float(value as f32),
+            _ => SettingValue::UInt(value),
+        }
+    }
+
+    /// Water is the primary beverage, so its per-beverage settings are the main monitoring
+    /// settings. Returns the ID the setting is actually stored and reported under.
+    pub fn resolve_alias(self) -> Self {
+        match self {
+            SettingsAccessorId::BeverageTargetDaily(BeverageType::Water) => {
+                SettingsAccessorId::MonitoringTargetDaily
+            }
+            SettingsAccessorId::BeverageTargetDirection(BeverageType::Water) => {
+                SettingsAccessorId::MonitoringTargetDirection
+            }
+            _ => self,
+        }
+    }
 }
 
 pub trait SettingsAccessor {
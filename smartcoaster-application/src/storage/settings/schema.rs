@@ -0,0 +1,208 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Layout of the settings in flash. Each setting is stored under a fixed key from [`keys`], and
+//! the schema version stored with them says how their values are to be read. When the layout
+//! changes the version is bumped and a migration is added to [`migrate_step`] to convert settings
+//! written by older firmware. Migrations run at boot, before the settings are loaded.
+
+use crate::hmi::screens::monitoring::DEBUG_SCREEN_INDEX;
+use crate::storage::settings::{SettingValue, StorageError};
+use crate::storage::storage_manager::StorageManager;
+use defmt::{info, warn};
+
+pub type SettingKey = u16;
+
+/// Version of the settings layout written by this firmware.
+///
+/// * 0 - before the version was stored.
+/// * 1 - adds the first run setup completion flag and moves the debug monitoring screen index.
+///   Stored value types are unchanged.
+/// * 2 - stores the hourly target as a float rather than an unsigned integer.
+pub const SETTINGS_SCHEMA_VERSION: u8 = 2;
+
+/// Flash keys of the settings. A key must never change or be reused for a different setting, as
+/// it is how settings written by older firmware are found.
+pub mod keys {
+    use super::SettingKey;
+
+    pub const WEIGHING_SYSTEM_TARE_OFFSET: SettingKey = 0;
+    pub const WEIGHING_SYSTEM_CALIBRATION_GRADIENT: SettingKey = 1;
+    pub const SYSTEM_LED_BRIGHTNESS: SettingKey = 2;
+    pub const SYSTEM_DISPLAY_BRIGHTNESS: SettingKey = 3;
+    pub const WEIGHING_SYSTEM_BITS_TO_DISCARD: SettingKey = 4;
+    pub const MONITORING_TARGET_TYPE: SettingKey = 5;
+    pub const MONITORING_TARGET_DAILY: SettingKey = 6;
+    pub const DISPLAY_TIMEOUT_MINUTES: SettingKey = 7;
+    pub const MONITORING_DAILY_TARGET_TIME: SettingKey = 8;
+    pub const MONITORING_TARGET_HOURLY: SettingKey = 9;
+    pub const MONITORING_DISPLAY_INDEX: SettingKey = 10;
+    pub const MONITORING_DAY_START_TIME: SettingKey = 11;
+    pub const MONITORING_DRINKING_WINDOW_START: SettingKey = 12;
+    pub const MONITORING_PACING_PROFILE: SettingKey = 13;
+    pub const MONITORING_TARGET_DIRECTION: SettingKey = 14;
+    pub const MONITORING_ACTIVE_BEVERAGE: SettingKey = 15;
+    pub const COFFEE_TARGET_DAILY: SettingKey = 16;
+    pub const COFFEE_TARGET_DIRECTION: SettingKey = 17;
+    pub const TEA_TARGET_DAILY: SettingKey = 18;
+    pub const TEA_TARGET_DIRECTION: SettingKey = 19;
+    pub const OTHER_TARGET_DAILY: SettingKey = 20;
+    pub const OTHER_TARGET_DIRECTION: SettingKey = 21;
+    pub const MONITORING_REMINDER_INTERVAL: SettingKey = 22;
+    pub const MONITORING_QUIET_START: SettingKey = 23;
+    pub const MONITORING_QUIET_END: SettingKey = 24;
+    pub const MONITORING_CUSTOM_SCREEN: SettingKey = 25;
+    pub const SYSTEM_SETUP_COMPLETE: SettingKey = 26;
+    pub const SYSTEM_USB_DRIVE: SettingKey = 27;
+
+    /// Holds the schema version rather than a setting.
+    pub const SCHEMA_VERSION: SettingKey = 0x8000;
+    /// Holds the NVM page erase counts rather than a setting, see `storage::wear`.
+    pub const NVM_ERASE_COUNTS: SettingKey = 0x8001;
+}
+
+/// Reads the schema version of the settings in `storage`. Storage with settings but no version
+/// was written before the version was stored, and is version 0. Empty storage has no version.
+pub async fn read_schema_version(
+    storage: &mut impl StorageManager,
+) -> Result<Option<u8>, StorageError> {
+    match storage
+        .read_key_value_pair::<SettingValue>(keys::SCHEMA_VERSION)
+        .await?
+    {
+        Some(SettingValue::SmallUInt(version)) => Ok(Some(version)),
+        Some(_) => Err(StorageError::DecodeError),
+        None if storage.get_key_value_usage().await?.live_keys == 0 => Ok(None),
+        None => Ok(Some(0)),
+    }
+}
+
+/// Marks the settings in `storage` as written by this firmware.
+pub async fn write_schema_version(storage: &mut impl StorageManager) -> Result<(), StorageError> {
+    storage
+        .save_key_value_pair(
+            keys::SCHEMA_VERSION,
+            SettingValue::SmallUInt(SETTINGS_SCHEMA_VERSION),
+        )
+        .await
+}
+
+/// Brings the settings in `storage` up to [`SETTINGS_SCHEMA_VERSION`] and returns the version
+/// they were at. The version is saved after each step, so a migration interrupted by power loss
+/// carries on from the step it was on. Settings from newer firmware, e.g. after a roll back, are
+/// left as they are.
+pub async fn migrate_settings(storage: &mut impl StorageManager) -> Result<u8, StorageError> {
+    let Some(found_version) = read_schema_version(storage).await? else {
+        write_schema_version(storage).await?;
+        return Ok(SETTINGS_SCHEMA_VERSION);
+    };
+
+    if found_version > SETTINGS_SCHEMA_VERSION {
+        warn!(
+            "Settings schema version {} is newer than {}, not migrating",
+            found_version, SETTINGS_SCHEMA_VERSION
+        );
+        return Ok(found_version);
+    }
+
+    for version in found_version..SETTINGS_SCHEMA_VERSION {
+        info!("Migrating settings from schema version {}", version);
+        migrate_step(storage, version).await?;
+        storage
+            .save_key_value_pair(keys::SCHEMA_VERSION, SettingValue::SmallUInt(version + 1))
+            .await?;
+    }
+    Ok(found_version)
+}
+
+/// Converts settings at schema `version` to `version + 1`. Each step may be run again if power is
+/// lost before the new version is saved.
+async fn migrate_step(storage: &mut impl StorageManager, version: u8) -> Result<(), StorageError> {
+    match version {
+        0 => {
+            mark_existing_setup_complete(storage).await?;
+            move_debug_screen_index(storage).await
+        }
+        1 => convert_hourly_target_to_float(storage).await,
+        _ => Ok(()),
+    }
+}
+
+/// Index of the debug monitoring screen in version 0, when it followed the four original screens.
+const V0_DEBUG_SCREEN_INDEX: u8 = 4;
+/// Index of the debug monitoring screen from version 1, after the screens added since.
+const V1_DEBUG_SCREEN_INDEX: u8 = 11;
+// Moving the debug screen again changes what a stored index means, so needs a new schema version
+// and a migration step rather than a change here.
+const _: () = assert!(
+    V1_DEBUG_SCREEN_INDEX == DEBUG_SCREEN_INDEX,
+    "debug screen moved, add a settings migration"
+);
+
+/// The selected monitoring screen is stored as its index in the screen list. New screens were
+/// added before the debug screen, so a device left on it would open on a different screen.
+async fn move_debug_screen_index(storage: &mut impl StorageManager) -> Result<(), StorageError> {
+    if let Some(SettingValue::SmallUInt(V0_DEBUG_SCREEN_INDEX)) = storage
+        .read_key_value_pair::<SettingValue>(keys::MONITORING_DISPLAY_INDEX)
+        .await?
+    {
+        storage
+            .save_key_value_pair(
+                keys::MONITORING_DISPLAY_INDEX,
+                SettingValue::SmallUInt(V1_DEBUG_SCREEN_INDEX),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+/// Devices that were calibrated and had a target chosen were set up before the first run setup
+/// existed, so should not be taken through it.
+async fn mark_existing_setup_complete(
+    storage: &mut impl StorageManager,
+) -> Result<(), StorageError> {
+    let calibrated = storage
+        .read_key_value_pair::<SettingValue>(keys::WEIGHING_SYSTEM_CALIBRATION_GRADIENT)
+        .await?
+        .is_some();
+    let target_chosen = storage
+        .read_key_value_pair::<SettingValue>(keys::MONITORING_TARGET_TYPE)
+        .await?
+        .is_some();
+    if calibrated && target_chosen {
+        storage
+            .save_key_value_pair(keys::SYSTEM_SETUP_COMPLETE, SettingValue::SmallUInt(1))
+            .await?;
+    }
+    Ok(())
+}
+
+/// The hourly target is a rate, and is kept as a float like the other rates. Targets already
+/// converted, when the step is run again after power loss, are left as they are.
+async fn convert_hourly_target_to_float(
+    storage: &mut impl StorageManager,
+) -> Result<(), StorageError> {
+    if let Some(SettingValue::UInt(target)) = storage
+        .read_key_value_pair::<SettingValue>(keys::MONITORING_TARGET_HOURLY)
+        .await?
+    {
+        storage
+            .save_key_value_pair(
+                keys::MONITORING_TARGET_HOURLY,
+                SettingValue::Float(target as f32),
+            )
+            .await?;
+    }
+    Ok(())
+}
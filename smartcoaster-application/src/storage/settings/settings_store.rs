@@ -12,47 +12,98 @@
 // You should have received a copy of the GNU General Public License along with
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use crate::error_log::{log_error, ErrorCode, ErrorLogData, ErrorModule};
 use crate::storage::settings::messaging::SettingsMessage;
 use crate::storage::settings::messaging::{SettingsChannelPublisher, SETTINGS_CHANNEL};
+use crate::storage::settings::schema::{keys, migrate_settings, SettingKey};
 use crate::storage::settings::{SettingError, SettingValue};
+use crate::storage::signal::signal_storage_work;
 use crate::storage::storage_manager::{StorageManager, NV_STORAGE};
 use defmt::{debug, warn, Debug2Format};
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use embassy_sync::mutex::Mutex;
-use heapless::spsc::Queue;
+use embassy_time::{Duration, Instant};
 use heapless::FnvIndexMap;
-use strum::{EnumCount, EnumIter, IntoEnumIterator};
+use strum::{EnumCount, EnumIter, IntoEnumIterator, IntoStaticStr};
 
 pub static SETTINGS_STORE: SettingsManagerMutex = Mutex::new(SettingsManager::new());
 
 pub type SettingsManagerMutex = Mutex<CriticalSectionRawMutex, SettingsManager>;
 
-type SettingKey = u16;
-#[repr(u16)]
-#[derive(Clone, PartialEq, EnumCount, EnumIter, Debug)]
+/// Settings kept in flash. The snake case name of each is used when exporting them.
+#[derive(Clone, PartialEq, EnumCount, EnumIter, IntoStaticStr, Debug)]
+#[strum(serialize_all = "snake_case")]
 pub enum StoredSettings {
-    WeighingSystemTareOffset(SettingValue) = 0,
-    WeighingSystemCalibrationGradient(SettingValue) = 1,
-    SystemLedBrightness(SettingValue) = 2,
-    SystemDisplayBrightness(SettingValue) = 3,
-    WeighingSystemBitsToDiscard(SettingValue) = 4,
-    MonitoringTargetType(SettingValue) = 5,
-    MonitoringTargetDaily(SettingValue) = 6,
-    DisplayTimeoutMinutes(SettingValue) = 7,
-    MonitoringDailyTargetTime(SettingValue) = 8,
-    MonitoringTargetHourly(SettingValue) = 9,
-    MonitoringDisplayIndex(SettingValue) = 10,
+    WeighingSystemTareOffset(SettingValue),
+    WeighingSystemCalibrationGradient(SettingValue),
+    SystemLedBrightness(SettingValue),
+    SystemDisplayBrightness(SettingValue),
+    WeighingSystemBitsToDiscard(SettingValue),
+    MonitoringTargetType(SettingValue),
+    MonitoringTargetDaily(SettingValue),
+    DisplayTimeoutMinutes(SettingValue),
+    MonitoringDailyTargetTime(SettingValue),
+    MonitoringTargetHourly(SettingValue),
+    MonitoringDisplayIndex(SettingValue),
+    MonitoringDayStartTime(SettingValue),
+    MonitoringDrinkingWindowStart(SettingValue),
+    MonitoringPacingProfile(SettingValue),
+    MonitoringTargetDirection(SettingValue),
+    MonitoringActiveBeverage(SettingValue),
+    CoffeeTargetDaily(SettingValue),
+    CoffeeTargetDirection(SettingValue),
+    TeaTargetDaily(SettingValue),
+    TeaTargetDirection(SettingValue),
+    OtherTargetDaily(SettingValue),
+    OtherTargetDirection(SettingValue),
+    MonitoringReminderInterval(SettingValue),
+    MonitoringQuietStart(SettingValue),
+    MonitoringQuietEnd(SettingValue),
+    MonitoringCustomScreen(SettingValue),
+    SystemSetupComplete(SettingValue),
+    SystemUsbDrive(SettingValue),
 }
 
 impl StoredSettings {
-    pub(crate) fn discriminant(&self) -> u16 {
-        // SAFETY: Because `Self` is marked `repr(u8)`, its layout is a `repr(C)` `union`
-        // between `repr(C)` structs, each of which has the `u8` discriminant as its first
-        // field, so we can read the discriminant without offsetting the pointer.
-        unsafe { *<*const _>::from(self).cast::<u16>() }
+    /// Key the setting is stored under in flash.
+    pub(crate) fn key(&self) -> SettingKey {
+        match self {
+            StoredSettings::WeighingSystemTareOffset(_) => keys::WEIGHING_SYSTEM_TARE_OFFSET,
+            StoredSettings::WeighingSystemCalibrationGradient(_) => {
+                keys::WEIGHING_SYSTEM_CALIBRATION_GRADIENT
+            }
+            StoredSettings::SystemLedBrightness(_) => keys::SYSTEM_LED_BRIGHTNESS,
+            StoredSettings::SystemDisplayBrightness(_) => keys::SYSTEM_DISPLAY_BRIGHTNESS,
+            StoredSettings::WeighingSystemBitsToDiscard(_) => keys::WEIGHING_SYSTEM_BITS_TO_DISCARD,
+            StoredSettings::MonitoringTargetType(_) => keys::MONITORING_TARGET_TYPE,
+            StoredSettings::MonitoringTargetDaily(_) => keys::MONITORING_TARGET_DAILY,
+            StoredSettings::DisplayTimeoutMinutes(_) => keys::DISPLAY_TIMEOUT_MINUTES,
+            StoredSettings::MonitoringDailyTargetTime(_) => keys::MONITORING_DAILY_TARGET_TIME,
+            StoredSettings::MonitoringTargetHourly(_) => keys::MONITORING_TARGET_HOURLY,
+            StoredSettings::MonitoringDisplayIndex(_) => keys::MONITORING_DISPLAY_INDEX,
+            StoredSettings::MonitoringDayStartTime(_) => keys::MONITORING_DAY_START_TIME,
+            StoredSettings::MonitoringDrinkingWindowStart(_) => {
+                keys::MONITORING_DRINKING_WINDOW_START
+            }
+            StoredSettings::MonitoringPacingProfile(_) => keys::MONITORING_PACING_PROFILE,
+            StoredSettings::MonitoringTargetDirection(_) => keys::MONITORING_TARGET_DIRECTION,
+            StoredSettings::MonitoringActiveBeverage(_) => keys::MONITORING_ACTIVE_BEVERAGE,
+            StoredSettings::CoffeeTargetDaily(_) => keys::COFFEE_TARGET_DAILY,
+            StoredSettings::CoffeeTargetDirection(_) => keys::COFFEE_TARGET_DIRECTION,
+            StoredSettings::TeaTargetDaily(_) => keys::TEA_TARGET_DAILY,
+            StoredSettings::TeaTargetDirection(_) => keys::TEA_TARGET_DIRECTION,
+            StoredSettings::OtherTargetDaily(_) => keys::OTHER_TARGET_DAILY,
+            StoredSettings::OtherTargetDirection(_) => keys::OTHER_TARGET_DIRECTION,
+            StoredSettings::MonitoringReminderInterval(_) => keys::MONITORING_REMINDER_INTERVAL,
+            StoredSettings::MonitoringQuietStart(_) => keys::MONITORING_QUIET_START,
+            StoredSettings::MonitoringQuietEnd(_) => keys::MONITORING_QUIET_END,
+            StoredSettings::MonitoringCustomScreen(_) => keys::MONITORING_CUSTOM_SCREEN,
+            StoredSettings::SystemSetupComplete(_) => keys::SYSTEM_SETUP_COMPLETE,
+            StoredSettings::SystemUsbDrive(_) => keys::SYSTEM_USB_DRIVE,
+        }
     }
 
-    fn value(&self) -> SettingValue {
+    pub(crate) fn value(&self) -> SettingValue {
         match self {
             StoredSettings::WeighingSystemTareOffset(v) => v.clone(),
             StoredSettings::WeighingSystemCalibrationGradient(v) => v.clone(),
@@ -65,10 +116,44 @@ impl StoredSettings {
             StoredSettings::MonitoringDailyTargetTime(v) => v.clone(),
             StoredSettings::MonitoringTargetHourly(v) => v.clone(),
             StoredSettings::MonitoringDisplayIndex(v) => v.clone(),
+            StoredSettings::MonitoringDayStartTime(v) => v.clone(),
+            StoredSettings::MonitoringDrinkingWindowStart(v) => v.clone(),
+            StoredSettings::MonitoringPacingProfile(v) => v.clone(),
+            StoredSettings::MonitoringTargetDirection(v) => v.clone(),
+            StoredSettings::MonitoringActiveBeverage(v) => v.clone(),
+            StoredSettings::CoffeeTargetDaily(v) => v.clone(),
+            StoredSettings::CoffeeTargetDirection(v) => v.clone(),
+            StoredSettings::TeaTargetDaily(v) => v.clone(),
+            StoredSettings::TeaTargetDirection(v) => v.clone(),
+            StoredSettings::OtherTargetDaily(v) => v.clone(),
+            StoredSettings::OtherTargetDirection(v) => v.clone(),
+            StoredSettings::MonitoringReminderInterval(v) => v.clone(),
+            StoredSettings::MonitoringQuietStart(v) => v.clone(),
+            StoredSettings::MonitoringQuietEnd(v) => v.clone(),
+            StoredSettings::MonitoringCustomScreen(v) => v.clone(),
+            StoredSettings::SystemSetupComplete(v) => v.clone(),
+            StoredSettings::SystemUsbDrive(v) => v.clone(),
         }
     }
 }
 
+/// Saves to a setting made within this time of each other are written to flash once.
+pub const SAVE_DEBOUNCE: Duration = Duration::from_secs(2);
+/// Longest a save waits to be written while the setting keeps changing.
+pub const SAVE_MAX_DELAY: Duration = Duration::from_secs(10);
+
+struct PendingSave {
+    setting: StoredSettings,
+    first_queued: Instant,
+    last_queued: Instant,
+}
+
+impl PendingSave {
+    fn due(&self) -> Instant {
+        (self.last_queued + SAVE_DEBOUNCE).min(self.first_queued + SAVE_MAX_DELAY)
+    }
+}
+
 pub struct SettingsManager {
     settings_cache: FnvIndexMap<
         SettingKey,
@@ -76,7 +161,9 @@ pub struct SettingsManager {
         { StoredSettings::COUNT.next_power_of_two() },
     >,
     settings_initialised: bool,
-    save_queue: Queue<StoredSettings, 5>,
+    /// Saves waiting to be written, at most one per setting.
+    pending_saves:
+        FnvIndexMap<SettingKey, PendingSave, { StoredSettings::COUNT.next_power_of_two() }>,
     settings_publisher: Option<SettingsChannelPublisher<'static>>,
 }
 
@@ -85,22 +172,21 @@ impl SettingsManager {
         Self {
             settings_cache: FnvIndexMap::new(),
             settings_initialised: false,
-            save_queue: Queue::new(),
+            pending_saves: FnvIndexMap::new(),
             settings_publisher: None,
         }
     }
 
     pub async fn initialise(&mut self) {
         self.settings_publisher = Some(SETTINGS_CHANNEL.publisher().unwrap());
+        self.migrate_schema().await;
 
         for setting in StoredSettings::iter() {
             let value = self.load_setting_from_flash(&setting).await;
             if let Ok(setting_value) = value {
-                let _ = self
-                    .settings_cache
-                    .insert(setting.discriminant(), setting_value);
+                let _ = self.settings_cache.insert(setting.key(), setting_value);
             } else {
-                let _ = self.settings_cache.insert(setting.discriminant(), None);
+                let _ = self.settings_cache.insert(setting.key(), None);
             }
         }
 
@@ -108,6 +194,19 @@ impl SettingsManager {
         debug!("Settings initialised");
     }
 
+    /// Converts settings written by older firmware before they are loaded. If that fails the
+    /// settings are still loaded, so any that were not converted are read as they are.
+    async fn migrate_schema(&mut self) {
+        let mut storage = NV_STORAGE.lock().await;
+        if let Err(e) = migrate_settings(&mut *storage).await {
+            warn!("Unable to migrate settings. Error: {:?}", e);
+            log_error(ErrorLogData::new(
+                ErrorModule::Storage,
+                ErrorCode::SettingsMigrationFailed,
+            ));
+        }
+    }
+
     pub fn is_initialized(&self) -> bool {
         self.settings_publisher.is_some() && self.settings_initialised
     }
@@ -120,30 +219,69 @@ impl SettingsManager {
 
         {
             let mut storage = NV_STORAGE.lock().await;
+            // changed and then changed back before it was written
+            let stored = storage
+                .read_key_value_pair::<SettingValue>(setting.key())
+                .await
+                .unwrap_or(None);
+            if stored.as_ref() == Some(&setting.value()) {
+                debug!("Setting unchanged - {}", Debug2Format(&setting));
+                return Ok(());
+            }
+
             storage
-                .save_key_value_pair(setting.discriminant(), setting.value())
+                .save_key_value_pair(setting.key(), setting.value())
                 .await
                 .map_err(|e| {
                     warn!("Unable to save setting. Error: {:?}", e);
+                    log_error(
+                        ErrorLogData::new(ErrorModule::Storage, ErrorCode::SettingSaveFailed)
+                            .with_value(setting.key() as u32),
+                    );
                     SettingError::SaveError
                 })?;
         }
 
         let _ = self
             .settings_cache
-            .insert(setting.discriminant(), Some(setting.value()));
+            .insert(setting.key(), Some(setting.value()));
         debug!("Setting saved - {}", Debug2Format(&setting));
 
         Ok(())
     }
 
-    pub async fn process_queued_saves(&mut self) -> Result<(), SettingError> {
-        while let Some(item) = self.save_queue.dequeue() {
-            self.save_setting(item).await?;
+    /// Writes the queued saves that are due, or all of them if `flush` is set. A save that fails
+    /// is queued again, to be retried once the debounce time has passed.
+    pub async fn process_queued_saves(&mut self, flush: bool) -> Result<(), SettingError> {
+        let now = Instant::now();
+        while let Some(key) = self
+            .pending_saves
+            .iter()
+            .find(|(_, pending)| flush || pending.due() <= now)
+            .map(|(key, _)| *key)
+        {
+            let pending = self.pending_saves.remove(&key).unwrap();
+            if let Err(e) = self.save_setting(pending.setting.clone()).await {
+                // the entry was just removed, so there is room for it
+                let _ = self.pending_saves.insert(
+                    key,
+                    PendingSave {
+                        setting: pending.setting,
+                        first_queued: now,
+                        last_queued: now,
+                    },
+                );
+                return Err(e);
+            }
         }
         Ok(())
     }
 
+    /// When the next queued save is due to be written.
+    pub fn next_save_due(&self) -> Option<Instant> {
+        self.pending_saves.values().map(PendingSave::due).min()
+    }
+
     async fn load_setting_from_flash(
         &mut self,
         setting: &StoredSettings,
@@ -158,7 +296,7 @@ impl SettingsManager {
         }
 
         let value = storage
-            .read_key_value_pair::<SettingValue>(setting.discriminant())
+            .read_key_value_pair::<SettingValue>(setting.key())
             .await
             .map_err(|e| {
                 warn!("Unable to load setting. Error: {:?}", e);
@@ -174,10 +312,28 @@ impl SettingsManager {
             .unwrap_or(None)
     }
 
+    /// Queues a save, replacing any save of the same setting still waiting to be written. The
+    /// new value is read back straight away.
     pub fn queue_settings_save(&mut self, setting: StoredSettings) -> Result<(), SettingError> {
-        self.save_queue
-            .enqueue(setting)
-            .map_err(|_| SettingError::SaveQueueFull)
+        let now = Instant::now();
+        let key = setting.key();
+        let first_queued = self
+            .pending_saves
+            .get(&key)
+            .map_or(now, |pending| pending.first_queued);
+        let _ = self.settings_cache.insert(key, Some(setting.value()));
+        self.pending_saves
+            .insert(
+                key,
+                PendingSave {
+                    setting,
+                    first_queued,
+                    last_queued: now,
+                },
+            )
+            .map_err(|_| SettingError::SaveQueueFull)?;
+        signal_storage_work();
+        Ok(())
     }
 
     pub fn alert_system(&mut self, message: SettingsMessage) {
@@ -0,0 +1,44 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Wakes the storage task when work is queued for it. Anything that queues a settings save, log
+//! write or read, error or factory reset signals here, and the storage task sleeps until it does
+//! or a held back settings or NVM erase count save is due.
+
+use defmt::trace;
+use embassy_futures::select::select;
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::signal::Signal;
+use embassy_time::{Instant, Timer};
+
+static STORAGE_WORK: Signal<CriticalSectionRawMutex, ()> = Signal::new();
+
+/// Tells the storage task there is work queued. Signals made before the task next waits are
+/// combined, so the task processes everything queued in one pass.
+pub fn signal_storage_work() {
+    STORAGE_WORK.signal(());
+}
+
+/// Waits until work is queued for the storage task, or until `deadline` if there is one.
+pub async fn wait_for_storage_work(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => {
+            select(STORAGE_WORK.wait(), Timer::at(deadline)).await;
+        }
+        None => {
+            STORAGE_WORK.wait().await;
+            trace!("Storage work queued");
+        }
+    }
+}
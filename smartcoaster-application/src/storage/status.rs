@@ -0,0 +1,210 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Usage of each NVM partition, for the storage status screen and USB query, and the wear of its
+//! pages.
+
+use crate::storage::historical::accessor::RetrievedLogEntry;
+use crate::storage::historical::log_config::Logs;
+use crate::storage::historical::manager::DATA_BUFFER_SIZE;
+use crate::storage::settings::StorageError;
+use crate::storage::storage_manager::{StorageManager, NV_STORAGE};
+use crate::storage::wear::{EraseCounts, NVM_PAGE_COUNT};
+use crate::{FLASH_ERASE_SIZE, SETTINGS_NVM_FLASH_OFFSET_RANGE};
+use chrono::NaiveDateTime;
+use core::ops::Range;
+use defmt::Format;
+use heapless::Vec;
+
+/// Logs must span at least this long before a logging rate is estimated from them.
+const MIN_RATE_SPAN_SECONDS: i64 = 60 * 60;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+pub enum NvmPartition {
+    ErrorLog,
+    DailyRollupLog,
+    ActivityLog,
+    Settings,
+}
+
+impl NvmPartition {
+    pub const COUNT: usize = 4;
+    /// In flash address order.
+    pub const ALL: [NvmPartition; Self::COUNT] = [
+        NvmPartition::ErrorLog,
+        NvmPartition::DailyRollupLog,
+        NvmPartition::ActivityLog,
+        NvmPartition::Settings,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NvmPartition::ErrorLog => "Error log",
+            NvmPartition::DailyRollupLog => "Daily log",
+            NvmPartition::ActivityLog => "Activity log",
+            NvmPartition::Settings => "Settings",
+        }
+    }
+
+    /// Offsets of the partition in the NVM partition.
+    pub fn range(&self) -> Range<u32> {
+        match self.log() {
+            Some(log) => log.get_config().storage_range,
+            None => SETTINGS_NVM_FLASH_OFFSET_RANGE,
+        }
+    }
+
+    fn log(&self) -> Option<Logs> {
+        match self {
+            NvmPartition::ErrorLog => Some(Logs::ErrorLog),
+            NvmPartition::DailyRollupLog => Some(Logs::DailyRollupLog),
+            NvmPartition::ActivityLog => Some(Logs::ConsumptionLog),
+            NvmPartition::Settings => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PartitionStatus {
+    pub partition: NvmPartition,
+    pub capacity: u32,
+    pub used: u32,
+    /// Log records, or for settings every stored item including old values not yet cleaned up.
+    pub record_count: u32,
+    pub oldest: Option<NaiveDateTime>,
+    pub newest: Option<NaiveDateTime>,
+    /// Settings with a stored value. Only reported for the settings partition.
+    pub live_settings: Option<u32>,
+    /// Erases of the partition's pages, if they are being counted.
+    pub wear: Option<PartitionWear>,
+}
+
+impl PartitionStatus {
+    /// Estimates how long until a log fills and starts overwriting its oldest records, assuming
+    /// records keep being written at the rate seen between the oldest and newest record.
+    pub fn days_until_wrap(&self) -> Option<u32> {
+        let span_seconds = (self.newest? - self.oldest?).num_seconds();
+        if span_seconds < MIN_RATE_SPAN_SECONDS || self.used == 0 {
+            return None;
+        }
+        let remaining = self.capacity.saturating_sub(self.used) as u64;
+        let seconds_to_fill = remaining * span_seconds as u64 / self.used as u64;
+        Some(
+            (seconds_to_fill / SECONDS_PER_DAY)
+                .try_into()
+                .unwrap_or(u32::MAX),
+        )
+    }
+}
+
+/// Erases of the pages of a partition.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PartitionWear {
+    /// Erases of the most erased page.
+    pub max_page_erases: u32,
+    pub total_erases: u32,
+}
+
+impl PartitionWear {
+    pub fn from_erase_counts(partition: NvmPartition, counts: &EraseCounts) -> Self {
+        partition
+            .range()
+            .step_by(FLASH_ERASE_SIZE)
+            .map(|offset| counts.get(offset))
+            .fold(Self::default(), |wear, erases| Self {
+                max_page_erases: wear.max_page_erases.max(erases),
+                total_erases: wear.total_erases.saturating_add(erases),
+            })
+    }
+}
+
+pub async fn read_partition_status(
+    partition: NvmPartition,
+) -> Result<PartitionStatus, StorageError> {
+    let mut storage = NV_STORAGE.lock().await;
+    let wear = storage
+        .erase_counts()
+        .map(|counts| PartitionWear::from_erase_counts(partition, &counts));
+    match partition.log() {
+        Some(log) => {
+            let config = log.get_config();
+            let mut oldest = [0; DATA_BUFFER_SIZE];
+            let mut newest = [0; DATA_BUFFER_SIZE];
+            let usage = storage
+                .get_log_usage(&config, &mut oldest, &mut newest)
+                .await?;
+            let timestamp = |buffer: &[u8]| {
+                (usage.record_count > 0)
+                    .then(|| RetrievedLogEntry::from_buffer(buffer).ok())
+                    .flatten()
+                    .map(|entry| entry.timestamp)
+            };
+            let capacity = config.storage_range.len() as u32;
+            Ok(PartitionStatus {
+                partition,
+                capacity,
+                used: capacity.saturating_sub(usage.space_remaining),
+                record_count: usage.record_count,
+                oldest: timestamp(&oldest),
+                newest: timestamp(&newest),
+                live_settings: None,
+                wear,
+            })
+        }
+        None => {
+            let usage = storage.get_key_value_usage().await?;
+            let capacity = SETTINGS_NVM_FLASH_OFFSET_RANGE.len() as u32;
+            Ok(PartitionStatus {
+                partition,
+                capacity,
+                used: usage.used_bytes.min(capacity),
+                record_count: usage.stored_items,
+                oldest: None,
+                newest: None,
+                live_settings: Some(usage.live_keys),
+                wear,
+            })
+        }
+    }
+}
+
+/// Erases of a page of the NVM partition.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PageWear {
+    pub partition: NvmPartition,
+    /// Offset of the page in the NVM partition.
+    pub offset: u32,
+    pub erase_count: u32,
+}
+
+/// Erase counts of every page of the NVM partition, in address order.
+pub async fn read_page_wear() -> Result<Vec<PageWear, NVM_PAGE_COUNT>, StorageError> {
+    let counts = NV_STORAGE
+        .lock()
+        .await
+        .erase_counts()
+        .ok_or(StorageError::NotInitialized)?;
+    let mut pages = Vec::new();
+    for partition in NvmPartition::ALL {
+        for offset in partition.range().step_by(FLASH_ERASE_SIZE) {
+            let _ = pages.push(PageWear {
+                partition,
+                offset,
+                erase_count: counts.get(offset),
+            });
+        }
+    }
+    Ok(pages)
+}
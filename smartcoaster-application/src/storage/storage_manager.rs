@@ -13,19 +13,23 @@
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use crate::storage::historical::manager::DATA_BUFFER_SIZE;
+use crate::storage::log_pages::{LogPages, LogPagesFlash};
 use crate::storage::settings::StorageError;
+use crate::storage::settings::accessor::SAVE_MAX_DELAY;
+use crate::storage::settings::schema::keys;
+use crate::storage::wear::{EraseCounts, WearTrackingFlash};
 use core::cell::RefCell;
 use core::future::Future;
+use core::ops::ControlFlow;
 use core::ops::Range;
 use defmt::{Debug2Format, debug, error, trace, warn};
 use embassy_embedded_hal::adapter::BlockingAsync;
 use embassy_embedded_hal::flash::partition::BlockingPartition;
-use embassy_rp::flash;
-use embassy_rp::peripherals::FLASH;
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use embassy_sync::mutex::Mutex;
-use embassy_time::{Duration, Timer};
-use embedded_storage_async::nor_flash::NorFlash;
+use embassy_time::{Duration, Instant, Timer};
+use embedded_storage_async::nor_flash::{NorFlash, ReadNorFlash};
+use heapless::FnvIndexSet;
 use sequential_storage::cache::NoCache;
 use sequential_storage::map;
 use sequential_storage::map::Value;
@@ -36,6 +40,27 @@ pub struct StoredLogConfig {
     pub allow_overwrite_old: bool,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LogUsage {
+    pub record_count: u32,
+    pub space_remaining: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyValueUsage {
+    /// Every item in the map, including old values that have not been cleaned up yet.
+    pub stored_items: u32,
+    /// Keys that currently have a value.
+    pub live_keys: u32,
+    /// Estimate of the flash used by the stored items, including their headers.
+    pub used_bytes: u32,
+}
+
+/// Size of the header sequential-storage writes before each item. Used to estimate the space used
+/// by the key/value map.
+const ITEM_HEADER_SIZE: u32 = 8;
+const MAX_KEY_VALUE_KEYS: usize = 64;
+
 pub trait StorageManager {
     fn is_initialized(&self) -> bool;
     fn clear_data(&mut self) -> impl Future<Output = Result<(), StorageError>>;
@@ -73,11 +98,77 @@ pub trait StorageManager {
         start: usize,
         count: usize,
         buf: &mut [[u8; DATA_BUFFER_SIZE]],
+    ) -> impl Future<Output = Result<usize, StorageError>> {
+        self.get_log_items_in_pages(config, LogPages::ALL, start, count, buf)
+    }
+
+    /// As [`Self::get_log_items`], for the log made up of only the entries in `pages`. Entries in
+    /// the other pages are passed over without being read.
+    fn get_log_items_in_pages(
+        &mut self,
+        config: &StoredLogConfig,
+        pages: LogPages,
+        start: usize,
+        count: usize,
+        buf: &mut [[u8; DATA_BUFFER_SIZE]],
     ) -> impl Future<Output = Result<usize, StorageError>>;
+
+    /// Passes each entry in a log to `visit`, oldest first, in a single pass over the log. Gives
+    /// the number of entries visited.
+    fn for_each_log_item(
+        &mut self,
+        config: &StoredLogConfig,
+        mut visit: impl FnMut(&[u8]),
+    ) -> impl Future<Output = Result<usize, StorageError>> {
+        self.visit_log_items(config, 0, move |item| {
+            visit(item);
+            ControlFlow::Continue(())
+        })
+    }
+
+    /// Passes the entries in a log from the one at `start` to `visit`, oldest first, until it
+    /// breaks. The log can only be read from its oldest entry, so the entries before `start` are
+    /// still read, but not passed on. Gives the number of entries visited.
+    fn visit_log_items(
+        &mut self,
+        config: &StoredLogConfig,
+        start: usize,
+        visit: impl FnMut(&[u8]) -> ControlFlow<()>,
+    ) -> impl Future<Output = Result<usize, StorageError>> {
+        self.visit_log_items_in_pages(config, LogPages::ALL, start, visit)
+    }
+
+    /// As [`Self::visit_log_items`], for the log made up of only the entries in `pages`.
+    fn visit_log_items_in_pages(
+        &mut self,
+        config: &StoredLogConfig,
+        pages: LogPages,
+        start: usize,
+        visit: impl FnMut(&[u8]) -> ControlFlow<()>,
+    ) -> impl Future<Output = Result<usize, StorageError>>;
+
+    /// Counts the entries in a log, copying the oldest and newest entries into the buffers.
+    fn get_log_usage(
+        &mut self,
+        config: &StoredLogConfig,
+        oldest: &mut [u8; DATA_BUFFER_SIZE],
+        newest: &mut [u8; DATA_BUFFER_SIZE],
+    ) -> impl Future<Output = Result<LogUsage, StorageError>>;
+
+    fn get_key_value_usage(&mut self) -> impl Future<Output = Result<KeyValueUsage, StorageError>>;
 }
 
-pub type BlockingFlash =
-    embassy_rp::flash::Flash<'static, FLASH, flash::Blocking, { crate::FLASH_SIZE }>;
+#[cfg(target_os = "none")]
+pub type BlockingFlash = embassy_rp::flash::Flash<
+    'static,
+    embassy_rp::peripherals::FLASH,
+    embassy_rp::flash::Blocking,
+    { crate::FLASH_SIZE },
+>;
+
+/// Host builds have no flash device so only the NVM partition is provided, backed by RAM.
+#[cfg(not(target_os = "none"))]
+pub type BlockingFlash = crate::storage::ram_flash::RamFlash<{ crate::NVM_PARTITION_SIZE }>;
 
 pub type BlockingAsyncPartition =
     BlockingAsync<BlockingPartition<'static, CriticalSectionRawMutex, BlockingFlash>>;
@@ -92,7 +183,7 @@ pub struct StorageManagerSequentialStorage<F>
 where
     F: NorFlash,
 {
-    flash: Option<F>,
+    flash: Option<WearTrackingFlash<F>>,
     key_value_range: Option<Range<u32>>,
     flash_cache: NoCache,
     storage_initialised: bool,
@@ -111,8 +202,8 @@ where
         }
     }
 
-    async fn initialise(&mut self, flash: F, settings_range_in_partition: Range<u32>) {
-        self.flash = Some(flash);
+    pub async fn initialise(&mut self, flash: F, settings_range_in_partition: Range<u32>) {
+        self.flash = Some(WearTrackingFlash::new(flash));
         self.key_value_range = Some(settings_range_in_partition);
         debug!(
             "Storage initialising. KeyValue flash address range: 0x{:x} to 0x{:x}, flash size: {}",
@@ -121,9 +212,53 @@ where
             self.flash.as_ref().unwrap().capacity(),
         );
 
+        match self
+            .read_key_value_pair::<EraseCounts>(keys::NVM_ERASE_COUNTS)
+            .await
+        {
+            Ok(Some(saved)) => self.flash.as_mut().unwrap().add_saved_counts(&saved),
+            Ok(None) => {}
+            Err(_) => warn!("Unable to load NVM erase counts"),
+        }
+
         self.storage_initialised = true;
         debug!("Storage initialised");
     }
+
+    /// Erases of each page of the NVM partition, or `None` before initialisation.
+    pub fn erase_counts(&self) -> Option<EraseCounts> {
+        self.flash.as_ref().map(|flash| *flash.erase_counts())
+    }
+
+    /// When the erase counts are next due to be saved, like a settings save held back for
+    /// [`SAVE_MAX_DELAY`] after the first erase that has not been saved.
+    pub fn erase_counts_save_due(&self) -> Option<Instant> {
+        self.flash
+            .as_ref()
+            .and_then(|flash| flash.unsaved_since())
+            .map(|unsaved_since| unsaved_since + SAVE_MAX_DELAY)
+    }
+
+    /// Saves the erase counts if pages have been erased since they were last saved.
+    pub async fn save_erase_counts(&mut self) -> Result<(), StorageError> {
+        let Some(flash) = self.flash.as_mut() else {
+            return Err(StorageError::NotInitialized);
+        };
+        if flash.unsaved_since().is_none() {
+            return Ok(());
+        }
+        let counts = *flash.erase_counts();
+        // erases made while saving are saved next time
+        flash.set_unsaved_since(None);
+        self.save_key_value_pair(keys::NVM_ERASE_COUNTS, counts)
+            .await
+            .inspect_err(|_| {
+                if let Some(flash) = self.flash.as_mut() {
+                    // tried again once the delay has passed rather than on every storage pass
+                    flash.set_unsaved_since(Some(Instant::now()));
+                }
+            })
+    }
 }
 
 impl<F> StorageManager for StorageManagerSequentialStorage<F>
@@ -278,18 +413,23 @@ where
         })
     }
 
-    async fn get_log_items(
+    async fn get_log_items_in_pages(
         &mut self,
         config: &StoredLogConfig,
+        pages: LogPages,
         start: usize,
         count: usize,
         buf: &mut [[u8; DATA_BUFFER_SIZE]],
     ) -> Result<usize, StorageError> {
         trace!("Getting log items start = {}, max count = {}", start, count);
-        let flash = self.flash.as_mut().unwrap();
+        let mut flash = LogPagesFlash::new(
+            self.flash.as_mut().unwrap(),
+            config.storage_range.clone(),
+            pages,
+        );
         let mut cache = NoCache::new();
         let mut storage_iter =
-            sequential_storage::queue::iter(flash, config.storage_range.clone(), &mut cache)
+            sequential_storage::queue::iter(&mut flash, config.storage_range.clone(), &mut cache)
                 .await
                 .map_err(|_| {
                     error!("Unable to get iterator for NVM queue");
@@ -314,6 +454,8 @@ where
 
         // retrieve entries
         while index < start + count {
+            // clear any previous entry so shorter records are zero padded
+            buf[retrieved_count].fill(0);
             let entry = storage_iter
                 .next(&mut buf[retrieved_count])
                 .await
@@ -331,6 +473,139 @@ where
         trace!("Retrieved {} entries", retrieved_count);
         Ok(retrieved_count)
     }
+
+    async fn visit_log_items_in_pages(
+        &mut self,
+        config: &StoredLogConfig,
+        pages: LogPages,
+        start: usize,
+        mut visit: impl FnMut(&[u8]) -> ControlFlow<()>,
+    ) -> Result<usize, StorageError> {
+        let mut flash = LogPagesFlash::new(
+            self.flash.as_mut().unwrap(),
+            config.storage_range.clone(),
+            pages,
+        );
+        let mut cache = NoCache::new();
+        let mut storage_iter =
+            sequential_storage::queue::iter(&mut flash, config.storage_range.clone(), &mut cache)
+                .await
+                .map_err(|_| {
+                    error!("Unable to get iterator for NVM queue");
+                    StorageError::RetrieveError
+                })?;
+
+        let mut index = 0;
+        let mut count = 0;
+        let mut entry_buf = [0; DATA_BUFFER_SIZE];
+        loop {
+            entry_buf.fill(0);
+            let entry = storage_iter.next(&mut entry_buf).await.map_err(|_| {
+                error!("Failed to read while visiting entries");
+                StorageError::RetrieveError
+            })?;
+            if entry.is_none() {
+                break;
+            }
+            index += 1;
+            if index <= start {
+                continue;
+            }
+            count += 1;
+            if visit(&entry_buf).is_break() {
+                break;
+            }
+        }
+        Ok(count)
+    }
+
+    async fn get_log_usage(
+        &mut self,
+        config: &StoredLogConfig,
+        oldest: &mut [u8; DATA_BUFFER_SIZE],
+        newest: &mut [u8; DATA_BUFFER_SIZE],
+    ) -> Result<LogUsage, StorageError> {
+        let space_remaining = self.get_space_remaining(config).await?;
+
+        let flash = self.flash.as_mut().unwrap();
+        let mut cache = NoCache::new();
+        let mut storage_iter =
+            sequential_storage::queue::iter(flash, config.storage_range.clone(), &mut cache)
+                .await
+                .map_err(|_| {
+                    error!("Unable to get iterator for NVM queue");
+                    StorageError::RetrieveError
+                })?;
+
+        oldest.fill(0);
+        newest.fill(0);
+        let mut record_count = 0;
+        let mut entry_buf = [0; DATA_BUFFER_SIZE];
+        loop {
+            entry_buf.fill(0);
+            let entry = storage_iter.next(&mut entry_buf).await.map_err(|_| {
+                error!("Failed to read while counting entries");
+                StorageError::RetrieveError
+            })?;
+            if entry.is_none() {
+                break;
+            }
+            if record_count == 0 {
+                oldest.copy_from_slice(&entry_buf);
+            }
+            newest.copy_from_slice(&entry_buf);
+            record_count += 1;
+        }
+
+        Ok(LogUsage {
+            record_count,
+            space_remaining,
+        })
+    }
+
+    async fn get_key_value_usage(&mut self) -> Result<KeyValueUsage, StorageError> {
+        if !self.is_initialized() {
+            error!("Trying to check storage before initialisation");
+            return Err(StorageError::NotInitialized);
+        }
+
+        let flash = self.flash.as_mut().unwrap();
+        let storage_range = self.key_value_range.clone().unwrap();
+        let mut data_buffer = [0; DATA_BUFFER_SIZE];
+        let mut items = map::fetch_all_items::<u16, _, _>(
+            flash,
+            storage_range,
+            &mut self.flash_cache,
+            &mut data_buffer,
+        )
+        .await
+        .map_err(|_| {
+            error!("Unable to get iterator for key/value map");
+            StorageError::CapacityCheckError
+        })?;
+
+        let mut usage = KeyValueUsage {
+            stored_items: 0,
+            live_keys: 0,
+            used_bytes: 0,
+        };
+        let mut keys = FnvIndexSet::<u16, MAX_KEY_VALUE_KEYS>::new();
+        loop {
+            let item = items.next::<&[u8]>(&mut data_buffer).await.map_err(|_| {
+                error!("Failed to read key/value map item");
+                StorageError::CapacityCheckError
+            })?;
+            let Some((key, value)) = item else {
+                break;
+            };
+            usage.stored_items += 1;
+            usage.used_bytes += ITEM_HEADER_SIZE + size_of::<u16>() as u32 + value.len() as u32;
+            let _ = keys.insert(key);
+        }
+        usage.live_keys = keys.len() as u32;
+
+        Ok(usage)
+    }
 }
 
 pub async fn initialise_storage(
@@ -355,6 +630,25 @@ pub async fn initialise_storage(
         .await;
 }
 
+/// Saves the NVM erase counts if they are due to be saved, see `storage::wear`.
+pub async fn save_erase_counts() {
+    let mut storage = NV_STORAGE.lock().await;
+    if storage
+        .erase_counts_save_due()
+        .is_none_or(|due| due > Instant::now())
+    {
+        return;
+    }
+    if let Err(e) = storage.save_erase_counts().await {
+        warn!("Unable to save NVM erase counts: {:?}", e);
+    }
+}
+
+/// When the storage task next needs to save the NVM erase counts.
+pub async fn next_erase_counts_save_due() -> Option<Instant> {
+    NV_STORAGE.lock().await.erase_counts_save_due()
+}
+
 pub async fn wait_for_storage_initialisation() {
     trace!("Checking storage initialisation");
     loop {
@@ -0,0 +1,166 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Counts how many times each page of the NVM partition has been erased, as a measure of flash
+//! wear. The counts are kept in the settings map so they carry across reboots. Saving them writes
+//! every count, so erases are saved together once the first has gone unsaved for a while. Erases
+//! since the counts were last saved are lost if power is removed, so the figures are a lower bound.
+
+use crate::{FLASH_ERASE_SIZE, NVM_PARTITION_SIZE};
+use embassy_time::Instant;
+use embedded_storage_async::nor_flash::{ErrorType, NorFlash, ReadNorFlash};
+use sequential_storage::map::{SerializationError, Value};
+
+pub const NVM_PAGE_COUNT: usize = NVM_PARTITION_SIZE / FLASH_ERASE_SIZE;
+
+/// Erases of each page of the NVM partition, in address order.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EraseCounts([u32; NVM_PAGE_COUNT]);
+
+impl EraseCounts {
+    pub const fn new() -> Self {
+        Self([0; NVM_PAGE_COUNT])
+    }
+
+    /// Erases of the page holding `offset` in the NVM partition.
+    pub fn get(&self, offset: u32) -> u32 {
+        self.0
+            .get(offset as usize / FLASH_ERASE_SIZE)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn as_slice(&self) -> &[u32] {
+        &self.0
+    }
+
+    fn add(&mut self, other: &EraseCounts) {
+        for (count, other) in self.0.iter_mut().zip(other.0) {
+            *count = count.saturating_add(other);
+        }
+    }
+}
+
+impl Default for EraseCounts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Value<'_> for EraseCounts {
+    fn serialize_into(&self, buffer: &mut [u8]) -> Result<usize, SerializationError> {
+        let size = NVM_PAGE_COUNT * size_of::<u32>();
+        if buffer.len() < size {
+            return Err(SerializationError::BufferTooSmall);
+        }
+        for (chunk, count) in buffer.chunks_exact_mut(size_of::<u32>()).zip(self.0) {
+            chunk.copy_from_slice(&count.to_le_bytes());
+        }
+        Ok(size)
+    }
+
+    /// Reads as many counts as were stored, so counts saved with a different number of pages
+    /// still load.
+    fn deserialize_from(buffer: &[u8]) -> Result<Self, SerializationError>
+    where
+        Self: Sized,
+    {
+        let mut counts = Self::new();
+        for (count, chunk) in counts
+            .0
+            .iter_mut()
+            .zip(buffer.chunks_exact(size_of::<u32>()))
+        {
+            *count = u32::from_le_bytes(chunk.try_into().unwrap());
+        }
+        Ok(counts)
+    }
+}
+
+/// Flash that counts the erases of each of its pages.
+pub struct WearTrackingFlash<F> {
+    flash: F,
+    erase_counts: EraseCounts,
+    /// When the oldest erase not yet saved was made.
+    unsaved_since: Option<Instant>,
+}
+
+impl<F: NorFlash> WearTrackingFlash<F> {
+    pub fn new(flash: F) -> Self {
+        Self {
+            flash,
+            erase_counts: EraseCounts::new(),
+            unsaved_since: None,
+        }
+    }
+
+    pub fn erase_counts(&self) -> &EraseCounts {
+        &self.erase_counts
+    }
+
+    /// Adds the counts saved before this boot to those made since.
+    pub fn add_saved_counts(&mut self, saved: &EraseCounts) {
+        self.erase_counts.add(saved);
+    }
+
+    /// When the oldest erase since the counts were last saved was made, or `None` if there has
+    /// been no erase since.
+    pub fn unsaved_since(&self) -> Option<Instant> {
+        self.unsaved_since
+    }
+
+    pub fn set_unsaved_since(&mut self, unsaved_since: Option<Instant>) {
+        self.unsaved_since = unsaved_since;
+    }
+}
+
+impl<F: NorFlash> ErrorType for WearTrackingFlash<F> {
+    type Error = F::Error;
+}
+
+impl<F: NorFlash> ReadNorFlash for WearTrackingFlash<F> {
+    const READ_SIZE: usize = F::READ_SIZE;
+
+    async fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        self.flash.read(offset, bytes).await
+    }
+
+    fn capacity(&self) -> usize {
+        self.flash.capacity()
+    }
+}
+
+impl<F: NorFlash> NorFlash for WearTrackingFlash<F> {
+    const WRITE_SIZE: usize = F::WRITE_SIZE;
+    const ERASE_SIZE: usize = F::ERASE_SIZE;
+
+    async fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+        self.flash.erase(from, to).await?;
+        for page in (from..to).step_by(F::ERASE_SIZE) {
+            if let Some(count) = self
+                .erase_counts
+                .0
+                .get_mut(page as usize / FLASH_ERASE_SIZE)
+            {
+                *count = count.saturating_add(1);
+            }
+        }
+        self.unsaved_since.get_or_insert_with(Instant::now);
+        Ok(())
+    }
+
+    async fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.flash.write(offset, bytes).await
+    }
+}
@@ -0,0 +1,90 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use defmt::trace;
+use embassy_rp::peripherals::USB;
+use embassy_rp::usb::Driver;
+use embassy_usb::class::cdc_acm::{BufferedReceiver, Sender};
+use embedded_io_async::{Read, Write};
+use minicbor::Decode;
+
+/// Largest message the application sends or receives. Application messages are all small, unlike
+/// the bootloader's firmware chunks.
+pub const MAX_MESSAGE_SIZE: usize = 128;
+
+#[derive(Debug)]
+pub enum ReceiveError {
+    ReadError,
+    DecodeError,
+    MessageTooLarge,
+}
+
+#[derive(Debug)]
+pub enum SendError {
+    EncodeError,
+    UsbError,
+    MessageTooLarge,
+}
+
+pub async fn read_cbor_message<'d, 'b, M>(
+    rx: &mut BufferedReceiver<'d, Driver<'d, USB>>,
+    buffer: &'b mut [u8],
+) -> Result<M, ReceiveError>
+where
+    M: Decode<'b, ()>,
+{
+    trace!("Reading framing length prefix");
+    let mut length_bytes = [0u8; 2];
+    rx.read_exact(&mut length_bytes)
+        .await
+        .map_err(|_| ReceiveError::ReadError)?;
+
+    let message_len = u16::from_be_bytes(length_bytes) as usize;
+    if message_len > MAX_MESSAGE_SIZE || message_len > buffer.len() {
+        return Err(ReceiveError::MessageTooLarge);
+    }
+
+    trace!("Reading {} bytes for the message", message_len);
+    rx.read_exact(&mut buffer[..message_len])
+        .await
+        .map_err(|_| ReceiveError::ReadError)?;
+
+    minicbor::decode::<M>(&buffer[..message_len]).map_err(|_| ReceiveError::DecodeError)
+}
+
+pub async fn send_cbor_message<'d, M>(
+    tx: &mut Sender<'d, Driver<'d, USB>>,
+    msg: &M,
+) -> Result<(), SendError>
+where
+    M: minicbor::Encode<()> + minicbor::CborLen<()>,
+{
+    let encoded_len = minicbor::len(msg);
+    if encoded_len > MAX_MESSAGE_SIZE {
+        return Err(SendError::MessageTooLarge);
+    }
+
+    let mut buffer = [0u8; MAX_MESSAGE_SIZE];
+    minicbor::encode(msg, &mut buffer[..]).map_err(|_| SendError::EncodeError)?;
+
+    // Write length prefix (big-endian u16)
+    let length_bytes = (encoded_len as u16).to_be_bytes();
+    tx.write_all(&length_bytes)
+        .await
+        .map_err(|_| SendError::UsbError)?;
+
+    tx.write_all(&buffer[..encoded_len])
+        .await
+        .map_err(|_| SendError::UsbError)
+}
@@ -0,0 +1,329 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::error_log::{ErrorLogReader, ErrorLogRecord};
+use crate::hmi::screens::monitoring::read_custom_layout;
+use crate::hmi::screens::settings_menu::system_options::UsbDriveOptions;
+use crate::storage::factory_reset::{request_factory_reset, FactoryResetOptions};
+use crate::storage::historical::messaging::HistoricalLogChannel;
+use crate::storage::settings::accessor::FlashSettingsAccessor;
+use crate::storage::settings::{SettingValue, SettingsAccessor, SettingsAccessorId};
+use crate::storage::status::{read_partition_status, NvmPartition, PartitionStatus};
+use crate::usb::cbor_send_receive::{
+    read_cbor_message, send_cbor_message, SendError, MAX_MESSAGE_SIZE,
+};
+use crate::usb::mass_storage::MassStorageClass;
+use defmt::{debug, info, warn, Debug2Format};
+use embassy_executor::Spawner;
+use embassy_rp::peripherals::USB;
+use embassy_rp::usb::{Driver, InterruptHandler};
+use embassy_rp::{bind_interrupts, Peri};
+use embassy_sync::pubsub::PubSubChannel;
+use embassy_usb::class::cdc_acm::{BufferedReceiver, CdcAcmClass, Sender, State};
+use smartcoaster_messages::application::builder::ApplicationMessagesBuilder;
+use smartcoaster_messages::application::nvm_status::NvmPartitionId;
+use smartcoaster_messages::custom_data_types::VersionNumber;
+use smartcoaster_messages::general::builder::GeneralMessagesBuilder;
+use smartcoaster_messages::general::hello::SystemMode::Application;
+use smartcoaster_messages::{ApplicationMessages, GeneralMessages};
+use smartcoaster_widgets::grid::GridLayout;
+use static_cell::StaticCell;
+
+bind_interrupts!(struct UsbIrqs {
+    USBCTRL_IRQ => InterruptHandler<USB>;
+});
+
+const MAX_PACKET_SIZE: u8 = 64;
+
+static ERROR_LOG_DOWNLOAD_CHANNEL: HistoricalLogChannel = PubSubChannel::new();
+
+/// Answers requests from a host connected over USB serial.
+pub struct HostLink {
+    error_log_reader: ErrorLogReader,
+}
+
+impl HostLink {
+    pub fn new() -> Self {
+        Self {
+            error_log_reader: ErrorLogReader::new(&ERROR_LOG_DOWNLOAD_CHANNEL),
+        }
+    }
+
+    pub async fn start(&mut self, usb_peripheral: Peri<'static, USB>, spawner: Spawner) -> ! {
+        let driver = Driver::new(usb_peripheral, UsbIrqs);
+
+        let config = {
+            let mut config = embassy_usb::Config::new(0x1209, 0x4004); // Pending acceptance of USB PID from pid.codes
+            config.manufacturer = Some("SmartCoaster");
+            config.product = Some("SmartCoaster");
+            config.serial_number = Some("12345678"); // TODO get this from flash device
+            config.max_power = 500;
+            config.max_packet_size_0 = MAX_PACKET_SIZE;
+            config
+        };
+
+        let mut builder = {
+            static CONFIG_DESCRIPTOR: StaticCell<[u8; 256]> = StaticCell::new();
+            static BOS_DESCRIPTOR: StaticCell<[u8; 256]> = StaticCell::new();
+            static CONTROL_BUF: StaticCell<[u8; 64]> = StaticCell::new();
+
+            embassy_usb::Builder::new(
+                driver,
+                config,
+                CONFIG_DESCRIPTOR.init([0; 256]),
+                BOS_DESCRIPTOR.init([0; 256]),
+                &mut [], // no msos descriptors
+                CONTROL_BUF.init([0; 64]),
+            )
+        };
+
+        static STATE: StaticCell<State> = StaticCell::new();
+        let state = STATE.init(State::new());
+        let class = CdcAcmClass::new(&mut builder, state, MAX_PACKET_SIZE as u16);
+        let mass_storage = usb_drive_enabled()
+            .await
+            .then(|| MassStorageClass::new(&mut builder, MAX_PACKET_SIZE as u16));
+
+        let usb = builder.build();
+        spawner.must_spawn(usb_device_task(usb));
+        if let Some(mass_storage) = mass_storage {
+            info!("USB drive enabled");
+            spawner.must_spawn(mass_storage_task(mass_storage));
+        }
+
+        let (mut sender, receiver) = class.split();
+        static RX_BUF: StaticCell<[u8; MAX_MESSAGE_SIZE]> = StaticCell::new();
+        let rx_buf = RX_BUF.init([0u8; MAX_MESSAGE_SIZE]);
+        let mut buffered_rx = receiver.into_buffered(rx_buf);
+
+        loop {
+            sender.wait_connection().await;
+            info!("USB host connected");
+            self.handle_session(&mut sender, &mut buffered_rx).await;
+        }
+    }
+
+    /// Runs one session with the host, from the hello exchange until the host says goodbye or the
+    /// link fails.
+    async fn handle_session<'d>(
+        &mut self,
+        sender: &mut Sender<'d, Driver<'d, USB>>,
+        receiver: &mut BufferedReceiver<'d, Driver<'d, USB>>,
+    ) {
+        let mut buffer = [0u8; MAX_MESSAGE_SIZE];
+
+        loop {
+            match read_cbor_message(receiver, &mut buffer).await {
+                Ok(GeneralMessages::Hello(_)) => break,
+                Ok(message) => debug!("Ignoring message: {:?}", Debug2Format(&message)),
+                Err(e) => {
+                    warn!("Failed to read message: {:?}", Debug2Format(&e));
+                    return;
+                }
+            }
+        }
+
+        let hello_resp = GeneralMessagesBuilder::new()
+            .hello_resp()
+            .mode(Application)
+            .version(application_version())
+            .build();
+        if let Err(e) = send_cbor_message(sender, &hello_resp).await {
+            warn!("Failed to send HelloResp: {:?}", Debug2Format(&e));
+            return;
+        }
+
+        loop {
+            let result = match read_cbor_message(receiver, &mut buffer).await {
+                Ok(ApplicationMessages::CustomScreenLayoutReq(_)) => {
+                    send_custom_screen_layout(sender).await
+                }
+                Ok(ApplicationMessages::SetCustomScreenLayoutReq(req)) => {
+                    set_custom_screen_layout(req.packed).await;
+                    send_custom_screen_layout(sender).await
+                }
+                Ok(ApplicationMessages::ErrorLogReq(_)) => self.send_error_log(sender).await,
+                Ok(ApplicationMessages::NvmStatusReq(_)) => send_nvm_status(sender).await,
+                Ok(ApplicationMessages::FactoryResetReq(req)) => {
+                    start_factory_reset(sender, req.keep_calibration).await
+                }
+                Ok(ApplicationMessages::Goodbye(_)) => {
+                    info!("USB host said goodbye");
+                    return;
+                }
+                Ok(message) => {
+                    debug!("Ignoring message: {:?}", Debug2Format(&message));
+                    Ok(())
+                }
+                Err(e) => {
+                    warn!("Failed to read message: {:?}", Debug2Format(&e));
+                    return;
+                }
+            };
+
+            if let Err(e) = result {
+                warn!("Failed to send to USB host: {:?}", Debug2Format(&e));
+                return;
+            }
+        }
+    }
+
+    async fn send_error_log<'d>(
+        &mut self,
+        sender: &mut Sender<'d, Driver<'d, USB>>,
+    ) -> Result<(), SendError> {
+        info!("Sending error log to USB host");
+        let mut record_count = 0;
+        self.error_log_reader.start_read().await;
+        while let Some(record) = self.error_log_reader.next_record().await {
+            if let Err(e) = send_cbor_message(sender, &error_log_record_message(&record)).await {
+                // finish the read so the log reader is left idle for the next request
+                while self.error_log_reader.next_record().await.is_some() {}
+                return Err(e);
+            }
+            record_count += 1;
+        }
+
+        let end = ApplicationMessagesBuilder::new()
+            .error_log_end()
+            .record_count(record_count)
+            .build();
+        send_cbor_message(sender, &end).await
+    }
+}
+
+impl Default for HostLink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Stores the layout if it can be unpacked. The monitoring screen picks it up next time it is shown.
+async fn set_custom_screen_layout(packed: u32) {
+    if GridLayout::try_from(packed).is_err() {
+        warn!("Ignoring invalid custom screen layout {:#x}", packed);
+        return;
+    }
+    FlashSettingsAccessor::new()
+        .save_setting(
+            SettingsAccessorId::MonitoringCustomScreen,
+            SettingValue::UInt(packed),
+        )
+        .await
+        .unwrap_or_else(|e| {
+            warn!(
+                "Failed to store custom screen layout: {:?}",
+                Debug2Format(&e)
+            )
+        });
+}
+
+async fn send_custom_screen_layout<'d>(
+    sender: &mut Sender<'d, Driver<'d, USB>>,
+) -> Result<(), SendError> {
+    let layout = read_custom_layout(&FlashSettingsAccessor::new()).await;
+    let message = ApplicationMessagesBuilder::new().custom_screen_layout(layout.into());
+    send_cbor_message(sender, &message).await
+}
+
+/// Acknowledges the request before the reset starts, as the device reboots once it is done.
+async fn start_factory_reset<'d>(
+    sender: &mut Sender<'d, Driver<'d, USB>>,
+    keep_calibration: bool,
+) -> Result<(), SendError> {
+    let resp = ApplicationMessagesBuilder::new().factory_reset_resp();
+    send_cbor_message(sender, &resp).await?;
+    request_factory_reset(FactoryResetOptions { keep_calibration });
+    Ok(())
+}
+
+async fn send_nvm_status<'d>(sender: &mut Sender<'d, Driver<'d, USB>>) -> Result<(), SendError> {
+    info!("Sending NVM status to USB host");
+    let mut partition_count = 0;
+    for partition in NvmPartition::ALL {
+        match read_partition_status(partition).await {
+            Ok(status) => {
+                send_cbor_message(sender, &nvm_partition_status_message(&status)).await?;
+                partition_count += 1;
+            }
+            Err(e) => warn!("Failed to read status of {}: {}", partition, e),
+        }
+    }
+
+    let end = ApplicationMessagesBuilder::new().nvm_status_end(partition_count);
+    send_cbor_message(sender, &end).await
+}
+
+fn nvm_partition_status_message(status: &PartitionStatus) -> ApplicationMessages {
+    let partition = match status.partition {
+        NvmPartition::ErrorLog => NvmPartitionId::ErrorLog,
+        NvmPartition::DailyRollupLog => NvmPartitionId::DailyRollupLog,
+        NvmPartition::ActivityLog => NvmPartitionId::ActivityLog,
+        NvmPartition::Settings => NvmPartitionId::Settings,
+    };
+    let timestamp = |t: Option<chrono::NaiveDateTime>| t.map(|t| t.and_utc().timestamp());
+    let builder = ApplicationMessagesBuilder::new()
+        .nvm_partition_status()
+        .partition(partition)
+        .usage(status.capacity, status.used)
+        .record_count(status.record_count)
+        .time_span(timestamp(status.oldest), timestamp(status.newest))
+        .days_until_wrap(status.days_until_wrap())
+        .live_settings(status.live_settings);
+    match status.wear {
+        Some(wear) => builder.wear(wear.max_page_erases, wear.total_erases),
+        None => builder,
+    }
+    .build()
+}
+
+fn error_log_record_message(record: &ErrorLogRecord) -> ApplicationMessages {
+    ApplicationMessagesBuilder::new()
+        .error_log_record()
+        .timestamp(record.timestamp.and_utc().timestamp())
+        .module(record.data.module.into())
+        .code(record.data.code.into())
+        .values(record.data.values)
+        .build()
+}
+
+/// The drive is added to the device as it is set up, so the setting applies from the next boot.
+async fn usb_drive_enabled() -> bool {
+    match FlashSettingsAccessor::new()
+        .get_setting(SettingsAccessorId::SystemUsbDrive)
+        .await
+    {
+        Some(SettingValue::SmallUInt(value)) => value != UsbDriveOptions::OFF,
+        _ => false,
+    }
+}
+
+fn application_version() -> VersionNumber {
+    let part = |value: &str| value.parse().unwrap_or(0);
+    VersionNumber::new(
+        part(env!("CARGO_PKG_VERSION_MAJOR")),
+        part(env!("CARGO_PKG_VERSION_MINOR")),
+        part(env!("CARGO_PKG_VERSION_PATCH")),
+    )
+}
+
+#[embassy_executor::task]
+async fn usb_device_task(mut usb: embassy_usb::UsbDevice<'static, Driver<'static, USB>>) {
+    usb.run().await;
+}
+
+#[embassy_executor::task]
+async fn mass_storage_task(mut mass_storage: MassStorageClass<'static, Driver<'static, USB>>) {
+    mass_storage.run().await;
+}
@@ -0,0 +1,389 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Read-only USB mass storage, so the coaster shows up as a drive holding its data as files. Uses
+//! the bulk-only transport with the SCSI commands hosts need to mount a drive. The volume is made
+//! from the data in flash when the host starts using the drive, so reconnecting shows newer data.
+
+use crate::rtc::accessor::RtcAccessor;
+use crate::storage::export::ExportVolume;
+use crate::storage::settings::StorageError;
+use crate::storage::storage_manager::{wait_for_storage_initialisation, NV_STORAGE};
+use chrono::NaiveDateTime;
+use defmt::{debug, info, warn, Format};
+use embassy_usb::control::{InResponse, OutResponse, Recipient, Request, RequestType};
+use embassy_usb::driver::{Driver, Endpoint, EndpointError, EndpointIn, EndpointOut};
+use embassy_usb::types::InterfaceNumber;
+use embassy_usb::{Builder, Handler};
+use static_cell::StaticCell;
+
+const CLASS_MASS_STORAGE: u8 = 0x08;
+const SUBCLASS_SCSI: u8 = 0x06;
+const PROTOCOL_BULK_ONLY: u8 = 0x50;
+
+const REQUEST_GET_MAX_LUN: u8 = 0xFE;
+const REQUEST_BULK_ONLY_RESET: u8 = 0xFF;
+
+const CBW_SIGNATURE: u32 = 0x4342_5355;
+const CBW_SIZE: usize = 31;
+const CSW_SIGNATURE: u32 = 0x5342_5355;
+const CSW_SIZE: usize = 13;
+const CBW_DIRECTION_IN: u8 = 0x80;
+
+const SCSI_TEST_UNIT_READY: u8 = 0x00;
+const SCSI_REQUEST_SENSE: u8 = 0x03;
+const SCSI_INQUIRY: u8 = 0x12;
+const SCSI_START_STOP_UNIT: u8 = 0x1B;
+const SCSI_MODE_SENSE_6: u8 = 0x1A;
+const SCSI_PREVENT_ALLOW_MEDIUM_REMOVAL: u8 = 0x1E;
+const SCSI_READ_FORMAT_CAPACITIES: u8 = 0x23;
+const SCSI_READ_CAPACITY_10: u8 = 0x25;
+const SCSI_READ_10: u8 = 0x28;
+const SCSI_VERIFY_10: u8 = 0x2F;
+const SCSI_MODE_SENSE_10: u8 = 0x5A;
+
+/// Direct access block device, removable, SPC-2, with the vendor, product and revision padded to
+/// 8, 16 and 4 characters.
+const INQUIRY_RESPONSE: [u8; 36] = *b"\x00\x80\x04\x02\x1f\x00\x00\x00SmartCstCoaster Data    0.3 ";
+
+/// Why the last command failed, as reported to REQUEST SENSE.
+#[derive(Clone, Copy, Debug, PartialEq, Format)]
+enum Sense {
+    NoSense,
+    InvalidCommand,
+    WriteProtected,
+    ReadError,
+}
+
+impl Sense {
+    /// Sense key, additional sense code and qualifier.
+    fn codes(&self) -> (u8, u8, u8) {
+        match self {
+            Sense::NoSense => (0x00, 0x00, 0x00),
+            Sense::InvalidCommand => (0x05, 0x20, 0x00),
+            Sense::WriteProtected => (0x07, 0x27, 0x00),
+            Sense::ReadError => (0x03, 0x11, 0x00),
+        }
+    }
+}
+
+/// Command block wrapper, which carries each SCSI command from the host.
+struct CommandBlock {
+    tag: u32,
+    data_length: u32,
+    direction_in: bool,
+    command: [u8; 16],
+}
+
+impl CommandBlock {
+    fn parse(packet: &[u8]) -> Option<Self> {
+        if packet.len() != CBW_SIZE
+            || u32::from_le_bytes(packet[0..4].try_into().unwrap()) != CBW_SIGNATURE
+        {
+            return None;
+        }
+        let mut command = [0; 16];
+        command.copy_from_slice(&packet[15..31]);
+        Some(Self {
+            tag: u32::from_le_bytes(packet[4..8].try_into().unwrap()),
+            data_length: u32::from_le_bytes(packet[8..12].try_into().unwrap()),
+            direction_in: packet[12] & CBW_DIRECTION_IN != 0,
+            command,
+        })
+    }
+
+    fn lba(&self) -> u32 {
+        u32::from_be_bytes(self.command[2..6].try_into().unwrap())
+    }
+
+    fn block_count(&self) -> u32 {
+        u16::from_be_bytes(self.command[7..9].try_into().unwrap()) as u32
+    }
+}
+
+/// Answers the class requests on the control endpoint. There is one logical unit, and a bulk-only
+/// reset needs nothing doing as commands are only handled whole.
+struct MassStorageControl {
+    interface: InterfaceNumber,
+}
+
+impl Handler for MassStorageControl {
+    fn control_out(&mut self, req: Request, _data: &[u8]) -> Option<OutResponse> {
+        if req.request_type != RequestType::Class
+            || req.recipient != Recipient::Interface
+            || req.index != self.interface.0 as u16
+        {
+            return None;
+        }
+        match req.request {
+            REQUEST_BULK_ONLY_RESET => Some(OutResponse::Accepted),
+            _ => Some(OutResponse::Rejected),
+        }
+    }
+
+    fn control_in<'a>(&'a mut self, req: Request, buf: &'a mut [u8]) -> Option<InResponse<'a>> {
+        if req.request_type != RequestType::Class
+            || req.recipient != Recipient::Interface
+            || req.index != self.interface.0 as u16
+        {
+            return None;
+        }
+        match req.request {
+            REQUEST_GET_MAX_LUN => {
+                buf[0] = 0;
+                Some(InResponse::Accepted(&buf[..1]))
+            }
+            _ => Some(InResponse::Rejected),
+        }
+    }
+}
+
+pub struct MassStorageClass<'d, D: Driver<'d>> {
+    read_ep: D::EndpointOut,
+    write_ep: D::EndpointIn,
+    max_packet_size: u16,
+    volume: Option<ExportVolume>,
+    sense: Sense,
+    rtc: Option<RtcAccessor>,
+}
+
+impl<'d, D: Driver<'d>> MassStorageClass<'d, D> {
+    pub fn new(builder: &mut Builder<'d, D>, max_packet_size: u16) -> Self {
+        let mut function = builder.function(CLASS_MASS_STORAGE, SUBCLASS_SCSI, PROTOCOL_BULK_ONLY);
+        let mut interface = function.interface();
+        let interface_number = interface.interface_number();
+        let mut alt =
+            interface.alt_setting(CLASS_MASS_STORAGE, SUBCLASS_SCSI, PROTOCOL_BULK_ONLY, None);
+        let read_ep = alt.endpoint_bulk_out(None, max_packet_size);
+        let write_ep = alt.endpoint_bulk_in(None, max_packet_size);
+        drop(function);
+
+        static CONTROL: StaticCell<MassStorageControl> = StaticCell::new();
+        builder.handler(CONTROL.init(MassStorageControl {
+            interface: interface_number,
+        }));
+
+        Self {
+            read_ep,
+            write_ep,
+            max_packet_size,
+            volume: None,
+            sense: Sense::NoSense,
+            rtc: RtcAccessor::new().ok(),
+        }
+    }
+
+    /// Answers commands from the host for as long as the device is connected.
+    pub async fn run(&mut self) -> ! {
+        let mut packet = [0u8; 64];
+        loop {
+            self.read_ep.wait_enabled().await;
+            info!("USB drive connected");
+            // a fresh volume for each connection
+            self.volume = None;
+            self.sense = Sense::NoSense;
+
+            loop {
+                let command = match self.read_ep.read(&mut packet).await {
+                    Ok(size) => match CommandBlock::parse(&packet[..size]) {
+                        Some(command) => command,
+                        None => {
+                            warn!("Ignoring invalid command block of {} bytes", size);
+                            continue;
+                        }
+                    },
+                    Err(EndpointError::Disabled) => break,
+                    Err(e) => {
+                        warn!("USB drive read failed: {}", e);
+                        continue;
+                    }
+                };
+
+                if let Err(e) = self.handle_command(&command).await {
+                    warn!("USB drive command failed: {}", e);
+                    if e == EndpointError::Disabled {
+                        break;
+                    }
+                }
+            }
+            info!("USB drive disconnected");
+        }
+    }
+
+    /// Carries out a command, including its data phase, and sends its status.
+    async fn handle_command(&mut self, command: &CommandBlock) -> Result<(), EndpointError> {
+        let mut response = [0u8; 36];
+        let (passed, data_sent) = match command.command[0] {
+            SCSI_TEST_UNIT_READY
+            | SCSI_PREVENT_ALLOW_MEDIUM_REMOVAL
+            | SCSI_START_STOP_UNIT
+            | SCSI_VERIFY_10 => (true, 0),
+            SCSI_INQUIRY => {
+                // vital product data pages are not supported
+                if command.command[1] & 0x01 != 0 {
+                    (false, 0)
+                } else {
+                    response.copy_from_slice(&INQUIRY_RESPONSE);
+                    let sent = self.send_response(command, &response).await?;
+                    (true, sent)
+                }
+            }
+            SCSI_REQUEST_SENSE => {
+                let (key, code, qualifier) = self.sense.codes();
+                response[..18].copy_from_slice(&[
+                    0x70, 0, key, 0, 0, 0, 0, 10, 0, 0, 0, 0, code, qualifier, 0, 0, 0, 0,
+                ]);
+                self.sense = Sense::NoSense;
+                let sent = self.send_response(command, &response[..18]).await?;
+                // the sense has been read, so REQUEST SENSE itself always passes
+                return self.send_status(command, sent, true).await;
+            }
+            SCSI_MODE_SENSE_6 => {
+                // no mode pages, write protected
+                let sent = self.send_response(command, &[3, 0, 0x80, 0]).await?;
+                (true, sent)
+            }
+            SCSI_MODE_SENSE_10 => {
+                let sent = self
+                    .send_response(command, &[0, 6, 0, 0x80, 0, 0, 0, 0])
+                    .await?;
+                (true, sent)
+            }
+            SCSI_READ_CAPACITY_10 => {
+                response[0..4].copy_from_slice(&(ExportVolume::SECTOR_COUNT - 1).to_be_bytes());
+                response[4..8].copy_from_slice(&(ExportVolume::SECTOR_SIZE as u32).to_be_bytes());
+                let sent = self.send_response(command, &response[..8]).await?;
+                (true, sent)
+            }
+            SCSI_READ_FORMAT_CAPACITIES => {
+                // one formatted capacity descriptor
+                response[3] = 8;
+                response[4..8].copy_from_slice(&ExportVolume::SECTOR_COUNT.to_be_bytes());
+                response[8..12].copy_from_slice(&(ExportVolume::SECTOR_SIZE as u32).to_be_bytes());
+                response[8] = 0x02;
+                let sent = self.send_response(command, &response[..12]).await?;
+                (true, sent)
+            }
+            SCSI_READ_10 => self.read_sectors(command).await?,
+            operation => {
+                debug!("Unsupported SCSI command {:#x}", operation);
+                (false, 0)
+            }
+        };
+
+        if !passed && self.sense == Sense::NoSense {
+            self.sense = if command.direction_in {
+                Sense::InvalidCommand
+            } else {
+                // anything sending data is a write, which is not allowed
+                Sense::WriteProtected
+            };
+        }
+        if !passed && !command.direction_in {
+            self.discard_data(command.data_length).await?;
+        }
+        if !passed && command.direction_in && command.data_length > 0 && data_sent == 0 {
+            // end the data stage the host is expecting
+            self.write_ep.write(&[]).await?;
+        }
+        self.send_status(command, data_sent, passed).await
+    }
+
+    /// Sends the start of `data`, up to the length the host asked for, giving the bytes sent.
+    async fn send_response(
+        &mut self,
+        command: &CommandBlock,
+        data: &[u8],
+    ) -> Result<u32, EndpointError> {
+        let len = data.len().min(command.data_length as usize);
+        for chunk in data[..len].chunks(self.max_packet_size as usize) {
+            self.write_ep.write(chunk).await?;
+        }
+        Ok(len as u32)
+    }
+
+    async fn read_sectors(&mut self, command: &CommandBlock) -> Result<(bool, u32), EndpointError> {
+        let lba = command.lba();
+        let count = command.block_count();
+        if lba.saturating_add(count) > ExportVolume::SECTOR_COUNT
+            || count * ExportVolume::SECTOR_SIZE as u32 != command.data_length
+        {
+            return Ok((false, 0));
+        }
+
+        let mut passed = true;
+        let mut sector = [0u8; ExportVolume::SECTOR_SIZE];
+        for lba in lba..lba + count {
+            if let Err(e) = self.read_sector(lba, &mut sector).await {
+                // carry on so the host gets the length it asked for, and fail the command
+                warn!("Failed to read USB drive sector {}: {}", lba, e);
+                sector.fill(0);
+                self.sense = Sense::ReadError;
+                passed = false;
+            }
+            for chunk in sector.chunks(self.max_packet_size as usize) {
+                self.write_ep.write(chunk).await?;
+            }
+        }
+        Ok((passed, command.data_length))
+    }
+
+    async fn read_sector(
+        &mut self,
+        lba: u32,
+        sector: &mut [u8; ExportVolume::SECTOR_SIZE],
+    ) -> Result<(), StorageError> {
+        wait_for_storage_initialisation().await;
+        let mut storage = NV_STORAGE.lock().await;
+        let volume = match &mut self.volume {
+            Some(volume) => volume,
+            volume @ None => {
+                let created = self
+                    .rtc
+                    .as_mut()
+                    .map_or(NaiveDateTime::default(), |rtc| rtc.get_date_time());
+                volume.insert(ExportVolume::new(&mut *storage, created).await?)
+            }
+        };
+        volume.read_sector(&mut *storage, lba, sector).await
+    }
+
+    /// Reads and drops data the host sends for a command that is not carried out.
+    async fn discard_data(&mut self, mut length: u32) -> Result<(), EndpointError> {
+        let mut packet = [0u8; 64];
+        while length > 0 {
+            let size = self.read_ep.read(&mut packet).await?;
+            if size == 0 {
+                break;
+            }
+            length = length.saturating_sub(size as u32);
+        }
+        Ok(())
+    }
+
+    async fn send_status(
+        &mut self,
+        command: &CommandBlock,
+        data_sent: u32,
+        passed: bool,
+    ) -> Result<(), EndpointError> {
+        let mut status = [0u8; CSW_SIZE];
+        status[0..4].copy_from_slice(&CSW_SIGNATURE.to_le_bytes());
+        status[4..8].copy_from_slice(&command.tag.to_le_bytes());
+        let residue = command.data_length.saturating_sub(data_sent);
+        status[8..12].copy_from_slice(&residue.to_le_bytes());
+        status[12] = if passed { 0 } else { 1 };
+        self.write_ep.write(&status).await
+    }
+}
@@ -0,0 +1,21 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! USB serial link to a host while the application is running. Uses the same framing and hello
+//! exchange as the bootloader so host tools can tell which one they are talking to. If turned on in
+//! the settings, the device also shows up as a read-only drive holding its data.
+
+mod cbor_send_receive;
+pub mod host_link;
+pub mod mass_storage;
@@ -86,7 +86,7 @@ pub struct WeighingSystemOverChannel {
 }
 
 impl WeighingSystemOverChannel {
-    pub(crate) fn new(
+    pub fn new(
         weight_event_rx: WeightChannelSubscriber<'static>,
         weight_request_tx: WeightRequestPublisher<'static>,
     ) -> Self {
@@ -12,9 +12,9 @@
 // You should have received a copy of the GNU General Public License along with
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
-pub(crate) mod weight;
-pub(crate) mod interface;
-pub(crate) mod messaging;
+pub mod weight;
+pub mod interface;
+pub mod messaging;
 
 
 pub trait WeighingSystem {
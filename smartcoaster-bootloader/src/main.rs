@@ -30,7 +30,11 @@ use embassy_rp::gpio::{Input, Pull};
 use embassy_sync::blocking_mutex::Mutex;
 use embassy_time::{Duration, Timer};
 
-const FLASH_SIZE: usize = 16 * 1024 * 1024;
+mod partitions {
+    #![allow(dead_code)]
+    include!(concat!(env!("OUT_DIR"), "/partitions.rs"));
+}
+use partitions::FLASH_SIZE;
 
 #[embassy_executor::main]
 async fn main(spawner: Spawner) -> ! {
@@ -0,0 +1,180 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use circular_buffer::CircularBuffer;
+use smartcoaster_messages::application::builder::ApplicationMessagesBuilder;
+use smartcoaster_messages::general::builder::GeneralMessagesBuilder;
+use smartcoaster_messages::general::hello::SystemMode::Application;
+use smartcoaster_messages::{ApplicationMessages, FrameError, GeneralMessages};
+
+use crate::SessionHandlerError;
+
+/// An error log entry as downloaded from the device. Module and code are the raw values stored on
+/// the device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorLogEntry {
+    /// Seconds since the Unix epoch
+    pub timestamp: i64,
+    pub module: u8,
+    pub code: u8,
+    pub values: [Option<u32>; 2],
+}
+
+#[derive(Debug)]
+enum ErrorLogSessionState {
+    Start,
+    WaitingHelloResp,
+    ReceivingRecords,
+    Done,
+}
+
+/// Downloads the error log from a device running the application firmware.
+pub struct SmartcoasterHostErrorLogDownloader<const BUFFER_SIZE: usize> {
+    session_state: ErrorLogSessionState,
+    tx_message_buffer: [u8; BUFFER_SIZE],
+    tx_valid_bytes_size: usize,
+    rx_message_buffer: CircularBuffer::<BUFFER_SIZE, u8>,
+    entries: Vec<ErrorLogEntry>,
+}
+
+impl<const BUFFER_SIZE: usize> SmartcoasterHostErrorLogDownloader<BUFFER_SIZE> {
+    pub fn new() -> Self {
+        Self {
+            session_state: ErrorLogSessionState::Start,
+            tx_message_buffer: [0u8; BUFFER_SIZE],
+            tx_valid_bytes_size: 0,
+            rx_message_buffer: CircularBuffer::<BUFFER_SIZE, u8>::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn session_handler(mut session: SmartcoasterHostErrorLogDownloader<BUFFER_SIZE>, incoming_bytes: &[u8]) -> Result<SmartcoasterHostErrorLogDownloader<BUFFER_SIZE>, SessionHandlerError> {
+        if incoming_bytes.len() + session.rx_message_buffer.len() > session.rx_message_buffer.capacity() {
+            return Err(SessionHandlerError::RxBufferNotEnoughSpace);
+        }
+        session.rx_message_buffer.extend_from_slice(incoming_bytes);
+        session.rx_message_buffer.make_contiguous();
+
+        log::trace!("Session state: {:?}", session.session_state);
+
+        match session.session_state {
+            ErrorLogSessionState::Start => {
+                let hello = GeneralMessagesBuilder::new().hello();
+                session.tx_valid_bytes_size =
+                    smartcoaster_messages::frame_message(&hello, &mut session.tx_message_buffer)?;
+                session.session_state = ErrorLogSessionState::WaitingHelloResp;
+            }
+            ErrorLogSessionState::WaitingHelloResp => {
+                let Some(message) = session.next_message::<GeneralMessages>()? else {
+                    return Ok(session);
+                };
+
+                match message {
+                    GeneralMessages::HelloResp(hello_resp) => {
+                        log::trace!("Received hello response: {:?}", hello_resp);
+                        if hello_resp.mode != Application {
+                            return Err(SessionHandlerError::IncorrectDeviceMode);
+                        }
+
+                        let req = ApplicationMessagesBuilder::new().error_log_req();
+                        session.tx_valid_bytes_size =
+                            smartcoaster_messages::frame_message(&req, &mut session.tx_message_buffer)?;
+                        session.session_state = ErrorLogSessionState::ReceivingRecords;
+                    }
+                    _ => {
+                        log::trace!("Unexpected message: {:?}", message);
+                        return Err(SessionHandlerError::UnexpectedMessage);
+                    }
+                }
+            }
+            ErrorLogSessionState::ReceivingRecords => {
+                let Some(message) = session.next_message::<ApplicationMessages>()? else {
+                    return Ok(session);
+                };
+
+                match message {
+                    ApplicationMessages::ErrorLogRecord(record) => {
+                        session.entries.push(ErrorLogEntry {
+                            timestamp: record.timestamp,
+                            module: record.module,
+                            code: record.code,
+                            values: [record.value_1, record.value_2],
+                        });
+                    }
+                    ApplicationMessages::ErrorLogEnd(end) => {
+                        if end.record_count as usize != session.entries.len() {
+                            log::warn!("Device sent {} records but received {}", end.record_count, session.entries.len());
+                        }
+                        let goodbye = ApplicationMessagesBuilder::new().goodbye();
+                        session.tx_valid_bytes_size =
+                            smartcoaster_messages::frame_message(&goodbye, &mut session.tx_message_buffer)?;
+                        session.session_state = ErrorLogSessionState::Done;
+                    }
+                    _ => {
+                        log::trace!("Unexpected message: {:?}", message);
+                        return Err(SessionHandlerError::UnexpectedMessage);
+                    }
+                }
+            }
+            ErrorLogSessionState::Done => {
+                return Err(SessionHandlerError::SessionEnded);
+            }
+        }
+
+        if session.rx_message_buffer.len() > 0 && !Self::is_session_ended(&session) {
+            let empty_buffer = [0u8; 0];
+            return SmartcoasterHostErrorLogDownloader::session_handler(session, &empty_buffer);
+        }
+
+        Ok(session)
+    }
+
+    /// Decodes the next message from the receive buffer, or returns `None` if more bytes are needed.
+    fn next_message<M: for<'b> minicbor::Decode<'b, ()>>(&mut self) -> Result<Option<M>, SessionHandlerError> {
+        let (message_buffer, _) = self.rx_message_buffer.as_slices();
+        let (consumed_bytes_count, message) = match smartcoaster_messages::decode_framed_message(message_buffer) {
+            Ok(result) => result,
+            Err(FrameError::BufferTooSmall(expected_len)) => {
+                log::trace!("Need {expected_len} bytes to decode");
+                return Ok(None);
+            }
+            Err(e) => return Err(SessionHandlerError::FramingError(e)),
+        };
+        self.rx_message_buffer.consume(consumed_bytes_count);
+        Ok(Some(message))
+    }
+
+    pub fn get_bytes_to_send(session: &mut SmartcoasterHostErrorLogDownloader<BUFFER_SIZE>) -> Option<&[u8]> {
+        if session.tx_valid_bytes_size > 0 {
+            let message_size = session.tx_valid_bytes_size;
+            session.tx_valid_bytes_size = 0;
+            return Some(&session.tx_message_buffer[..message_size]);
+        }
+        None
+    }
+
+    pub fn get_entries(session: &SmartcoasterHostErrorLogDownloader<BUFFER_SIZE>) -> &[ErrorLogEntry] {
+        &session.entries
+    }
+
+    pub fn is_session_ended(session: &SmartcoasterHostErrorLogDownloader<BUFFER_SIZE>) -> bool {
+        matches!(session.session_state, ErrorLogSessionState::Done)
+    }
+}
+
+impl<const BUFFER_SIZE: usize> Default for SmartcoasterHostErrorLogDownloader<BUFFER_SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use circular_buffer::CircularBuffer;
+use smartcoaster_messages::application::builder::ApplicationMessagesBuilder;
+use smartcoaster_messages::general::builder::GeneralMessagesBuilder;
+use smartcoaster_messages::general::hello::SystemMode::Application;
+use smartcoaster_messages::{ApplicationMessages, FrameError, GeneralMessages};
+
+use crate::SessionHandlerError;
+
+#[derive(Debug)]
+enum FactoryResetSessionState {
+    Start,
+    WaitingHelloResp,
+    WaitingFactoryResetResp,
+    Done,
+}
+
+/// Asks a device running the application to factory reset itself. The device reboots once the
+/// reset is complete, so the session ends as soon as the request is acknowledged.
+pub struct SmartcoasterHostFactoryReset<const BUFFER_SIZE: usize> {
+    keep_calibration: bool,
+    session_state: FactoryResetSessionState,
+    tx_message_buffer: [u8; BUFFER_SIZE],
+    tx_valid_bytes_size: usize,
+    rx_message_buffer: CircularBuffer::<BUFFER_SIZE, u8>,
+}
+
+impl<const BUFFER_SIZE: usize> SmartcoasterHostFactoryReset<BUFFER_SIZE> {
+    pub fn new(keep_calibration: bool) -> Self {
+        Self {
+            keep_calibration,
+            session_state: FactoryResetSessionState::Start,
+            tx_message_buffer: [0u8; BUFFER_SIZE],
+            tx_valid_bytes_size: 0,
+            rx_message_buffer: CircularBuffer::<BUFFER_SIZE, u8>::new(),
+        }
+    }
+
+    pub fn session_handler(mut session: SmartcoasterHostFactoryReset<BUFFER_SIZE>, incoming_bytes: &[u8]) -> Result<SmartcoasterHostFactoryReset<BUFFER_SIZE>, SessionHandlerError> {
+        if incoming_bytes.len() + session.rx_message_buffer.len() > session.rx_message_buffer.capacity() {
+            return Err(SessionHandlerError::RxBufferNotEnoughSpace);
+        }
+        session.rx_message_buffer.extend_from_slice(incoming_bytes);
+        session.rx_message_buffer.make_contiguous();
+
+        log::trace!("Session state: {:?}", session.session_state);
+
+        match session.session_state {
+            FactoryResetSessionState::Start => {
+                let hello = GeneralMessagesBuilder::new().hello();
+                session.tx_valid_bytes_size =
+                    smartcoaster_messages::frame_message(&hello, &mut session.tx_message_buffer)?;
+                session.session_state = FactoryResetSessionState::WaitingHelloResp;
+            }
+            FactoryResetSessionState::WaitingHelloResp => {
+                let Some(message) = session.next_message::<GeneralMessages>()? else {
+                    return Ok(session);
+                };
+
+                match message {
+                    GeneralMessages::HelloResp(hello_resp) => {
+                        log::trace!("Received hello response: {:?}", hello_resp);
+                        if hello_resp.mode != Application {
+                            return Err(SessionHandlerError::IncorrectDeviceMode);
+                        }
+
+                        let req = ApplicationMessagesBuilder::new().factory_reset_req(session.keep_calibration);
+                        session.tx_valid_bytes_size =
+                            smartcoaster_messages::frame_message(&req, &mut session.tx_message_buffer)?;
+                        session.session_state = FactoryResetSessionState::WaitingFactoryResetResp;
+                    }
+                    _ => {
+                        log::trace!("Unexpected message: {:?}", message);
+                        return Err(SessionHandlerError::UnexpectedMessage);
+                    }
+                }
+            }
+            FactoryResetSessionState::WaitingFactoryResetResp => {
+                let Some(message) = session.next_message::<ApplicationMessages>()? else {
+                    return Ok(session);
+                };
+
+                match message {
+                    ApplicationMessages::FactoryResetResp(_) => {
+                        let goodbye = ApplicationMessagesBuilder::new().goodbye();
+                        session.tx_valid_bytes_size =
+                            smartcoaster_messages::frame_message(&goodbye, &mut session.tx_message_buffer)?;
+                        session.session_state = FactoryResetSessionState::Done;
+                    }
+                    _ => {
+                        log::trace!("Unexpected message: {:?}", message);
+                        return Err(SessionHandlerError::UnexpectedMessage);
+                    }
+                }
+            }
+            FactoryResetSessionState::Done => {
+                return Err(SessionHandlerError::SessionEnded);
+            }
+        }
+
+        Ok(session)
+    }
+
+    /// Decodes the next message from the receive buffer, or returns `None` if more bytes are needed.
+    fn next_message<M: for<'b> minicbor::Decode<'b, ()>>(&mut self) -> Result<Option<M>, SessionHandlerError> {
+        let (message_buffer, _) = self.rx_message_buffer.as_slices();
+        let (consumed_bytes_count, message) = match smartcoaster_messages::decode_framed_message(message_buffer) {
+            Ok(result) => result,
+            Err(FrameError::BufferTooSmall(expected_len)) => {
+                log::trace!("Need {expected_len} bytes to decode");
+                return Ok(None);
+            }
+            Err(e) => return Err(SessionHandlerError::FramingError(e)),
+        };
+        self.rx_message_buffer.consume(consumed_bytes_count);
+        Ok(Some(message))
+    }
+
+    pub fn get_bytes_to_send(session: &mut SmartcoasterHostFactoryReset<BUFFER_SIZE>) -> Option<&[u8]> {
+        if session.tx_valid_bytes_size > 0 {
+            let message_size = session.tx_valid_bytes_size;
+            session.tx_valid_bytes_size = 0;
+            return Some(&session.tx_message_buffer[..message_size]);
+        }
+        None
+    }
+
+    pub fn is_session_ended(session: &SmartcoasterHostFactoryReset<BUFFER_SIZE>) -> bool {
+        matches!(session.session_state, FactoryResetSessionState::Done)
+    }
+}
@@ -12,6 +12,10 @@
 // You should have received a copy of the GNU General Public License along with
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
+mod custom_screen;
+mod error_log_download;
+mod factory_reset;
+mod nvm_status;
 mod util;
 
 #[cfg(target_arch = "wasm32")]
@@ -25,6 +29,11 @@ use smartcoaster_messages::custom_data_types::{AsconHash256Bytes, VersionNumber}
 use smartcoaster_messages::general::builder::GeneralMessagesBuilder;
 use smartcoaster_messages::general::hello::SystemMode::Bootloader;
 
+pub use custom_screen::SmartcoasterHostCustomScreenLayout;
+pub use error_log_download::{ErrorLogEntry, SmartcoasterHostErrorLogDownloader};
+pub use factory_reset::SmartcoasterHostFactoryReset;
+pub use nvm_status::{NvmPartitionEntry, SmartcoasterHostNvmStatusReader};
+pub use smartcoaster_messages::application::nvm_status::NvmPartitionId;
 pub use smartcoaster_messages::FrameError;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -0,0 +1,192 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use circular_buffer::CircularBuffer;
+use smartcoaster_messages::application::builder::ApplicationMessagesBuilder;
+use smartcoaster_messages::application::nvm_status::NvmPartitionId;
+use smartcoaster_messages::general::builder::GeneralMessagesBuilder;
+use smartcoaster_messages::general::hello::SystemMode::Application;
+use smartcoaster_messages::{ApplicationMessages, FrameError, GeneralMessages};
+
+use crate::SessionHandlerError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NvmPartitionEntry {
+    pub partition: NvmPartitionId,
+    pub capacity: u32,
+    pub used: u32,
+    pub record_count: u32,
+    /// Seconds since the Unix epoch
+    pub oldest: Option<i64>,
+    /// Seconds since the Unix epoch
+    pub newest: Option<i64>,
+    pub days_until_wrap: Option<u32>,
+    pub live_settings: Option<u32>,
+    /// Erases of the most erased page
+    pub max_page_erases: Option<u32>,
+    pub total_erases: Option<u32>,
+}
+
+#[derive(Debug)]
+enum NvmStatusSessionState {
+    Start,
+    WaitingHelloResp,
+    ReceivingStatus,
+    Done,
+}
+
+pub struct SmartcoasterHostNvmStatusReader<const BUFFER_SIZE: usize> {
+    session_state: NvmStatusSessionState,
+    tx_message_buffer: [u8; BUFFER_SIZE],
+    tx_valid_bytes_size: usize,
+    rx_message_buffer: CircularBuffer::<BUFFER_SIZE, u8>,
+    partitions: Vec<NvmPartitionEntry>,
+}
+
+impl<const BUFFER_SIZE: usize> SmartcoasterHostNvmStatusReader<BUFFER_SIZE> {
+    pub fn new() -> Self {
+        Self {
+            session_state: NvmStatusSessionState::Start,
+            tx_message_buffer: [0u8; BUFFER_SIZE],
+            tx_valid_bytes_size: 0,
+            rx_message_buffer: CircularBuffer::<BUFFER_SIZE, u8>::new(),
+            partitions: Vec::new(),
+        }
+    }
+
+    pub fn session_handler(mut session: SmartcoasterHostNvmStatusReader<BUFFER_SIZE>, incoming_bytes: &[u8]) -> Result<SmartcoasterHostNvmStatusReader<BUFFER_SIZE>, SessionHandlerError> {
+        if incoming_bytes.len() + session.rx_message_buffer.len() > session.rx_message_buffer.capacity() {
+            return Err(SessionHandlerError::RxBufferNotEnoughSpace);
+        }
+        session.rx_message_buffer.extend_from_slice(incoming_bytes);
+        session.rx_message_buffer.make_contiguous();
+
+        log::trace!("Session state: {:?}", session.session_state);
+
+        match session.session_state {
+            NvmStatusSessionState::Start => {
+                let hello = GeneralMessagesBuilder::new().hello();
+                session.tx_valid_bytes_size =
+                    smartcoaster_messages::frame_message(&hello, &mut session.tx_message_buffer)?;
+                session.session_state = NvmStatusSessionState::WaitingHelloResp;
+            }
+            NvmStatusSessionState::WaitingHelloResp => {
+                let Some(message) = session.next_message::<GeneralMessages>()? else {
+                    return Ok(session);
+                };
+
+                match message {
+                    GeneralMessages::HelloResp(hello_resp) => {
+                        log::trace!("Received hello response: {:?}", hello_resp);
+                        if hello_resp.mode != Application {
+                            return Err(SessionHandlerError::IncorrectDeviceMode);
+                        }
+
+                        let req = ApplicationMessagesBuilder::new().nvm_status_req();
+                        session.tx_valid_bytes_size =
+                            smartcoaster_messages::frame_message(&req, &mut session.tx_message_buffer)?;
+                        session.session_state = NvmStatusSessionState::ReceivingStatus;
+                    }
+                    _ => {
+                        log::trace!("Unexpected message: {:?}", message);
+                        return Err(SessionHandlerError::UnexpectedMessage);
+                    }
+                }
+            }
+            NvmStatusSessionState::ReceivingStatus => {
+                let Some(message) = session.next_message::<ApplicationMessages>()? else {
+                    return Ok(session);
+                };
+
+                match message {
+                    ApplicationMessages::NvmPartitionStatus(status) => {
+                        session.partitions.push(NvmPartitionEntry {
+                            partition: status.partition,
+                            capacity: status.capacity,
+                            used: status.used,
+                            record_count: status.record_count,
+                            oldest: status.oldest,
+                            newest: status.newest,
+                            days_until_wrap: status.days_until_wrap,
+                            live_settings: status.live_settings,
+                            max_page_erases: status.max_page_erases,
+                            total_erases: status.total_erases,
+                        });
+                    }
+                    ApplicationMessages::NvmStatusEnd(end) => {
+                        if end.partition_count as usize != session.partitions.len() {
+                            log::warn!("Device sent {} partitions but received {}", end.partition_count, session.partitions.len());
+                        }
+                        let goodbye = ApplicationMessagesBuilder::new().goodbye();
+                        session.tx_valid_bytes_size =
+                            smartcoaster_messages::frame_message(&goodbye, &mut session.tx_message_buffer)?;
+                        session.session_state = NvmStatusSessionState::Done;
+                    }
+                    _ => {
+                        log::trace!("Unexpected message: {:?}", message);
+                        return Err(SessionHandlerError::UnexpectedMessage);
+                    }
+                }
+            }
+            NvmStatusSessionState::Done => {
+                return Err(SessionHandlerError::SessionEnded);
+            }
+        }
+
+        if session.rx_message_buffer.len() > 0 && !Self::is_session_ended(&session) {
+            let empty_buffer = [0u8; 0];
+            return SmartcoasterHostNvmStatusReader::session_handler(session, &empty_buffer);
+        }
+
+        Ok(session)
+    }
+
+    /// Decodes the next message from the receive buffer, or returns `None` if more bytes are needed.
+    fn next_message<M: for<'b> minicbor::Decode<'b, ()>>(&mut self) -> Result<Option<M>, SessionHandlerError> {
+        let (message_buffer, _) = self.rx_message_buffer.as_slices();
+        let (consumed_bytes_count, message) = match smartcoaster_messages::decode_framed_message(message_buffer) {
+            Ok(result) => result,
+            Err(FrameError::BufferTooSmall(expected_len)) => {
+                log::trace!("Need {expected_len} bytes to decode");
+                return Ok(None);
+            }
+            Err(e) => return Err(SessionHandlerError::FramingError(e)),
+        };
+        self.rx_message_buffer.consume(consumed_bytes_count);
+        Ok(Some(message))
+    }
+
+    pub fn get_bytes_to_send(session: &mut SmartcoasterHostNvmStatusReader<BUFFER_SIZE>) -> Option<&[u8]> {
+        if session.tx_valid_bytes_size > 0 {
+            let message_size = session.tx_valid_bytes_size;
+            session.tx_valid_bytes_size = 0;
+            return Some(&session.tx_message_buffer[..message_size]);
+        }
+        None
+    }
+
+    pub fn get_partitions(session: &SmartcoasterHostNvmStatusReader<BUFFER_SIZE>) -> &[NvmPartitionEntry] {
+        &session.partitions
+    }
+
+    pub fn is_session_ended(session: &SmartcoasterHostNvmStatusReader<BUFFER_SIZE>) -> bool {
+        matches!(session.session_state, NvmStatusSessionState::Done)
+    }
+}
+
+impl<const BUFFER_SIZE: usize> Default for SmartcoasterHostNvmStatusReader<BUFFER_SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,273 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use crate::ApplicationMessages;
+use crate::application::custom_screen::{
+    CustomScreenLayout, CustomScreenLayoutReq, SetCustomScreenLayoutReq,
+};
+use crate::application::error_log::{ErrorLogEnd, ErrorLogRecord, ErrorLogReq};
+use crate::application::factory_reset::{FactoryResetReq, FactoryResetResp};
+use crate::application::nvm_status::{
+    NvmPartitionId, NvmPartitionStatus, NvmStatusEnd, NvmStatusReq,
+};
+use crate::general::goodbye::{Goodbye, GoodbyeReason};
+
+/// A builder for creating `ApplicationMessages`.
+pub struct ApplicationMessagesBuilder;
+
+impl ApplicationMessagesBuilder {
+    /// Creates a new `ApplicationMessagesBuilder`.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Builds an `ApplicationMessages::CustomScreenLayoutReq` message.
+    pub fn custom_screen_layout_req(self) -> ApplicationMessages {
+        ApplicationMessages::CustomScreenLayoutReq(CustomScreenLayoutReq {})
+    }
+
+    /// Builds an `ApplicationMessages::SetCustomScreenLayoutReq` message.
+    pub fn set_custom_screen_layout_req(self, packed: u32) -> ApplicationMessages {
+        ApplicationMessages::SetCustomScreenLayoutReq(SetCustomScreenLayoutReq { packed })
+    }
+
+    /// Builds an `ApplicationMessages::CustomScreenLayout` message.
+    pub fn custom_screen_layout(self, packed: u32) -> ApplicationMessages {
+        ApplicationMessages::CustomScreenLayout(CustomScreenLayout { packed })
+    }
+
+    /// Builds an `ApplicationMessages::ErrorLogReq` message.
+    pub fn error_log_req(self) -> ApplicationMessages {
+        ApplicationMessages::ErrorLogReq(ErrorLogReq {})
+    }
+
+    /// Begins building an `ApplicationMessages::ErrorLogRecord` message.
+    pub fn error_log_record(self) -> ErrorLogRecordBuilder {
+        ErrorLogRecordBuilder::new()
+    }
+
+    /// Begins building an `ApplicationMessages::ErrorLogEnd` message.
+    pub fn error_log_end(self) -> ErrorLogEndBuilder {
+        ErrorLogEndBuilder::new()
+    }
+
+    /// Builds an `ApplicationMessages::NvmStatusReq` message.
+    pub fn nvm_status_req(self) -> ApplicationMessages {
+        ApplicationMessages::NvmStatusReq(NvmStatusReq {})
+    }
+
+    /// Begins building an `ApplicationMessages::NvmPartitionStatus` message.
+    pub fn nvm_partition_status(self) -> NvmPartitionStatusBuilder {
+        NvmPartitionStatusBuilder::new()
+    }
+
+    /// Builds an `ApplicationMessages::NvmStatusEnd` message.
+    pub fn nvm_status_end(self, partition_count: u32) -> ApplicationMessages {
+        ApplicationMessages::NvmStatusEnd(NvmStatusEnd { partition_count })
+    }
+
+    /// Builds an `ApplicationMessages::FactoryResetReq` message.
+    pub fn factory_reset_req(self, keep_calibration: bool) -> ApplicationMessages {
+        ApplicationMessages::FactoryResetReq(FactoryResetReq { keep_calibration })
+    }
+
+    /// Builds an `ApplicationMessages::FactoryResetResp` message.
+    pub fn factory_reset_resp(self) -> ApplicationMessages {
+        ApplicationMessages::FactoryResetResp(FactoryResetResp {})
+    }
+
+    /// Builds an `ApplicationMessages::Goodbye` message to end the session.
+    pub fn goodbye(self) -> ApplicationMessages {
+        ApplicationMessages::Goodbye(Goodbye {
+            reason: GoodbyeReason::SessionComplete,
+        })
+    }
+}
+
+impl Default for ApplicationMessagesBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct ErrorLogRecordBuilder {
+    timestamp: Option<i64>,
+    module: Option<u8>,
+    code: Option<u8>,
+    values: [Option<u32>; 2],
+}
+
+impl ErrorLogRecordBuilder {
+    fn new() -> Self {
+        Self {
+            timestamp: None,
+            module: None,
+            code: None,
+            values: [None; 2],
+        }
+    }
+
+    pub fn timestamp(mut self, timestamp: i64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    pub fn module(mut self, module: u8) -> Self {
+        self.module = Some(module);
+        self
+    }
+
+    pub fn code(mut self, code: u8) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Sets the optional context values that accompany the error.
+    pub fn values(mut self, values: [Option<u32>; 2]) -> Self {
+        self.values = values;
+        self
+    }
+
+    /// Builds the `ApplicationMessages::ErrorLogRecord` message.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `timestamp`, `module` or `code` have not been set.
+    pub fn build(self) -> ApplicationMessages {
+        ApplicationMessages::ErrorLogRecord(ErrorLogRecord {
+            timestamp: self.timestamp.expect("timestamp must be set"),
+            module: self.module.expect("module must be set"),
+            code: self.code.expect("code must be set"),
+            value_1: self.values[0],
+            value_2: self.values[1],
+        })
+    }
+}
+
+pub struct ErrorLogEndBuilder {
+    record_count: Option<u32>,
+}
+
+impl ErrorLogEndBuilder {
+    fn new() -> Self {
+        Self { record_count: None }
+    }
+
+    pub fn record_count(mut self, count: u32) -> Self {
+        self.record_count = Some(count);
+        self
+    }
+
+    /// Builds the `ApplicationMessages::ErrorLogEnd` message.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `record_count` has not been set.
+    pub fn build(self) -> ApplicationMessages {
+        ApplicationMessages::ErrorLogEnd(ErrorLogEnd {
+            record_count: self.record_count.expect("record_count must be set"),
+        })
+    }
+}
+
+pub struct NvmPartitionStatusBuilder {
+    partition: Option<NvmPartitionId>,
+    capacity: Option<u32>,
+    used: Option<u32>,
+    record_count: u32,
+    oldest: Option<i64>,
+    newest: Option<i64>,
+    days_until_wrap: Option<u32>,
+    live_settings: Option<u32>,
+    max_page_erases: Option<u32>,
+    total_erases: Option<u32>,
+}
+
+impl NvmPartitionStatusBuilder {
+    fn new() -> Self {
+        Self {
+            partition: None,
+            capacity: None,
+            used: None,
+            record_count: 0,
+            oldest: None,
+            newest: None,
+            days_until_wrap: None,
+            live_settings: None,
+            max_page_erases: None,
+            total_erases: None,
+        }
+    }
+
+    pub fn partition(mut self, partition: NvmPartitionId) -> Self {
+        self.partition = Some(partition);
+        self
+    }
+
+    /// Sets the partition size and how much of it is in use, in bytes.
+    pub fn usage(mut self, capacity: u32, used: u32) -> Self {
+        self.capacity = Some(capacity);
+        self.used = Some(used);
+        self
+    }
+
+    pub fn record_count(mut self, record_count: u32) -> Self {
+        self.record_count = record_count;
+        self
+    }
+
+    /// Sets the timestamps of the oldest and newest log records.
+    pub fn time_span(mut self, oldest: Option<i64>, newest: Option<i64>) -> Self {
+        self.oldest = oldest;
+        self.newest = newest;
+        self
+    }
+
+    pub fn days_until_wrap(mut self, days: Option<u32>) -> Self {
+        self.days_until_wrap = days;
+        self
+    }
+
+    pub fn live_settings(mut self, live_settings: Option<u32>) -> Self {
+        self.live_settings = live_settings;
+        self
+    }
+
+    /// Sets the erases of the most erased page of the partition and of all its pages.
+    pub fn wear(mut self, max_page_erases: u32, total_erases: u32) -> Self {
+        self.max_page_erases = Some(max_page_erases);
+        self.total_erases = Some(total_erases);
+        self
+    }
+
+    /// Builds the `ApplicationMessages::NvmPartitionStatus` message.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `partition` or `usage` have not been set.
+    pub fn build(self) -> ApplicationMessages {
+        ApplicationMessages::NvmPartitionStatus(NvmPartitionStatus {
+            partition: self.partition.expect("partition must be set"),
+            capacity: self.capacity.expect("capacity must be set"),
+            used: self.used.expect("used must be set"),
+            record_count: self.record_count,
+            oldest: self.oldest,
+            newest: self.newest,
+            days_until_wrap: self.days_until_wrap,
+            live_settings: self.live_settings,
+            max_page_erases: self.max_page_erases,
+            total_erases: self.total_erases,
+        })
+    }
+}
@@ -0,0 +1,32 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use minicbor::{CborLen, Decode, Encode};
+
+/// Asks for the custom monitoring screen layout in use.
+#[derive(Debug, PartialEq, Encode, Decode, CborLen)]
+pub struct CustomScreenLayoutReq {}
+
+/// Asks the device to store a new custom monitoring screen layout. `packed` holds the layout in
+/// the same form the device stores it in its settings. A layout the device can't unpack is ignored.
+#[derive(Debug, PartialEq, Encode, Decode, CborLen)]
+pub struct SetCustomScreenLayoutReq {
+    #[n(0)] pub packed: u32,
+}
+
+/// The reply to either request, holding the layout the device is now using.
+#[derive(Debug, PartialEq, Encode, Decode, CborLen)]
+pub struct CustomScreenLayout {
+    #[n(0)] pub packed: u32,
+}
@@ -0,0 +1,34 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use minicbor::{CborLen, Decode, Encode};
+
+#[derive(Debug, PartialEq, Encode, Decode, CborLen)]
+pub struct ErrorLogReq {}
+
+/// A single error log entry. The timestamp is seconds since the Unix epoch, module and code
+/// are the raw values stored by the application so that unknown values can still be shown.
+#[derive(Debug, PartialEq, Encode, Decode, CborLen)]
+pub struct ErrorLogRecord {
+    #[n(0)] pub timestamp: i64,
+    #[n(1)] pub module: u8,
+    #[n(2)] pub code: u8,
+    #[n(3)] pub value_1: Option<u32>,
+    #[n(4)] pub value_2: Option<u32>,
+}
+
+#[derive(Debug, PartialEq, Encode, Decode, CborLen)]
+pub struct ErrorLogEnd {
+    #[n(0)] pub record_count: u32,
+}
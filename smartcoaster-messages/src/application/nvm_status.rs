@@ -0,0 +1,47 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use minicbor::{CborLen, Decode, Encode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Encode, Decode, CborLen)]
+pub enum NvmPartitionId {
+    #[n(0)] ErrorLog,
+    #[n(1)] DailyRollupLog,
+    #[n(2)] ActivityLog,
+    #[n(3)] Settings,
+}
+
+#[derive(Debug, PartialEq, Encode, Decode, CborLen)]
+pub struct NvmStatusReq {}
+
+/// Usage of one partition. Timestamps are seconds since the Unix epoch. Erase counts are left out
+/// by firmware that does not track them.
+#[derive(Debug, PartialEq, Encode, Decode, CborLen)]
+pub struct NvmPartitionStatus {
+    #[n(0)] pub partition: NvmPartitionId,
+    #[n(1)] pub capacity: u32,
+    #[n(2)] pub used: u32,
+    #[n(3)] pub record_count: u32,
+    #[n(4)] pub oldest: Option<i64>,
+    #[n(5)] pub newest: Option<i64>,
+    #[n(6)] pub days_until_wrap: Option<u32>,
+    #[n(7)] pub live_settings: Option<u32>,
+    #[n(8)] pub max_page_erases: Option<u32>,
+    #[n(9)] pub total_erases: Option<u32>,
+}
+
+#[derive(Debug, PartialEq, Encode, Decode, CborLen)]
+pub struct NvmStatusEnd {
+    #[n(0)] pub partition_count: u32,
+}
@@ -18,6 +18,7 @@ use minicbor::{CborLen, Decode, Encode};
 pub enum GoodbyeReason {
     #[n(0)] InstallingNewFirmware,
     #[n(1)] DownloadHashMismatch,
+    #[n(2)] SessionComplete,
 }
 
 #[derive(Debug, PartialEq, Encode, Decode, CborLen)]
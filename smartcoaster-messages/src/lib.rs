@@ -13,12 +13,19 @@
 // You should have received a copy of the GNU General Public License along with
 // this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use crate::application::custom_screen::{
+    CustomScreenLayout, CustomScreenLayoutReq, SetCustomScreenLayoutReq,
+};
+use crate::application::error_log::{ErrorLogEnd, ErrorLogRecord, ErrorLogReq};
+use crate::application::factory_reset::{FactoryResetReq, FactoryResetResp};
+use crate::application::nvm_status::{NvmPartitionStatus, NvmStatusEnd, NvmStatusReq};
 use crate::bootloader::chunk::{ChunkReq, ChunkResp};
 use crate::bootloader::ready_to_download::{ReadyToDownload, ReadyToDownloadResponse};
 use crate::general::goodbye::Goodbye;
 use crate::general::hello::{Hello, HelloResp};
 use minicbor::{CborLen, Decode, Encode};
 
+pub mod application;
 pub mod bootloader;
 pub mod custom_data_types;
 pub mod general;
@@ -45,6 +52,17 @@ pub enum BootloaderMessages {
 #[derive(Debug, PartialEq, Decode, Encode, CborLen)]
 pub enum ApplicationMessages {
     #[n(0)] Goodbye(#[n(0)] Goodbye),
+    #[n(1)] CustomScreenLayoutReq(#[n(0)] CustomScreenLayoutReq),
+    #[n(2)] SetCustomScreenLayoutReq(#[n(0)] SetCustomScreenLayoutReq),
+    #[n(3)] CustomScreenLayout(#[n(0)] CustomScreenLayout),
+    #[n(4)] ErrorLogReq(#[n(0)] ErrorLogReq),
+    #[n(5)] ErrorLogRecord(#[n(0)] ErrorLogRecord),
+    #[n(6)] ErrorLogEnd(#[n(0)] ErrorLogEnd),
+    #[n(7)] NvmStatusReq(#[n(0)] NvmStatusReq),
+    #[n(8)] NvmPartitionStatus(#[n(0)] NvmPartitionStatus),
+    #[n(9)] NvmStatusEnd(#[n(0)] NvmStatusEnd),
+    #[n(10)] FactoryResetReq(#[n(0)] FactoryResetReq),
+    #[n(11)] FactoryResetResp(#[n(0)] FactoryResetResp),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
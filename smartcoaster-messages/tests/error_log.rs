@@ -0,0 +1,97 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Error log download messages survive framing and decoding.
+
+use smartcoaster_messages::application::builder::ApplicationMessagesBuilder;
+use smartcoaster_messages::application::error_log::ErrorLogRecord;
+use smartcoaster_messages::{ApplicationMessages, decode_framed_message, frame_message};
+
+fn round_trip(message: &ApplicationMessages) -> ApplicationMessages {
+    let mut buf = [0u8; 64];
+    let size = frame_message(message, &mut buf).unwrap();
+    let (consumed, decoded) = decode_framed_message(&buf[..size]).unwrap();
+    assert_eq!(consumed, size);
+    decoded
+}
+
+#[test]
+fn error_log_request_round_trips() {
+    let message = ApplicationMessagesBuilder::new().error_log_req();
+    assert_eq!(round_trip(&message), message);
+}
+
+#[test]
+fn error_log_records_round_trip() {
+    let message = ApplicationMessagesBuilder::new()
+        .error_log_record()
+        .timestamp(1_760_000_000)
+        .module(3)
+        .code(2)
+        .values([Some(7), Some(u32::MAX)])
+        .build();
+    assert_eq!(round_trip(&message), message);
+}
+
+#[test]
+fn error_log_records_without_values_round_trip() {
+    let message = ApplicationMessagesBuilder::new()
+        .error_log_record()
+        .timestamp(-1)
+        .module(255)
+        .code(0)
+        .build();
+    assert_eq!(
+        round_trip(&message),
+        ApplicationMessages::ErrorLogRecord(ErrorLogRecord {
+            timestamp: -1,
+            module: 255,
+            code: 0,
+            value_1: None,
+            value_2: None,
+        })
+    );
+}
+
+#[test]
+fn error_log_end_round_trips() {
+    let message = ApplicationMessagesBuilder::new()
+        .error_log_end()
+        .record_count(42)
+        .build();
+    assert_eq!(round_trip(&message), message);
+}
+
+#[test]
+fn records_are_decoded_one_at_a_time() {
+    let first = ApplicationMessagesBuilder::new()
+        .error_log_record()
+        .timestamp(1)
+        .module(0)
+        .code(7)
+        .values([Some(1), None])
+        .build();
+    let end = ApplicationMessagesBuilder::new()
+        .error_log_end()
+        .record_count(1)
+        .build();
+    let mut buf = [0u8; 64];
+    let mut size = frame_message(&first, &mut buf).unwrap();
+    size += frame_message(&end, &mut buf[size..]).unwrap();
+
+    let (consumed, decoded) = decode_framed_message::<ApplicationMessages>(&buf[..size]).unwrap();
+    assert_eq!(decoded, first);
+    let (_, decoded) = decode_framed_message::<ApplicationMessages>(&buf[consumed..size]).unwrap();
+    assert_eq!(decoded, end);
+}
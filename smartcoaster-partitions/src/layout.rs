@@ -0,0 +1,49 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! The flash layout of the board. This is the only place partition addresses are defined; the
+//! linker scripts and the Rust constants used by the bootloader and application are generated
+//! from it.
+//!
+//! To add a partition give it a free, erase aligned range. Application storage lives in the NVM
+//! partitions at the end of flash, which must stay contiguous. New NVM partitions are added at the
+//! start of that area so that the existing partitions keep their addresses and data.
+
+use crate::{KIB, MIB, Partition, Region};
+
+/// Flash fitted to the board.
+pub const FLASH_SIZE: u32 = 16 * MIB;
+/// Smallest unit of flash that can be erased.
+pub const ERASE_SIZE: u32 = 4 * KIB;
+
+pub const RAM_START: u32 = 0x2000_0000;
+pub const RAM_SIZE: u32 = 264 * KIB;
+
+pub const PARTITIONS: &[Partition] = &[
+    // Large size needed if the bootloader is built with trace logging
+    Partition::new("bootloader", Region::Bootloader, 0x0000_0000, 64 * KIB),
+    Partition::new("bootloader_state", Region::Bootloader, 0x0001_0000, 4 * KIB),
+    Partition::new("active", Region::Firmware, 0x0001_1000, 2 * MIB),
+    // One page larger than the active partition, as needed by the bootloader to swap images
+    Partition::new("dfu", Region::Firmware, 0x0021_1000, 2 * MIB + ERASE_SIZE),
+    Partition::new("error_log", Region::Nvm, FLASH_SIZE - 56 * KIB, 8 * KIB),
+    Partition::new(
+        "daily_rollup_log",
+        Region::Nvm,
+        FLASH_SIZE - 48 * KIB,
+        8 * KIB,
+    ),
+    Partition::new("activity_log", Region::Nvm, FLASH_SIZE - 40 * KIB, 32 * KIB),
+    Partition::new("settings", Region::Nvm, FLASH_SIZE - 8 * KIB, 8 * KIB),
+];
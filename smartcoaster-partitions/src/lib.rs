@@ -0,0 +1,411 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Flash partition table shared by the bootloader and the application. Both build scripts check
+//! the table and generate the linker symbols and Rust constants from it, so the two images always
+//! agree on where everything is.
+
+pub mod layout;
+
+use core::fmt;
+use core::fmt::Write;
+use core::ops::Range;
+
+pub const KIB: u32 = 1024;
+pub const MIB: u32 = 1024 * KIB;
+
+/// Size of the second stage bootloader the RP2040 boot ROM loads from the start of flash. It sits
+/// at the start of the first bootloader partition.
+pub const BOOT2_SIZE: u32 = 0x100;
+
+/// Flash address the RP2040 maps flash to.
+pub const FLASH_BASE_ADDRESS: u32 = 0x1000_0000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Region {
+    /// Used by the bootloader alone.
+    Bootloader,
+    /// Firmware images managed by the bootloader.
+    Firmware,
+    /// Application storage. NVM partitions must be contiguous as the application accesses them
+    /// through one flash partition.
+    Nvm,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Partition {
+    /// Used to name the generated symbols, so must be a valid identifier.
+    pub name: &'static str,
+    pub region: Region,
+    /// Offset from the start of flash.
+    pub offset: u32,
+    pub size: u32,
+}
+
+impl Partition {
+    pub const fn new(name: &'static str, region: Region, offset: u32, size: u32) -> Self {
+        Self {
+            name,
+            region,
+            offset,
+            size,
+        }
+    }
+
+    pub fn range(&self) -> Range<u32> {
+        self.offset..self.offset + self.size
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LayoutError {
+    Empty(&'static str),
+    Misaligned(&'static str),
+    OutOfBounds(&'static str),
+    Overlap(&'static str, &'static str),
+    DuplicateName(&'static str),
+    /// There is a gap before the named NVM partition.
+    NvmNotContiguous(&'static str),
+    NoNvm,
+    /// No bootloader partition at the start of flash to hold the second stage bootloader.
+    NoBoot2Space,
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutError::Empty(name) => write!(f, "partition {name} has no size"),
+            LayoutError::Misaligned(name) => {
+                write!(f, "partition {name} is not aligned to the flash erase size")
+            }
+            LayoutError::OutOfBounds(name) => write!(f, "partition {name} does not fit in flash"),
+            LayoutError::Overlap(first, second) => {
+                write!(f, "partitions {first} and {second} overlap")
+            }
+            LayoutError::DuplicateName(name) => write!(f, "partition {name} is defined twice"),
+            LayoutError::NvmNotContiguous(name) => {
+                write!(
+                    f,
+                    "NVM partition {name} does not follow on from the previous one"
+                )
+            }
+            LayoutError::NoNvm => write!(f, "there are no NVM partitions"),
+            LayoutError::NoBoot2Space => write!(
+                f,
+                "a bootloader partition larger than boot2 must start at the beginning of flash"
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FlashLayout<'a> {
+    pub flash_size: u32,
+    pub erase_size: u32,
+    pub ram_start: u32,
+    pub ram_size: u32,
+    pub partitions: &'a [Partition],
+}
+
+/// The layout of the board as described in [`layout`].
+pub fn board_layout() -> FlashLayout<'static> {
+    FlashLayout {
+        flash_size: layout::FLASH_SIZE,
+        erase_size: layout::ERASE_SIZE,
+        ram_start: layout::RAM_START,
+        ram_size: layout::RAM_SIZE,
+        partitions: layout::PARTITIONS,
+    }
+}
+
+impl FlashLayout<'_> {
+    pub fn validate(&self) -> Result<(), LayoutError> {
+        for (index, partition) in self.partitions.iter().enumerate() {
+            if partition.size == 0 {
+                return Err(LayoutError::Empty(partition.name));
+            }
+            if partition.offset % self.erase_size != 0 || partition.size % self.erase_size != 0 {
+                return Err(LayoutError::Misaligned(partition.name));
+            }
+            if partition
+                .offset
+                .checked_add(partition.size)
+                .is_none_or(|end| end > self.flash_size)
+            {
+                return Err(LayoutError::OutOfBounds(partition.name));
+            }
+            for other in &self.partitions[index + 1..] {
+                if other.name == partition.name {
+                    return Err(LayoutError::DuplicateName(partition.name));
+                }
+                let (a, b) = (partition.range(), other.range());
+                if a.start < b.end && b.start < a.end {
+                    return Err(LayoutError::Overlap(partition.name, other.name));
+                }
+            }
+        }
+
+        if !self
+            .partitions
+            .iter()
+            .any(|p| p.region == Region::Bootloader && p.offset == 0 && p.size > BOOT2_SIZE)
+        {
+            return Err(LayoutError::NoBoot2Space);
+        }
+
+        let mut nvm_end = None;
+        for partition in self.nvm_partitions() {
+            if nvm_end.is_some_and(|end| end != partition.offset) {
+                return Err(LayoutError::NvmNotContiguous(partition.name));
+            }
+            nvm_end = Some(partition.offset + partition.size);
+        }
+        if nvm_end.is_none() {
+            return Err(LayoutError::NoNvm);
+        }
+
+        Ok(())
+    }
+
+    /// NVM partitions in address order.
+    pub fn nvm_partitions(&self) -> impl Iterator<Item = &Partition> {
+        let mut nvm: Vec<&Partition> = self
+            .partitions
+            .iter()
+            .filter(|p| p.region == Region::Nvm)
+            .collect();
+        nvm.sort_by_key(|p| p.offset);
+        nvm.into_iter()
+    }
+
+    /// Range of flash, as offsets from the start of flash, covering all the NVM partitions.
+    pub fn nvm_range(&self) -> Range<u32> {
+        let start = self.nvm_partitions().map(|p| p.offset).min().unwrap_or(0);
+        let end = self
+            .nvm_partitions()
+            .map(|p| p.range().end)
+            .max()
+            .unwrap_or(0);
+        start..end
+    }
+
+    /// Generates the linker script symbols included by each crate's `memory.x`.
+    pub fn linker_script(&self) -> String {
+        let mut script = String::new();
+        writeln!(
+            script,
+            "/* Generated from smartcoaster-partitions - do not edit */"
+        )
+        .unwrap();
+        writeln!(script, "_flash_start = {:#010x};", FLASH_BASE_ADDRESS).unwrap();
+        writeln!(script, "_flash_size = {:#x};", self.flash_size).unwrap();
+        writeln!(script, "_boot2_size = {:#x};", BOOT2_SIZE).unwrap();
+        writeln!(script, "_end_of_flash_memory = _flash_start + _flash_size;").unwrap();
+        writeln!(script, "_ram_start = {:#010x};", self.ram_start).unwrap();
+        writeln!(script, "_ram_size = {:#x};", self.ram_size).unwrap();
+        writeln!(script, "_page_size = {:#x};", self.erase_size).unwrap();
+
+        for partition in self.partitions {
+            writeln!(script).unwrap();
+            let name = partition.name;
+            if partition.offset == 0 {
+                // boot2 is placed at the start of the first partition by the linker
+                writeln!(script, "_{name}_start = _flash_start + _boot2_size;").unwrap();
+                writeln!(
+                    script,
+                    "_{name}_size = {:#x} - _boot2_size;",
+                    partition.size
+                )
+                .unwrap();
+            } else {
+                writeln!(
+                    script,
+                    "_{name}_start = _flash_start + {:#x};",
+                    partition.offset
+                )
+                .unwrap();
+                writeln!(script, "_{name}_size = {:#x};", partition.size).unwrap();
+            }
+            writeln!(script, "_{name}_end = _{name}_start + _{name}_size;").unwrap();
+        }
+
+        let nvm_range = self.nvm_range();
+        writeln!(script).unwrap();
+        writeln!(
+            script,
+            "_app_nvm_start = _flash_start + {:#x};",
+            nvm_range.start
+        )
+        .unwrap();
+        writeln!(script, "_app_nvm_total_size = {:#x};", nvm_range.len()).unwrap();
+        script
+    }
+
+    /// Generates Rust constants for the flash size and NVM partitions. Partition ranges are
+    /// offsets from the start of the NVM area.
+    pub fn rust_constants(&self) -> String {
+        let mut constants = String::new();
+        writeln!(
+            constants,
+            "// Generated from smartcoaster-partitions - do not edit"
+        )
+        .unwrap();
+        writeln!(
+            constants,
+            "pub const FLASH_SIZE: usize = {:#x};",
+            self.flash_size
+        )
+        .unwrap();
+        writeln!(
+            constants,
+            "pub const FLASH_ERASE_SIZE: usize = {:#x};",
+            self.erase_size
+        )
+        .unwrap();
+
+        let nvm_range = self.nvm_range();
+        writeln!(
+            constants,
+            "pub const NVM_PARTITION_SIZE: usize = {:#x};",
+            nvm_range.len()
+        )
+        .unwrap();
+        writeln!(
+            constants,
+            "pub const NVM_PARTITION_RANGE: core::ops::Range<u32> = {:#x}..{:#x};",
+            nvm_range.start, nvm_range.end
+        )
+        .unwrap();
+        for partition in self.nvm_partitions() {
+            let range = partition.range();
+            writeln!(
+                constants,
+                "pub const {}_NVM_FLASH_OFFSET_RANGE: core::ops::Range<u32> = {:#x}..{:#x};",
+                partition.name.to_uppercase(),
+                range.start - nvm_range.start,
+                range.end - nvm_range.start
+            )
+            .unwrap();
+        }
+        constants
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(partitions: &[Partition]) -> FlashLayout<'_> {
+        FlashLayout {
+            flash_size: 64 * KIB,
+            erase_size: 4 * KIB,
+            ram_start: 0x2000_0000,
+            ram_size: 16 * KIB,
+            partitions,
+        }
+    }
+
+    const BOOTLOADER: Partition = Partition::new("bootloader", Region::Bootloader, 0, 8 * KIB);
+    const SETTINGS: Partition = Partition::new("settings", Region::Nvm, 56 * KIB, 8 * KIB);
+
+    #[test]
+    fn board_layout_is_valid() {
+        assert_eq!(board_layout().validate(), Ok(()));
+    }
+
+    #[test]
+    fn board_nvm_is_at_end_of_flash() {
+        assert_eq!(board_layout().nvm_range().end, layout::FLASH_SIZE);
+    }
+
+    #[test]
+    fn misaligned_partition_rejected() {
+        let log = Partition::new("log", Region::Nvm, 50 * KIB, 6 * KIB);
+        assert_eq!(
+            layout(&[BOOTLOADER, log, SETTINGS]).validate(),
+            Err(LayoutError::Misaligned("log"))
+        );
+    }
+
+    #[test]
+    fn overlap_rejected() {
+        let log = Partition::new("log", Region::Nvm, 52 * KIB, 8 * KIB);
+        assert_eq!(
+            layout(&[BOOTLOADER, log, SETTINGS]).validate(),
+            Err(LayoutError::Overlap("log", "settings"))
+        );
+    }
+
+    #[test]
+    fn partition_past_end_of_flash_rejected() {
+        let log = Partition::new("log", Region::Nvm, 60 * KIB, 8 * KIB);
+        assert_eq!(
+            layout(&[BOOTLOADER, log]).validate(),
+            Err(LayoutError::OutOfBounds("log"))
+        );
+    }
+
+    #[test]
+    fn gap_in_nvm_rejected() {
+        let log = Partition::new("log", Region::Nvm, 44 * KIB, 8 * KIB);
+        assert_eq!(
+            layout(&[BOOTLOADER, log, SETTINGS]).validate(),
+            Err(LayoutError::NvmNotContiguous("settings"))
+        );
+    }
+
+    #[test]
+    fn duplicate_name_rejected() {
+        let settings = Partition::new("settings", Region::Nvm, 48 * KIB, 8 * KIB);
+        assert_eq!(
+            layout(&[BOOTLOADER, settings, SETTINGS]).validate(),
+            Err(LayoutError::DuplicateName("settings"))
+        );
+    }
+
+    #[test]
+    fn bootloader_required_at_start() {
+        let bootloader = Partition::new("bootloader", Region::Bootloader, 4 * KIB, 4 * KIB);
+        assert_eq!(
+            layout(&[bootloader, SETTINGS]).validate(),
+            Err(LayoutError::NoBoot2Space)
+        );
+    }
+
+    #[test]
+    fn nvm_offsets_relative_to_nvm_start() {
+        let log = Partition::new("log", Region::Nvm, 48 * KIB, 8 * KIB);
+        let constants = layout(&[BOOTLOADER, SETTINGS, log]).rust_constants();
+        assert!(
+            constants.contains(
+                "pub const NVM_PARTITION_RANGE: core::ops::Range<u32> = 0xc000..0x10000;"
+            )
+        );
+        assert!(constants.contains(
+            "pub const LOG_NVM_FLASH_OFFSET_RANGE: core::ops::Range<u32> = 0x0..0x2000;"
+        ));
+        assert!(constants.contains(
+            "pub const SETTINGS_NVM_FLASH_OFFSET_RANGE: core::ops::Range<u32> = 0x2000..0x4000;"
+        ));
+    }
+
+    #[test]
+    fn linker_script_reserves_boot2() {
+        let script = layout(&[BOOTLOADER, SETTINGS]).linker_script();
+        assert!(script.contains("_bootloader_start = _flash_start + _boot2_size;"));
+        assert!(script.contains("_bootloader_size = 0x2000 - _boot2_size;"));
+        assert!(script.contains("_app_nvm_start = _flash_start + 0xe000;"));
+        assert!(script.contains("_app_nvm_total_size = 0x2000;"));
+    }
+}
@@ -0,0 +1,221 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Measures a "last hour" or "since day start" read of the consumption log, finding where it starts
+//! and then reading the entries from there, as the log manager does. Compares the page index, which
+//! reads only the page holding the start, with the chunked scan from the oldest entry it replaced.
+//! Runs against flash held in RAM, counting flash reads as well as time, and fails if the indexed
+//! reads grow in step with the log rather than staying near a page.
+//!
+//! Run with `cargo bench --no-default-features --bench log_search`.
+
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+use embassy_embedded_hal::adapter::BlockingAsync;
+use embassy_embedded_hal::flash::partition::BlockingPartition;
+use embassy_futures::block_on;
+use embassy_sync::blocking_mutex::Mutex;
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use smartcoaster_application::drink_monitor::log_data::DrinkMonitorLogData;
+use smartcoaster_application::storage::historical::LogEncodeDecode;
+use smartcoaster_application::storage::historical::accessor::RetrievedLogEntry;
+use smartcoaster_application::storage::historical::index::{
+    LogIndex, LogPosition, find_log_start, read_log_items,
+};
+use smartcoaster_application::storage::historical::log_config::Logs;
+use smartcoaster_application::storage::historical::manager::{
+    DATA_BUFFER_SIZE, MAX_READ_CHUNK_SIZE,
+};
+use smartcoaster_application::storage::historical::timestamp::encode_timestamp;
+use smartcoaster_application::storage::storage_manager::{
+    BlockingAsyncPartition, BlockingFlash, StorageManager, StorageManagerSequentialStorage,
+    StoredLogConfig,
+};
+use smartcoaster_application::{NVM_PARTITION_SIZE, SETTINGS_NVM_FLASH_OFFSET_RANGE};
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+type FlashMutex = Mutex<CriticalSectionRawMutex, RefCell<BlockingFlash>>;
+type Storage = StorageManagerSequentialStorage<BlockingAsyncPartition>;
+
+const LOG_SIZES: [u32; 4] = [50, 200, 500, 1000];
+const RUNS: u32 = 20;
+
+fn entry_time(index: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2025, 6, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+        + TimeDelta::minutes(15 * index as i64)
+}
+
+/// Consumption log holding `entries` drinks, one every 15 minutes.
+fn filled_log(entries: u32) -> (&'static FlashMutex, Storage, StoredLogConfig) {
+    let flash: &'static FlashMutex =
+        Box::leak(Box::new(Mutex::new(RefCell::new(BlockingFlash::new()))));
+    let partition = BlockingAsync::new(BlockingPartition::new(flash, 0, NVM_PARTITION_SIZE as u32));
+    let mut storage = Storage::new();
+    block_on(storage.initialise(partition, SETTINGS_NVM_FLASH_OFFSET_RANGE.clone()));
+    let config = Logs::ConsumptionLog.get_config();
+    for n in 0..entries {
+        let mut data = [0u8; DATA_BUFFER_SIZE];
+        let size = encode_timestamp(entry_time(n), &mut data);
+        let size = size
+            + DrinkMonitorLogData::default()
+                .encode(&mut data[size..])
+                .unwrap();
+        block_on(storage.write_log_data(&config, &data[..size])).unwrap();
+    }
+    (flash, storage, config)
+}
+
+/// The search used before the index, reading chunks from the oldest entry until the target.
+fn chunked_scan(storage: &mut Storage, config: &StoredLogConfig, target: NaiveDateTime) -> usize {
+    let mut buffer = [[0u8; DATA_BUFFER_SIZE]; MAX_READ_CHUNK_SIZE];
+    let mut position = 0;
+    loop {
+        let count =
+            block_on(storage.get_log_items(config, position, MAX_READ_CHUNK_SIZE, &mut buffer))
+                .unwrap();
+        for item in &buffer[..count] {
+            if RetrievedLogEntry::from_buffer(item).unwrap().timestamp >= target {
+                return position;
+            }
+            position += 1;
+        }
+        if count < MAX_READ_CHUNK_SIZE {
+            return position;
+        }
+    }
+}
+
+/// Reads the entries from `start` a chunk at a time, giving the number read.
+fn read_from(storage: &mut Storage, config: &StoredLogConfig, start: usize) -> usize {
+    let mut buffer = [[0u8; DATA_BUFFER_SIZE]; MAX_READ_CHUNK_SIZE];
+    let mut read = 0;
+    loop {
+        let count =
+            block_on(storage.get_log_items(config, start + read, MAX_READ_CHUNK_SIZE, &mut buffer))
+                .unwrap();
+        read += count;
+        if count < MAX_READ_CHUNK_SIZE {
+            return read;
+        }
+    }
+}
+
+/// Reads the entries from `position` a chunk at a time through the index, giving the number read.
+fn read_on_from(
+    storage: &mut Storage,
+    config: &StoredLogConfig,
+    index: &mut LogIndex,
+    mut position: LogPosition,
+) -> usize {
+    let mut buffer = [[0u8; DATA_BUFFER_SIZE]; MAX_READ_CHUNK_SIZE];
+    let mut read = 0;
+    loop {
+        let count = block_on(read_log_items(
+            storage,
+            config,
+            index,
+            &mut position,
+            &mut buffer,
+        ))
+        .unwrap();
+        read += count;
+        if count < MAX_READ_CHUNK_SIZE {
+            return read;
+        }
+    }
+}
+
+fn read_count(flash: &FlashMutex) -> usize {
+    flash.lock(|flash| flash.borrow().read_count())
+}
+
+/// Runs `read` repeatedly, giving the average time and flash reads of a run.
+fn measure(flash: &FlashMutex, mut read: impl FnMut() -> usize) -> (Duration, usize, usize) {
+    let reads_before = read_count(flash);
+    let start = Instant::now();
+    let mut position = 0;
+    for _ in 0..RUNS {
+        position = read();
+    }
+    let elapsed = start.elapsed() / RUNS;
+    let reads = (read_count(flash) - reads_before) / RUNS as usize;
+    (elapsed, reads, position)
+}
+
+fn report(name: &str, entries: u32, (elapsed, reads, position): (Duration, usize, usize)) {
+    println!(
+        "{name:<28} {entries:>5} entries  start {position:>4}  {reads:>7} flash reads  {elapsed:>10.2?}"
+    );
+}
+
+fn main() {
+    // entries held and indexed flash reads per entry of the "last hour" read, for each log size
+    let mut last_hour_reads = Vec::new();
+    for entries in LOG_SIZES {
+        let (flash, mut storage, config) = filled_log(entries);
+        let mut held = 0;
+        block_on(storage.for_each_log_item(&config, |_| held += 1)).unwrap();
+        let last = entry_time(entries - 1);
+        let targets = [
+            ("last hour", last - TimeDelta::hours(1)),
+            ("since day start", last.date().and_hms_opt(0, 0, 0).unwrap()),
+        ];
+
+        for (query, target) in targets {
+            let scan = measure(flash, || {
+                let start = chunked_scan(&mut storage, &config, target);
+                read_from(&mut storage, &config, start);
+                start
+            });
+            report(&format!("{query} - chunked scan"), entries, scan);
+
+            let reads_before = read_count(flash);
+            let mut index = LogIndex::new();
+            let start =
+                block_on(find_log_start(&mut storage, &config, &mut index, target)).unwrap();
+            let position = index.position_in_log(&start);
+            let build_reads = read_count(flash) - reads_before;
+            println!(
+                "{:<28} {entries:>5} entries  start {position:>4}  {build_reads:>7} flash reads",
+                format!("{query} - index build")
+            );
+
+            let indexed = measure(flash, || {
+                let start =
+                    block_on(find_log_start(&mut storage, &config, &mut index, target)).unwrap();
+                let position = index.position_in_log(&start);
+                read_on_from(&mut storage, &config, &mut index, start);
+                position
+            });
+            assert_eq!(indexed.2, scan.2);
+            report(&format!("{query} - indexed"), entries, indexed);
+            if query == "last hour" {
+                last_hour_reads.push((held, indexed.1));
+            }
+        }
+    }
+
+    let (smallest, smallest_reads) = last_hour_reads[0];
+    let (largest, largest_reads) = *last_hour_reads.last().unwrap();
+    println!(
+        "last hour - indexed: {smallest_reads} flash reads for {smallest} entries, {largest_reads} for {largest}"
+    );
+    assert!(
+        2 * largest_reads * smallest < smallest_reads * largest,
+        "indexed flash reads grow with the log"
+    );
+}
@@ -0,0 +1,95 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A display with no window. Each flushed frame is kept as text so it can be inspected.
+
+use crate::DISPLAY_SIZE;
+use crate::snapshot::to_text;
+use embedded_graphics::Pixel;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics_simulator::SimulatorDisplay;
+use smartcoaster_application::hmi::display::HmiDisplay;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Screen {
+    /// Last flushed frame, in the format used by the snapshot tests.
+    pub frame: String,
+    pub contrast: Option<u8>,
+}
+
+pub struct HeadlessDisplay {
+    buffer: SimulatorDisplay<BinaryColor>,
+    screen: Arc<Mutex<Screen>>,
+}
+
+impl HeadlessDisplay {
+    pub fn new() -> Self {
+        Self {
+            buffer: SimulatorDisplay::new(DISPLAY_SIZE),
+            screen: Arc::default(),
+        }
+    }
+
+    /// Shared with the display so the screen can be inspected while the display task owns it.
+    pub fn screen(&self) -> Arc<Mutex<Screen>> {
+        self.screen.clone()
+    }
+}
+
+impl Default for HeadlessDisplay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OriginDimensions for HeadlessDisplay {
+    fn size(&self) -> Size {
+        DISPLAY_SIZE
+    }
+}
+
+impl DrawTarget for HeadlessDisplay {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.buffer.draw_iter(pixels)
+    }
+}
+
+impl HmiDisplay for HeadlessDisplay {
+    fn init(&mut self) -> Result<(), ()> {
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), ()> {
+        self.screen.lock().unwrap().frame = to_text(&self.buffer);
+        Ok(())
+    }
+
+    fn clear_buffer(&mut self) {
+        self.buffer.clear(BinaryColor::Off).unwrap();
+    }
+
+    fn set_contrast(&mut self, contrast: u8) -> Result<(), ()> {
+        self.screen.lock().unwrap().contrast = Some(contrast);
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! LEDs that record the mode they are set to rather than driving a WS2812 array.
+
+use smartcoaster_application::led::led_control::{LedArrayMode, LedControl};
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LedState {
+    pub mode: Option<LedArrayMode>,
+    pub brightness: Option<u8>,
+}
+
+/// Clones share the same state so the LEDs can be inspected while the LED task owns them.
+#[derive(Clone, Default)]
+pub struct RecordingLedControl {
+    state: Arc<Mutex<LedState>>,
+}
+
+impl RecordingLedControl {
+    pub fn state(&self) -> LedState {
+        *self.state.lock().unwrap()
+    }
+}
+
+impl LedControl for RecordingLedControl {
+    fn set_mode(&mut self, mode: LedArrayMode) {
+        self.state.lock().unwrap().mode = Some(mode);
+    }
+
+    fn set_speed_factor(&mut self, _speed: f32) {}
+
+    fn set_repetition_factor(&mut self, _repetition_factor: f32) {}
+
+    async fn set_brightness(&mut self, brightness: u8) {
+        self.state.lock().unwrap().brightness = Some(brightness);
+    }
+
+    async fn led_update(&mut self) {}
+}
@@ -0,0 +1,564 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Runs the whole application on the host. The tasks are the ones the firmware runs, spawned on
+//! embassy's std executor, with the RP2040 peripherals replaced by:
+//!
+//! * a [`ScriptedStrainGauge`] whose load is set by the scenario
+//! * flash held in RAM
+//! * a [`HostRtc`] that starts at a chosen time
+//! * LEDs that record the mode they are set to
+//! * a [`HeadlessDisplay`]
+//!
+//! The application keeps its storage, settings and clock in statics, so only one can be started in
+//! a process. Each end-to-end scenario is therefore its own integration test file.
+
+pub mod display;
+pub mod led;
+pub mod strain_gauge;
+
+use crate::app::display::{HeadlessDisplay, Screen};
+use crate::app::led::{LedState, RecordingLedControl};
+use crate::app::strain_gauge::{ScriptedStrainGauge, StrainGaugeScript, calibration_settings};
+use chrono::{NaiveDateTime, TimeDelta};
+use embassy_executor::Executor;
+use embassy_futures::block_on;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::pubsub::PubSubChannel;
+use embedded_alloc::LlffHeap as Heap;
+use smartcoaster_app_core::daily_summary::DailySummary;
+use smartcoaster_application::application::application_manager::ApplicationManager;
+use smartcoaster_application::application::application_state::ApplicationState;
+use smartcoaster_application::application::led_manager::LedManager;
+use smartcoaster_application::application::messaging::{
+    ApplicationChannel, ApplicationChannelPublisher, ApplicationChannelSubscriber, ApplicationData,
+    ApplicationMessage,
+};
+use smartcoaster_application::application::weighing_manager::WeighingManager;
+use smartcoaster_application::drink_monitor::drink_monitoring::{
+    DrinkMonitoring, MonitoringStateSubstates,
+};
+use smartcoaster_application::drink_monitor::log_data::DrinkMonitorLogData;
+use smartcoaster_application::drink_monitor::messaging::{
+    DrinkMonitorChannel, DrinkMonitorChannelPublisher, DrinkMonitorChannelSubscriber,
+    DrinkMonitoringUpdate,
+};
+use smartcoaster_application::drink_monitor::rollup_data::DailyRollupLogData;
+use smartcoaster_application::error_log::process_error_log_queue;
+use smartcoaster_application::hmi::display::DisplayManager;
+use smartcoaster_application::hmi::messaging::{
+    HmiChannel, HmiChannelPublisher, HmiChannelSubscriber, HmiMessage, UiActionChannel,
+    UiActionChannelPublisher, UiActionChannelSubscriber,
+};
+use smartcoaster_application::hmi::rotary_encoder::Direction;
+use smartcoaster_application::rtc::HostRtc;
+use smartcoaster_application::storage::historical::LogEncodeDecode;
+use smartcoaster_application::storage::historical::accessor::{
+    HistoricalLogAccessor, RetrievedLogEntry,
+};
+use smartcoaster_application::storage::historical::log_config::Logs;
+use smartcoaster_application::storage::historical::manager::{
+    DATA_BUFFER_SIZE, MAX_READ_CHUNK_SIZE, process_log_queues,
+};
+use smartcoaster_application::storage::historical::timestamp::encode_timestamp;
+use smartcoaster_application::storage::settings::accessor::{
+    FlashSettingsAccessor, flush_settings, initialise_settings, next_settings_save_due,
+    process_save_queue,
+};
+use smartcoaster_application::storage::settings::{
+    SettingValue, SettingsAccessor, SettingsAccessorId,
+};
+use smartcoaster_application::storage::signal::wait_for_storage_work;
+use smartcoaster_application::storage::storage_manager::{
+    BlockingFlash, NV_STORAGE, StorageManager, initialise_storage, next_erase_counts_save_due,
+    save_erase_counts,
+};
+use smartcoaster_application::weight::messaging::{
+    WeighingSystemOverChannel, WeightChannel, WeightChannelPublisher, WeightRequestChannel,
+    WeightRequestSubscriber,
+};
+use smartcoaster_application::weight::weight::WeightScale;
+use smartcoaster_application::{NVM_PARTITION_SIZE, SETTINGS_NVM_FLASH_OFFSET_RANGE};
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+static HMI_CHANNEL: HmiChannel = PubSubChannel::new();
+static UI_ACTION_CHANNEL: UiActionChannel = PubSubChannel::new();
+static WEIGHT_CHANNEL: WeightChannel = PubSubChannel::new();
+static WEIGHT_REQUEST_CHANNEL: WeightRequestChannel = PubSubChannel::new();
+static APP_CHANNEL: ApplicationChannel = PubSubChannel::new();
+static DRINK_MONITOR_CHANNEL: DrinkMonitorChannel = PubSubChannel::new();
+
+/// Only used for the heap status screen, so is never given any memory.
+static HEAP: Heap = Heap::empty();
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+/// How often the scenario checks for an expected outcome.
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(20);
+
+pub struct AppConfig {
+    start_time: NaiveDateTime,
+    settings: Vec<(SettingsAccessorId, SettingValue)>,
+    load: f32,
+    first_run: bool,
+    daily_summaries: Vec<DailySummary>,
+}
+
+impl AppConfig {
+    pub fn new(start_time: NaiveDateTime) -> Self {
+        Self {
+            start_time,
+            settings: Vec::new(),
+            load: 0.0,
+            first_run: false,
+            daily_summaries: Vec::new(),
+        }
+    }
+
+    /// Stores a setting before the application starts.
+    pub fn with_setting(mut self, id: SettingsAccessorId, value: SettingValue) -> Self {
+        self.settings.push((id, value));
+        self
+    }
+
+    /// Starts as a freshly flashed device that has not been through first run setup. The scale is
+    /// still calibrated, so setup skips calibration.
+    pub fn first_run(mut self) -> Self {
+        self.first_run = true;
+        self
+    }
+
+    /// Load on the coaster, in grams, when the application starts.
+    pub fn with_load(mut self, grams: f32) -> Self {
+        self.load = grams;
+        self
+    }
+
+    /// Stores a completed day in the daily rollup log before the application starts, as if it
+    /// had been stored when the day ended. Days must be added oldest first.
+    pub fn with_daily_summary(mut self, summary: DailySummary) -> Self {
+        self.daily_summaries.push(summary);
+        self
+    }
+}
+
+/// What the application has told the rest of the system, as seen on the application channel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Observations {
+    pub application_state: ApplicationState,
+    pub monitoring_substate: Option<MonitoringStateSubstates>,
+    pub total_consumed: Option<f32>,
+    pub streak: Option<u32>,
+    /// Number of times the daily goal has been reported as achieved.
+    pub goals_achieved: u32,
+    /// Every consumption reported, oldest first.
+    pub consumptions: Vec<f32>,
+}
+
+impl Observations {
+    fn record(&mut self, message: ApplicationMessage) {
+        match message {
+            ApplicationMessage::ApplicationStateUpdate(state) => self.application_state = state,
+            ApplicationMessage::ApplicationDataUpdate(ApplicationData::MonitoringUpdate(
+                update,
+            )) => match update {
+                DrinkMonitoringUpdate::UpdateMonitoringSubstate(substate) => {
+                    self.monitoring_substate = Some(substate)
+                }
+                DrinkMonitoringUpdate::TotalConsumed(total) => self.total_consumed = Some(total),
+                DrinkMonitoringUpdate::Streak(streak) => self.streak = Some(streak),
+                DrinkMonitoringUpdate::GoalAchieved => self.goals_achieved += 1,
+                DrinkMonitoringUpdate::Consumption(consumption) => {
+                    self.consumptions.push(consumption)
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
+
+impl Default for Observations {
+    fn default() -> Self {
+        Self {
+            application_state: ApplicationState::Startup,
+            monitoring_substate: None,
+            total_consumed: None,
+            streak: None,
+            goals_achieved: 0,
+            consumptions: Vec::new(),
+        }
+    }
+}
+
+/// A consumption log record read back from flash.
+pub struct ConsumptionLogEntry {
+    pub timestamp: NaiveDateTime,
+    pub data: DrinkMonitorLogData,
+}
+
+/// A running application and the controls to drive it.
+pub struct App {
+    hmi_publisher: HmiChannelPublisher<'static>,
+    scale: StrainGaugeScript,
+    leds: RecordingLedControl,
+    screen: Arc<Mutex<Screen>>,
+    observations: Arc<Mutex<Observations>>,
+}
+
+impl App {
+    /// Sets up storage and starts the application tasks on their own thread.
+    ///
+    /// # Panics
+    ///
+    /// If an application has already been started in this process.
+    pub fn start(config: AppConfig) -> Self {
+        assert!(
+            !STARTED.swap(true, Ordering::SeqCst),
+            "Only one application can be started in a process"
+        );
+
+        let flash = Box::leak(Box::new(BlockingMutex::<CriticalSectionRawMutex, _>::new(
+            RefCell::new(BlockingFlash::new()),
+        )));
+        block_on(async {
+            initialise_storage(
+                flash,
+                0..NVM_PARTITION_SIZE as u32,
+                SETTINGS_NVM_FLASH_OFFSET_RANGE.clone(),
+            )
+            .await;
+            initialise_settings().await;
+            let settings = FlashSettingsAccessor::new();
+            let setup_complete = (!config.first_run).then_some((
+                SettingsAccessorId::SystemSetupComplete,
+                SettingValue::SmallUInt(1),
+            ));
+            for (id, value) in calibration_settings()
+                .into_iter()
+                .chain(setup_complete)
+                .chain(config.settings)
+            {
+                settings
+                    .save_setting(id, value)
+                    .await
+                    .expect("Unable to queue setting");
+            }
+            flush_settings().await;
+
+            let rollup_log = Logs::DailyRollupLog.get_config();
+            let mut storage = NV_STORAGE.lock().await;
+            for summary in &config.daily_summaries {
+                let mut data = [0u8; DATA_BUFFER_SIZE];
+                let day_end = summary.day_start + TimeDelta::days(1);
+                let size = encode_timestamp(day_end, &mut data);
+                let size = size
+                    + DailyRollupLogData::from(summary)
+                        .encode(&mut data[size..])
+                        .expect("Unable to encode daily summary");
+                storage
+                    .write_log_data(&rollup_log, &data[..size])
+                    .await
+                    .expect("Unable to store daily summary");
+            }
+        });
+
+        let scale = StrainGaugeScript::default();
+        scale.set_load(config.load);
+        let leds = RecordingLedControl::default();
+        let display = HeadlessDisplay::new();
+        let screen = display.screen();
+        let observations = Arc::new(Mutex::new(Observations::default()));
+        // published before any task starts so they all see the start time
+        let rtc = HostRtc::new(config.start_time);
+
+        let app = Self {
+            hmi_publisher: HMI_CHANNEL.publisher().unwrap(),
+            scale: scale.clone(),
+            leds: leds.clone(),
+            screen,
+            observations: observations.clone(),
+        };
+
+        thread::spawn(move || {
+            let executor: &'static mut Executor = Box::leak(Box::new(Executor::new()));
+            executor.run(|spawner| {
+                spawner.must_spawn(rtc_task(rtc));
+                spawner.must_spawn(storage_task());
+                spawner.must_spawn(observer_task(
+                    APP_CHANNEL.subscriber().unwrap(),
+                    observations,
+                ));
+                spawner.must_spawn(led_task(
+                    leds,
+                    APP_CHANNEL.subscriber().unwrap(),
+                    DRINK_MONITOR_CHANNEL.subscriber().unwrap(),
+                ));
+                spawner.must_spawn(weighing_task(
+                    ScriptedStrainGauge::new(scale),
+                    WEIGHT_REQUEST_CHANNEL.subscriber().unwrap(),
+                    WEIGHT_CHANNEL.publisher().unwrap(),
+                ));
+                spawner.must_spawn(display_task(
+                    display,
+                    APP_CHANNEL.subscriber().unwrap(),
+                    UI_ACTION_CHANNEL.publisher().unwrap(),
+                ));
+                spawner.must_spawn(application_task(
+                    APP_CHANNEL.publisher().unwrap(),
+                    HMI_CHANNEL.subscriber().unwrap(),
+                    UI_ACTION_CHANNEL.subscriber().unwrap(),
+                    DRINK_MONITOR_CHANNEL.subscriber().unwrap(),
+                    WeighingSystemOverChannel::new(
+                        WEIGHT_CHANNEL.subscriber().unwrap(),
+                        WEIGHT_REQUEST_CHANNEL.publisher().unwrap(),
+                    ),
+                    &HEAP,
+                ));
+                spawner.must_spawn(drink_monitor_task(
+                    DRINK_MONITOR_CHANNEL.publisher().unwrap(),
+                    APP_CHANNEL.subscriber().unwrap(),
+                    WeighingSystemOverChannel::new(
+                        WEIGHT_CHANNEL.subscriber().unwrap(),
+                        WEIGHT_REQUEST_CHANNEL.publisher().unwrap(),
+                    ),
+                ));
+            })
+        });
+
+        app
+    }
+
+    /// Controls the load on the coaster.
+    pub fn scale(&self) -> &StrainGaugeScript {
+        &self.scale
+    }
+
+    pub fn press_button(&self) {
+        self.hmi_publisher
+            .publish_immediate(HmiMessage::PushButtonPressed(true));
+        self.hmi_publisher
+            .publish_immediate(HmiMessage::PushButtonPressed(false));
+    }
+
+    /// Turns the encoder by `steps` detents, clockwise when positive.
+    pub fn turn(&self, steps: i32) {
+        let direction = if steps > 0 {
+            Direction::Clockwise
+        } else {
+            Direction::CounterClockwise
+        };
+        for _ in 0..steps.unsigned_abs() {
+            self.hmi_publisher
+                .publish_immediate(HmiMessage::EncoderUpdate(direction));
+        }
+    }
+
+    pub fn observations(&self) -> Observations {
+        self.observations.lock().unwrap().clone()
+    }
+
+    pub fn leds(&self) -> LedState {
+        self.leds.state()
+    }
+
+    pub fn screen(&self) -> Screen {
+        self.screen.lock().unwrap().clone()
+    }
+
+    /// Reads a setting as the application sees it. Saves are written by the storage task, so may
+    /// lag the screens by a fraction of a second.
+    pub fn setting(&self, id: SettingsAccessorId) -> Option<SettingValue> {
+        block_on(FlashSettingsAccessor::new().get_setting(id))
+    }
+
+    /// Reads the consumption log from flash. Records are written by the storage task, so may lag
+    /// the drink monitor by a fraction of a second.
+    pub fn consumption_log(&self) -> Vec<ConsumptionLogEntry> {
+        read_log(Logs::ConsumptionLog)
+            .into_iter()
+            .map(|entry| ConsumptionLogEntry {
+                timestamp: entry.timestamp,
+                data: DrinkMonitorLogData::from_bytes(&entry.data)
+                    .expect("Unable to decode consumption log record"),
+            })
+            .collect()
+    }
+
+    /// Reads the completed days from the daily rollup log, oldest first.
+    pub fn daily_summaries(&self) -> Vec<DailySummary> {
+        read_log(Logs::DailyRollupLog)
+            .into_iter()
+            .map(|entry| {
+                DailyRollupLogData::from_bytes(&entry.data)
+                    .and_then(|data| data.get_summary())
+                    .expect("Unable to decode daily summary")
+            })
+            .collect()
+    }
+
+    /// Waits for `condition` to hold, checking it until `timeout` has passed. Returns whether it
+    /// held.
+    pub fn wait_for(
+        &self,
+        timeout: std::time::Duration,
+        mut condition: impl FnMut(&Self) -> bool,
+    ) -> bool {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if condition(self) {
+                return true;
+            }
+            if Instant::now() >= deadline {
+                return false;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+/// Reads every record of `log` from flash.
+fn read_log(log: Logs) -> Vec<RetrievedLogEntry> {
+    let config = log.get_config();
+    let mut entries = Vec::new();
+    let mut buf = [[0u8; DATA_BUFFER_SIZE]; MAX_READ_CHUNK_SIZE];
+    block_on(async {
+        let mut storage = NV_STORAGE.lock().await;
+        loop {
+            let count = storage
+                .get_log_items(&config, entries.len(), MAX_READ_CHUNK_SIZE, &mut buf)
+                .await
+                .expect("Unable to read log");
+            for record in &buf[..count] {
+                entries.push(
+                    RetrievedLogEntry::from_buffer(record).expect("Unable to decode log timestamp"),
+                );
+            }
+            if count < MAX_READ_CHUNK_SIZE {
+                break;
+            }
+        }
+    });
+    entries
+}
+
+#[embassy_executor::task]
+async fn rtc_task(mut rtc: HostRtc) {
+    rtc.run().await;
+}
+
+#[embassy_executor::task]
+async fn storage_task() {
+    let mut error_log = HistoricalLogAccessor::new(Logs::ErrorLog);
+    loop {
+        let save_due = next_settings_save_due()
+            .await
+            .into_iter()
+            .chain(next_erase_counts_save_due().await)
+            .min();
+        wait_for_storage_work(save_due).await;
+        process_save_queue().await;
+        process_error_log_queue(&mut error_log).await;
+        process_log_queues().await;
+        save_erase_counts().await;
+    }
+}
+
+#[embassy_executor::task]
+async fn observer_task(
+    mut app_subscriber: ApplicationChannelSubscriber<'static>,
+    observations: Arc<Mutex<Observations>>,
+) {
+    loop {
+        let message = app_subscriber.next_message_pure().await;
+        observations.lock().unwrap().record(message);
+    }
+}
+
+#[embassy_executor::task]
+async fn led_task(
+    led_control: RecordingLedControl,
+    application_subscriber: ApplicationChannelSubscriber<'static>,
+    drink_monitor_subscriber: DrinkMonitorChannelSubscriber<'static>,
+) {
+    let mut led_manager = LedManager::new(
+        led_control,
+        application_subscriber,
+        drink_monitor_subscriber,
+    );
+    led_manager.run().await;
+}
+
+#[embassy_executor::task]
+async fn weighing_task(
+    strain_gauge: ScriptedStrainGauge,
+    weight_request_subscriber: WeightRequestSubscriber<'static>,
+    weight_event_sender: WeightChannelPublisher<'static>,
+) {
+    let settings = FlashSettingsAccessor::new();
+    let weight_scale = WeightScale::new(strain_gauge, settings).await.unwrap();
+    let mut weighing_manager =
+        WeighingManager::new(weight_request_subscriber, weight_event_sender, weight_scale);
+    weighing_manager.run().await;
+}
+
+#[embassy_executor::task]
+async fn display_task(
+    display: HeadlessDisplay,
+    app_subscriber: ApplicationChannelSubscriber<'static>,
+    ui_action_publisher: UiActionChannelPublisher<'static>,
+) {
+    let settings = FlashSettingsAccessor::new();
+    let mut display_manager =
+        DisplayManager::new(display, app_subscriber, ui_action_publisher, &settings).await;
+    display_manager.run().await;
+}
+
+#[embassy_executor::task]
+async fn application_task(
+    app_channel_sender: ApplicationChannelPublisher<'static>,
+    hmi_channel_receiver: HmiChannelSubscriber<'static>,
+    ui_action_channel_receiver: UiActionChannelSubscriber<'static>,
+    drink_monitor_receiver: DrinkMonitorChannelSubscriber<'static>,
+    weight_interface: WeighingSystemOverChannel,
+    heap: &'static Heap,
+) {
+    let mut application_manager =
+        ApplicationManager::new(app_channel_sender, weight_interface, heap);
+    application_manager
+        .run(
+            ui_action_channel_receiver,
+            hmi_channel_receiver,
+            drink_monitor_receiver,
+        )
+        .await;
+}
+
+#[embassy_executor::task]
+async fn drink_monitor_task(
+    drink_monitor_publisher: DrinkMonitorChannelPublisher<'static>,
+    application_channel_subscriber: ApplicationChannelSubscriber<'static>,
+    weight_interface: WeighingSystemOverChannel,
+) {
+    let settings = FlashSettingsAccessor::new();
+    let mut drink_monitor = DrinkMonitoring::new(drink_monitor_publisher, weight_interface);
+    drink_monitor
+        .run(application_channel_subscriber, settings)
+        .await;
+}
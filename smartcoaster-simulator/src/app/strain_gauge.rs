@@ -0,0 +1,145 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A strain gauge that reports a load set by the scenario rather than one measured by an HX711.
+
+use embassy_time::{Duration, Instant, Timer};
+use smartcoaster_application::storage::settings::{SettingValue, SettingsAccessorId};
+use smartcoaster_application::weight::interface::AsyncStrainGaugeInterface;
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+
+/// Reading with nothing on the coaster.
+const EMPTY_READING: i32 = 80_000;
+/// A power of two so the calibration gradient, and so the weights, are exact.
+const COUNTS_PER_GRAM: i32 = 512;
+const BITS_TO_DISCARD: u8 = 1;
+/// The HX711 at 80 samples per second.
+const SAMPLE_PERIOD: Duration = Duration::from_micros(12_500);
+
+/// Calibration that converts the gauge readings to the scripted load in grams. Stored before the
+/// application starts so the scale doesn't need calibrating.
+pub fn calibration_settings() -> [(SettingsAccessorId, SettingValue); 3] {
+    [
+        (
+            SettingsAccessorId::WeighingSystemTareOffset,
+            SettingValue::Float((EMPTY_READING >> BITS_TO_DISCARD) as f32),
+        ),
+        (
+            SettingsAccessorId::WeighingSystemCalibrationGradient,
+            SettingValue::Float((1 << BITS_TO_DISCARD) as f32 / COUNTS_PER_GRAM as f32),
+        ),
+        (
+            SettingsAccessorId::WeighingSystemBitsToDiscard,
+            SettingValue::SmallUInt(BITS_TO_DISCARD),
+        ),
+    ]
+}
+
+#[derive(Default)]
+struct Script {
+    load: f32,
+    /// When the current load is replaced by the next step. None once the script has finished.
+    load_until: Option<Instant>,
+    steps: VecDeque<(f32, Duration)>,
+}
+
+impl Script {
+    fn load(&mut self) -> f32 {
+        while let Some(until) = self.load_until {
+            if Instant::now() < until {
+                break;
+            }
+            match self.steps.pop_front() {
+                Some((load, hold)) => {
+                    self.load = load;
+                    self.load_until = Some(until + hold);
+                }
+                None => self.load_until = None,
+            }
+        }
+        self.load
+    }
+}
+
+/// Controls the load on a [`ScriptedStrainGauge`]. Clones control the same gauge.
+#[derive(Clone, Default)]
+pub struct StrainGaugeScript {
+    script: Arc<Mutex<Script>>,
+}
+
+impl StrainGaugeScript {
+    /// Sets the load in grams straight away, abandoning any script that is running.
+    pub fn set_load(&self, grams: f32) {
+        let mut script = self.script.lock().unwrap();
+        script.steps.clear();
+        script.load_until = None;
+        script.load = grams;
+    }
+
+    /// Runs a script of loads, each given in grams with how long it is held for. The final load is
+    /// held once the script finishes.
+    pub fn run(&self, steps: &[(f32, Duration)]) {
+        let mut script = self.script.lock().unwrap();
+        script.steps = steps.iter().copied().collect();
+        script.load_until = Some(Instant::now());
+        script.load();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        let mut script = self.script.lock().unwrap();
+        script.load();
+        script.load_until.is_none()
+    }
+
+    pub fn load(&self) -> f32 {
+        self.script.lock().unwrap().load()
+    }
+}
+
+pub struct ScriptedStrainGauge {
+    script: StrainGaugeScript,
+}
+
+impl ScriptedStrainGauge {
+    pub fn new(script: StrainGaugeScript) -> Self {
+        Self { script }
+    }
+}
+
+impl AsyncStrainGaugeInterface for ScriptedStrainGauge {
+    type Error = Infallible;
+
+    async fn initialize(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn get_next_reading(&mut self) -> Result<i32, Self::Error> {
+        Timer::after(SAMPLE_PERIOD).await;
+        Ok(EMPTY_READING + (self.script.load() * COUNTS_PER_GRAM as f32).round() as i32)
+    }
+
+    async fn power_down(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn power_up(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn get_adc_bit_count(&self) -> usize {
+        24
+    }
+}
@@ -0,0 +1,197 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Drives the application's HMI screens on a desktop. The screens are the same code that runs on
+//! the device, rendered to an `embedded-graphics-simulator` display rather than the SH1106.
+//!
+//! [`app`] goes further and runs the whole application, with mocked peripherals, so end-to-end
+//! scenarios can be tested.
+
+pub mod app;
+mod defmt_logger;
+pub mod settings;
+pub mod snapshot;
+
+use chrono::{NaiveDateTime, TimeDelta};
+use embassy_futures::block_on;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics_simulator::SimulatorDisplay;
+use smartcoaster_application::application::application_state::ApplicationState;
+use smartcoaster_application::application::messaging::ApplicationData;
+use smartcoaster_application::drink_monitor::drink_monitoring::MonitoringStateSubstates;
+use smartcoaster_application::drink_monitor::messaging::{
+    DAY_PROGRESS_POINTS, DrinkMonitoringUpdate,
+};
+use smartcoaster_application::hmi::messaging::{
+    UiActionChannel, UiActionChannelSubscriber, UiRequestMessage,
+};
+use smartcoaster_application::hmi::screen_manager::ScreenManager;
+use smartcoaster_application::hmi::screens::UiInput;
+use smartcoaster_application::storage::settings::SettingsAccessor;
+
+/// Size of the SH1106 display fitted to the coaster.
+pub const DISPLAY_SIZE: Size = Size::new(128, 64);
+
+/// The HMI screens with a display to draw them on. State changes requested by the screens are
+/// applied straight away, standing in for the application manager.
+pub struct Simulator<SA>
+where
+    SA: SettingsAccessor + 'static,
+{
+    screens: ScreenManager<'static, SA>,
+    ui_action_subscriber: UiActionChannelSubscriber<'static>,
+    display: SimulatorDisplay<BinaryColor>,
+    now: NaiveDateTime,
+}
+
+impl<SA> Simulator<SA>
+where
+    SA: SettingsAccessor + 'static,
+{
+    pub fn new(settings: SA, now: NaiveDateTime) -> Self {
+        // Each simulator has its own channel and settings so several can run side by side
+        let ui_action_channel: &'static UiActionChannel =
+            Box::leak(Box::new(UiActionChannel::new()));
+        let settings: &'static SA = Box::leak(Box::new(settings));
+        let screens = block_on(ScreenManager::new(
+            ui_action_channel.publisher().unwrap(),
+            settings,
+        ));
+
+        Self {
+            screens,
+            ui_action_subscriber: ui_action_channel.subscriber().unwrap(),
+            display: SimulatorDisplay::new(DISPLAY_SIZE),
+            now,
+        }
+    }
+
+    pub fn display_state(&self) -> ApplicationState {
+        self.screens.display_state()
+    }
+
+    pub fn set_display_state(&mut self, state: ApplicationState) {
+        block_on(self.screens.set_display_state(state, self.now));
+        self.process_ui_actions();
+    }
+
+    pub fn input(&mut self, input: UiInput) {
+        block_on(self.screens.route_ui_input(input));
+        self.process_ui_actions();
+    }
+
+    /// Presses and releases the encoder button.
+    pub fn click(&mut self) {
+        self.input(UiInput::ButtonPress);
+        self.input(UiInput::ButtonRelease);
+    }
+
+    /// Turns the encoder by `steps` detents, clockwise when positive.
+    pub fn turn(&mut self, steps: i32) {
+        for _ in 0..steps.unsigned_abs() {
+            if steps > 0 {
+                self.input(UiInput::EncoderClockwise);
+            } else {
+                self.input(UiInput::EncoderCounterClockwise);
+            }
+        }
+    }
+
+    pub fn application_data(&mut self, data: ApplicationData) {
+        self.input(UiInput::ApplicationData(data));
+    }
+
+    pub fn set_date_time(&mut self, now: NaiveDateTime) {
+        self.now = now;
+        if self.display_state() != ApplicationState::SetSystemDateTime {
+            self.input(UiInput::DateTimeUpdate(now));
+        }
+    }
+
+    /// Draws the current screen and returns the display.
+    pub fn render(&mut self) -> &SimulatorDisplay<BinaryColor> {
+        self.display.clear(BinaryColor::Off).unwrap();
+        self.screens.draw(&mut self.display).unwrap();
+        &self.display
+    }
+
+    fn process_ui_actions(&mut self) {
+        while let Some(request) = self.ui_action_subscriber.try_next_message_pure() {
+            if let UiRequestMessage::ChangeState(state) = request {
+                block_on(self.screens.set_display_state(state, self.now));
+            }
+        }
+    }
+}
+
+/// A day part way through, with the vessel on the coaster, for screens to show while there is no
+/// drink monitor running.
+pub fn sample_monitoring_updates(now: NaiveDateTime) -> Vec<DrinkMonitoringUpdate> {
+    let mut updates = vec![
+        DrinkMonitoringUpdate::UpdateMonitoringSubstate(MonitoringStateSubstates::VesselPlaced),
+        DrinkMonitoringUpdate::Consumption(250.0),
+        DrinkMonitoringUpdate::TotalConsumed(1150.0),
+        DrinkMonitoringUpdate::TargetConsumption(2000.0),
+        DrinkMonitoringUpdate::DayAverageHourlyConsumptionRate(164.0),
+        DrinkMonitoringUpdate::LastHourConsumptionRate(250.0),
+        DrinkMonitoringUpdate::TargetRate(170.0),
+        DrinkMonitoringUpdate::PlanDifference(-120.0),
+        DrinkMonitoringUpdate::LastDrinkTime(now - TimeDelta::minutes(35)),
+        DrinkMonitoringUpdate::Streak(4),
+        DrinkMonitoringUpdate::SevenDayAverage(Some(1840.0)),
+        DrinkMonitoringUpdate::ThirtyDayAverage(Some(1760.0)),
+    ];
+    for (days_ago, total) in [1950.0, 2100.0, 1600.0, 2050.0, 1800.0, 2200.0, 1180.0]
+        .into_iter()
+        .enumerate()
+    {
+        updates.push(DrinkMonitoringUpdate::DailyTotal(
+            days_ago as u8 + 1,
+            total,
+            total >= 2000.0,
+        ));
+    }
+    for (hours_ago, total) in [250.0, 0.0, 300.0, 150.0, 0.0, 200.0, 250.0]
+        .into_iter()
+        .enumerate()
+    {
+        updates.push(DrinkMonitoringUpdate::HourlyConsumption(
+            hours_ago as u8,
+            total,
+        ));
+    }
+    // 7 hours into a day with the target due 16 hours after the day starts
+    let target_at = |hour: usize| 2000.0 * f32::min(hour as f32 / 16.0, 1.0);
+    let mut consumed = 0.0;
+    for (hour, drunk) in [0.0, 0.0, 250.0, 200.0, 0.0, 300.0, 0.0, 400.0]
+        .into_iter()
+        .enumerate()
+    {
+        consumed += drunk;
+        updates.push(DrinkMonitoringUpdate::DayProgress(
+            hour as u8,
+            Some(consumed),
+            target_at(hour),
+        ));
+    }
+    for hour in 8..DAY_PROGRESS_POINTS {
+        updates.push(DrinkMonitoringUpdate::DayProgress(
+            hour as u8,
+            None,
+            target_at(hour),
+        ));
+    }
+    updates
+}
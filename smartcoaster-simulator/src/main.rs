@@ -0,0 +1,126 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Interactive simulator. The arrow keys turn the encoder and Enter or Space is the encoder
+//! button. P saves a screenshot of the display.
+
+use chrono::{Local, NaiveDateTime, Timelike};
+use embassy_futures::block_on;
+use embassy_sync::blocking_mutex::Mutex;
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embedded_graphics_simulator::sdl2::Keycode;
+use embedded_graphics_simulator::{
+    BinaryColorTheme, OutputSettingsBuilder, SimulatorEvent, Window,
+};
+use smartcoaster_application::application::application_state::ApplicationState;
+use smartcoaster_application::application::messaging::ApplicationData;
+use smartcoaster_application::hmi::screens::UiInput;
+use smartcoaster_application::storage::settings::accessor::{
+    FlashSettingsAccessor, initialise_settings, process_save_queue,
+};
+use smartcoaster_application::storage::storage_manager::{BlockingFlash, initialise_storage};
+use smartcoaster_application::{NVM_PARTITION_SIZE, SETTINGS_NVM_FLASH_OFFSET_RANGE};
+use smartcoaster_simulator::{Simulator, sample_monitoring_updates};
+use std::cell::RefCell;
+use std::thread;
+use std::time::Duration;
+
+const SCREENSHOT_PATH: &str = "smartcoaster-screenshot.png";
+
+fn ui_input_for_key_down(keycode: Keycode, repeat: bool) -> Option<UiInput> {
+    match keycode {
+        Keycode::Right | Keycode::Down => Some(UiInput::EncoderClockwise),
+        Keycode::Left | Keycode::Up => Some(UiInput::EncoderCounterClockwise),
+        Keycode::Return | Keycode::Space if !repeat => Some(UiInput::ButtonPress),
+        _ => None,
+    }
+}
+
+fn ui_input_for_key_up(keycode: Keycode) -> Option<UiInput> {
+    match keycode {
+        Keycode::Return | Keycode::Space => Some(UiInput::ButtonRelease),
+        _ => None,
+    }
+}
+
+fn now() -> NaiveDateTime {
+    Local::now().naive_local().with_nanosecond(0).unwrap()
+}
+
+fn main() {
+    // Settings are kept in flash held in RAM so they last for the session
+    let flash = Box::leak(Box::new(Mutex::<CriticalSectionRawMutex, _>::new(
+        RefCell::new(BlockingFlash::new()),
+    )));
+    block_on(async {
+        initialise_storage(
+            flash,
+            0..NVM_PARTITION_SIZE as u32,
+            SETTINGS_NVM_FLASH_OFFSET_RANGE.clone(),
+        )
+        .await;
+        initialise_settings().await;
+    });
+
+    let mut last_update = now();
+    let mut simulator = Simulator::new(FlashSettingsAccessor::new(), last_update);
+    simulator.set_display_state(ApplicationState::Monitoring);
+    for update in sample_monitoring_updates(last_update) {
+        simulator.application_data(ApplicationData::MonitoringUpdate(update));
+    }
+
+    let output_settings = OutputSettingsBuilder::new()
+        .theme(BinaryColorTheme::OledBlue)
+        .scale(4)
+        .build();
+    let mut window = Window::new("Smart Coaster", &output_settings);
+
+    'running: loop {
+        let now = now();
+        if now != last_update {
+            simulator.set_date_time(now);
+            last_update = now;
+        }
+
+        window.update(simulator.render());
+        for event in window.events() {
+            let input = match event {
+                SimulatorEvent::Quit => break 'running,
+                SimulatorEvent::KeyDown {
+                    keycode: Keycode::P,
+                    repeat: false,
+                    ..
+                } => {
+                    let screenshot = simulator.render().to_rgb_output_image(&output_settings);
+                    match screenshot.save_png(SCREENSHOT_PATH) {
+                        Ok(()) => println!("Screenshot saved to {SCREENSHOT_PATH}"),
+                        Err(e) => eprintln!("Unable to save screenshot: {e}"),
+                    }
+                    None
+                }
+                SimulatorEvent::KeyDown {
+                    keycode, repeat, ..
+                } => ui_input_for_key_down(keycode, repeat),
+                SimulatorEvent::KeyUp { keycode, .. } => ui_input_for_key_up(keycode),
+                _ => None,
+            };
+            if let Some(input) = input {
+                simulator.input(input);
+            }
+        }
+
+        block_on(process_save_queue());
+        thread::sleep(Duration::from_millis(1000 / 30));
+    }
+}
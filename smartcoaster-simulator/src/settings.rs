@@ -0,0 +1,67 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Settings held in memory, for running the screens without flash storage.
+
+use smartcoaster_application::storage::settings::{
+    SettingValue, SettingsAccessor, SettingsAccessorId,
+};
+use std::convert::Infallible;
+use std::sync::Mutex;
+
+#[derive(Default)]
+pub struct MemorySettings {
+    values: Mutex<Vec<(SettingsAccessorId, SettingValue)>>,
+}
+
+impl MemorySettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a value before the settings are handed to the screens.
+    pub fn with(self, id: SettingsAccessorId, value: SettingValue) -> Self {
+        self.set(id, value);
+        self
+    }
+
+    fn set(&self, id: SettingsAccessorId, value: SettingValue) {
+        let mut values = self.values.lock().unwrap();
+        match values.iter_mut().find(|(stored_id, _)| *stored_id == id) {
+            Some((_, stored_value)) => *stored_value = value,
+            None => values.push((id, value)),
+        }
+    }
+}
+
+impl SettingsAccessor for MemorySettings {
+    type Error = Infallible;
+
+    async fn get_setting(&self, id: SettingsAccessorId) -> Option<SettingValue> {
+        let values = self.values.lock().unwrap();
+        values
+            .iter()
+            .find(|(stored_id, _)| *stored_id == id)
+            .map(|(_, value)| *value)
+    }
+
+    async fn save_setting(
+        &self,
+        id: SettingsAccessorId,
+        value: SettingValue,
+    ) -> Result<(), Self::Error> {
+        self.set(id, value);
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Text snapshots of the display. Each pixel is a character, `#` when lit and `.` when not, so
+//! snapshots can be reviewed and diffed like any other file.
+
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics_simulator::SimulatorDisplay;
+use std::path::Path;
+use std::{env, fs};
+
+/// Set to write the snapshot files rather than compare against them. `new` writes only the
+/// snapshots that are missing, any other value rewrites them all.
+pub const UPDATE_SNAPSHOTS_VAR: &str = "UPDATE_SNAPSHOTS";
+const UPDATE_MISSING_SNAPSHOTS: &str = "new";
+
+pub fn to_text(display: &SimulatorDisplay<BinaryColor>) -> String {
+    let size = display.size();
+    let mut text = String::with_capacity(((size.width + 1) * size.height) as usize);
+    for y in 0..size.height as i32 {
+        for x in 0..size.width as i32 {
+            text.push(match display.get_pixel(Point::new(x, y)) {
+                BinaryColor::On => '#',
+                BinaryColor::Off => '.',
+            });
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Compares the display against the snapshot file at `path`. The file is written instead when
+/// `UPDATE_SNAPSHOTS` is set. A missing snapshot fails so a new screen cannot pass unreviewed.
+pub fn assert_snapshot(path: &Path, display: &SimulatorDisplay<BinaryColor>) {
+    let actual = to_text(display);
+    if let Some(update) = env::var_os(UPDATE_SNAPSHOTS_VAR) {
+        if update != UPDATE_MISSING_SNAPSHOTS || !path.exists() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, &actual).unwrap();
+            eprintln!("wrote snapshot {}", path.display());
+        }
+        return;
+    }
+
+    if !path.exists() {
+        panic!(
+            "no snapshot at {}\n\nactual:\n{}\nRun with {}=new to create it",
+            path.display(),
+            actual,
+            UPDATE_SNAPSHOTS_VAR
+        );
+    }
+
+    let expected = fs::read_to_string(path).unwrap();
+    if actual != expected {
+        panic!(
+            "display does not match {}\n\nexpected:\n{}\nactual:\n{}\nRun with {}=1 to accept the change",
+            path.display(),
+            expected,
+            actual,
+            UPDATE_SNAPSHOTS_VAR
+        );
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Start just before the day starts and take a drink just after, before the drink monitor's next
+//! periodic update, and check the drink counts towards the new day and its goal. The application is started
+//! once per process, so this scenario has its own test file.
+
+use chrono::{NaiveDate, NaiveDateTime};
+use smartcoaster_application::application::application_state::ApplicationState;
+use smartcoaster_application::drink_monitor::drink_monitoring::MonitoringStateSubstates;
+use smartcoaster_application::hmi::screens::settings_menu::monitoring_options::MonitoringTargetPeriodOptions;
+use smartcoaster_application::storage::settings::{SettingValue, SettingsAccessorId};
+use smartcoaster_simulator::app::{App, AppConfig};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// Time from the start until the day starts, on the application's clock.
+const BEFORE_DAY_START: Duration = Duration::from_secs(5);
+const DAILY_TARGET: u32 = 100;
+
+fn start_time() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2025, 3, 12)
+        .unwrap()
+        .and_hms_opt(23, 59, 55)
+        .unwrap()
+}
+
+fn substate_is(app: &App, substate: MonitoringStateSubstates) -> bool {
+    app.observations().monitoring_substate == Some(substate)
+}
+
+#[test]
+fn drink_just_after_day_start_counts_towards_new_day() {
+    let started = Instant::now();
+    let app = App::start(
+        AppConfig::new(start_time())
+            .with_setting(
+                SettingsAccessorId::MonitoringTargetType,
+                SettingValue::SmallUInt(MonitoringTargetPeriodOptions::Daily.into()),
+            )
+            .with_setting(
+                SettingsAccessorId::MonitoringTargetDaily,
+                SettingValue::UInt(DAILY_TARGET),
+            ),
+    );
+
+    assert!(app.wait_for(TIMEOUT, |app| {
+        let observations = app.observations();
+        observations.application_state == ApplicationState::Monitoring
+            && observations.total_consumed == Some(0.0)
+    }));
+
+    app.scale().set_load(350.0);
+    assert!(app.wait_for(TIMEOUT, |app| substate_is(
+        app,
+        MonitoringStateSubstates::VesselPlaced
+    )));
+    app.scale().set_load(0.0);
+    assert!(app.wait_for(TIMEOUT, |app| substate_is(
+        app,
+        MonitoringStateSubstates::VesselRemoved
+    )));
+
+    // put the cup back once the day has started, well before the periodic update a minute in
+    thread::sleep((BEFORE_DAY_START + Duration::from_secs(1)).saturating_sub(started.elapsed()));
+    app.scale().set_load(200.0);
+
+    assert!(
+        app.wait_for(TIMEOUT, |app| app.observations().total_consumed
+            == Some(150.0))
+    );
+    // the goal is reached for the new day, not the one that has ended
+    assert!(app.wait_for(TIMEOUT, |app| {
+        let observations = app.observations();
+        observations.goals_achieved == 1 && observations.streak == Some(1)
+    }));
+    assert!(app.wait_for(TIMEOUT, |app| {
+        let summaries = app.daily_summaries();
+        summaries.len() == 1
+            && summaries[0].day_start == start_time().date().and_hms_opt(0, 0, 0).unwrap()
+            && summaries[0].total_consumption == 0.0
+            && !summaries[0].target_achieved
+    }));
+}
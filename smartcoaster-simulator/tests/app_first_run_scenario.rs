@@ -0,0 +1,90 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Walk through first run setup on a freshly flashed device. The application is started once per
+//! process, so this scenario has its own test file.
+
+use chrono::NaiveDate;
+use smartcoaster_application::application::application_state::{ApplicationState, FirstRunStep};
+use smartcoaster_application::hmi::screens::settings_menu::monitoring_options::MonitoringTargetPeriodOptions;
+use smartcoaster_application::storage::settings::{SettingValue, SettingsAccessorId};
+use smartcoaster_simulator::app::{App, AppConfig};
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn wait_for_state(app: &App, state: ApplicationState) -> bool {
+    app.wait_for(TIMEOUT, |app| app.observations().application_state == state)
+}
+
+#[test]
+fn first_run_setup_is_marked_complete() {
+    let start_time = NaiveDate::from_ymd_opt(2025, 3, 12)
+        .unwrap()
+        .and_hms_opt(9, 0, 0)
+        .unwrap();
+    let app = App::start(AppConfig::new(start_time).first_run());
+
+    assert!(wait_for_state(
+        &app,
+        ApplicationState::FirstRunSetup(FirstRunStep::Welcome)
+    ));
+    app.press_button();
+
+    // each entry screen starts on its first field, so one turn back selects cancel
+    assert!(wait_for_state(&app, ApplicationState::SetSystemDateTime));
+    app.turn(-1);
+    app.press_button();
+
+    // the simulated scale is already calibrated, so calibration is skipped
+    assert!(wait_for_state(
+        &app,
+        ApplicationState::FirstRunSetup(FirstRunStep::TargetType)
+    ));
+    app.turn(1);
+    app.press_button();
+
+    assert!(wait_for_state(
+        &app,
+        ApplicationState::FirstRunSetup(FirstRunStep::TargetValue(
+            MonitoringTargetPeriodOptions::Hourly
+        ))
+    ));
+    app.turn(-1);
+    app.press_button();
+
+    assert!(wait_for_state(
+        &app,
+        ApplicationState::TimeEntry(SettingsAccessorId::MonitoringDayStartTime)
+    ));
+    app.turn(-1);
+    app.press_button();
+
+    assert!(wait_for_state(
+        &app,
+        ApplicationState::FirstRunSetup(FirstRunStep::Complete)
+    ));
+    app.press_button();
+    assert!(wait_for_state(&app, ApplicationState::Monitoring));
+
+    assert!(app.wait_for(TIMEOUT, |app| {
+        app.setting(SettingsAccessorId::SystemSetupComplete) == Some(SettingValue::SmallUInt(1))
+    }));
+    assert_eq!(
+        app.setting(SettingsAccessorId::MonitoringTargetType),
+        Some(SettingValue::SmallUInt(
+            MonitoringTargetPeriodOptions::Hourly.into()
+        ))
+    );
+}
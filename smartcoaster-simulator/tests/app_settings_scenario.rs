@@ -0,0 +1,52 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Open the settings menu from monitoring with the encoder button. The application is started once
+//! per process, so this scenario has its own test file.
+
+use chrono::NaiveDate;
+use smart_leds::RGB8;
+use smartcoaster_application::application::application_state::ApplicationState;
+use smartcoaster_application::led::led_control::LedArrayMode;
+use smartcoaster_simulator::app::{App, AppConfig};
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[test]
+fn button_opens_settings_with_purple_leds() {
+    let start_time = NaiveDate::from_ymd_opt(2025, 3, 12)
+        .unwrap()
+        .and_hms_opt(9, 0, 0)
+        .unwrap();
+    let app = App::start(AppConfig::new(start_time).with_load(300.0));
+
+    assert!(
+        app.wait_for(TIMEOUT, |app| app.observations().application_state
+            == ApplicationState::Monitoring
+            && !app.screen().frame.is_empty())
+    );
+    let monitoring_frame = app.screen().frame;
+
+    app.press_button();
+    assert!(
+        app.wait_for(TIMEOUT, |app| app.observations().application_state
+            == ApplicationState::Settings)
+    );
+    assert!(app.wait_for(TIMEOUT, |app| app.leds().mode
+        == Some(LedArrayMode::StaticColour {
+            colour: RGB8::new(191, 64, 191)
+        })));
+    assert!(app.wait_for(TIMEOUT, |app| app.screen().frame != monitoring_frame));
+}
@@ -0,0 +1,101 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Place a cup, take a 50 ml sip and put the cup back. The application is started once per
+//! process, so this scenario has its own test file.
+
+use chrono::{NaiveDate, NaiveDateTime};
+use smart_leds::RGB8;
+use smartcoaster_application::application::application_state::ApplicationState;
+use smartcoaster_application::drink_monitor::drink_monitoring::MonitoringStateSubstates;
+use smartcoaster_application::hmi::screens::settings_menu::monitoring_options::MonitoringTargetPeriodOptions;
+use smartcoaster_application::led::led_control::LedArrayMode;
+use smartcoaster_application::storage::settings::{SettingValue, SettingsAccessorId};
+use smartcoaster_simulator::app::{App, AppConfig};
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const GREEN: RGB8 = RGB8::new(50, 168, 82);
+
+fn start_time() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2025, 3, 12)
+        .unwrap()
+        .and_hms_opt(9, 0, 0)
+        .unwrap()
+}
+
+fn substate_is(app: &App, substate: MonitoringStateSubstates) -> bool {
+    app.observations().monitoring_substate == Some(substate)
+}
+
+#[test]
+fn sip_is_logged_and_leds_show_on_target() {
+    let app = App::start(
+        AppConfig::new(start_time())
+            .with_setting(
+                SettingsAccessorId::MonitoringTargetType,
+                SettingValue::SmallUInt(MonitoringTargetPeriodOptions::Hourly.into()),
+            )
+            .with_setting(
+                SettingsAccessorId::MonitoringTargetHourly,
+                SettingValue::Float(30.0),
+            ),
+    );
+
+    // the drink monitor reports the day's total once it has a stable empty weight
+    assert!(app.wait_for(TIMEOUT, |app| {
+        let observations = app.observations();
+        observations.application_state == ApplicationState::Monitoring
+            && observations.total_consumed == Some(0.0)
+    }));
+
+    app.scale().set_load(350.0);
+    assert!(app.wait_for(TIMEOUT, |app| substate_is(
+        app,
+        MonitoringStateSubstates::VesselPlaced
+    )));
+
+    app.scale().set_load(0.0);
+    assert!(app.wait_for(TIMEOUT, |app| substate_is(
+        app,
+        MonitoringStateSubstates::VesselRemoved
+    )));
+
+    app.scale().set_load(300.0);
+    assert!(app.wait_for(TIMEOUT, |app| {
+        app.observations()
+            .consumptions
+            .iter()
+            .any(|consumption| (consumption - 50.0).abs() < 0.5)
+    }));
+    assert!(
+        app.wait_for(TIMEOUT, |app| app.observations().total_consumed
+            == Some(50.0))
+    );
+
+    assert!(app.wait_for(TIMEOUT, |app| {
+        app.consumption_log().iter().any(|entry| {
+            (entry.data.get_last_consumption() - 50.0).abs() < 0.5
+                && entry.data.get_total_consumption() == 50.0
+                && entry.timestamp >= start_time()
+        })
+    }));
+
+    // nothing drunk in the last hour is within 50 ml of the 30 ml/hour target
+    assert!(app.wait_for(TIMEOUT, |app| matches!(
+        app.leds().mode,
+        Some(LedArrayMode::SingleColourWheel { colour: GREEN, .. })
+    )));
+    assert!(app.screen().frame.contains('#'));
+}
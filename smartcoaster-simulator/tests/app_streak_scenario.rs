@@ -0,0 +1,66 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Boot with a long run of days on target in the daily rollup log and check the whole run is
+//! counted. The application is started once per process, so this scenario has its own test file.
+
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+use smartcoaster_app_core::daily_summary::DailySummary;
+use smartcoaster_app_core::target_direction::TargetDirection;
+use smartcoaster_application::hmi::screens::settings_menu::monitoring_options::MonitoringTargetPeriodOptions;
+use smartcoaster_application::storage::settings::{SettingValue, SettingsAccessorId};
+use smartcoaster_simulator::app::{App, AppConfig};
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const DAYS_ON_TARGET: i64 = 40;
+const DAILY_TARGET: f32 = 2000.0;
+
+fn start_time() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2025, 3, 12)
+        .unwrap()
+        .and_hms_opt(9, 0, 0)
+        .unwrap()
+}
+
+/// Summary for the day `days_ago` days before today, with `total` drunk at midday.
+fn summary(days_ago: i64, total: f32) -> DailySummary {
+    let day_start = start_time().date().and_hms_opt(0, 0, 0).unwrap() - TimeDelta::days(days_ago);
+    let mut summary = DailySummary::new(day_start);
+    summary.add_drink(day_start + TimeDelta::hours(12), total);
+    summary.complete(Some(DAILY_TARGET), TargetDirection::Minimum);
+    summary
+}
+
+#[test]
+fn streak_is_loaded_from_daily_summaries() {
+    // a missed day before the run, so only the run counts
+    let mut config = AppConfig::new(start_time())
+        .with_setting(
+            SettingsAccessorId::MonitoringTargetType,
+            SettingValue::SmallUInt(MonitoringTargetPeriodOptions::Daily.into()),
+        )
+        .with_setting(
+            SettingsAccessorId::MonitoringTargetDaily,
+            SettingValue::UInt(DAILY_TARGET as u32),
+        )
+        .with_daily_summary(summary(DAYS_ON_TARGET + 1, DAILY_TARGET / 2.0));
+    for days_ago in (1..=DAYS_ON_TARGET).rev() {
+        config = config.with_daily_summary(summary(days_ago, DAILY_TARGET));
+    }
+    let app = App::start(config);
+
+    assert!(app.wait_for(TIMEOUT, |app| app.observations().streak
+        == Some(DAYS_ON_TARGET as u32)));
+}
@@ -0,0 +1,131 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Error log record encoding, as stored in the error log partition.
+
+use smartcoaster_application::error_log::{
+    ERROR_RECORD_VERSION, ErrorCode, ErrorLogData, ErrorModule,
+};
+use smartcoaster_application::storage::historical::manager::DATA_BUFFER_SIZE;
+use smartcoaster_application::storage::historical::record::{LogRecordType, RecordHeader};
+use smartcoaster_application::storage::historical::{LogEncodeDecode, LogEncodeDecodeError};
+
+/// Bytes available for a record once the timestamp has been written, zero padded as read back.
+const RECORD_SIZE: usize = DATA_BUFFER_SIZE - 10;
+
+fn round_trip(data: ErrorLogData) -> ErrorLogData {
+    let mut buf = [0u8; RECORD_SIZE];
+    data.encode(&mut buf).unwrap();
+    ErrorLogData::from_bytes(&buf).unwrap()
+}
+
+#[test]
+fn records_round_trip() {
+    let data = ErrorLogData::new(ErrorModule::Storage, ErrorCode::SettingSaveFailed)
+        .with_value(7)
+        .with_value(u32::MAX);
+    assert_eq!(round_trip(data), data);
+}
+
+#[test]
+fn records_start_with_a_header() {
+    let mut buf = [0u8; RECORD_SIZE];
+    let size = ErrorLogData::new(ErrorModule::Storage, ErrorCode::SettingSaveFailed)
+        .encode(&mut buf)
+        .unwrap();
+    assert_eq!(
+        RecordHeader::decode(&buf[..size]).unwrap(),
+        Some(RecordHeader::new(
+            LogRecordType::Error,
+            ERROR_RECORD_VERSION
+        ))
+    );
+}
+
+#[test]
+fn records_without_a_header_are_read() {
+    let data = ErrorLogData::new(ErrorModule::Storage, ErrorCode::SettingSaveFailed).with_value(7);
+    let mut buf = [0u8; RECORD_SIZE];
+    data.encode(&mut buf).unwrap();
+    buf.copy_within(RecordHeader::SIZE.., 0);
+    assert_eq!(ErrorLogData::from_bytes(&buf).unwrap(), data);
+}
+
+#[test]
+fn other_records_are_rejected() {
+    let mut buf = [0u8; RECORD_SIZE];
+    ErrorLogData::new(ErrorModule::Storage, ErrorCode::SettingSaveFailed)
+        .encode(&mut buf)
+        .unwrap();
+    buf[1] = LogRecordType::Consumption.into();
+    assert!(matches!(
+        ErrorLogData::from_bytes(&buf),
+        Err(LogEncodeDecodeError::DecodeFailed)
+    ));
+}
+
+#[test]
+fn records_without_values_round_trip() {
+    let data = ErrorLogData::new(ErrorModule::System, ErrorCode::WatchdogReset);
+    assert_eq!(round_trip(data).values, [None, None]);
+    assert_eq!(round_trip(data), data);
+}
+
+#[test]
+fn records_with_one_value_round_trip() {
+    let data =
+        ErrorLogData::new(ErrorModule::DrinkMonitor, ErrorCode::ScaleReadFailed).with_value(0);
+    assert_eq!(round_trip(data).values, [Some(0), None]);
+}
+
+#[test]
+fn values_beyond_the_limit_are_dropped() {
+    let data = ErrorLogData::new(ErrorModule::Application, ErrorCode::LogWriteFailed)
+        .with_value(1)
+        .with_value(2)
+        .with_value(3);
+    assert_eq!(round_trip(data).values, [Some(1), Some(2)]);
+}
+
+#[test]
+fn unknown_modules_and_codes_are_kept() {
+    let data = ErrorLogData::new(ErrorModule::Unknown(200), ErrorCode::Unknown(201)).with_value(5);
+    assert_eq!(round_trip(data), data);
+}
+
+#[test]
+fn truncated_records_are_rejected() {
+    let data = ErrorLogData::new(ErrorModule::Storage, ErrorCode::LogClearFailed)
+        .with_value(7)
+        .with_value(8);
+    let mut buf = [0u8; RECORD_SIZE];
+    let size = data.encode(&mut buf).unwrap();
+
+    for cut in 0..size {
+        assert!(
+            ErrorLogData::from_bytes(&buf[..cut]).is_err(),
+            "cut at {cut}"
+        );
+    }
+}
+
+#[test]
+fn records_that_do_not_fit_are_rejected() {
+    let data = ErrorLogData::new(ErrorModule::Storage, ErrorCode::SettingSaveFailed).with_value(7);
+    let mut buf = [0u8; 2];
+    assert!(matches!(
+        data.encode(&mut buf),
+        Err(LogEncodeDecodeError::BufferTooSmall)
+    ));
+}
@@ -0,0 +1,369 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! The USB drive export volume, read back as a host would read it. The image is opened with a FAT
+//! implementation in the tests, and `mounts_on_linux` checks it against the Linux vfat driver.
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+use embassy_embedded_hal::adapter::BlockingAsync;
+use embassy_embedded_hal::flash::partition::BlockingPartition;
+use embassy_futures::block_on;
+use embassy_sync::blocking_mutex::Mutex;
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use smartcoaster_application::drink_monitor::beverage::BeverageType;
+use smartcoaster_application::drink_monitor::log_data::DrinkMonitorLogData;
+use smartcoaster_application::error_log::{ErrorCode, ErrorLogData, ErrorModule};
+use smartcoaster_application::hmi::screens::settings_menu::monitoring_options::MonitoringTargetPeriodOptions;
+use smartcoaster_application::storage::StoredDataValue;
+use smartcoaster_application::storage::export::{
+    ERRORS_FILE_NAME, ExportVolume, HISTORY_FILE_NAME, SETTINGS_FILE_NAME,
+};
+use smartcoaster_application::storage::historical::LogEncodeDecode;
+use smartcoaster_application::storage::historical::log_config::Logs;
+use smartcoaster_application::storage::historical::manager::DATA_BUFFER_SIZE;
+use smartcoaster_application::storage::historical::timestamp::encode_timestamp;
+use smartcoaster_application::storage::settings::schema::keys;
+use smartcoaster_application::storage::storage_manager::{
+    BlockingAsyncPartition, BlockingFlash, StorageManager, StorageManagerSequentialStorage,
+};
+use smartcoaster_application::{NVM_PARTITION_SIZE, SETTINGS_NVM_FLASH_OFFSET_RANGE};
+use std::cell::RefCell;
+use std::io::{Cursor, Read};
+
+type FlashMutex = Mutex<CriticalSectionRawMutex, RefCell<BlockingFlash>>;
+type Storage = StorageManagerSequentialStorage<BlockingAsyncPartition>;
+
+fn flash() -> &'static FlashMutex {
+    Box::leak(Box::new(Mutex::new(RefCell::new(BlockingFlash::new()))))
+}
+
+fn boot() -> Storage {
+    boot_on(flash())
+}
+
+fn boot_on(flash: &'static FlashMutex) -> Storage {
+    let partition = BlockingAsync::new(BlockingPartition::new(flash, 0, NVM_PARTITION_SIZE as u32));
+    let mut storage = Storage::new();
+    block_on(storage.initialise(partition, SETTINGS_NVM_FLASH_OFFSET_RANGE.clone()));
+    storage
+}
+
+fn start_time() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2025, 6, 1)
+        .unwrap()
+        .and_hms_opt(8, 0, 0)
+        .unwrap()
+}
+
+/// Writes an entry as the log manager does.
+fn write_entry(storage: &mut Storage, log: Logs, time: NaiveDateTime, entry: impl LogEncodeDecode) {
+    let mut data = [0u8; DATA_BUFFER_SIZE];
+    let size = encode_timestamp(time, &mut data);
+    let size = size + entry.encode(&mut data[size..]).unwrap();
+    block_on(storage.write_log_data(&log.get_config(), &data[..size])).unwrap();
+}
+
+fn read_count(flash: &FlashMutex) -> usize {
+    flash.lock(|flash| flash.borrow().read_count())
+}
+
+/// Writes tea entries a minute apart from `first` up to `count`.
+fn write_history(storage: &mut Storage, first: i64, count: i64) {
+    for n in first..count {
+        write_entry(
+            storage,
+            Logs::ConsumptionLog,
+            start_time() + TimeDelta::minutes(n),
+            drink(n as f32, n as f32 * 2.0, BeverageType::Tea),
+        );
+    }
+}
+
+fn history_len(storage: &mut Storage) -> usize {
+    block_on(storage.for_each_log_item(&Logs::ConsumptionLog.get_config(), |_| {})).unwrap()
+}
+
+fn drink(consumed: f32, total: f32, beverage: BeverageType) -> DrinkMonitorLogData {
+    DrinkMonitorLogData::new(
+        0.0,
+        2000,
+        MonitoringTargetPeriodOptions::Daily,
+        total,
+        NaiveTime::from_hms_opt(20, 0, 0).unwrap(),
+        consumed,
+        beverage,
+    )
+}
+
+/// Storage holding a few settings, drinks and errors.
+fn populated_storage() -> Storage {
+    let mut storage = boot();
+    for (key, value) in [
+        (keys::SYSTEM_LED_BRIGHTNESS, StoredDataValue::SmallUInt(64)),
+        (
+            keys::WEIGHING_SYSTEM_CALIBRATION_GRADIENT,
+            StoredDataValue::Float(0.25),
+        ),
+        (
+            keys::MONITORING_DAY_START_TIME,
+            StoredDataValue::Time(NaiveTime::from_hms_opt(7, 0, 0).unwrap()),
+        ),
+    ] {
+        block_on(storage.save_key_value_pair(key, value)).unwrap();
+    }
+
+    write_entry(
+        &mut storage,
+        Logs::ConsumptionLog,
+        start_time(),
+        drink(250.0, 250.0, BeverageType::Water),
+    );
+    write_entry(
+        &mut storage,
+        Logs::ConsumptionLog,
+        start_time() + TimeDelta::minutes(30),
+        drink(180.4, 180.4, BeverageType::Coffee),
+    );
+    write_entry(
+        &mut storage,
+        Logs::ErrorLog,
+        start_time() + TimeDelta::seconds(5),
+        ErrorLogData::new(ErrorModule::Storage, ErrorCode::SettingSaveFailed).with_value(2),
+    );
+    storage
+}
+
+/// Reads the volume in sector order.
+fn read_image(volume: &mut ExportVolume, storage: &mut Storage) -> Vec<u8> {
+    read_sectors(volume, storage, 0..ExportVolume::SECTOR_COUNT)
+}
+
+fn read_sectors(
+    volume: &mut ExportVolume,
+    storage: &mut Storage,
+    sectors: impl Iterator<Item = u32>,
+) -> Vec<u8> {
+    let mut image = vec![0; ExportVolume::SECTOR_COUNT as usize * ExportVolume::SECTOR_SIZE];
+    for lba in sectors {
+        let mut sector = [0; ExportVolume::SECTOR_SIZE];
+        block_on(volume.read_sector(storage, lba, &mut sector)).unwrap();
+        image[lba as usize * ExportVolume::SECTOR_SIZE..][..ExportVolume::SECTOR_SIZE]
+            .copy_from_slice(&sector);
+    }
+    image
+}
+
+fn export(storage: &mut Storage) -> Vec<u8> {
+    let mut volume = block_on(ExportVolume::new(storage, start_time())).unwrap();
+    read_image(&mut volume, storage)
+}
+
+/// Names and contents of the files in the root directory of `image`.
+fn read_files(image: Vec<u8>) -> Vec<(String, String)> {
+    let fs = fatfs::FileSystem::new(Cursor::new(image), fatfs::FsOptions::new()).unwrap();
+    assert_eq!(fs.fat_type(), fatfs::FatType::Fat16);
+    fs.root_dir()
+        .iter()
+        .map(|entry| {
+            let entry = entry.unwrap();
+            let mut contents = String::new();
+            entry.to_file().read_to_string(&mut contents).unwrap();
+            (entry.file_name(), contents)
+        })
+        .collect()
+}
+
+fn file(files: &[(String, String)], name: &str) -> String {
+    files
+        .iter()
+        .find(|(file_name, _)| file_name == name)
+        .unwrap_or_else(|| panic!("{} missing", name))
+        .1
+        .clone()
+}
+
+#[test]
+fn files_hold_logs_and_settings() {
+    let mut storage = populated_storage();
+    let files = read_files(export(&mut storage));
+
+    let names: Vec<_> = files.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(
+        names,
+        [HISTORY_FILE_NAME, SETTINGS_FILE_NAME, ERRORS_FILE_NAME]
+    );
+    assert_eq!(
+        file(&files, HISTORY_FILE_NAME),
+        "time,beverage,consumed_ml,day_total_ml,daily_target_ml\r\n\
+         2025-06-01 08:00:00,Water,250,250,2000\r\n\
+         2025-06-01 08:30:00,Coffee,180,180,2000\r\n"
+    );
+    assert_eq!(
+        file(&files, ERRORS_FILE_NAME),
+        "time,module,error,value_1,value_2\r\n\
+         2025-06-01 08:00:05,Storage,Setting save failed,2,\r\n"
+    );
+    assert_eq!(
+        file(&files, SETTINGS_FILE_NAME),
+        "{\r\n  \"weighing_system_calibration_gradient\": 0.25,\r\n  \
+         \"system_led_brightness\": 64,\r\n  \
+         \"monitoring_day_start_time\": \"07:00:00\"\r\n}\r\n"
+    );
+}
+
+#[test]
+fn empty_storage_has_headers_only() {
+    let mut storage = boot();
+    let files = read_files(export(&mut storage));
+    assert_eq!(
+        file(&files, HISTORY_FILE_NAME),
+        "time,beverage,consumed_ml,day_total_ml,daily_target_ml\r\n"
+    );
+    assert_eq!(
+        file(&files, ERRORS_FILE_NAME),
+        "time,module,error,value_1,value_2\r\n"
+    );
+    assert_eq!(file(&files, SETTINGS_FILE_NAME), "{\r\n}\r\n");
+}
+
+#[test]
+fn long_history_reads_the_same_in_any_order() {
+    let mut storage = boot();
+    write_history(&mut storage, 0, 400);
+
+    let mut volume = block_on(ExportVolume::new(&mut storage, start_time())).unwrap();
+    let in_order = read_image(&mut volume, &mut storage);
+    let reversed = read_sectors(
+        &mut volume,
+        &mut storage,
+        (0..ExportVolume::SECTOR_COUNT).rev(),
+    );
+    assert!(in_order == reversed);
+
+    let history = file(&read_files(in_order), HISTORY_FILE_NAME);
+    let rows: Vec<_> = history.lines().skip(1).collect();
+    assert!(history.len() > 10 * ExportVolume::SECTOR_SIZE);
+    assert_eq!(rows.first(), Some(&"2025-06-01 08:00:00,Tea,0,0,2000"));
+    let last_row = format!(
+        "{},Tea,399,798,2000",
+        (start_time() + TimeDelta::minutes(399)).format("%Y-%m-%d %H:%M:%S")
+    );
+    assert_eq!(rows.last(), Some(&last_row.as_str()));
+}
+
+#[test]
+fn reading_history_reads_each_log_page_about_once() {
+    let flash = flash();
+    let mut storage = boot_on(flash);
+    write_history(&mut storage, 0, 400);
+
+    let reads_before = read_count(flash);
+    history_len(&mut storage);
+    let whole_log_reads = read_count(flash) - reads_before;
+
+    let mut volume = block_on(ExportVolume::new(&mut storage, start_time())).unwrap();
+    let reads_before = read_count(flash);
+    let history = file(
+        &read_files(read_image(&mut volume, &mut storage)),
+        HISTORY_FILE_NAME,
+    );
+    let volume_reads = read_count(flash) - reads_before;
+
+    // every sector of the file would read the whole log if rows were found from the start
+    let history_sectors = history.len() / ExportVolume::SECTOR_SIZE;
+    assert!(history_sectors > 20);
+    assert!(
+        volume_reads < 4 * whole_log_reads,
+        "{} reads for the volume, {} for the log",
+        volume_reads,
+        whole_log_reads
+    );
+}
+
+#[test]
+fn history_keeps_its_size_when_the_log_wraps() {
+    let mut storage = boot();
+    let mut count = 0;
+    while history_len(&mut storage) == count as usize {
+        write_history(&mut storage, count, count + 1);
+        count += 1;
+    }
+
+    let mut volume = block_on(ExportVolume::new(&mut storage, start_time())).unwrap();
+    let before = file(
+        &read_files(read_image(&mut volume, &mut storage)),
+        HISTORY_FILE_NAME,
+    );
+    // enough entries to reuse the pages holding the start of the file
+    write_history(&mut storage, count, count + 2 * count / 3);
+
+    let history = file(
+        &read_files(read_image(&mut volume, &mut storage)),
+        HISTORY_FILE_NAME,
+    );
+    assert_eq!(history.len(), before.len());
+    assert_eq!(history.lines().next(), before.lines().next());
+    assert!(history.ends_with('\n'));
+}
+
+#[test]
+fn entries_logged_after_export_are_left_out() {
+    let mut storage = populated_storage();
+    let mut volume = block_on(ExportVolume::new(&mut storage, start_time())).unwrap();
+    let before = read_image(&mut volume, &mut storage);
+
+    write_entry(
+        &mut storage,
+        Logs::ConsumptionLog,
+        start_time() + TimeDelta::hours(1),
+        drink(100.0, 350.0, BeverageType::Water),
+    );
+    assert!(read_image(&mut volume, &mut storage) == before);
+}
+
+/// Loop mounts the image with the vfat driver. Run as root with
+/// `cargo test --no-default-features --test export -- --ignored`.
+#[test]
+#[ignore = "needs root and vfat support to loop mount"]
+fn mounts_on_linux() {
+    use std::process::Command;
+
+    let mut storage = populated_storage();
+    let image = export(&mut storage);
+    let dir = std::env::temp_dir().join(format!("smartcoaster-export-{}", std::process::id()));
+    let mount_point = dir.join("mnt");
+    std::fs::create_dir_all(&mount_point).unwrap();
+    let image_path = dir.join("export.img");
+    std::fs::write(&image_path, &image).unwrap();
+
+    let status = Command::new("mount")
+        .args(["-o", "loop,ro", "-t", "vfat"])
+        .arg(&image_path)
+        .arg(&mount_point)
+        .status()
+        .unwrap();
+    assert!(status.success(), "mount failed");
+    let read = |name: &str| std::fs::read_to_string(mount_point.join(name));
+    let mounted = [HISTORY_FILE_NAME, SETTINGS_FILE_NAME, ERRORS_FILE_NAME].map(read);
+    Command::new("umount").arg(&mount_point).status().unwrap();
+
+    let files = read_files(image);
+    for (name, contents) in [HISTORY_FILE_NAME, SETTINGS_FILE_NAME, ERRORS_FILE_NAME]
+        .into_iter()
+        .zip(mounted)
+    {
+        assert_eq!(contents.unwrap(), file(&files, name), "{}", name);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
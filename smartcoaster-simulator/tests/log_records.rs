@@ -0,0 +1,138 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Consumption log record encoding, including records written by older firmware.
+
+use chrono::NaiveTime;
+use sequential_storage::map::Value;
+use smartcoaster_application::drink_monitor::beverage::BeverageType;
+use smartcoaster_application::drink_monitor::log_data::{
+    CONSUMPTION_RECORD_VERSION, DrinkMonitorLogData,
+};
+use smartcoaster_application::hmi::screens::settings_menu::monitoring_options::MonitoringTargetPeriodOptions;
+use smartcoaster_application::storage::StoredDataValue;
+use smartcoaster_application::storage::historical::manager::DATA_BUFFER_SIZE;
+use smartcoaster_application::storage::historical::record::{LogRecordType, RecordHeader};
+use smartcoaster_application::storage::historical::{LogEncodeDecode, LogEncodeDecodeError};
+
+/// Bytes available for a record once the timestamp has been written, zero padded as read back.
+const RECORD_SIZE: usize = DATA_BUFFER_SIZE - 10;
+
+fn log_data() -> DrinkMonitorLogData {
+    DrinkMonitorLogData::new(
+        125.0,
+        2000,
+        MonitoringTargetPeriodOptions::Daily,
+        850.5,
+        NaiveTime::from_hms_opt(21, 0, 0).unwrap(),
+        110.25,
+        BeverageType::Tea,
+    )
+}
+
+fn assert_log_data(data: &DrinkMonitorLogData, beverage: BeverageType) {
+    assert_eq!(data.get_total_consumption(), 850.5);
+    assert_eq!(data.get_last_consumption(), 110.25);
+    assert_eq!(data.get_daily_consumption_target(), Some(2000));
+    assert_eq!(data.get_beverage(), beverage);
+}
+
+/// Builds a record as firmware without record headers wrote it, giving it with its size.
+fn v0_record() -> ([u8; RECORD_SIZE], usize) {
+    let mut buf = [0u8; RECORD_SIZE];
+    let values = [
+        StoredDataValue::Float(125.0),
+        StoredDataValue::UInt(2000),
+        StoredDataValue::SmallUInt(MonitoringTargetPeriodOptions::Daily.into()),
+        StoredDataValue::Float(850.5),
+        StoredDataValue::Time(NaiveTime::from_hms_opt(21, 0, 0).unwrap()),
+        StoredDataValue::Float(110.25),
+    ];
+    let mut size = 0;
+    for value in values {
+        size += value.serialize_into(&mut buf[size..]).unwrap();
+    }
+    (buf, size)
+}
+
+#[test]
+fn records_round_trip() {
+    let mut buf = [0u8; RECORD_SIZE];
+    let size = log_data().encode(&mut buf).unwrap();
+
+    let header = RecordHeader::decode(&buf[..size]).unwrap();
+    assert_eq!(
+        header,
+        Some(RecordHeader::new(
+            LogRecordType::Consumption,
+            CONSUMPTION_RECORD_VERSION
+        ))
+    );
+    assert_log_data(
+        &DrinkMonitorLogData::from_bytes(&buf).unwrap(),
+        BeverageType::Tea,
+    );
+}
+
+#[test]
+fn v0_records_are_read_as_water() {
+    let data = DrinkMonitorLogData::from_bytes(&v0_record().0).unwrap();
+    assert_log_data(&data, BeverageType::Water);
+}
+
+#[test]
+fn v0_records_decode_six_values() {
+    // a seventh value after them is not the beverage
+    let (mut buf, size) = v0_record();
+    StoredDataValue::SmallUInt(BeverageType::Coffee.into())
+        .serialize_into(&mut buf[size..])
+        .unwrap();
+
+    let data = DrinkMonitorLogData::from_bytes(&buf).unwrap();
+    assert_log_data(&data, BeverageType::Water);
+}
+
+#[test]
+fn newer_records_with_appended_fields_are_read() {
+    let mut buf = [0u8; RECORD_SIZE];
+    let mut size = log_data().encode(&mut buf).unwrap();
+    buf[2] = CONSUMPTION_RECORD_VERSION + 1;
+    size += StoredDataValue::UInt(42)
+        .serialize_into(&mut buf[size..])
+        .unwrap();
+    assert!(size < RECORD_SIZE);
+
+    assert_log_data(
+        &DrinkMonitorLogData::from_bytes(&buf).unwrap(),
+        BeverageType::Tea,
+    );
+}
+
+#[test]
+fn unknown_record_types_are_rejected() {
+    let mut buf = [0u8; RECORD_SIZE];
+    log_data().encode(&mut buf).unwrap();
+    buf[1] = 0xFF;
+
+    assert!(matches!(
+        DrinkMonitorLogData::from_bytes(&buf),
+        Err(LogEncodeDecodeError::DecodeFailed)
+    ));
+}
+
+#[test]
+fn records_fit_after_the_timestamp() {
+    let mut buf = [0u8; RECORD_SIZE];
+    assert!(log_data().encode(&mut buf).is_ok());
+}
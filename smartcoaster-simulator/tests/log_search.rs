@@ -0,0 +1,483 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Log entry timestamps and the indexed search for where log reads start.
+
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+use embassy_embedded_hal::adapter::BlockingAsync;
+use embassy_embedded_hal::flash::partition::BlockingPartition;
+use embassy_futures::block_on;
+use embassy_sync::blocking_mutex::Mutex;
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use smartcoaster_application::storage::StoredDataValue;
+use smartcoaster_application::storage::historical::index::{
+    LogIndex, LogPosition, find_log_start, read_log_items,
+};
+use smartcoaster_application::storage::historical::log_config::Logs;
+use smartcoaster_application::storage::historical::manager::DATA_BUFFER_SIZE;
+use smartcoaster_application::storage::historical::timestamp::{
+    COMPACT_TIMESTAMP_SIZE, decode_timestamp, encode_timestamp,
+};
+use smartcoaster_application::storage::historical::{LogEncodeDecode, SimpleLogEntry};
+use smartcoaster_application::storage::storage_manager::{
+    BlockingAsyncPartition, BlockingFlash, StorageManager, StorageManagerSequentialStorage,
+    StoredLogConfig,
+};
+use smartcoaster_application::{NVM_PARTITION_SIZE, SETTINGS_NVM_FLASH_OFFSET_RANGE};
+use std::cell::RefCell;
+
+type FlashMutex = Mutex<CriticalSectionRawMutex, RefCell<BlockingFlash>>;
+type Storage = StorageManagerSequentialStorage<BlockingAsyncPartition>;
+
+fn flash() -> &'static FlashMutex {
+    Box::leak(Box::new(Mutex::new(RefCell::new(BlockingFlash::new()))))
+}
+
+fn boot() -> Storage {
+    boot_on(flash())
+}
+
+fn boot_on(flash: &'static FlashMutex) -> Storage {
+    let partition = BlockingAsync::new(BlockingPartition::new(flash, 0, NVM_PARTITION_SIZE as u32));
+    let mut storage = Storage::new();
+    block_on(storage.initialise(partition, SETTINGS_NVM_FLASH_OFFSET_RANGE.clone()));
+    storage
+}
+
+fn start_time() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2025, 6, 1)
+        .unwrap()
+        .and_hms_opt(8, 0, 0)
+        .unwrap()
+}
+
+/// Timestamp of the `index`th entry written, one every ten minutes.
+fn entry_time(index: u32) -> NaiveDateTime {
+    start_time() + TimeDelta::minutes(10 * index as i64)
+}
+
+/// Writes an entry as the log manager does, keeping the index up to date.
+fn write_entry(storage: &mut Storage, config: &StoredLogConfig, index: &mut LogIndex, n: u32) {
+    write_entry_at(storage, config, index, n, entry_time(n));
+}
+
+fn write_entry_at(
+    storage: &mut Storage,
+    config: &StoredLogConfig,
+    index: &mut LogIndex,
+    n: u32,
+    timestamp: NaiveDateTime,
+) {
+    let mut data = [0u8; DATA_BUFFER_SIZE];
+    let size = encode_timestamp(timestamp, &mut data);
+    let size = size
+        + SimpleLogEntry {
+            data: StoredDataValue::UInt(n),
+        }
+        .encode(&mut data[size..])
+        .unwrap();
+    block_on(storage.write_log_data(config, &data[..size])).unwrap();
+    index.append(timestamp);
+}
+
+/// Timestamp and number of an entry written by `write_entry_at`.
+fn decode_entry(item: &[u8]) -> (NaiveDateTime, u32) {
+    let (timestamp, size) = decode_timestamp(item).unwrap();
+    let StoredDataValue::UInt(n) = SimpleLogEntry::from_bytes(&item[size..]).unwrap().data else {
+        panic!("entry without a number");
+    };
+    (timestamp, n)
+}
+
+/// Timestamps and numbers of the entries in the log, oldest first.
+fn log_entries(storage: &mut Storage, config: &StoredLogConfig) -> Vec<(NaiveDateTime, u32)> {
+    let mut entries = Vec::new();
+    block_on(storage.for_each_log_item(config, |item| entries.push(decode_entry(item)))).unwrap();
+    entries
+}
+
+/// Timestamps of the entries in the log, oldest first.
+fn log_times(storage: &mut Storage, config: &StoredLogConfig) -> Vec<NaiveDateTime> {
+    log_entries(storage, config)
+        .into_iter()
+        .map(|(time, _)| time)
+        .collect()
+}
+
+/// Flash reads made by `f`.
+fn reads_by<T>(flash: &FlashMutex, f: impl FnOnce() -> T) -> (T, usize) {
+    let reads_before = read_count(flash);
+    let result = f();
+    (result, read_count(flash) - reads_before)
+}
+
+fn read_count(flash: &FlashMutex) -> usize {
+    flash.lock(|flash| flash.borrow().read_count())
+}
+
+/// Checks the search against a straight scan of the log for targets around every entry, and that
+/// a read from where it starts gives the entry the scan found first.
+fn assert_search_matches_scan(
+    storage: &mut Storage,
+    config: &StoredLogConfig,
+    index: &mut LogIndex,
+) {
+    let entries = log_entries(storage, config);
+    let first = entries.first().unwrap().0;
+    let last = entries.last().unwrap().0;
+    let mut target = first - TimeDelta::minutes(15);
+    while target <= last + TimeDelta::minutes(15) {
+        let expected = entries
+            .iter()
+            .position(|(time, _)| *time >= target)
+            .unwrap_or(entries.len());
+        let mut start = block_on(find_log_start(storage, config, index, target)).unwrap();
+        assert_eq!(
+            index.position_in_log(&start),
+            expected,
+            "start for {target}"
+        );
+
+        let mut buf = [[0u8; DATA_BUFFER_SIZE]; 1];
+        let count = block_on(read_log_items(storage, config, index, &mut start, &mut buf)).unwrap();
+        assert_eq!(
+            buf[..count].iter().map(|item| decode_entry(item)).next(),
+            entries.get(expected).copied(),
+            "first entry read for {target}"
+        );
+        target += TimeDelta::minutes(5);
+    }
+}
+
+#[test]
+fn timestamps_are_stored_in_four_bytes_to_the_second() {
+    let mut buf = [0u8; DATA_BUFFER_SIZE];
+    let timestamp = start_time() + TimeDelta::milliseconds(1500);
+    assert_eq!(
+        encode_timestamp(timestamp, &mut buf),
+        COMPACT_TIMESTAMP_SIZE
+    );
+    assert_eq!(
+        decode_timestamp(&buf).unwrap(),
+        (start_time() + TimeDelta::seconds(1), COMPACT_TIMESTAMP_SIZE)
+    );
+}
+
+#[test]
+fn legacy_timestamps_are_read() {
+    let mut buf = [0u8; DATA_BUFFER_SIZE];
+    // 2025-06-01 08:00:00.005 as older firmware wrote it
+    buf[..10].copy_from_slice(&[0xE9, 0x07, 6, 1, 8, 0, 0, 0x40, 0x4B, 0x4C]);
+    assert_eq!(
+        decode_timestamp(&buf).unwrap(),
+        (start_time() + TimeDelta::milliseconds(5), 10)
+    );
+}
+
+#[test]
+fn search_finds_the_first_entry_after_a_time() {
+    let mut storage = boot();
+    let config = Logs::ConsumptionLog.get_config();
+    let mut index = LogIndex::new();
+    index.clear();
+    for n in 0..500 {
+        write_entry(&mut storage, &config, &mut index, n);
+    }
+    assert_search_matches_scan(&mut storage, &config, &mut index);
+}
+
+#[test]
+fn search_reads_one_page_of_the_log() {
+    let flash = flash();
+    let mut storage = boot_on(flash);
+    let config = Logs::ConsumptionLog.get_config();
+    let mut index = LogIndex::new();
+    index.clear();
+    for n in 0..800 {
+        write_entry(&mut storage, &config, &mut index, n);
+    }
+    // the index finds the pages written since it was last used
+    block_on(find_log_start(
+        &mut storage,
+        &config,
+        &mut index,
+        start_time(),
+    ))
+    .unwrap();
+    for n in 800..805 {
+        write_entry(&mut storage, &config, &mut index, n);
+    }
+
+    let (_, whole_log_reads) = reads_by(flash, || {
+        block_on(storage.for_each_log_item(&config, |_| {})).unwrap()
+    });
+    let last_hour = entry_time(804) - TimeDelta::hours(1);
+    let (mut start, search_reads) = reads_by(flash, || {
+        block_on(find_log_start(&mut storage, &config, &mut index, last_hour)).unwrap()
+    });
+    assert_eq!(index.position_in_log(&start), 798);
+    assert!(
+        2 * search_reads < whole_log_reads,
+        "search took {search_reads} reads, the whole log {whole_log_reads}"
+    );
+
+    let mut buf = [[0u8; DATA_BUFFER_SIZE]; 10];
+    let (count, read_reads) = reads_by(flash, || {
+        block_on(read_log_items(
+            &mut storage,
+            &config,
+            &mut index,
+            &mut start,
+            &mut buf,
+        ))
+        .unwrap()
+    });
+    let numbers: Vec<_> = buf[..count]
+        .iter()
+        .map(|item| decode_entry(item).1)
+        .collect();
+    assert_eq!(numbers, (798..805).collect::<Vec<_>>());
+    assert!(
+        2 * read_reads < whole_log_reads,
+        "read took {read_reads} reads, the whole log {whole_log_reads}"
+    );
+}
+
+#[test]
+fn search_builds_the_index_from_the_log() {
+    let mut storage = boot();
+    let config = Logs::ConsumptionLog.get_config();
+    let mut unused = LogIndex::new();
+    for n in 0..200 {
+        write_entry(&mut storage, &config, &mut unused, n);
+    }
+
+    // as after a reboot, nothing is known about the log
+    let mut index = LogIndex::new();
+    assert!(!index.is_valid());
+    assert_search_matches_scan(&mut storage, &config, &mut index);
+    assert!(index.is_valid());
+}
+
+#[test]
+fn search_recovers_when_old_entries_are_overwritten() {
+    let mut storage = boot();
+    // two sectors, so a full log drops half its entries to make space
+    let config = StoredLogConfig {
+        storage_range: 0..0x2000,
+        allow_overwrite_old: true,
+    };
+    let mut index = LogIndex::new();
+    index.clear();
+    let mut n = 0;
+    while log_times(&mut storage, &config).len() == n as usize {
+        write_entry(&mut storage, &config, &mut index, n);
+        n += 1;
+    }
+    for _ in 0..50 {
+        write_entry(&mut storage, &config, &mut index, n);
+        n += 1;
+    }
+    assert_search_matches_scan(&mut storage, &config, &mut index);
+}
+
+#[test]
+fn search_keeps_the_index_when_pages_are_reused() {
+    let flash = flash();
+    let mut storage = boot_on(flash);
+    let config = Logs::ConsumptionLog.get_config();
+    let mut index = LogIndex::new();
+    index.clear();
+    let mut n = 0;
+    while log_times(&mut storage, &config).len() == n as usize {
+        write_entry(&mut storage, &config, &mut index, n);
+        n += 1;
+    }
+    let target = entry_time(n - 1) - TimeDelta::hours(1);
+    block_on(find_log_start(&mut storage, &config, &mut index, target)).unwrap();
+
+    // write until the oldest page is reused again, searching as the log manager does
+    let oldest = log_times(&mut storage, &config)[0];
+    let (_, whole_log_reads) = reads_by(flash, || {
+        block_on(storage.for_each_log_item(&config, |_| {})).unwrap()
+    });
+    while log_times(&mut storage, &config)[0] == oldest {
+        write_entry(&mut storage, &config, &mut index, n);
+        n += 1;
+        let target = entry_time(n - 1) - TimeDelta::hours(1);
+        let (_, search_reads) = reads_by(flash, || {
+            block_on(find_log_start(&mut storage, &config, &mut index, target)).unwrap()
+        });
+        assert!(
+            2 * search_reads < whole_log_reads,
+            "search after entry {n} took {search_reads} reads, the whole log {whole_log_reads}"
+        );
+    }
+    assert_search_matches_scan(&mut storage, &config, &mut index);
+}
+
+#[test]
+fn search_scans_the_log_when_the_clock_was_set_back() {
+    let mut storage = boot();
+    let config = Logs::ConsumptionLog.get_config();
+    let mut index = LogIndex::new();
+    index.clear();
+    for n in 0..300 {
+        write_entry(&mut storage, &config, &mut index, n);
+    }
+    for n in 300..400 {
+        write_entry_at(&mut storage, &config, &mut index, n, entry_time(n - 200));
+    }
+    assert!(!index.is_ordered());
+    assert_search_matches_scan(&mut storage, &config, &mut index);
+
+    // as after a reboot
+    let mut index = LogIndex::new();
+    assert_search_matches_scan(&mut storage, &config, &mut index);
+    assert!(index.is_valid() && !index.is_ordered());
+}
+
+#[test]
+fn search_uses_the_index_again_once_entries_out_of_order_are_overwritten() {
+    let mut storage = boot();
+    let config = StoredLogConfig {
+        storage_range: 0..0x2000,
+        allow_overwrite_old: true,
+    };
+    let mut index = LogIndex::new();
+    index.clear();
+    for n in 0..40 {
+        write_entry(&mut storage, &config, &mut index, n);
+    }
+    for n in 40..50 {
+        write_entry_at(&mut storage, &config, &mut index, n, entry_time(n - 30));
+    }
+    assert_search_matches_scan(&mut storage, &config, &mut index);
+    assert!(!index.is_ordered());
+
+    let mut n = 50;
+    while !log_times(&mut storage, &config).is_sorted() {
+        assert!(n < 2000, "entries out of order never overwritten");
+        write_entry(&mut storage, &config, &mut index, n);
+        n += 1;
+    }
+    assert_search_matches_scan(&mut storage, &config, &mut index);
+    assert!(index.is_valid() && index.is_ordered());
+}
+
+/// Numbers of the next `count` entries read from `position`.
+fn read_numbers(
+    storage: &mut Storage,
+    config: &StoredLogConfig,
+    index: &mut LogIndex,
+    position: &mut LogPosition,
+    count: usize,
+) -> Vec<u32> {
+    let mut buf = vec![[0u8; DATA_BUFFER_SIZE]; count];
+    let count = block_on(read_log_items(storage, config, index, position, &mut buf)).unwrap();
+    buf[..count]
+        .iter()
+        .map(|item| decode_entry(item).1)
+        .collect()
+}
+
+#[test]
+fn read_goes_on_from_where_it_stopped_while_entries_are_written() {
+    let mut storage = boot();
+    let config = Logs::ConsumptionLog.get_config();
+    let mut index = LogIndex::new();
+    index.clear();
+    for n in 0..300 {
+        write_entry(&mut storage, &config, &mut index, n);
+    }
+    let mut position = block_on(find_log_start(
+        &mut storage,
+        &config,
+        &mut index,
+        entry_time(100),
+    ))
+    .unwrap();
+    assert_eq!(
+        read_numbers(&mut storage, &config, &mut index, &mut position, 10),
+        (100..110).collect::<Vec<_>>()
+    );
+    for n in 300..310 {
+        write_entry(&mut storage, &config, &mut index, n);
+    }
+    assert_eq!(
+        read_numbers(&mut storage, &config, &mut index, &mut position, 10),
+        (110..120).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn read_goes_on_from_the_oldest_entry_once_its_page_is_reused() {
+    let mut storage = boot();
+    // two sectors, so a full log drops half its entries to make space
+    let config = StoredLogConfig {
+        storage_range: 0..0x2000,
+        allow_overwrite_old: true,
+    };
+    let mut index = LogIndex::new();
+    index.clear();
+    let mut n = 0;
+    while log_times(&mut storage, &config).len() == n as usize {
+        write_entry(&mut storage, &config, &mut index, n);
+        n += 1;
+    }
+    let oldest = log_entries(&mut storage, &config)[0].1;
+    let mut position = block_on(find_log_start(
+        &mut storage,
+        &config,
+        &mut index,
+        entry_time(oldest),
+    ))
+    .unwrap();
+    assert_eq!(
+        read_numbers(&mut storage, &config, &mut index, &mut position, 5),
+        (oldest..oldest + 5).collect::<Vec<_>>()
+    );
+
+    while log_entries(&mut storage, &config)[0].1 == oldest {
+        write_entry(&mut storage, &config, &mut index, n);
+        n += 1;
+    }
+    let oldest = log_entries(&mut storage, &config)[0].1;
+    assert_eq!(
+        read_numbers(&mut storage, &config, &mut index, &mut position, 5),
+        (oldest..oldest + 5).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn search_of_a_cleared_log_starts_at_the_beginning() {
+    let mut storage = boot();
+    let config = Logs::ConsumptionLog.get_config();
+    let mut index = LogIndex::new();
+    index.clear();
+    for n in 0..20 {
+        write_entry(&mut storage, &config, &mut index, n);
+    }
+    block_on(storage.clear_log_data(&config)).unwrap();
+    index.clear();
+
+    let start = block_on(find_log_start(
+        &mut storage,
+        &config,
+        &mut index,
+        start_time(),
+    ))
+    .unwrap();
+    assert_eq!(index.position_in_log(&start), 0);
+}
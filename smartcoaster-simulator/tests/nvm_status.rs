@@ -0,0 +1,123 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+//! The storage status read for the NVM status screen and USB query. These read the storage the
+//! application uses, which is a static, so are kept apart from the other storage tests.
+
+use chrono::{NaiveDate, TimeDelta};
+use embassy_futures::block_on;
+use embassy_sync::blocking_mutex::Mutex;
+use smartcoaster_application::error_log::{ErrorCode, ErrorLogData, ErrorModule};
+use smartcoaster_application::storage::historical::LogEncodeDecode;
+use smartcoaster_application::storage::historical::log_config::Logs;
+use smartcoaster_application::storage::historical::manager::DATA_BUFFER_SIZE;
+use smartcoaster_application::storage::historical::timestamp::encode_timestamp;
+use smartcoaster_application::storage::status::{
+    NvmPartition, PartitionWear, read_page_wear, read_partition_status,
+};
+use smartcoaster_application::storage::storage_manager::{
+    BlockingFlash, NV_STORAGE, StorageManager, initialise_storage,
+};
+use smartcoaster_application::storage::wear::NVM_PAGE_COUNT;
+use smartcoaster_application::{
+    FLASH_ERASE_SIZE, NVM_PARTITION_SIZE, SETTINGS_NVM_FLASH_OFFSET_RANGE,
+};
+use std::cell::RefCell;
+use std::sync::Once;
+
+fn initialise() {
+    static INITIALISED: Once = Once::new();
+    INITIALISED.call_once(|| {
+        let flash = Box::leak(Box::new(Mutex::new(RefCell::new(BlockingFlash::new()))));
+        block_on(initialise_storage(
+            flash,
+            0..NVM_PARTITION_SIZE as u32,
+            SETTINGS_NVM_FLASH_OFFSET_RANGE.clone(),
+        ));
+    });
+}
+
+/// Writes to the error log until it has wrapped, so its oldest page has been erased.
+fn wrap_error_log() {
+    let config = Logs::ErrorLog.get_config();
+    let start = NaiveDate::from_ymd_opt(2025, 6, 1)
+        .unwrap()
+        .and_hms_opt(8, 0, 0)
+        .unwrap();
+    let entry = ErrorLogData::new(ErrorModule::Storage, ErrorCode::SettingSaveFailed);
+    block_on(async {
+        let mut storage = NV_STORAGE.lock().await;
+        let mut data = [0u8; DATA_BUFFER_SIZE];
+        let mut written = 0;
+        while storage.for_each_log_item(&config, |_| {}).await.unwrap() == written {
+            let size = encode_timestamp(start + TimeDelta::seconds(written as i64), &mut data);
+            let size = size + entry.encode(&mut data[size..]).unwrap();
+            storage
+                .write_log_data(&config, &data[..size])
+                .await
+                .unwrap();
+            written += 1;
+        }
+    });
+}
+
+#[test]
+fn page_wear_covers_every_page_in_address_order() {
+    initialise();
+    wrap_error_log();
+
+    let pages = block_on(read_page_wear()).unwrap();
+    assert_eq!(pages.len(), NVM_PAGE_COUNT);
+    for (page, wear) in pages.iter().enumerate() {
+        assert_eq!(wear.offset, (page * FLASH_ERASE_SIZE) as u32);
+        assert!(wear.partition.range().contains(&wear.offset));
+    }
+
+    for partition in [NvmPartition::ErrorLog, NvmPartition::ActivityLog] {
+        let erase_counts: Vec<_> = pages
+            .iter()
+            .filter(|wear| wear.partition == partition)
+            .map(|wear| wear.erase_count)
+            .collect();
+        assert_eq!(
+            erase_counts.len(),
+            partition.range().len() / FLASH_ERASE_SIZE
+        );
+        // only the error log has been written
+        if partition == NvmPartition::ErrorLog {
+            assert!(erase_counts.iter().any(|&count| count > 0));
+        } else {
+            assert!(erase_counts.iter().all(|&count| count == 0));
+        }
+    }
+}
+
+#[test]
+fn partition_status_sums_page_wear() {
+    initialise();
+    wrap_error_log();
+
+    let pages = block_on(read_page_wear()).unwrap();
+    for partition in NvmPartition::ALL {
+        let counts = pages
+            .iter()
+            .filter(|wear| wear.partition == partition)
+            .map(|wear| wear.erase_count);
+        let expected = PartitionWear {
+            max_page_erases: counts.clone().max().unwrap(),
+            total_erases: counts.sum(),
+        };
+        let status = block_on(read_partition_status(partition)).unwrap();
+        assert_eq!(status.wear, Some(expected), "{:?}", partition);
+    }
+}
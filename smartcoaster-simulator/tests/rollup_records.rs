@@ -0,0 +1,87 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Daily rollup log record encoding, as stored in the rollup log partition.
+
+use chrono::{NaiveDate, TimeDelta};
+use smartcoaster_app_core::daily_summary::DailySummary;
+use smartcoaster_application::drink_monitor::rollup_data::{
+    DailyRollupLogData, ROLLUP_RECORD_VERSION,
+};
+use smartcoaster_application::storage::historical::manager::DATA_BUFFER_SIZE;
+use smartcoaster_application::storage::historical::record::{LogRecordType, RecordHeader};
+use smartcoaster_application::storage::historical::{LogEncodeDecode, LogEncodeDecodeError};
+
+/// Bytes available for a record once the timestamp has been written, zero padded as read back.
+const RECORD_SIZE: usize = DATA_BUFFER_SIZE - 10;
+
+fn summary() -> DailySummary {
+    let day_start = NaiveDate::from_ymd_opt(2025, 6, 1)
+        .unwrap()
+        .and_hms_opt(6, 0, 0)
+        .unwrap();
+    let mut summary = DailySummary::new(day_start);
+    summary.total_consumption = 1850.5;
+    summary.drink_count = 9;
+    summary.first_drink = Some(day_start + TimeDelta::minutes(45));
+    summary.last_drink = Some(day_start + TimeDelta::hours(15));
+    summary.target_achieved = true;
+    summary
+}
+
+fn encode(summary: &DailySummary) -> ([u8; RECORD_SIZE], usize) {
+    let mut buf = [0u8; RECORD_SIZE];
+    let size = DailyRollupLogData::from(summary).encode(&mut buf).unwrap();
+    (buf, size)
+}
+
+#[test]
+fn records_round_trip() {
+    let (buf, size) = encode(&summary());
+    assert_eq!(
+        RecordHeader::decode(&buf[..size]).unwrap(),
+        Some(RecordHeader::new(
+            LogRecordType::DailyRollup,
+            ROLLUP_RECORD_VERSION
+        ))
+    );
+    let data = DailyRollupLogData::from_bytes(&buf).unwrap();
+    assert_eq!(data.get_summary().unwrap(), summary());
+}
+
+#[test]
+fn days_without_drinks_round_trip() {
+    let summary = DailySummary::new(summary().day_start);
+    let (buf, _) = encode(&summary);
+    let data = DailyRollupLogData::from_bytes(&buf).unwrap();
+    assert_eq!(data.get_summary().unwrap(), summary);
+}
+
+#[test]
+fn records_without_a_header_are_read() {
+    let (mut buf, _) = encode(&summary());
+    buf.copy_within(RecordHeader::SIZE.., 0);
+    let data = DailyRollupLogData::from_bytes(&buf).unwrap();
+    assert_eq!(data.get_summary().unwrap(), summary());
+}
+
+#[test]
+fn other_records_are_rejected() {
+    let (mut buf, _) = encode(&summary());
+    buf[1] = LogRecordType::Error.into();
+    assert!(matches!(
+        DailyRollupLogData::from_bytes(&buf),
+        Err(LogEncodeDecodeError::DecodeFailed)
+    ));
+}
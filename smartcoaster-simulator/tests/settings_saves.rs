@@ -0,0 +1,266 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+//! Coalescing of settings saves. Saves are queued and written by the storage task, which these
+//! tests stand in for by processing the queue themselves. The settings and storage are statics
+//! shared by the tests in this file, so the tests take turns.
+
+use embassy_futures::block_on;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use smartcoaster_application::storage::factory_reset::{FactoryResetOptions, factory_reset};
+use smartcoaster_application::storage::restart::prepare_for_restart;
+use smartcoaster_application::storage::settings::accessor::{
+    FlashSettingsAccessor, SAVE_DEBOUNCE, SAVE_MAX_DELAY, flush_settings, initialise_settings,
+    next_settings_save_due, process_save_queue,
+};
+use smartcoaster_application::storage::settings::schema::{SettingKey, keys};
+use smartcoaster_application::storage::settings::{
+    SettingValue, SettingsAccessor, SettingsAccessorId,
+};
+use smartcoaster_application::storage::storage_manager::{
+    BlockingFlash, NV_STORAGE, StorageManager, initialise_storage,
+};
+use smartcoaster_application::{NVM_PARTITION_SIZE, SETTINGS_NVM_FLASH_OFFSET_RANGE};
+use std::cell::RefCell;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+type FlashMutex = BlockingMutex<CriticalSectionRawMutex, RefCell<BlockingFlash>>;
+
+/// Margin for the storage task being late to process a due save.
+const PROCESS_MARGIN: Duration = Duration::from_millis(300);
+
+/// Flash the storage is over, set up by the first test to start.
+static FLASH: OnceLock<&'static FlashMutex> = OnceLock::new();
+
+/// Sets up storage and settings the first time, then waits for any other test to finish.
+fn start() -> MutexGuard<'static, ()> {
+    static TURN: Mutex<()> = Mutex::new(());
+    FLASH.get_or_init(|| {
+        let flash: &'static FlashMutex = Box::leak(Box::new(BlockingMutex::new(RefCell::new(
+            BlockingFlash::new(),
+        ))));
+        block_on(async {
+            initialise_storage(
+                flash,
+                0..NVM_PARTITION_SIZE as u32,
+                SETTINGS_NVM_FLASH_OFFSET_RANGE.clone(),
+            )
+            .await;
+            initialise_settings().await;
+        });
+        flash
+    });
+    let turn = TURN.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    // nothing left queued by the last test
+    block_on(flush_settings());
+    turn
+}
+
+fn save(id: SettingsAccessorId, value: SettingValue) {
+    block_on(FlashSettingsAccessor::new().save_setting(id, value)).unwrap();
+}
+
+/// Value of `key` in flash, rather than as the application sees it.
+fn stored(key: SettingKey) -> Option<SettingValue> {
+    block_on(async {
+        NV_STORAGE
+            .lock()
+            .await
+            .read_key_value_pair::<SettingValue>(key)
+            .await
+            .unwrap()
+    })
+}
+
+/// Items written to the settings map, each flash write of a setting adding one.
+fn stored_items() -> u32 {
+    block_on(async {
+        NV_STORAGE
+            .lock()
+            .await
+            .get_key_value_usage()
+            .await
+            .unwrap()
+            .stored_items
+    })
+}
+
+fn std_duration(duration: embassy_time::Duration) -> Duration {
+    Duration::from_millis(duration.as_millis())
+}
+
+#[test]
+fn repeated_saves_are_written_once() {
+    let _turn = start();
+    let items = stored_items();
+
+    for brightness in 10..20 {
+        save(
+            SettingsAccessorId::SystemLedBrightness,
+            SettingValue::SmallUInt(brightness),
+        );
+        block_on(process_save_queue());
+    }
+    assert_eq!(stored_items(), items);
+
+    thread::sleep(std_duration(SAVE_DEBOUNCE) + PROCESS_MARGIN);
+    block_on(process_save_queue());
+    assert_eq!(stored_items(), items + 1);
+    assert_eq!(
+        stored(keys::SYSTEM_LED_BRIGHTNESS),
+        Some(SettingValue::SmallUInt(19))
+    );
+    assert_eq!(block_on(next_settings_save_due()), None);
+}
+
+#[test]
+fn flush_writes_straight_away() {
+    let _turn = start();
+    let items = stored_items();
+
+    save(
+        SettingsAccessorId::SystemDisplayBrightness,
+        SettingValue::SmallUInt(42),
+    );
+    assert!(block_on(next_settings_save_due()).is_some());
+    block_on(flush_settings());
+
+    assert_eq!(stored_items(), items + 1);
+    assert_eq!(
+        stored(keys::SYSTEM_DISPLAY_BRIGHTNESS),
+        Some(SettingValue::SmallUInt(42))
+    );
+    assert_eq!(block_on(next_settings_save_due()), None);
+}
+
+#[test]
+fn setting_that_keeps_changing_is_written_after_the_max_delay() {
+    let _turn = start();
+    let first_saved = Instant::now();
+    let mut index = 0;
+    // saved again before the debounce time each time, so only the max delay makes it due
+    let written = loop {
+        index += 1;
+        save(
+            SettingsAccessorId::MonitoringDisplayIndex,
+            SettingValue::SmallUInt(index),
+        );
+        block_on(process_save_queue());
+        if stored(keys::MONITORING_DISPLAY_INDEX) == Some(SettingValue::SmallUInt(index)) {
+            break first_saved.elapsed();
+        }
+        assert!(first_saved.elapsed() < std_duration(SAVE_MAX_DELAY) + PROCESS_MARGIN);
+        thread::sleep(std_duration(SAVE_DEBOUNCE) / 2);
+    };
+
+    assert!(written >= std_duration(SAVE_MAX_DELAY));
+}
+
+#[test]
+fn setting_changed_back_before_writing_is_not_written() {
+    let _turn = start();
+    save(
+        SettingsAccessorId::DisplayTimeoutMinutes,
+        SettingValue::SmallUInt(5),
+    );
+    block_on(flush_settings());
+    let items = stored_items();
+
+    save(
+        SettingsAccessorId::DisplayTimeoutMinutes,
+        SettingValue::SmallUInt(10),
+    );
+    save(
+        SettingsAccessorId::DisplayTimeoutMinutes,
+        SettingValue::SmallUInt(5),
+    );
+    block_on(flush_settings());
+
+    assert_eq!(stored_items(), items);
+    assert_eq!(
+        stored(keys::DISPLAY_TIMEOUT_MINUTES),
+        Some(SettingValue::SmallUInt(5))
+    );
+}
+
+#[test]
+fn factory_reset_writes_queued_saves_first() {
+    let _turn = start();
+    save(
+        SettingsAccessorId::WeighingSystemTareOffset,
+        SettingValue::Float(1234.5),
+    );
+    save(
+        SettingsAccessorId::SystemLedBrightness,
+        SettingValue::SmallUInt(99),
+    );
+
+    block_on(factory_reset(FactoryResetOptions {
+        keep_calibration: true,
+    }))
+    .unwrap();
+
+    // the tare offset queued just before is the one kept
+    assert_eq!(
+        stored(keys::WEIGHING_SYSTEM_TARE_OFFSET),
+        Some(SettingValue::Float(1234.5))
+    );
+    assert_eq!(stored(keys::SYSTEM_LED_BRIGHTNESS), None);
+    assert_eq!(block_on(next_settings_save_due()), None);
+}
+
+#[test]
+fn restart_writes_queued_saves_first() {
+    let _turn = start();
+    save(
+        SettingsAccessorId::SystemLedBrightness,
+        SettingValue::SmallUInt(77),
+    );
+    assert!(block_on(next_settings_save_due()).is_some());
+
+    block_on(prepare_for_restart());
+
+    assert_eq!(
+        stored(keys::SYSTEM_LED_BRIGHTNESS),
+        Some(SettingValue::SmallUInt(77))
+    );
+    assert_eq!(block_on(next_settings_save_due()), None);
+}
+
+#[test]
+fn failed_save_is_retried() {
+    let _turn = start();
+    save(
+        SettingsAccessorId::WeighingSystemBitsToDiscard,
+        SettingValue::SmallUInt(3),
+    );
+
+    FLASH
+        .get()
+        .unwrap()
+        .lock(|flash| flash.borrow_mut().lose_power_after(0));
+    block_on(flush_settings());
+    assert_eq!(stored(keys::WEIGHING_SYSTEM_BITS_TO_DISCARD), None);
+    assert!(block_on(next_settings_save_due()).is_some());
+
+    thread::sleep(std_duration(SAVE_DEBOUNCE) + PROCESS_MARGIN);
+    block_on(process_save_queue());
+    assert_eq!(
+        stored(keys::WEIGHING_SYSTEM_BITS_TO_DISCARD),
+        Some(SettingValue::SmallUInt(3))
+    );
+    assert_eq!(block_on(next_settings_save_due()), None);
+}
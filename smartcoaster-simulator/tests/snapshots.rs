@@ -0,0 +1,320 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Snapshots of each HMI screen to catch layout regressions. Snapshots are kept in
+//! `tests/snapshots`; run with `UPDATE_SNAPSHOTS=1` to regenerate them after an intended change and
+//! review the diff. The about screen is not covered as it shows the git commit of the build.
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use smartcoaster_app_core::reminder::ReminderLevel;
+use smartcoaster_application::application::application_state::{
+    ApplicationState, CalibrationStateSubstates, ConfirmationId, FirstRunStep,
+};
+use smartcoaster_application::application::messaging::ApplicationData;
+use smartcoaster_application::drink_monitor::messaging::DrinkMonitoringUpdate;
+use smartcoaster_application::error_log::{ErrorCode, ErrorLogData, ErrorLogRecord, ErrorModule};
+use smartcoaster_application::hmi::screens::monitoring::MAX_SCREENS;
+use smartcoaster_application::hmi::screens::settings_menu::monitoring_options::MonitoringTargetPeriodOptions;
+use smartcoaster_application::storage::settings::{SettingValue, SettingsAccessorId};
+use smartcoaster_application::storage::status::{NvmPartition, PartitionStatus, PartitionWear};
+use smartcoaster_simulator::settings::MemorySettings;
+use smartcoaster_simulator::snapshot::{assert_snapshot, to_text};
+use smartcoaster_simulator::{Simulator, sample_monitoring_updates};
+use std::path::Path;
+
+fn now() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2025, 3, 12)
+        .unwrap()
+        .and_hms_opt(14, 30, 0)
+        .unwrap()
+}
+
+fn simulator(settings: MemorySettings) -> Simulator<MemorySettings> {
+    Simulator::new(settings, now())
+}
+
+fn check(simulator: &mut Simulator<MemorySettings>, name: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("snapshots")
+        .join(format!("{name}.txt"));
+    assert_snapshot(&path, simulator.render());
+}
+
+fn monitoring_simulator(settings: MemorySettings) -> Simulator<MemorySettings> {
+    let mut s = simulator(settings);
+    s.set_display_state(ApplicationState::Monitoring);
+    for update in sample_monitoring_updates(now()) {
+        s.application_data(ApplicationData::MonitoringUpdate(update));
+    }
+    s
+}
+
+#[test]
+fn startup() {
+    let mut s = simulator(MemorySettings::new());
+    assert_eq!(s.display_state(), ApplicationState::Startup);
+    check(&mut s, "startup");
+}
+
+#[test]
+fn error_message() {
+    let mut s = simulator(MemorySettings::new());
+    s.set_display_state(ApplicationState::ErrorScreenWithMessage(
+        "Weighing system not responding",
+    ));
+    check(&mut s, "error_message");
+}
+
+#[test]
+fn settings_menu() {
+    let mut s = simulator(MemorySettings::new());
+    s.set_display_state(ApplicationState::Settings);
+    check(&mut s, "settings_menu");
+    s.turn(4);
+    check(&mut s, "settings_menu_scrolled");
+}
+
+#[test]
+fn monitoring_screens() {
+    for index in 0..MAX_SCREENS {
+        let settings = MemorySettings::new().with(
+            SettingsAccessorId::MonitoringDisplayIndex,
+            SettingValue::SmallUInt(index),
+        );
+        let mut s = monitoring_simulator(settings);
+        check(&mut s, &format!("monitoring_{:02}", index + 1));
+    }
+}
+
+#[test]
+fn monitoring_before_vessel_placed() {
+    let mut s = simulator(MemorySettings::new());
+    s.set_display_state(ApplicationState::Monitoring);
+    check(&mut s, "monitoring_waiting");
+}
+
+#[test]
+fn encoder_changes_monitoring_screen() {
+    let mut s = monitoring_simulator(MemorySettings::new());
+    s.turn(1);
+    let mut expected = monitoring_simulator(MemorySettings::new().with(
+        SettingsAccessorId::MonitoringDisplayIndex,
+        SettingValue::SmallUInt(1),
+    ));
+    assert_eq!(to_text(s.render()), to_text(expected.render()));
+}
+
+#[test]
+fn button_opens_settings_from_monitoring() {
+    let mut s = monitoring_simulator(MemorySettings::new());
+    s.click();
+    assert_eq!(s.display_state(), ApplicationState::Settings);
+}
+
+#[test]
+fn button_opens_settings_while_waiting_with_reminder_due() {
+    let mut s = simulator(MemorySettings::new());
+    s.set_display_state(ApplicationState::Monitoring);
+    s.application_data(ApplicationData::MonitoringUpdate(
+        DrinkMonitoringUpdate::ReminderDue(ReminderLevel::Gentle),
+    ));
+    s.click();
+    assert_eq!(s.display_state(), ApplicationState::Settings);
+}
+
+#[test]
+fn button_snoozes_shown_reminder() {
+    let mut s = monitoring_simulator(MemorySettings::new());
+    s.application_data(ApplicationData::MonitoringUpdate(
+        DrinkMonitoringUpdate::ReminderDue(ReminderLevel::Gentle),
+    ));
+    let reminder = to_text(s.render());
+    s.click();
+    assert_eq!(s.display_state(), ApplicationState::Monitoring);
+    assert_ne!(to_text(s.render()), reminder);
+}
+
+#[test]
+fn number_entry() {
+    let settings = MemorySettings::new().with(
+        SettingsAccessorId::MonitoringTargetDaily,
+        SettingValue::UInt(2000),
+    );
+    let mut s = simulator(settings);
+    s.set_display_state(ApplicationState::NumberEntry(
+        SettingsAccessorId::MonitoringTargetDaily,
+    ));
+    check(&mut s, "number_entry");
+}
+
+#[test]
+fn time_entry() {
+    let settings = MemorySettings::new().with(
+        SettingsAccessorId::MonitoringDayStartTime,
+        SettingValue::Time(NaiveTime::from_hms_opt(4, 0, 0).unwrap()),
+    );
+    let mut s = simulator(settings);
+    s.set_display_state(ApplicationState::TimeEntry(
+        SettingsAccessorId::MonitoringDayStartTime,
+    ));
+    check(&mut s, "time_entry");
+}
+
+#[test]
+fn system_date_time_entry() {
+    let mut s = simulator(MemorySettings::new());
+    s.set_display_state(ApplicationState::SetSystemDateTime);
+    check(&mut s, "system_date_time_entry");
+}
+
+#[test]
+fn confirmation() {
+    let mut s = simulator(MemorySettings::new());
+    s.set_display_state(ApplicationState::ConfirmationScreen(
+        ConfirmationId::ClearHistoricalConsumptionLog,
+    ));
+    check(&mut s, "confirmation");
+}
+
+#[test]
+fn calibration() {
+    let mut s = simulator(MemorySettings::new());
+    s.set_display_state(ApplicationState::Calibration);
+    for (substate, name) in [
+        (CalibrationStateSubstates::Tare, "calibration_tare"),
+        (CalibrationStateSubstates::Wait, "calibration_wait"),
+        (
+            CalibrationStateSubstates::Calibration(100),
+            "calibration_mass",
+        ),
+        (
+            CalibrationStateSubstates::CalibrationDone,
+            "calibration_done",
+        ),
+    ] {
+        s.application_data(ApplicationData::CalibrationSubstate(substate));
+        check(&mut s, name);
+    }
+}
+
+#[test]
+fn heap_status() {
+    let mut s = simulator(MemorySettings::new());
+    s.set_display_state(ApplicationState::HeapStatus);
+    s.application_data(ApplicationData::HeapStatus {
+        used: 5120,
+        free: 11264,
+    });
+    check(&mut s, "heap_status");
+}
+
+#[test]
+fn test_mode() {
+    let mut s = simulator(MemorySettings::new());
+    s.set_display_state(ApplicationState::TestScreen);
+    s.application_data(ApplicationData::Weight(342.5));
+    check(&mut s, "test_mode");
+}
+
+#[test]
+fn error_log_empty() {
+    let mut s = simulator(MemorySettings::new());
+    s.set_display_state(ApplicationState::ErrorLog);
+    check(&mut s, "error_log_empty");
+}
+
+#[test]
+fn error_log() {
+    let mut s = simulator(MemorySettings::new());
+    s.set_display_state(ApplicationState::ErrorLog);
+    s.application_data(ApplicationData::ErrorLogRecord(ErrorLogRecord {
+        timestamp: now(),
+        data: ErrorLogData::new(ErrorModule::Storage, ErrorCode::SettingSaveFailed).with_value(4),
+    }));
+    s.application_data(ApplicationData::ErrorLogRecord(ErrorLogRecord {
+        timestamp: now() - chrono::Duration::hours(2),
+        data: ErrorLogData::new(ErrorModule::System, ErrorCode::WatchdogReset),
+    }));
+    check(&mut s, "error_log");
+}
+
+#[test]
+fn nvm_status_reading() {
+    let mut s = simulator(MemorySettings::new());
+    s.set_display_state(ApplicationState::NvmStatus);
+    check(&mut s, "nvm_status_reading");
+}
+
+#[test]
+fn nvm_status() {
+    let mut s = simulator(MemorySettings::new());
+    s.set_display_state(ApplicationState::NvmStatus);
+    s.application_data(ApplicationData::NvmPartitionStatus(PartitionStatus {
+        partition: NvmPartition::ActivityLog,
+        capacity: 0x8000,
+        used: 0x2000,
+        record_count: 512,
+        oldest: Some(now() - chrono::Duration::days(10)),
+        newest: Some(now()),
+        live_settings: None,
+        wear: Some(PartitionWear {
+            max_page_erases: 40,
+            total_erases: 250,
+        }),
+    }));
+    s.application_data(ApplicationData::NvmPartitionStatus(PartitionStatus {
+        partition: NvmPartition::Settings,
+        capacity: 0x2000,
+        used: 480,
+        record_count: 30,
+        oldest: None,
+        newest: None,
+        live_settings: Some(18),
+        wear: Some(PartitionWear {
+            max_page_erases: 12,
+            total_erases: 20,
+        }),
+    }));
+    check(&mut s, "nvm_status");
+    s.turn(2);
+    check(&mut s, "nvm_status_wear");
+    // the wear page is the last
+    s.turn(1);
+    check(&mut s, "nvm_status_wear");
+}
+
+#[test]
+fn first_run_setup() {
+    let mut s = simulator(MemorySettings::new());
+    s.set_display_state(ApplicationState::FirstRunSetup(FirstRunStep::Welcome));
+    check(&mut s, "first_run_welcome");
+    s.click();
+    assert_eq!(s.display_state(), ApplicationState::SetSystemDateTime);
+
+    s.set_display_state(ApplicationState::FirstRunSetup(FirstRunStep::TargetType));
+    check(&mut s, "first_run_target_type");
+    s.turn(1);
+    check(&mut s, "first_run_target_type_hourly");
+
+    s.set_display_state(ApplicationState::FirstRunSetup(FirstRunStep::TargetValue(
+        MonitoringTargetPeriodOptions::Hourly,
+    )));
+    check(&mut s, "first_run_target_value");
+
+    s.set_display_state(ApplicationState::FirstRunSetup(FirstRunStep::Complete));
+    check(&mut s, "first_run_complete");
+    s.click();
+    assert_eq!(s.display_state(), ApplicationState::Monitoring);
+}
@@ -0,0 +1,886 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Storage manager tests against flash held in RAM, including power loss, bit flips and erase
+//! failures injected into the flash.
+
+use embassy_embedded_hal::adapter::BlockingAsync;
+use embassy_embedded_hal::flash::partition::BlockingPartition;
+use embassy_futures::block_on;
+use embassy_sync::blocking_mutex::Mutex;
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_time::Instant;
+use smartcoaster_application::hmi::screens::monitoring::MAX_SCREENS;
+use smartcoaster_application::storage::StoredDataValue;
+use smartcoaster_application::storage::factory_reset::{FactoryResetOptions, reset_storage};
+use smartcoaster_application::storage::historical::log_config::Logs;
+use smartcoaster_application::storage::historical::manager::DATA_BUFFER_SIZE;
+use smartcoaster_application::storage::settings::StorageError;
+use smartcoaster_application::storage::settings::accessor::SAVE_MAX_DELAY;
+use smartcoaster_application::storage::settings::schema::{
+    SETTINGS_SCHEMA_VERSION, keys, migrate_settings, read_schema_version,
+};
+use smartcoaster_application::storage::status::{NvmPartition, PartitionStatus};
+use smartcoaster_application::storage::storage_manager::{
+    BlockingAsyncPartition, BlockingFlash, StorageManager, StorageManagerSequentialStorage,
+    StoredLogConfig,
+};
+use smartcoaster_application::storage::wear::EraseCounts;
+use smartcoaster_application::{
+    FLASH_ERASE_SIZE, NVM_PARTITION_SIZE, SETTINGS_NVM_FLASH_OFFSET_RANGE,
+};
+use std::cell::RefCell;
+use std::ops::Range;
+
+type FlashMutex = Mutex<CriticalSectionRawMutex, RefCell<BlockingFlash>>;
+type Storage = StorageManagerSequentialStorage<BlockingAsyncPartition>;
+
+/// Two sectors, the smallest log sequential-storage supports.
+const SMALL_LOG_SIZE: u32 = 0x2000;
+const LOG_ENTRY_SIZE: usize = 16;
+
+fn flash() -> &'static FlashMutex {
+    Box::leak(Box::new(Mutex::new(RefCell::new(BlockingFlash::new()))))
+}
+
+/// Starts a storage manager over `flash`, as the firmware does at boot.
+fn boot(flash: &'static FlashMutex) -> Storage {
+    let partition = BlockingAsync::new(BlockingPartition::new(flash, 0, NVM_PARTITION_SIZE as u32));
+    let mut storage = Storage::new();
+    block_on(storage.initialise(partition, SETTINGS_NVM_FLASH_OFFSET_RANGE.clone()));
+    storage
+}
+
+fn inject(flash: &FlashMutex, fault: impl FnOnce(&mut BlockingFlash)) {
+    flash.lock(|flash| fault(&mut flash.borrow_mut()));
+}
+
+fn snapshot(flash: &FlashMutex) -> Vec<u8> {
+    flash.lock(|flash| flash.borrow().data().to_vec())
+}
+
+fn log_config(allow_overwrite_old: bool) -> StoredLogConfig {
+    StoredLogConfig {
+        storage_range: 0..SMALL_LOG_SIZE,
+        allow_overwrite_old,
+    }
+}
+
+/// Log entry identified by `index`.
+fn log_entry(index: u16) -> [u8; LOG_ENTRY_SIZE] {
+    let mut entry = [0xA5; LOG_ENTRY_SIZE];
+    entry[..2].copy_from_slice(&index.to_le_bytes());
+    entry
+}
+
+fn write_log_entry(storage: &mut Storage, config: &StoredLogConfig, index: u16) {
+    block_on(storage.write_log_data(config, &log_entry(index))).unwrap();
+}
+
+/// Reads back the whole log, checking every entry is one that was written, and returns the entry
+/// indexes.
+fn read_log(storage: &mut Storage, config: &StoredLogConfig) -> Vec<u16> {
+    let mut indexes = Vec::new();
+    let mut buf = [[0; DATA_BUFFER_SIZE]; 4];
+    loop {
+        let count =
+            block_on(storage.get_log_items(config, indexes.len(), buf.len(), &mut buf)).unwrap();
+        for item in &buf[..count] {
+            let index = u16::from_le_bytes([item[0], item[1]]);
+            assert_eq!(
+                item[..LOG_ENTRY_SIZE],
+                log_entry(index),
+                "log entry corrupted"
+            );
+            assert!(item[LOG_ENTRY_SIZE..].iter().all(|b| *b == 0));
+            indexes.push(index);
+        }
+        if count < buf.len() {
+            return indexes;
+        }
+    }
+}
+
+fn save(storage: &mut Storage, key: u16, value: StoredDataValue) -> Result<(), StorageError> {
+    block_on(storage.save_key_value_pair(key, value))
+}
+
+fn read(storage: &mut Storage, key: u16) -> Result<Option<StoredDataValue>, StorageError> {
+    block_on(storage.read_key_value_pair(key))
+}
+
+#[test]
+fn uninitialised_storage_is_rejected() {
+    let mut storage = Storage::new();
+    assert!(!storage.is_initialized());
+    assert!(matches!(
+        save(&mut storage, 1, StoredDataValue::UInt(1)),
+        Err(StorageError::NotInitialized)
+    ));
+    assert!(matches!(
+        read(&mut storage, 1),
+        Err(StorageError::NotInitialized)
+    ));
+}
+
+#[test]
+fn key_values_round_trip() {
+    let flash = flash();
+    let mut storage = boot(flash);
+    let time = chrono::NaiveTime::from_hms_opt(7, 30, 0).unwrap();
+    save(&mut storage, 1, StoredDataValue::Float(0.125)).unwrap();
+    save(&mut storage, 2, StoredDataValue::UInt(2000)).unwrap();
+    save(&mut storage, 3, StoredDataValue::Time(time)).unwrap();
+    save(&mut storage, 1, StoredDataValue::Float(-4.5)).unwrap();
+
+    assert_eq!(
+        read(&mut storage, 1).unwrap(),
+        Some(StoredDataValue::Float(-4.5))
+    );
+    assert_eq!(
+        read(&mut storage, 2).unwrap(),
+        Some(StoredDataValue::UInt(2000))
+    );
+    assert_eq!(
+        read(&mut storage, 3).unwrap(),
+        Some(StoredDataValue::Time(time))
+    );
+    assert_eq!(read(&mut storage, 4).unwrap(), None);
+
+    // and after a restart
+    let mut storage = boot(flash);
+    assert_eq!(
+        read(&mut storage, 1).unwrap(),
+        Some(StoredDataValue::Float(-4.5))
+    );
+}
+
+#[test]
+fn clear_data_removes_key_values() {
+    let mut storage = boot(flash());
+    save(&mut storage, 1, StoredDataValue::UInt(1)).unwrap();
+    block_on(storage.clear_data()).unwrap();
+    assert_eq!(read(&mut storage, 1).unwrap(), None);
+}
+
+/// Setting keys as stored in flash.
+const TARE_OFFSET_KEY: u16 = keys::WEIGHING_SYSTEM_TARE_OFFSET;
+const CALIBRATION_GRADIENT_KEY: u16 = keys::WEIGHING_SYSTEM_CALIBRATION_GRADIENT;
+const LED_BRIGHTNESS_KEY: u16 = keys::SYSTEM_LED_BRIGHTNESS;
+
+fn factory_reset_storage(keep_calibration: bool) -> Storage {
+    let mut storage = boot(flash());
+    save(&mut storage, TARE_OFFSET_KEY, StoredDataValue::Float(812.0)).unwrap();
+    save(
+        &mut storage,
+        CALIBRATION_GRADIENT_KEY,
+        StoredDataValue::Float(0.25),
+    )
+    .unwrap();
+    save(
+        &mut storage,
+        LED_BRIGHTNESS_KEY,
+        StoredDataValue::SmallUInt(64),
+    )
+    .unwrap();
+    let consumption_log = Logs::ConsumptionLog.get_config();
+    write_log_entry(&mut storage, &consumption_log, 0);
+    let error_log = Logs::ErrorLog.get_config();
+    write_log_entry(&mut storage, &error_log, 0);
+
+    block_on(reset_storage(
+        &mut storage,
+        FactoryResetOptions { keep_calibration },
+    ))
+    .unwrap();
+    assert!(read_log(&mut storage, &consumption_log).is_empty());
+    assert!(read_log(&mut storage, &error_log).is_empty());
+    assert_eq!(read(&mut storage, LED_BRIGHTNESS_KEY).unwrap(), None);
+    storage
+}
+
+#[test]
+fn factory_reset_erases_everything() {
+    let mut storage = factory_reset_storage(false);
+    assert_eq!(read(&mut storage, TARE_OFFSET_KEY).unwrap(), None);
+    assert_eq!(read(&mut storage, CALIBRATION_GRADIENT_KEY).unwrap(), None);
+    assert_eq!(
+        block_on(read_schema_version(&mut storage)).unwrap(),
+        Some(SETTINGS_SCHEMA_VERSION)
+    );
+}
+
+#[test]
+fn factory_reset_can_keep_calibration() {
+    let mut storage = factory_reset_storage(true);
+    assert_eq!(
+        read(&mut storage, TARE_OFFSET_KEY).unwrap(),
+        Some(StoredDataValue::Float(812.0))
+    );
+    assert_eq!(
+        read(&mut storage, CALIBRATION_GRADIENT_KEY).unwrap(),
+        Some(StoredDataValue::Float(0.25))
+    );
+}
+
+/// Stored form of a version 0 value, a `StoredDataValue` type byte followed by its little endian
+/// payload, written out by hand so the image does not depend on the current encoding.
+fn v0_value(type_byte: u8, payload: &[u8]) -> Vec<u8> {
+    [&[type_byte][..], payload].concat()
+}
+
+/// Settings range as the firmware from before the schema version was stored left it, after
+/// calibrating, choosing a target and leaving the monitoring display on the debug screen. Keys
+/// are the `StoredSettings` discriminants of that firmware and there is no schema version.
+fn settings_v0_image() -> Vec<u8> {
+    const FLOAT: u8 = 1;
+    const SMALL_UINT: u8 = 2;
+    const UINT: u8 = 3;
+    const TIME: u8 = 4;
+    let values = [
+        (0, v0_value(FLOAT, &812.5f32.to_le_bytes())),
+        (1, v0_value(FLOAT, &0.0625f32.to_le_bytes())),
+        (2, v0_value(SMALL_UINT, &[64])),
+        (5, v0_value(SMALL_UINT, &[1])),
+        (6, v0_value(UINT, &2000u32.to_le_bytes())),
+        (8, v0_value(TIME, &[20, 0, 0, 0, 0, 0, 0])),
+        (9, v0_value(UINT, &250u32.to_le_bytes())),
+        (10, v0_value(SMALL_UINT, &[V0_DEBUG_SCREEN_INDEX])),
+    ];
+
+    let flash = flash();
+    let mut storage = boot(flash);
+    for (key, value) in &values {
+        block_on(storage.save_key_value_pair(*key, value.as_slice())).unwrap();
+    }
+    snapshot(flash)[settings_range()].to_vec()
+}
+
+/// Keys of the settings the version 0 firmware had.
+const V0_KEYS: Range<u16> = keys::WEIGHING_SYSTEM_TARE_OFFSET..keys::MONITORING_DISPLAY_INDEX + 1;
+const V0_DEBUG_SCREEN_INDEX: u8 = 4;
+const V0_HOURLY_TARGET: u32 = 250;
+
+/// Settings held in the version 0 image before it is migrated.
+fn v0_settings() -> Vec<(u16, StoredDataValue)> {
+    let mut storage = boot(flash_from_image(&settings_v0_image()));
+    V0_KEYS
+        .filter_map(|key| read(&mut storage, key).unwrap().map(|value| (key, value)))
+        .collect()
+}
+
+fn settings_range() -> Range<usize> {
+    SETTINGS_NVM_FLASH_OFFSET_RANGE.start as usize..SETTINGS_NVM_FLASH_OFFSET_RANGE.end as usize
+}
+
+fn flash_from_image(image: &[u8]) -> &'static FlashMutex {
+    let flash = flash();
+    inject(flash, |flash| {
+        flash.load_image(settings_range().start, image)
+    });
+    flash
+}
+
+fn migrate(storage: &mut Storage) -> Result<u8, StorageError> {
+    block_on(migrate_settings(storage))
+}
+
+fn assert_migrated_v0_settings(storage: &mut Storage) {
+    assert_eq!(
+        block_on(read_schema_version(storage)).unwrap(),
+        Some(SETTINGS_SCHEMA_VERSION)
+    );
+    for (key, value) in v0_settings() {
+        if key == keys::MONITORING_DISPLAY_INDEX || key == keys::MONITORING_TARGET_HOURLY {
+            continue;
+        }
+        assert_eq!(read(storage, key).unwrap(), Some(value));
+    }
+    assert_eq!(
+        read(storage, keys::MONITORING_TARGET_HOURLY).unwrap(),
+        Some(StoredDataValue::Float(V0_HOURLY_TARGET as f32))
+    );
+    assert_eq!(
+        read(storage, keys::MONITORING_DISPLAY_INDEX).unwrap(),
+        Some(StoredDataValue::SmallUInt(MAX_SCREENS - 1))
+    );
+    assert_eq!(
+        read(storage, keys::SYSTEM_SETUP_COMPLETE).unwrap(),
+        Some(StoredDataValue::SmallUInt(1))
+    );
+}
+
+#[test]
+fn v0_image_holds_v0_settings() {
+    let settings = v0_settings();
+    let value = |key| settings.iter().find(|(k, _)| *k == key).map(|(_, v)| *v);
+    assert!(matches!(
+        value(CALIBRATION_GRADIENT_KEY),
+        Some(StoredDataValue::Float(_))
+    ));
+    assert!(matches!(
+        value(keys::MONITORING_TARGET_TYPE),
+        Some(StoredDataValue::SmallUInt(_))
+    ));
+    assert_eq!(
+        value(keys::MONITORING_TARGET_HOURLY),
+        Some(StoredDataValue::UInt(V0_HOURLY_TARGET))
+    );
+    assert_eq!(
+        value(keys::MONITORING_DISPLAY_INDEX),
+        Some(StoredDataValue::SmallUInt(V0_DEBUG_SCREEN_INDEX))
+    );
+}
+
+#[test]
+fn v0_settings_are_migrated() {
+    let mut storage = boot(flash_from_image(&settings_v0_image()));
+    assert_eq!(
+        block_on(read_schema_version(&mut storage)).unwrap(),
+        Some(0)
+    );
+    assert_eq!(migrate(&mut storage).unwrap(), 0);
+    assert_migrated_v0_settings(&mut storage);
+
+    assert_eq!(migrate(&mut storage).unwrap(), SETTINGS_SCHEMA_VERSION);
+    assert_migrated_v0_settings(&mut storage);
+}
+
+#[test]
+fn v1_hourly_target_is_converted_to_float() {
+    let mut storage = boot(flash());
+    save(
+        &mut storage,
+        keys::SCHEMA_VERSION,
+        StoredDataValue::SmallUInt(1),
+    )
+    .unwrap();
+    save(
+        &mut storage,
+        keys::MONITORING_TARGET_HOURLY,
+        StoredDataValue::UInt(120),
+    )
+    .unwrap();
+    save(
+        &mut storage,
+        keys::MONITORING_TARGET_DAILY,
+        StoredDataValue::UInt(2500),
+    )
+    .unwrap();
+
+    assert_eq!(migrate(&mut storage).unwrap(), 1);
+    assert_eq!(
+        read(&mut storage, keys::MONITORING_TARGET_HOURLY).unwrap(),
+        Some(StoredDataValue::Float(120.0))
+    );
+    assert_eq!(
+        read(&mut storage, keys::MONITORING_TARGET_DAILY).unwrap(),
+        Some(StoredDataValue::UInt(2500))
+    );
+    assert_eq!(
+        block_on(read_schema_version(&mut storage)).unwrap(),
+        Some(SETTINGS_SCHEMA_VERSION)
+    );
+}
+
+#[test]
+fn other_v0_screen_indexes_are_kept() {
+    let mut storage = boot(flash());
+    let index = StoredDataValue::SmallUInt(V0_DEBUG_SCREEN_INDEX - 1);
+    save(&mut storage, keys::MONITORING_DISPLAY_INDEX, index).unwrap();
+    assert_eq!(migrate(&mut storage).unwrap(), 0);
+    assert_eq!(
+        read(&mut storage, keys::MONITORING_DISPLAY_INDEX).unwrap(),
+        Some(index)
+    );
+}
+
+#[test]
+fn uncalibrated_v0_settings_still_need_setup() {
+    let mut storage = boot(flash());
+    save(
+        &mut storage,
+        LED_BRIGHTNESS_KEY,
+        StoredDataValue::SmallUInt(40),
+    )
+    .unwrap();
+    assert_eq!(migrate(&mut storage).unwrap(), 0);
+    assert_eq!(
+        read(&mut storage, keys::SYSTEM_SETUP_COMPLETE).unwrap(),
+        None
+    );
+    assert_eq!(
+        read(&mut storage, LED_BRIGHTNESS_KEY).unwrap(),
+        Some(StoredDataValue::SmallUInt(40))
+    );
+}
+
+#[test]
+fn empty_settings_start_at_current_schema() {
+    let mut storage = boot(flash());
+    assert_eq!(block_on(read_schema_version(&mut storage)).unwrap(), None);
+    assert_eq!(migrate(&mut storage).unwrap(), SETTINGS_SCHEMA_VERSION);
+    assert_eq!(
+        block_on(read_schema_version(&mut storage)).unwrap(),
+        Some(SETTINGS_SCHEMA_VERSION)
+    );
+    assert_eq!(
+        read(&mut storage, keys::SYSTEM_SETUP_COMPLETE).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn newer_schema_is_not_migrated() {
+    let mut storage = boot(flash());
+    let newer = SETTINGS_SCHEMA_VERSION + 1;
+    save(
+        &mut storage,
+        keys::SCHEMA_VERSION,
+        StoredDataValue::SmallUInt(newer),
+    )
+    .unwrap();
+    assert_eq!(migrate(&mut storage).unwrap(), newer);
+    assert_eq!(
+        block_on(read_schema_version(&mut storage)).unwrap(),
+        Some(newer)
+    );
+}
+
+#[test]
+fn power_loss_during_migration_is_recovered() {
+    let image = settings_v0_image();
+    // cut short at every point through the migration
+    for bytes in (0..64).step_by(4) {
+        let flash = flash_from_image(&image);
+        let mut storage = boot(flash);
+        inject(flash, |flash| flash.lose_power_after(bytes));
+        if migrate(&mut storage).is_ok() {
+            // the whole migration fitted before power was lost
+            break;
+        }
+
+        let mut storage = boot(flash);
+        migrate(&mut storage).unwrap();
+        assert_migrated_v0_settings(&mut storage);
+    }
+}
+
+#[test]
+fn log_is_read_oldest_first_in_pages() {
+    let mut storage = boot(flash());
+    let config = log_config(false);
+    for index in 0..11 {
+        write_log_entry(&mut storage, &config, index);
+    }
+
+    let mut buf = [[0; DATA_BUFFER_SIZE]; 5];
+    assert_eq!(
+        block_on(storage.get_log_items(&config, 0, 5, &mut buf)).unwrap(),
+        5
+    );
+    assert_eq!(buf[0][..LOG_ENTRY_SIZE], log_entry(0));
+    assert_eq!(buf[4][..LOG_ENTRY_SIZE], log_entry(4));
+    assert_eq!(
+        block_on(storage.get_log_items(&config, 5, 5, &mut buf)).unwrap(),
+        5
+    );
+    assert_eq!(buf[0][..LOG_ENTRY_SIZE], log_entry(5));
+    assert_eq!(
+        block_on(storage.get_log_items(&config, 10, 5, &mut buf)).unwrap(),
+        1
+    );
+    assert_eq!(buf[0][..LOG_ENTRY_SIZE], log_entry(10));
+    assert_eq!(
+        block_on(storage.get_log_items(&config, 11, 5, &mut buf)).unwrap(),
+        0
+    );
+    // a smaller page than the buffer
+    assert_eq!(
+        block_on(storage.get_log_items(&config, 3, 2, &mut buf)).unwrap(),
+        2
+    );
+    assert_eq!(buf[1][..LOG_ENTRY_SIZE], log_entry(4));
+
+    assert_eq!(read_log(&mut storage, &config), (0..11).collect::<Vec<_>>());
+}
+
+#[test]
+fn full_log_rejects_writes_unless_overwriting() {
+    let mut storage = boot(flash());
+    let config = log_config(false);
+    let mut written = 0;
+    while block_on(storage.write_log_data(&config, &log_entry(written))).is_ok() {
+        written += 1;
+        assert!(written < 1000, "log never filled");
+    }
+    assert!(written > 0);
+    assert!(matches!(
+        block_on(storage.write_log_data(&config, &log_entry(written))),
+        Err(StorageError::SaveError)
+    ));
+    assert_eq!(
+        read_log(&mut storage, &config),
+        (0..written).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn full_log_drops_oldest_when_overwriting() {
+    let mut storage = boot(flash());
+    let config = log_config(true);
+    const WRITTEN: u16 = 1000;
+    for index in 0..WRITTEN {
+        write_log_entry(&mut storage, &config, index);
+    }
+
+    let indexes = read_log(&mut storage, &config);
+    assert!(indexes[0] > 0, "oldest entries kept");
+    assert_eq!(*indexes.last().unwrap(), WRITTEN - 1);
+    assert_eq!(indexes, (indexes[0]..WRITTEN).collect::<Vec<_>>());
+}
+
+#[test]
+fn space_remaining_follows_writes_and_clear() {
+    let mut storage = boot(flash());
+    let config = log_config(false);
+    let empty = block_on(storage.get_space_remaining(&config)).unwrap();
+    assert!(empty > 0 && empty <= SMALL_LOG_SIZE);
+
+    write_log_entry(&mut storage, &config, 0);
+    let after_one = block_on(storage.get_space_remaining(&config)).unwrap();
+    assert!(after_one < empty);
+    write_log_entry(&mut storage, &config, 1);
+    assert_eq!(
+        block_on(storage.get_space_remaining(&config)).unwrap(),
+        after_one - (empty - after_one)
+    );
+
+    block_on(storage.clear_log_data(&config)).unwrap();
+    assert_eq!(
+        block_on(storage.get_space_remaining(&config)).unwrap(),
+        empty
+    );
+}
+
+#[test]
+fn log_usage_counts_entries_and_keeps_ends() {
+    let mut storage = boot(flash());
+    let config = log_config(false);
+    let mut oldest = [0; DATA_BUFFER_SIZE];
+    let mut newest = [0; DATA_BUFFER_SIZE];
+    let usage = block_on(storage.get_log_usage(&config, &mut oldest, &mut newest)).unwrap();
+    assert_eq!(usage.record_count, 0);
+    assert_eq!(
+        usage.space_remaining,
+        block_on(storage.get_space_remaining(&config)).unwrap()
+    );
+
+    for index in 0..5 {
+        write_log_entry(&mut storage, &config, index);
+    }
+    let usage = block_on(storage.get_log_usage(&config, &mut oldest, &mut newest)).unwrap();
+    assert_eq!(usage.record_count, 5);
+    assert_eq!(
+        usage.space_remaining,
+        block_on(storage.get_space_remaining(&config)).unwrap()
+    );
+    assert_eq!(oldest[..LOG_ENTRY_SIZE], log_entry(0));
+    assert_eq!(newest[..LOG_ENTRY_SIZE], log_entry(4));
+}
+
+#[test]
+fn key_value_usage_counts_superseded_values() {
+    let mut storage = boot(flash());
+    let empty = block_on(storage.get_key_value_usage()).unwrap();
+    assert_eq!(empty.stored_items, 0);
+    assert_eq!(empty.live_keys, 0);
+    assert_eq!(empty.used_bytes, 0);
+
+    save(&mut storage, 1, StoredDataValue::UInt(1)).unwrap();
+    save(&mut storage, 2, StoredDataValue::UInt(2)).unwrap();
+    save(&mut storage, 1, StoredDataValue::UInt(3)).unwrap();
+    let usage = block_on(storage.get_key_value_usage()).unwrap();
+    assert_eq!(usage.stored_items, 3);
+    assert_eq!(usage.live_keys, 2);
+    assert!(usage.used_bytes > 0);
+
+    block_on(storage.clear_data()).unwrap();
+    assert_eq!(block_on(storage.get_key_value_usage()).unwrap(), empty);
+}
+
+fn log_status(used: u32, span_hours: i64) -> PartitionStatus {
+    let oldest = chrono::NaiveDate::from_ymd_opt(2025, 1, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    PartitionStatus {
+        partition: NvmPartition::ActivityLog,
+        capacity: 0x8000,
+        used,
+        record_count: 10,
+        oldest: Some(oldest),
+        newest: Some(oldest + chrono::Duration::hours(span_hours)),
+        live_settings: None,
+        wear: None,
+    }
+}
+
+#[test]
+fn days_until_wrap_follows_logging_rate() {
+    // a quarter full after 10 days leaves 30 days at the same rate
+    assert_eq!(log_status(0x2000, 10 * 24).days_until_wrap(), Some(30));
+    assert_eq!(log_status(0x8000, 10 * 24).days_until_wrap(), Some(0));
+    // too little history to estimate a rate
+    assert_eq!(log_status(0x2000, 0).days_until_wrap(), None);
+    assert_eq!(
+        PartitionStatus {
+            oldest: None,
+            newest: None,
+            ..log_status(0, 0)
+        }
+        .days_until_wrap(),
+        None
+    );
+}
+
+#[test]
+fn clearing_log_leaves_other_data() {
+    let mut storage = boot(flash());
+    let config = log_config(false);
+    let other_log = StoredLogConfig {
+        storage_range: SMALL_LOG_SIZE..2 * SMALL_LOG_SIZE,
+        allow_overwrite_old: false,
+    };
+    write_log_entry(&mut storage, &config, 0);
+    write_log_entry(&mut storage, &other_log, 1);
+    save(&mut storage, 1, StoredDataValue::UInt(1)).unwrap();
+
+    block_on(storage.clear_log_data(&config)).unwrap();
+    assert!(read_log(&mut storage, &config).is_empty());
+    assert_eq!(read_log(&mut storage, &other_log), vec![1]);
+    assert_eq!(
+        read(&mut storage, 1).unwrap(),
+        Some(StoredDataValue::UInt(1))
+    );
+
+    // a cleared log can be written again
+    write_log_entry(&mut storage, &config, 2);
+    assert_eq!(read_log(&mut storage, &config), vec![2]);
+}
+
+#[test]
+fn power_loss_during_log_write_keeps_earlier_entries() {
+    let flash = flash();
+    let mut storage = boot(flash);
+    let config = log_config(false);
+    for index in 0..3 {
+        write_log_entry(&mut storage, &config, index);
+    }
+
+    inject(flash, |flash| flash.lose_power_after(LOG_ENTRY_SIZE / 2));
+    assert!(matches!(
+        block_on(storage.write_log_data(&config, &log_entry(3))),
+        Err(StorageError::SaveError)
+    ));
+
+    let mut storage = boot(flash);
+    assert_eq!(read_log(&mut storage, &config), vec![0, 1, 2]);
+    write_log_entry(&mut storage, &config, 4);
+    assert_eq!(read_log(&mut storage, &config), vec![0, 1, 2, 4]);
+}
+
+#[test]
+fn power_loss_during_save_keeps_previous_value() {
+    let flash = flash();
+    let mut storage = boot(flash);
+    save(&mut storage, 1, StoredDataValue::UInt(1)).unwrap();
+
+    // cut short at every point through the save of the new value
+    for bytes in 0..16 {
+        inject(flash, |flash| flash.lose_power_after(bytes));
+        if save(&mut storage, 1, StoredDataValue::UInt(2)).is_ok() {
+            // the whole save fitted before power was lost
+            inject(flash, |flash| flash.lose_power_after(usize::MAX));
+            save(&mut storage, 1, StoredDataValue::UInt(1)).unwrap();
+            continue;
+        }
+        let mut storage = boot(flash);
+        assert_eq!(
+            read(&mut storage, 1).unwrap(),
+            Some(StoredDataValue::UInt(1)),
+            "lost previous value with power lost after {bytes} bytes"
+        );
+    }
+}
+
+#[test]
+fn bit_flip_in_setting_is_not_returned() {
+    let flash = flash();
+    let mut storage = boot(flash);
+    let value = StoredDataValue::UInt(0x1234_5678);
+    let before = snapshot(flash);
+    save(&mut storage, 1, value).unwrap();
+    let after = snapshot(flash);
+    let last_written = (0..after.len())
+        .rev()
+        .find(|i| before[*i] != after[*i])
+        .unwrap();
+
+    inject(flash, |flash| flash.flip_bit(last_written, 0));
+    let mut storage = boot(flash);
+    match read(&mut storage, 1) {
+        Ok(Some(read_value)) => assert_eq!(read_value, value, "corrupted value returned"),
+        Ok(None) | Err(_) => {}
+    }
+}
+
+#[test]
+fn bit_flip_in_log_entry_is_not_returned() {
+    let flash = flash();
+    let mut storage = boot(flash);
+    let config = log_config(false);
+    write_log_entry(&mut storage, &config, 0);
+    let before = snapshot(flash);
+    write_log_entry(&mut storage, &config, 1);
+    let after = snapshot(flash);
+    let last_written = (0..after.len())
+        .rev()
+        .find(|i| before[*i] != after[*i])
+        .unwrap();
+
+    inject(flash, |flash| flash.flip_bit(last_written, 3));
+    let mut storage = boot(flash);
+    let mut buf = [[0; DATA_BUFFER_SIZE]; 2];
+    if let Ok(count) = block_on(storage.get_log_items(&config, 0, 2, &mut buf)) {
+        for item in &buf[..count] {
+            let index = u16::from_le_bytes([item[0], item[1]]);
+            assert_eq!(
+                item[..LOG_ENTRY_SIZE],
+                log_entry(index),
+                "corrupted entry returned"
+            );
+        }
+    }
+}
+
+#[test]
+fn erase_failure_is_reported() {
+    let flash = flash();
+    let mut storage = boot(flash);
+    let config = log_config(false);
+    write_log_entry(&mut storage, &config, 0);
+    save(&mut storage, 1, StoredDataValue::UInt(1)).unwrap();
+
+    inject(flash, |flash| flash.fail_erases(1));
+    assert!(matches!(
+        block_on(storage.clear_log_data(&config)),
+        Err(StorageError::EraseError)
+    ));
+    assert_eq!(read_log(&mut storage, &config), vec![0]);
+    inject(flash, |flash| flash.fail_erases(1));
+    assert!(matches!(
+        block_on(storage.clear_data()),
+        Err(StorageError::EraseError)
+    ));
+    assert_eq!(
+        read(&mut storage, 1).unwrap(),
+        Some(StoredDataValue::UInt(1))
+    );
+
+    // the flash works again once the fault has passed
+    block_on(storage.clear_log_data(&config)).unwrap();
+    assert!(read_log(&mut storage, &config).is_empty());
+}
+
+#[test]
+fn log_wrap_erases_are_counted_per_page() {
+    let mut storage = boot(flash());
+    assert_eq!(storage.erase_counts(), Some(EraseCounts::new()));
+
+    let config = log_config(true);
+    for index in 0..1000 {
+        write_log_entry(&mut storage, &config, index);
+    }
+    let counts = storage.erase_counts().unwrap();
+    assert!(counts.get(0) > 0);
+    assert!(counts.get(FLASH_ERASE_SIZE as u32) > 0);
+    // pages outside the log untouched
+    assert_eq!(counts.get(SMALL_LOG_SIZE), 0);
+}
+
+#[test]
+fn erase_counts_save_is_held_back() {
+    let mut storage = boot(flash());
+    assert_eq!(storage.erase_counts_save_due(), None);
+
+    let config = log_config(true);
+    let first_write = Instant::now();
+    for index in 0..1000 {
+        write_log_entry(&mut storage, &config, index);
+    }
+    // all the erases are saved together, no sooner than a held back settings save
+    let due = storage.erase_counts_save_due().unwrap();
+    assert!(due >= first_write + SAVE_MAX_DELAY);
+
+    block_on(storage.save_erase_counts()).unwrap();
+    assert_eq!(storage.erase_counts_save_due(), None);
+}
+
+#[test]
+fn erase_counts_are_kept_across_reboots() {
+    let flash = flash();
+    let mut storage = boot(flash);
+    let config = log_config(true);
+    for index in 0..1000 {
+        write_log_entry(&mut storage, &config, index);
+    }
+    block_on(storage.save_erase_counts()).unwrap();
+    let saved = storage.erase_counts().unwrap();
+
+    let mut storage = boot(flash);
+    let loaded = storage.erase_counts().unwrap();
+    assert_eq!(loaded.get(0), saved.get(0));
+    assert_eq!(
+        loaded.get(FLASH_ERASE_SIZE as u32),
+        saved.get(FLASH_ERASE_SIZE as u32)
+    );
+
+    // later erases add to the saved counts
+    block_on(storage.clear_log_data(&config)).unwrap();
+    assert!(storage.erase_counts().unwrap().get(0) > saved.get(0));
+}
+
+#[test]
+fn erase_counts_are_not_lost_by_factory_reset() {
+    let flash = flash();
+    let mut storage = boot(flash);
+    let config = log_config(true);
+    for index in 0..1000 {
+        write_log_entry(&mut storage, &config, index);
+    }
+    block_on(reset_storage(
+        &mut storage,
+        FactoryResetOptions {
+            keep_calibration: false,
+        },
+    ))
+    .unwrap();
+    block_on(storage.save_erase_counts()).unwrap();
+    let before_reboot = storage.erase_counts().unwrap();
+    assert!(before_reboot.get(0) > 0);
+
+    let storage = boot(flash);
+    assert_eq!(storage.erase_counts().unwrap().get(0), before_reboot.get(0));
+}
@@ -0,0 +1,303 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A user configurable screen made up of widgets placed on a 3x2 grid.
+//!
+//! The layout packs into a `u32` so it can be kept as a single setting. Each cell takes 4 bits,
+//! holding the [`WidgetKind`] value, starting from the least significant bits with the top left
+//! cell and working along each row. The top 8 bits are reserved and must be zero.
+
+use embedded_graphics::prelude::{Point, Size};
+use embedded_graphics::primitives::Rectangle;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WidgetKind {
+    Empty,
+    Clock,
+    TotalConsumed,
+    Target,
+    LastDrink,
+    HourlyRate,
+    ProgressBar,
+    DrinkToStayOnTarget,
+    Sparkline,
+}
+
+impl TryFrom<u8> for WidgetKind {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Empty),
+            1 => Ok(Self::Clock),
+            2 => Ok(Self::TotalConsumed),
+            3 => Ok(Self::Target),
+            4 => Ok(Self::LastDrink),
+            5 => Ok(Self::HourlyRate),
+            6 => Ok(Self::ProgressBar),
+            7 => Ok(Self::DrinkToStayOnTarget),
+            8 => Ok(Self::Sparkline),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<WidgetKind> for u8 {
+    fn from(value: WidgetKind) -> Self {
+        match value {
+            WidgetKind::Empty => 0,
+            WidgetKind::Clock => 1,
+            WidgetKind::TotalConsumed => 2,
+            WidgetKind::Target => 3,
+            WidgetKind::LastDrink => 4,
+            WidgetKind::HourlyRate => 5,
+            WidgetKind::ProgressBar => 6,
+            WidgetKind::DrinkToStayOnTarget => 7,
+            WidgetKind::Sparkline => 8,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GridLayout {
+    cells: [WidgetKind; GridLayout::CELL_COUNT],
+}
+
+impl GridLayout {
+    pub const COLUMNS: usize = 3;
+    pub const ROWS: usize = 2;
+    pub const CELL_COUNT: usize = Self::COLUMNS * Self::ROWS;
+    const BITS_PER_CELL: usize = 4;
+    const CELL_MASK: u32 = (1 << Self::BITS_PER_CELL) - 1;
+
+    /// Total and target across the top with the day's progress underneath.
+    pub const DEFAULT: GridLayout = GridLayout {
+        cells: [
+            WidgetKind::TotalConsumed,
+            WidgetKind::Target,
+            WidgetKind::Clock,
+            WidgetKind::Sparkline,
+            WidgetKind::Sparkline,
+            WidgetKind::DrinkToStayOnTarget,
+        ],
+    };
+
+    /// Unpacks a stored layout. No layout, or one that does not unpack, gives [`Self::DEFAULT`].
+    pub fn from_stored(packed: Option<u32>) -> Self {
+        packed
+            .and_then(|packed| Self::try_from(packed).ok())
+            .unwrap_or_default()
+    }
+
+    /// Widget in the cell at `index`, counting along each row from the top left.
+    pub fn cell(&self, index: usize) -> WidgetKind {
+        self.cells.get(index).copied().unwrap_or(WidgetKind::Empty)
+    }
+
+    pub fn set_cell(&mut self, index: usize, kind: WidgetKind) {
+        if let Some(cell) = self.cells.get_mut(index) {
+            *cell = kind;
+        }
+    }
+
+    /// Splits `bounds` into the area for each widget. Neighbouring cells in a row holding the same
+    /// widget are joined into one wider area. Empty cells are skipped.
+    pub fn regions(&self, bounds: Rectangle) -> impl Iterator<Item = (WidgetKind, Rectangle)> + '_ {
+        (0..Self::ROWS).flat_map(move |row| {
+            let row_cells = &self.cells[row * Self::COLUMNS..(row + 1) * Self::COLUMNS];
+            (0..Self::COLUMNS).filter_map(move |column| {
+                let kind = row_cells[column];
+                if kind == WidgetKind::Empty || (column > 0 && row_cells[column - 1] == kind) {
+                    return None;
+                }
+                let span = row_cells[column..]
+                    .iter()
+                    .take_while(|cell| **cell == kind)
+                    .count();
+                Some((kind, Self::cell_area(bounds, row, column, span)))
+            })
+        })
+    }
+
+    /// Area covered by `span` cells starting at `row` and `column`. Any pixels left over from
+    /// dividing the bounds go to the last row and column.
+    fn cell_area(bounds: Rectangle, row: usize, column: usize, span: usize) -> Rectangle {
+        let x = |column: usize| (column as u32 * bounds.size.width / Self::COLUMNS as u32) as i32;
+        let y = |row: usize| (row as u32 * bounds.size.height / Self::ROWS as u32) as i32;
+        let top_left = Point::new(x(column), y(row));
+        let bottom_right = Point::new(x(column + span), y(row + 1));
+        Rectangle::new(
+            bounds.top_left + top_left,
+            Size::new(
+                (bottom_right.x - top_left.x) as u32,
+                (bottom_right.y - top_left.y) as u32,
+            ),
+        )
+    }
+}
+
+impl Default for GridLayout {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl TryFrom<u32> for GridLayout {
+    type Error = ();
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        if value >> (Self::CELL_COUNT * Self::BITS_PER_CELL) != 0 {
+            return Err(());
+        }
+        let mut cells = [WidgetKind::Empty; Self::CELL_COUNT];
+        for (index, cell) in cells.iter_mut().enumerate() {
+            let kind = (value >> (index * Self::BITS_PER_CELL)) & Self::CELL_MASK;
+            *cell = WidgetKind::try_from(kind as u8)?;
+        }
+        Ok(Self { cells })
+    }
+}
+
+impl From<GridLayout> for u32 {
+    fn from(value: GridLayout) -> Self {
+        value
+            .cells
+            .iter()
+            .enumerate()
+            .fold(0, |packed, (index, kind)| {
+                packed | (u8::from(*kind) as u32) << (index * GridLayout::BITS_PER_CELL)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(cells: [WidgetKind; GridLayout::CELL_COUNT]) -> GridLayout {
+        GridLayout { cells }
+    }
+
+    fn bounds() -> Rectangle {
+        Rectangle::new(Point::new(0, 10), Size::new(128, 54))
+    }
+
+    #[test]
+    fn packing_round_trips() {
+        let packed = u32::from(GridLayout::DEFAULT);
+        assert_eq!(packed, 0x0078_8132);
+        assert_eq!(GridLayout::try_from(packed), Ok(GridLayout::DEFAULT));
+    }
+
+    #[test]
+    fn unknown_widget_or_reserved_bits_are_rejected() {
+        assert_eq!(GridLayout::try_from(0x0000_00F0), Err(()));
+        assert_eq!(GridLayout::try_from(0x0100_0000), Err(()));
+        assert_eq!(
+            GridLayout::try_from(0),
+            Ok(layout([WidgetKind::Empty; GridLayout::CELL_COUNT]))
+        );
+    }
+
+    #[test]
+    fn missing_or_invalid_stored_layout_is_the_default() {
+        let packed = 0x0011_1111;
+        assert_eq!(
+            GridLayout::from_stored(Some(packed)),
+            GridLayout::try_from(packed).unwrap()
+        );
+        assert_eq!(
+            GridLayout::from_stored(Some(0x0100_0000)),
+            GridLayout::DEFAULT
+        );
+        assert_eq!(GridLayout::from_stored(None), GridLayout::DEFAULT);
+    }
+
+    #[test]
+    fn cells_divide_bounds() {
+        let grid = layout([
+            WidgetKind::Clock,
+            WidgetKind::Target,
+            WidgetKind::LastDrink,
+            WidgetKind::HourlyRate,
+            WidgetKind::ProgressBar,
+            WidgetKind::TotalConsumed,
+        ]);
+        let mut regions = grid.regions(bounds());
+        assert_eq!(
+            regions.next(),
+            Some((
+                WidgetKind::Clock,
+                Rectangle::new(Point::new(0, 10), Size::new(42, 27))
+            ))
+        );
+        assert_eq!(
+            regions.next(),
+            Some((
+                WidgetKind::Target,
+                Rectangle::new(Point::new(42, 10), Size::new(43, 27))
+            ))
+        );
+        assert_eq!(
+            regions.nth(3).and_then(|(_, area)| area.bottom_right()),
+            Some(Point::new(127, 63))
+        );
+        assert_eq!(regions.next(), None);
+    }
+
+    #[test]
+    fn matching_neighbours_in_a_row_are_joined() {
+        let grid = layout([
+            WidgetKind::Sparkline,
+            WidgetKind::Sparkline,
+            WidgetKind::Sparkline,
+            WidgetKind::Clock,
+            WidgetKind::Target,
+            WidgetKind::Clock,
+        ]);
+        let regions: [(WidgetKind, Rectangle); 4] = {
+            let mut iter = grid.regions(bounds());
+            core::array::from_fn(|_| iter.next().unwrap())
+        };
+        assert_eq!(
+            regions[0],
+            (
+                WidgetKind::Sparkline,
+                Rectangle::new(Point::new(0, 10), Size::new(128, 27))
+            )
+        );
+        // the same widget is not joined when something else sits between
+        assert_eq!(regions[1].0, WidgetKind::Clock);
+        assert_eq!(regions[3].0, WidgetKind::Clock);
+        assert_eq!(regions[3].1.top_left, Point::new(85, 37));
+    }
+
+    #[test]
+    fn empty_cells_are_skipped() {
+        let mut grid = layout([WidgetKind::Empty; GridLayout::CELL_COUNT]);
+        grid.set_cell(4, WidgetKind::ProgressBar);
+        grid.set_cell(GridLayout::CELL_COUNT, WidgetKind::Clock);
+        let mut regions = grid.regions(bounds());
+        assert_eq!(
+            regions.next(),
+            Some((
+                WidgetKind::ProgressBar,
+                Rectangle::new(Point::new(42, 37), Size::new(43, 27))
+            ))
+        );
+        assert_eq!(regions.next(), None);
+        assert_eq!(grid.cell(4), WidgetKind::ProgressBar);
+        assert_eq!(grid.cell(GridLayout::CELL_COUNT), WidgetKind::Empty);
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A horizontal bar showing progress towards a target.
+
+use embedded_graphics::Drawable;
+use embedded_graphics::Pixel;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::{DrawTarget, Point, Primitive, Size};
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+
+/// Fills an outlined bar in proportion to `value` against `target`. An optional marker, such as
+/// where the value should be by now, is drawn as a tick that extends above and below the bar when
+/// there is room.
+///
+/// Once the target is exceeded the bar is rescaled to fit the value and a gap is left in the fill
+/// where the target sits. Without a target there is nothing to show progress against, so only the
+/// outline is drawn.
+pub struct ProgressBar {
+    bounds: Rectangle,
+    value: f32,
+    target: f32,
+    marker: Option<f32>,
+}
+
+impl ProgressBar {
+    /// Height needed before the marker extends beyond the bar.
+    const MIN_HEIGHT_FOR_OVERHANG: u32 = 8;
+    const MARKER_OVERHANG: u32 = 2;
+
+    pub fn new(position: Point, size: Size, value: f32, target: f32) -> Self {
+        Self {
+            bounds: Rectangle::new(position, size),
+            value,
+            target,
+            marker: None,
+        }
+    }
+
+    pub fn with_marker(mut self, marker: f32) -> Self {
+        self.marker = Some(marker);
+        self
+    }
+
+    fn overhang(&self) -> u32 {
+        if self.bounds.size.height >= Self::MIN_HEIGHT_FOR_OVERHANG {
+            Self::MARKER_OVERHANG
+        } else {
+            0
+        }
+    }
+
+    /// Outlined area of the bar, leaving space for the marker overhang.
+    fn bar(&self) -> Rectangle {
+        let overhang = self.overhang();
+        Rectangle::new(
+            self.bounds.top_left + Point::new(0, overhang as i32),
+            Size::new(
+                self.bounds.size.width,
+                self.bounds.size.height - 2 * overhang,
+            ),
+        )
+    }
+
+    /// Value shown at the right hand end of the bar.
+    fn full_scale(&self) -> f32 {
+        [self.value, self.marker.unwrap_or(0.0)]
+            .iter()
+            .fold(self.target, |largest, value| largest.max(*value))
+    }
+
+    /// Columns from the inside edge of the outline to reach `value`, limited to `width`.
+    fn columns_for(value: f32, full_scale: f32, width: u32) -> u32 {
+        let columns = (value.max(0.0) / full_scale * width as f32 + 0.5) as u32;
+        columns.min(width)
+    }
+}
+
+impl Drawable for ProgressBar {
+    type Color = BinaryColor;
+    type Output = ();
+
+    fn draw<D: DrawTarget<Color = BinaryColor>>(&self, display: &mut D) -> Result<(), D::Error> {
+        let bar = self.bar();
+        if bar.size.width < 3 || bar.size.height < 3 {
+            return Ok(());
+        }
+        bar.into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(display)?;
+        if self.target <= 0.0 {
+            return Ok(());
+        }
+
+        let inside = bar.offset(-1);
+        let width = inside.size.width;
+        let full_scale = self.full_scale();
+        let filled = Self::columns_for(self.value, full_scale, width);
+        // the target and marker sit on the column they reach, keeping the last column for the end
+        let column_for = |value| Self::columns_for(value, full_scale, width).min(width - 1);
+        let target_column = (self.value > self.target).then(|| column_for(self.target));
+        let marker_column = self.marker.map(column_for);
+
+        // each column is filled or not, with the target and marker columns inverted so they show
+        // against either
+        let inside_pixels = (0..width).flat_map(|column| {
+            let inverted = Some(column) == target_column || Some(column) == marker_column;
+            let on = (column < filled) != inverted;
+            (0..inside.size.height).filter(move |_| on).map(move |row| {
+                Pixel(
+                    inside.top_left + Point::new(column as i32, row as i32),
+                    BinaryColor::On,
+                )
+            })
+        });
+        display.draw_iter(inside_pixels)?;
+
+        if let Some(marker_column) = marker_column {
+            let x = inside.top_left.x + marker_column as i32;
+            let overhang = self.overhang() as i32;
+            let above = self.bounds.top_left.y..self.bounds.top_left.y + overhang;
+            let below_start = bar.top_left.y + bar.size.height as i32;
+            let below = below_start..below_start + overhang;
+            display.draw_iter(
+                above
+                    .chain(below)
+                    .map(|y| Pixel(Point::new(x, y), BinaryColor::On)),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics::mock_display::MockDisplay;
+
+    fn draw(progress_bar: ProgressBar) -> MockDisplay<BinaryColor> {
+        let mut display = MockDisplay::new();
+        progress_bar.draw(&mut display).unwrap();
+        display
+    }
+
+    #[test]
+    fn fill_is_proportional_to_target() {
+        let display = draw(ProgressBar::new(Point::zero(), Size::new(10, 4), 4.0, 8.0));
+        display.assert_pattern(&[
+            "##########", //
+            "#####    #", //
+            "#####    #", //
+            "##########", //
+        ]);
+    }
+
+    #[test]
+    fn marker_extends_beyond_bar_and_inverts_the_fill() {
+        let display =
+            draw(ProgressBar::new(Point::zero(), Size::new(10, 8), 2.0, 8.0).with_marker(4.0));
+        display.assert_pattern(&[
+            "     #    ", //
+            "     #    ", //
+            "##########", //
+            "###  #   #", //
+            "###  #   #", //
+            "##########", //
+            "     #    ", //
+            "     #    ", //
+        ]);
+
+        let display =
+            draw(ProgressBar::new(Point::zero(), Size::new(10, 4), 6.0, 8.0).with_marker(2.0));
+        display.assert_pattern(&[
+            "##########", //
+            "### ###  #", //
+            "### ###  #", //
+            "##########", //
+        ]);
+    }
+
+    #[test]
+    fn exceeding_target_rescales_and_marks_target() {
+        let display = draw(ProgressBar::new(Point::zero(), Size::new(10, 4), 12.0, 8.0));
+        display.assert_pattern(&[
+            "##########", //
+            "###### ###", //
+            "###### ###", //
+            "##########", //
+        ]);
+    }
+
+    #[test]
+    fn marker_past_the_end_stays_inside() {
+        let display =
+            draw(ProgressBar::new(Point::zero(), Size::new(10, 4), 0.0, 8.0).with_marker(20.0));
+        display.assert_pattern(&[
+            "##########", //
+            "#       ##", //
+            "#       ##", //
+            "##########", //
+        ]);
+    }
+
+    #[test]
+    fn zero_target_draws_outline_only() {
+        let display =
+            draw(ProgressBar::new(Point::zero(), Size::new(10, 4), 5.0, 0.0).with_marker(2.0));
+        display.assert_pattern(&[
+            "##########", //
+            "#        #", //
+            "#        #", //
+            "##########", //
+        ]);
+    }
+}
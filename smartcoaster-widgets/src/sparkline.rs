@@ -0,0 +1,186 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A compact line graph of a series of values against a target series.
+
+use embedded_graphics::Drawable;
+use embedded_graphics::Pixel;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::{DrawTarget, Point, Size};
+use embedded_graphics::primitives::{Line, PointsIter, Rectangle};
+
+/// Plots `values` as a solid line and `target` as a dotted line. Both are sampled at the same
+/// evenly spaced points across the width, so `values` may be shorter than `target` when the series
+/// is still in progress. Points should already be downsampled to no more than the width in pixels.
+pub struct Sparkline<'a> {
+    bounds: Rectangle,
+    values: &'a [f32],
+    target: &'a [f32],
+}
+
+impl<'a> Sparkline<'a> {
+    pub fn new(position: Point, size: Size, values: &'a [f32], target: &'a [f32]) -> Self {
+        Self {
+            bounds: Rectangle::new(position, size),
+            values,
+            target,
+        }
+    }
+
+    /// Value shown at the top of the graph. Never less than 1 to avoid dividing by zero.
+    fn scale_max(&self) -> f32 {
+        self.values
+            .iter()
+            .chain(self.target.iter())
+            .fold(1.0, |largest: f32, value| largest.max(*value))
+    }
+
+    fn point_count(&self) -> usize {
+        usize::max(self.values.len(), self.target.len())
+    }
+
+    fn to_point(&self, index: usize, value: f32, scale_max: f32) -> Point {
+        let size = self.bounds.size;
+        let x = if self.point_count() > 1 {
+            index as i32 * (size.width as i32 - 1) / (self.point_count() as i32 - 1)
+        } else {
+            0
+        };
+        let height = (size.height as i32 - 1) as f32;
+        let y_from_bottom = (value.clamp(0.0, scale_max) / scale_max * height + 0.5) as i32;
+        self.bounds.top_left + Point::new(x, size.height as i32 - 1 - y_from_bottom)
+    }
+
+    fn segments(&self, series: &'a [f32], scale_max: f32) -> impl Iterator<Item = Line> + '_ {
+        series.windows(2).enumerate().map(move |(index, pair)| {
+            Line::new(
+                self.to_point(index, pair[0], scale_max),
+                self.to_point(index + 1, pair[1], scale_max),
+            )
+        })
+    }
+}
+
+impl Drawable for Sparkline<'_> {
+    type Color = BinaryColor;
+    type Output = ();
+
+    fn draw<D: DrawTarget<Color = BinaryColor>>(&self, display: &mut D) -> Result<(), D::Error> {
+        if self.bounds.size.width == 0 || self.bounds.size.height == 0 {
+            return Ok(());
+        }
+        let scale_max = self.scale_max();
+
+        // every other pixel along the whole line, skipping the point shared between segments
+        let target_points = self
+            .segments(self.target, scale_max)
+            .enumerate()
+            .flat_map(|(index, line)| line.points().skip(usize::from(index > 0)));
+        display.draw_iter(
+            target_points
+                .step_by(2)
+                .map(|point| Pixel(point, BinaryColor::On)),
+        )?;
+
+        for line in self.segments(self.values, scale_max) {
+            display.draw_iter(line.points().map(|point| Pixel(point, BinaryColor::On)))?;
+        }
+        // a single value has no line to draw, so show it as a point
+        if let [value] = self.values {
+            display.draw_iter([Pixel(self.to_point(0, *value, scale_max), BinaryColor::On)])?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics::mock_display::MockDisplay;
+
+    fn draw(sparkline: Sparkline) -> MockDisplay<BinaryColor> {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        sparkline.draw(&mut display).unwrap();
+        display
+    }
+
+    #[test]
+    fn values_are_drawn_as_a_solid_line() {
+        let display = draw(Sparkline::new(
+            Point::zero(),
+            Size::new(5, 5),
+            &[0.0, 10.0],
+            &[],
+        ));
+        display.assert_pattern(&[
+            "    #", //
+            "   # ", //
+            "  #  ", //
+            " #   ", //
+            "#    ", //
+        ]);
+    }
+
+    #[test]
+    fn target_is_drawn_as_a_dotted_line() {
+        let display = draw(Sparkline::new(
+            Point::zero(),
+            Size::new(5, 3),
+            &[],
+            &[5.0, 5.0],
+        ));
+        display.assert_pattern(&[
+            "# # #", //
+        ]);
+    }
+
+    #[test]
+    fn partial_values_share_the_target_spacing() {
+        let display = draw(Sparkline::new(
+            Point::new(1, 1),
+            Size::new(5, 3),
+            &[0.0, 0.0, 0.0],
+            &[0.0, 0.0, 0.0, 0.0, 0.0],
+        ));
+        display.assert_pattern(&[
+            "      ", //
+            "      ", //
+            "      ", //
+            " ### #", //
+        ]);
+    }
+
+    #[test]
+    fn values_are_kept_within_bounds() {
+        let display = draw(Sparkline::new(
+            Point::new(2, 2),
+            Size::new(4, 4),
+            &[-5.0, 100.0, 50.0],
+            &[0.0, 0.0, 200.0],
+        ));
+        let bounds = Rectangle::new(Point::new(2, 2), Size::new(4, 4));
+        assert!(bounds.contains(display.affected_area().top_left));
+        assert!(bounds.contains(display.affected_area().bottom_right().unwrap()));
+    }
+
+    #[test]
+    fn single_value_is_drawn_as_a_point() {
+        let display = draw(Sparkline::new(Point::zero(), Size::new(3, 3), &[3.0], &[]));
+        display.assert_pattern(&[
+            "#", //
+        ]);
+    }
+}
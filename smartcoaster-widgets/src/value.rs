@@ -0,0 +1,144 @@
+// Copyright (C) 2025 Paul Hampson
+//
+// This program is free software: you can redistribute it and/or modify it under
+// the terms of the GNU General Public License version 3 as  published by the
+// Free Software Foundation.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more
+// details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A single labelled value, such as a total or a time, sized to fit the area it is given.
+
+use embedded_graphics::Drawable;
+use embedded_graphics::mono_font::ascii::{FONT_5X7, FONT_6X10, FONT_7X13_BOLD, FONT_9X15_BOLD};
+use embedded_graphics::mono_font::{MonoFont, MonoTextStyle};
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::{DrawTarget, Point, Size};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::{Alignment, Baseline, Text, TextStyleBuilder};
+
+/// Fonts to try for the value, largest first.
+const VALUE_FONTS: [&MonoFont<'static>; 4] =
+    [&FONT_9X15_BOLD, &FONT_7X13_BOLD, &FONT_6X10, &FONT_5X7];
+const LABEL_FONT: &MonoFont<'static> = &FONT_5X7;
+
+/// Draws `label` in a small font across the top of the area with `value` centred in the space
+/// below, using the largest font the value fits in. An empty label gives the whole area to the
+/// value.
+pub struct ValueWidget<'a> {
+    bounds: Rectangle,
+    label: &'a str,
+    value: &'a str,
+}
+
+impl<'a> ValueWidget<'a> {
+    pub fn new(bounds: Rectangle, label: &'a str, value: &'a str) -> Self {
+        Self {
+            bounds,
+            label,
+            value,
+        }
+    }
+
+    fn label_height(&self) -> u32 {
+        if self.label.is_empty() {
+            0
+        } else {
+            LABEL_FONT.character_size.height
+        }
+    }
+}
+
+/// Largest font that fits `characters` within `size`. Falls back to the smallest font, which will
+/// be clipped, when none fit.
+fn value_font(size: Size, characters: usize) -> &'static MonoFont<'static> {
+    VALUE_FONTS
+        .iter()
+        .copied()
+        .find(|font| {
+            let width = characters as u32 * font.character_size.width
+                + characters.saturating_sub(1) as u32 * font.character_spacing;
+            width <= size.width && font.character_size.height <= size.height
+        })
+        .unwrap_or(VALUE_FONTS[VALUE_FONTS.len() - 1])
+}
+
+impl Drawable for ValueWidget<'_> {
+    type Color = BinaryColor;
+    type Output = ();
+
+    fn draw<D: DrawTarget<Color = BinaryColor>>(&self, display: &mut D) -> Result<(), D::Error> {
+        let centred = TextStyleBuilder::new()
+            .alignment(Alignment::Center)
+            .baseline(Baseline::Middle)
+            .build();
+        let centre_x = self.bounds.top_left.x + (self.bounds.size.width / 2) as i32;
+
+        let label_height = self.label_height();
+        if label_height > 0 {
+            let label_style = MonoTextStyle::new(LABEL_FONT, BinaryColor::On);
+            let position = Point::new(centre_x, self.bounds.top_left.y + (label_height / 2) as i32);
+            Text::with_text_style(self.label, position, label_style, centred).draw(display)?;
+        }
+
+        let value_size = Size::new(
+            self.bounds.size.width,
+            self.bounds.size.height.saturating_sub(label_height),
+        );
+        let value_style = MonoTextStyle::new(
+            value_font(value_size, self.value.chars().count()),
+            BinaryColor::On,
+        );
+        let position = Point::new(
+            centre_x,
+            self.bounds.top_left.y + (label_height + value_size.height / 2) as i32,
+        );
+        Text::with_text_style(self.value, position, value_style, centred).draw(display)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics::mock_display::MockDisplay;
+
+    fn chosen_font_size(size: Size, characters: usize) -> Size {
+        value_font(size, characters).character_size
+    }
+
+    #[test]
+    fn largest_font_that_fits_is_used() {
+        assert_eq!(chosen_font_size(Size::new(42, 20), 4), Size::new(9, 15));
+        assert_eq!(chosen_font_size(Size::new(42, 20), 6), Size::new(7, 13));
+        assert_eq!(chosen_font_size(Size::new(42, 20), 7), Size::new(6, 10));
+        assert_eq!(chosen_font_size(Size::new(42, 12), 4), Size::new(6, 10));
+    }
+
+    #[test]
+    fn smallest_font_when_nothing_fits() {
+        assert_eq!(chosen_font_size(Size::new(10, 20), 8), Size::new(5, 7));
+        assert_eq!(chosen_font_size(Size::new(42, 2), 1), Size::new(5, 7));
+    }
+
+    #[test]
+    fn draws_within_bounds() {
+        let mut display = MockDisplay::new();
+        ValueWidget::new(
+            Rectangle::new(Point::new(0, 0), Size::new(42, 27)),
+            "Today",
+            "1234",
+        )
+        .draw(&mut display)
+        .unwrap();
+        let drawn = display.affected_area();
+        assert!(drawn.top_left.y >= 0);
+        assert!(drawn.bottom_right().unwrap().x < 42);
+        assert!(drawn.bottom_right().unwrap().y < 27);
+    }
+}